@@ -11,7 +11,9 @@ import java.net.MulticastSocket
 
 /**
  * Listens for X-Plane BECN (beacon) multicast packets on 239.255.1.1:49707 to
- * auto-discover X-Plane instances on the local network.
+ * auto-discover X-Plane instances on the local network. The EFB plugin sends
+ * its own Beacon to the same group while no tablet is connected, which also
+ * carries its ports and the loaded aircraft.
  *
  * Requires a [WifiManager.MulticastLock] to receive multicast on Android.
  * Timeout: 5 seconds per [start] call.
@@ -36,7 +38,7 @@ class BecnDiscovery(
                 withTimeoutOrNull(5_000L) {
                     while (true) {
                         socket.receive(packet)
-                        parseBecn(packet)?.let { onFound(it) }
+                        (parseBecn(packet) ?: parsePluginBeacon(packet))?.let { onFound(it) }
                     }
                 }
                 socket.close()
@@ -74,6 +76,16 @@ class BecnDiscovery(
         )
     }
 
+    private fun parsePluginBeacon(packet: DatagramPacket): XplaneInstance? {
+        val beacon = EfbProtocol.decodeBeacon(packet.data, packet.length) ?: return null
+        return XplaneInstance(
+            address = packet.address,
+            port = beacon.streamPort,
+            name = beacon.hostname,
+            aircraftIcao = beacon.aircraftIcao.ifEmpty { null },
+        )
+    }
+
     private fun extractNullTerminatedString(data: ByteArray, start: Int, limit: Int): String {
         val end = (start until limit).firstOrNull { data[it] == 0.toByte() } ?: limit
         return String(data, start, end - start, Charsets.US_ASCII)
@@ -84,4 +96,6 @@ data class XplaneInstance(
     val address: InetAddress,
     val port: Int,
     val name: String,
+    /** Known only when discovered through the EFB plugin's Beacon. */
+    val aircraftIcao: String? = null,
 )
@@ -28,6 +28,14 @@ class DataSourceManager(
     private val _simData = MutableStateFlow<SimSnapshot?>(null)
     val simData: StateFlow<SimSnapshot?> = _simData.asStateFlow()
 
+    private val _traffic = MutableStateFlow<List<TrafficTarget>>(emptyList())
+
+    /**
+     * TCAS targets of the active source: from plugin Traffic packets once the
+     * plugin sends them, otherwise the position-only snapshot arrays.
+     */
+    val traffic: StateFlow<List<TrafficTarget>> = _traffic.asStateFlow()
+
     private val _status = MutableStateFlow(
         ConnectionStatus(
             state = ConnectionStatus.State.DISCONNECTED,
@@ -44,6 +52,7 @@ class DataSourceManager(
         scope = scope,
         onSnapshot = { onPacketReceived(DataSource.PLUGIN, it) },
         onStatusChange = { /* future: track plugin connect/disconnect */ },
+        onTraffic = { onTrafficReceived(it) },
     )
 
     internal val gdl90Receiver = Gdl90Receiver(
@@ -87,9 +96,16 @@ class DataSourceManager(
         if (source.priority >= activeSource.priority) {
             activeSource = source
             _simData.value = snapshot
+            if (source != DataSource.PLUGIN || !pluginReceiver.extendedTraffic) {
+                _traffic.value = TrafficTarget.fromSnapshot(snapshot)
+            }
         }
     }
 
+    private fun onTrafficReceived(targets: List<TrafficTarget>) {
+        if (activeSource == DataSource.PLUGIN) _traffic.value = targets
+    }
+
     private suspend fun monitorSourceHealth() {
         while (true) {
             delay(100)
@@ -16,17 +16,32 @@ import java.util.concurrent.atomic.AtomicInteger
  * [9..13]  sequence    : u32
  * [13..17] checksum    : u32  CRC-32 of payload
  * ```
- * Payload is a serialized [SimSnapshot] (464 bytes) for SimData packets.
+ * Payload is a serialized [SimSnapshot] (464 bytes) for SimData packets,
+ * encoded by the generated [SimSnapshotCodec].
+ *
+ * Of the v2 features only capabilities that work over v1 headers are used:
+ * the tablet sends [buildHello] to opt in to Traffic packets.
  */
 object EfbProtocol {
 
     const val MAGIC: Long = 0xEFB12345L
     const val VERSION: Int = 1
     const val HEADER_LEN: Int = 17
-    const val PAYLOAD_LEN: Int = 464
+    const val PAYLOAD_LEN: Int = SimSnapshotCodec.PAYLOAD_LEN
 
     private const val PACKET_SIM_DATA: Byte = 0x01
     private const val PACKET_ACK: Byte = 0x03
+    private const val PACKET_HELLO: Byte = 0x07
+    private const val PACKET_HELLO_ACK: Byte = 0x08
+    private const val PACKET_FRAGMENT: Byte = 0x0A
+    private const val PACKET_BEACON: Byte = 0x0C
+    internal const val PACKET_TRAFFIC: Byte = 0x0F
+
+    /** Hello capability bit for variable-length Traffic packets. */
+    const val CAP_EXTENDED_TRAFFIC: Int = 1 shl 2
+
+    /** Fixed part of a Traffic target, before its callsign. */
+    private const val TRAFFIC_TARGET_LEN = 42
 
     private val sequence = AtomicInteger(0)
 
@@ -35,29 +50,111 @@ object EfbProtocol {
      * valid SimData frame, or null for any validation failure (wrong magic,
      * version mismatch, bad checksum, truncated payload, non-SimData type).
      */
-    fun decode(buf: ByteArray, len: Int): SimSnapshot? {
-        if (len < HEADER_LEN) return null
-        val bb = ByteBuffer.wrap(buf, 0, len).order(ByteOrder.LITTLE_ENDIAN)
+    fun decode(buf: ByteArray, len: Int): SimSnapshot? =
+        validPayload(buf, len, PACKET_SIM_DATA)?.let { deserializeSnapshot(it) }
 
-        val magic = bb.int.toLong() and 0xFFFFFFFFL
-        if (magic != MAGIC) return null
-
-        val version = bb.short.toInt() and 0xFFFF
-        if (version != VERSION) return null
+    /**
+     * Decode a plugin discovery Beacon. Returns null for anything else,
+     * including X-Plane's own BECN packets that share the multicast group.
+     *
+     * Payload: min/max version u16, capabilities u32, stream/command/TCP port
+     * u16 each, then plugin version, hostname and aircraft ICAO as
+     * length-prefixed (u8) UTF-8 strings.
+     */
+    fun decodeBeacon(buf: ByteArray, len: Int): PluginBeacon? {
+        val payload = validPayload(buf, len, PACKET_BEACON) ?: return null
+        if (payload.size < 14) return null
+        val bb = ByteBuffer.wrap(payload).order(ByteOrder.LITTLE_ENDIAN)
+        val minVersion   = bb.short.toInt() and 0xFFFF
+        val maxVersion   = bb.short.toInt() and 0xFFFF
+        val capabilities = bb.int
+        val streamPort   = bb.short.toInt() and 0xFFFF
+        val commandPort  = bb.short.toInt() and 0xFFFF
+        val tcpPort      = bb.short.toInt() and 0xFFFF
+        val strings = List(3) {
+            if (!bb.hasRemaining()) return null
+            val n = bb.get().toInt() and 0xFF
+            if (bb.remaining() < n) return null
+            ByteArray(n).also { bb.get(it) }.toString(Charsets.UTF_8)
+        }
+        return PluginBeacon(
+            minVersion    = minVersion,
+            maxVersion    = maxVersion,
+            capabilities  = capabilities,
+            streamPort    = streamPort,
+            commandPort   = commandPort,
+            tcpPort       = tcpPort,
+            pluginVersion = strings[0],
+            hostname      = strings[1],
+            aircraftIcao  = strings[2],
+        )
+    }
 
-        val packetType = bb.get()
-        if (packetType != PACKET_SIM_DATA) return null
+    /**
+     * Decode a Traffic packet into its targets, or null for anything else.
+     * Lists longer than one datagram arrive as Fragments; see [FragmentAssembler].
+     */
+    fun decodeTraffic(buf: ByteArray, len: Int): List<TrafficTarget>? =
+        validPayload(buf, len, PACKET_TRAFFIC)?.let { decodeTrafficPayload(it) }
 
-        val payloadLen = bb.short.toInt() and 0xFFFF
-        if (payloadLen > 65535 || len < HEADER_LEN + payloadLen) return null
+    /**
+     * Traffic payload: count u8, then per target ICAO address u32, lat/lon/alt
+     * f64, track/groundspeed/vertical speed f32, flags u8 (bit 0 on ground),
+     * threat u8 and a length-prefixed (u8) UTF-8 callsign. Trailing bytes are
+     * ignored.
+     */
+    internal fun decodeTrafficPayload(payload: ByteArray): List<TrafficTarget>? {
+        if (payload.isEmpty()) return null
+        val bb = ByteBuffer.wrap(payload).order(ByteOrder.LITTLE_ENDIAN)
+        val count = bb.get().toInt() and 0xFF
+        return List(count) {
+            if (bb.remaining() < TRAFFIC_TARGET_LEN + 1) return null
+            val icao      = bb.int
+            val lat       = bb.double
+            val lon       = bb.double
+            val altM      = bb.double
+            val track     = bb.float
+            val gs        = bb.float
+            val vs        = bb.float
+            val flags     = bb.get().toInt()
+            val threat    = ThreatLevel.fromWire(bb.get().toInt() and 0xFF) ?: return null
+            val n = bb.get().toInt() and 0xFF
+            if (bb.remaining() < n) return null
+            TrafficTarget(
+                icaoAddress      = icao,
+                callsign         = ByteArray(n).also { bb.get(it) }.toString(Charsets.UTF_8),
+                latitude         = lat,
+                longitude        = lon,
+                altitudeM        = altM,
+                trackDeg         = track,
+                groundspeedKt    = gs,
+                verticalSpeedFpm = vs,
+                onGround         = flags and 1 != 0,
+                threat           = threat,
+            )
+        }
+    }
 
-        bb.getInt()  // sequence (unused in decode)
-        val checksum = bb.int.toLong() and 0xFFFFFFFFL
+    /** Payload of a Fragment packet, for a [FragmentAssembler]. */
+    fun decodeFragment(buf: ByteArray, len: Int): ByteArray? = validPayload(buf, len, PACKET_FRAGMENT)
 
-        val payload = ByteArray(payloadLen).also { bb.get(it) }
-        if (crc32(payload) != checksum) return null
+    /**
+     * Build a Hello asking for protocol v1 with [capabilities]. The plugin
+     * answers with a HelloAck holding the capabilities it enabled.
+     */
+    fun buildHello(capabilities: Int, seq: Int = sequence.getAndIncrement()): ByteArray {
+        val payload = ByteBuffer.allocate(8).order(ByteOrder.LITTLE_ENDIAN)
+            .putShort(VERSION.toShort())
+            .putShort(VERSION.toShort())
+            .putInt(capabilities)
+        return buildPacket(seq, PACKET_HELLO, payload.array())
+    }
 
-        return deserializeSnapshot(payload)
+    /** Capabilities enabled by a HelloAck, or null for anything else. */
+    fun decodeHelloAck(buf: ByteArray, len: Int): Int? {
+        val payload = validPayload(buf, len, PACKET_HELLO_ACK) ?: return null
+        if (payload.size < 6) return null
+        return ByteBuffer.wrap(payload, 2, 4).order(ByteOrder.LITTLE_ENDIAN).int
     }
 
     /**
@@ -71,11 +168,40 @@ object EfbProtocol {
     /**
      * Build a minimal ACK datagram (empty payload) for the plugin watchdog.
      */
-    fun buildAck(seq: Int = 0): ByteArray = buildPacket(seq, PACKET_ACK, ByteArray(0))
+    fun buildAck(seq: Int = sequence.getAndIncrement()): ByteArray = buildPacket(seq, PACKET_ACK, ByteArray(0))
 
     // ── Internal helpers ──────────────────────────────────────────────────────
 
-    private fun buildPacket(seq: Int, type: Byte, payload: ByteArray): ByteArray {
+    /**
+     * Payload of a datagram of the given type, or null for any validation
+     * failure (wrong magic, version mismatch, bad checksum, truncated payload,
+     * other packet type). Trailing bytes such as an auth tag are ignored.
+     */
+    private fun validPayload(buf: ByteArray, len: Int, type: Byte): ByteArray? {
+        if (len < HEADER_LEN) return null
+        val bb = ByteBuffer.wrap(buf, 0, len).order(ByteOrder.LITTLE_ENDIAN)
+
+        val magic = bb.int.toLong() and 0xFFFFFFFFL
+        if (magic != MAGIC) return null
+
+        val version = bb.short.toInt() and 0xFFFF
+        if (version != VERSION) return null
+
+        val packetType = bb.get()
+        if (packetType != type) return null
+
+        val payloadLen = bb.short.toInt() and 0xFFFF
+        if (payloadLen > 65535 || len < HEADER_LEN + payloadLen) return null
+
+        bb.getInt()  // sequence (unused in decode)
+        val checksum = bb.int.toLong() and 0xFFFFFFFFL
+
+        val payload = ByteArray(payloadLen).also { bb.get(it) }
+        if (crc32(payload) != checksum) return null
+        return payload
+    }
+
+    internal fun buildPacket(seq: Int, type: Byte, payload: ByteArray): ByteArray {
         val checksum = crc32(payload)
         val buf = ByteBuffer.allocate(HEADER_LEN + payload.size).order(ByteOrder.LITTLE_ENDIAN)
         buf.putInt(MAGIC.toInt())
@@ -90,131 +216,13 @@ object EfbProtocol {
 
     private fun serializeSnapshot(s: SimSnapshot): ByteArray {
         val bb = ByteBuffer.allocate(PAYLOAD_LEN).order(ByteOrder.LITTLE_ENDIAN)
-        // Position
-        bb.putDouble(s.latitude)
-        bb.putDouble(s.longitude)
-        bb.putDouble(s.elevationM)
-        bb.putFloat(s.groundspeedMs)
-        // Attitude
-        bb.putFloat(s.pitchDeg)
-        bb.putFloat(s.rollDeg)
-        bb.putFloat(s.magHeadingDeg)
-        bb.putFloat(s.groundTrackDeg)
-        // Air data
-        bb.putFloat(s.iasKts)
-        bb.putFloat(s.tasKts)
-        bb.putFloat(s.vviFpm)
-        bb.putFloat(s.turnRateDegSec)
-        bb.putFloat(s.slipDeg)
-        bb.putFloat(s.oatDegc)
-        bb.putFloat(s.barometerInhg)
-        // Engine
-        bb.putFloat(s.rpm)
-        bb.putFloat(s.mapInhg)
-        bb.putFloat(s.fuelFlowKgSec)
-        bb.putFloat(s.oilPressPsi)
-        bb.putFloat(s.oilTempDegc)
-        for (x in s.egtDegc) bb.putFloat(x)
-        for (x in s.fuelQtyKg) bb.putFloat(x)
-        bb.putFloat(s.busVolts)
-        bb.putFloat(s.batteryAmps)
-        bb.putFloat(s.suctionInhg)
-        // Navigation
-        bb.putFloat(s.nav1HdefDot)
-        bb.putFloat(s.nav1VdefDot)
-        bb.putFloat(s.nav1ObsDeg)
-        bb.putFloat(s.gpsDistNm)
-        bb.putFloat(s.gpsBearingDeg)
-        // Autopilot
-        bb.putInt(s.apStateFlags)
-        bb.putFloat(s.fdPitchDeg)
-        bb.putFloat(s.fdRollDeg)
-        bb.putFloat(s.apHeadingBugDeg)
-        bb.putFloat(s.apAltitudeFt)
-        bb.putFloat(s.apVsFpm)
-        // Radios
-        bb.putInt(s.com1ActiveHz)
-        bb.putInt(s.com1StandbyHz)
-        bb.putInt(s.com2ActiveHz)
-        bb.putInt(s.nav1ActiveHz)
-        bb.putInt(s.nav1StandbyHz)
-        bb.putInt(s.transponderCode)
-        bb.putInt(s.transponderMode)
-        // Markers (bool as u8)
-        bb.put(if (s.outerMarker) 1 else 0)
-        bb.put(if (s.middleMarker) 1 else 0)
-        bb.put(if (s.innerMarker) 1 else 0)
-        // Weather
-        bb.putFloat(s.windDirDeg)
-        bb.putFloat(s.windSpeedKt)
-        // Traffic
-        for (x in s.trafficLat)  bb.putFloat(x)
-        for (x in s.trafficLon)  bb.putFloat(x)
-        for (x in s.trafficEleM) bb.putFloat(x)
-        bb.put(s.trafficCount.toByte())
-        // HSI
-        bb.putInt(s.hsiSource)
+        SimSnapshotCodec.encode(s, bb)
         return bb.array()
     }
 
     internal fun deserializeSnapshot(payload: ByteArray): SimSnapshot? {
         if (payload.size < PAYLOAD_LEN) return null
-        val bb = ByteBuffer.wrap(payload).order(ByteOrder.LITTLE_ENDIAN)
-        return SimSnapshot(
-            latitude          = bb.double,
-            longitude         = bb.double,
-            elevationM        = bb.double,
-            groundspeedMs     = bb.float,
-            pitchDeg          = bb.float,
-            rollDeg           = bb.float,
-            magHeadingDeg     = bb.float,
-            groundTrackDeg    = bb.float,
-            iasKts            = bb.float,
-            tasKts            = bb.float,
-            vviFpm            = bb.float,
-            turnRateDegSec    = bb.float,
-            slipDeg           = bb.float,
-            oatDegc           = bb.float,
-            barometerInhg     = bb.float,
-            rpm               = bb.float,
-            mapInhg           = bb.float,
-            fuelFlowKgSec     = bb.float,
-            oilPressPsi       = bb.float,
-            oilTempDegc       = bb.float,
-            egtDegc           = FloatArray(6) { bb.float },
-            fuelQtyKg         = FloatArray(2) { bb.float },
-            busVolts          = bb.float,
-            batteryAmps       = bb.float,
-            suctionInhg       = bb.float,
-            nav1HdefDot       = bb.float,
-            nav1VdefDot       = bb.float,
-            nav1ObsDeg        = bb.float,
-            gpsDistNm         = bb.float,
-            gpsBearingDeg     = bb.float,
-            apStateFlags      = bb.int,
-            fdPitchDeg        = bb.float,
-            fdRollDeg         = bb.float,
-            apHeadingBugDeg   = bb.float,
-            apAltitudeFt      = bb.float,
-            apVsFpm           = bb.float,
-            com1ActiveHz      = bb.int,
-            com1StandbyHz     = bb.int,
-            com2ActiveHz      = bb.int,
-            nav1ActiveHz      = bb.int,
-            nav1StandbyHz     = bb.int,
-            transponderCode   = bb.int,
-            transponderMode   = bb.int,
-            outerMarker       = bb.get().toInt() != 0,
-            middleMarker      = bb.get().toInt() != 0,
-            innerMarker       = bb.get().toInt() != 0,
-            windDirDeg        = bb.float,
-            windSpeedKt       = bb.float,
-            trafficLat        = FloatArray(20) { bb.float },
-            trafficLon        = FloatArray(20) { bb.float },
-            trafficEleM       = FloatArray(20) { bb.float },
-            trafficCount      = bb.get().toInt() and 0xFF,
-            hsiSource         = bb.int,
-        )
+        return SimSnapshotCodec.decode(ByteBuffer.wrap(payload).order(ByteOrder.LITTLE_ENDIAN))
     }
 
     // ── CRC-32 (ISO 3309 / Ethernet polynomial 0xEDB88320) ───────────────────
@@ -231,3 +239,51 @@ object EfbProtocol {
         return crc.inv() and 0xFFFFFFFFL
     }
 }
+
+/**
+ * Reassembles Fragment packets into the message they carry. The plugin sends
+ * the fragments of a message back to back, so only one message is kept: a
+ * fragment of another message drops an incomplete one.
+ *
+ * Fragment payload: message id u32, index u16, count u16, inner packet type
+ * u8, then the chunk.
+ */
+class FragmentAssembler {
+    private var messageId = 0
+    private var chunks: Array<ByteArray?> = emptyArray()
+
+    /** Add one fragment; returns (packet type, payload) once the message is complete. */
+    fun push(fragment: ByteArray): Pair<Byte, ByteArray>? {
+        if (fragment.size < 9) return null
+        val bb = ByteBuffer.wrap(fragment).order(ByteOrder.LITTLE_ENDIAN)
+        val id    = bb.int
+        val index = bb.short.toInt() and 0xFFFF
+        val count = bb.short.toInt() and 0xFFFF
+        val type  = bb.get()
+        if (index >= count) return null
+        if (id != messageId || chunks.size != count) {
+            messageId = id
+            chunks = arrayOfNulls(count)
+        }
+        chunks[index] = fragment.copyOfRange(9, fragment.size)
+        if (chunks.any { it == null }) return null
+        val message = chunks.fold(ByteArray(0)) { acc, c -> acc + c!! }
+        chunks = emptyArray()
+        return type to message
+    }
+}
+
+/** Discovery announcement multicast by the plugin while no tablet is connected. */
+data class PluginBeacon(
+    val minVersion: Int,
+    val maxVersion: Int,
+    val capabilities: Int,
+    val streamPort: Int,
+    val commandPort: Int,
+    /** 0 when the plugin's TCP transport is not listening. */
+    val tcpPort: Int,
+    val pluginVersion: String,
+    val hostname: String,
+    /** ICAO type of the loaded aircraft, empty if unknown. */
+    val aircraftIcao: String,
+)
@@ -0,0 +1,61 @@
+package com.nameless.efb.data.connectivity
+
+/**
+ * JNI bridge to the Rust efb-protocol codec (`libefb_protocol_ffi.so`, built
+ * from `plugin/efb-protocol-ffi`).
+ *
+ * Unlike [EfbProtocol], which mirrors the v1 codec by hand, this decodes
+ * every header version and compressed payloads exactly as the plugin
+ * encodes them. Native calls return a status code: 0 on success, negative
+ * on error (see [statusMessage]).
+ *
+ * [available] is false when the library is not packaged for the running
+ * ABI, or was built from a snapshot layout other than the generated
+ * [SimSnapshotCodec]; callers should fall back to [EfbProtocol].
+ */
+object NativeCodec {
+
+    /** Number of slots filled by [decodePacket]. */
+    const val HEADER_FIELDS = 8
+
+    const val STATUS_OK = 0
+
+    val available: Boolean = try {
+        System.loadLibrary("efb_protocol_ffi")
+        layoutChecksum() == SimSnapshotCodec.LAYOUT_CHECKSUM
+    } catch (e: UnsatisfiedLinkError) {
+        false
+    }
+
+    /**
+     * Validate a datagram and fill [header] with version, packet type, flags,
+     * sequence, sim time (µs), send time (µs), payload offset and payload
+     * length.
+     */
+    external fun decodePacket(buf: ByteArray, len: Int, header: LongArray): Int
+
+    /**
+     * Decode a SimData or Keyframe datagram into [out] as an uncompressed
+     * [EfbProtocol.PAYLOAD_LEN]-byte payload.
+     */
+    external fun decodeSimData(buf: ByteArray, len: Int, out: ByteArray): Int
+
+    /**
+     * Encode a command in the [CommandSink] JSON format as a CommandBinary
+     * ([binary] true) or CommandJson datagram. Returns null for an invalid
+     * command.
+     */
+    external fun encodeCommand(seq: Int, json: String, binary: Boolean): ByteArray?
+
+    /** `SNAPSHOT_LAYOUT_CHECKSUM` of the Rust build, unsigned. */
+    external fun layoutChecksum(): Long
+
+    external fun statusMessage(status: Int): String
+
+    /** Decode a SimData datagram, or null for any error. */
+    fun decode(buf: ByteArray, len: Int): SimSnapshot? {
+        val payload = ByteArray(EfbProtocol.PAYLOAD_LEN)
+        if (decodeSimData(buf, len, payload) != STATUS_OK) return null
+        return EfbProtocol.deserializeSnapshot(payload)
+    }
+}
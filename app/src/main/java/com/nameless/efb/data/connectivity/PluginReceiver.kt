@@ -14,12 +14,16 @@ import java.net.InetAddress
  *
  * For each valid [SimSnapshot] decoded by [EfbProtocol], invokes [onSnapshot]
  * and sends an ACK datagram back so the plugin watchdog doesn't pause streaming.
+ * Until the plugin answers with a HelloAck, each ACK is followed by a Hello
+ * asking for Traffic packets, whose targets are passed to [onTraffic].
+ * Plugins that predate Hello ignore it.
  */
 class PluginReceiver(
     private val scope: CoroutineScope,
     private val port: Int = 49100,
     private val onSnapshot: (SimSnapshot) -> Unit,
     private val onStatusChange: (Boolean) -> Unit,
+    private val onTraffic: (List<TrafficTarget>) -> Unit = {},
 ) {
     /** Epoch millis of the most recently received valid packet; 0 if none. */
     @Volatile var lastPacketTime: Long = 0L
@@ -28,6 +32,12 @@ class PluginReceiver(
     @Volatile private var pluginAddress: InetAddress? = null
     @Volatile private var pluginPort: Int = 49101
 
+    /** Whether the plugin has enabled Traffic packets for this tablet. */
+    @Volatile var extendedTraffic: Boolean = false
+        private set
+    @Volatile private var helloAcked = false
+
+    private val fragments = FragmentAssembler()
     private var socket: DatagramSocket? = null
     private var job: Job? = null
 
@@ -40,11 +50,16 @@ class PluginReceiver(
                 onStatusChange(true)
                 while (isActive) {
                     sock.receive(packet)
-                    val snapshot = EfbProtocol.decode(packet.data, packet.length) ?: continue
+                    val snapshot = EfbProtocol.decode(packet.data, packet.length)
+                    if (snapshot == null) {
+                        handleOtherPacket(packet)
+                        continue
+                    }
                     lastPacketTime = System.currentTimeMillis()
                     pluginAddress = packet.address
                     onSnapshot(snapshot)
                     sendAck(sock, packet.address, packet.port)
+                    if (!helloAcked) sendHello(sock, packet.address, packet.port)
                 }
             } catch (_: Exception) {
                 onStatusChange(false)
@@ -53,6 +68,8 @@ class PluginReceiver(
     }
 
     fun stop() {
+        helloAcked = false
+        extendedTraffic = false
         job?.cancel()
         socket?.close()
         socket = null
@@ -76,6 +93,25 @@ class PluginReceiver(
         }
     }
 
+    private fun handleOtherPacket(packet: DatagramPacket) {
+        val (buf, len) = packet.data to packet.length
+        EfbProtocol.decodeHelloAck(buf, len)?.let { caps ->
+            helloAcked = true
+            extendedTraffic = caps and EfbProtocol.CAP_EXTENDED_TRAFFIC != 0
+            return
+        }
+        EfbProtocol.decodeTraffic(buf, len)?.let { onTraffic(it); return }
+        val (type, message) = EfbProtocol.decodeFragment(buf, len)?.let { fragments.push(it) } ?: return
+        if (type == EfbProtocol.PACKET_TRAFFIC) {
+            EfbProtocol.decodeTrafficPayload(message)?.let(onTraffic)
+        }
+    }
+
+    private fun sendHello(sock: DatagramSocket, addr: InetAddress, replyPort: Int) {
+        val hello = EfbProtocol.buildHello(EfbProtocol.CAP_EXTENDED_TRAFFIC)
+        sock.send(DatagramPacket(hello, hello.size, addr, replyPort))
+    }
+
     private fun sendAck(sock: DatagramSocket, addr: InetAddress, replyPort: Int) {
         val ack = EfbProtocol.buildAck()
         sock.send(DatagramPacket(ack, ack.size, addr, replyPort))
@@ -1,87 +1,191 @@
+// Generated by efb-codegen from dataref-schema. Do not edit.
+// Regenerate with: cargo run -p efb-codegen -- --out <this file>
+
 package com.nameless.efb.data.connectivity
 
+import java.nio.ByteBuffer
+
 /**
- * Kotlin mirror of the Rust [SimSnapshot] struct in dataref-schema.
+ * Kotlin mirror of the Rust `SimSnapshot` struct in dataref-schema.
  *
- * Field order and names match the Rust definition exactly so that
- * [EfbProtocol.decode] can deserialize wire bytes into this class.
- *
- * Arrays ([FloatArray]) are used for fixed-size repeated fields to avoid
- * boxing overhead. Note: data class [copy] performs a shallow copy of arrays.
+ * Fields are in wire order. Arrays are primitive arrays to avoid boxing.
+ * Note: data class [copy] performs a shallow copy of arrays.
  */
 data class SimSnapshot(
-    // ── Position ──────────────────────────────────────────────────────────────
-    val latitude: Double = 0.0,
-    val longitude: Double = 0.0,
-    val elevationM: Double = 0.0,
-    val groundspeedMs: Float = 0f,
-
-    // ── Attitude ──────────────────────────────────────────────────────────────
-    val pitchDeg: Float = 0f,
-    val rollDeg: Float = 0f,
-    val magHeadingDeg: Float = 0f,
-    val groundTrackDeg: Float = 0f,
-
-    // ── Air data ──────────────────────────────────────────────────────────────
-    val iasKts: Float = 0f,
-    val tasKts: Float = 0f,
-    val vviFpm: Float = 0f,
-    val turnRateDegSec: Float = 0f,
-    val slipDeg: Float = 0f,
-    val oatDegc: Float = 15f,
-    val barometerInhg: Float = 29.92f,
-
-    // ── Engine (index 0) ──────────────────────────────────────────────────────
-    val rpm: Float = 0f,
-    val mapInhg: Float = 0f,
-    val fuelFlowKgSec: Float = 0f,
-    val oilPressPsi: Float = 0f,
-    val oilTempDegc: Float = 0f,
-    val egtDegc: FloatArray = FloatArray(6),
-    val fuelQtyKg: FloatArray = FloatArray(2),
-    val busVolts: Float = 0f,
-    val batteryAmps: Float = 0f,
-    val suctionInhg: Float = 0f,
-
-    // ── Navigation ────────────────────────────────────────────────────────────
-    val nav1HdefDot: Float = 0f,
-    val nav1VdefDot: Float = 0f,
-    val nav1ObsDeg: Float = 0f,
-    val gpsDistNm: Float = 0f,
-    val gpsBearingDeg: Float = 0f,
-
-    // ── Autopilot ─────────────────────────────────────────────────────────────
+    val latitude: Double = 0.0,                    // deg
+    val longitude: Double = 0.0,                   // deg
+    val elevationM: Double = 0.0,                  // m
+    val groundspeedMs: Float = 0f,                 // m/s
+    val pitchDeg: Float = 0f,                      // deg
+    val rollDeg: Float = 0f,                       // deg
+    val magHeadingDeg: Float = 0f,                 // deg
+    val groundTrackDeg: Float = 0f,                // deg
+    val iasKts: Float = 0f,                        // kt
+    val tasKts: Float = 0f,                        // kt
+    val vviFpm: Float = 0f,                        // ft/min
+    val turnRateDegSec: Float = 0f,                // deg/s
+    val slipDeg: Float = 0f,                       // deg
+    val oatDegc: Float = 15f,                      // degC
+    val barometerInhg: Float = 29.92f,             // inHg
+    val rpm: Float = 0f,                           // rpm
+    val mapInhg: Float = 0f,                       // inHg
+    val fuelFlowKgSec: Float = 0f,                 // kg/s
+    val oilPressPsi: Float = 0f,                   // psi
+    val oilTempDegc: Float = 0f,                   // degC
+    val egtDegc: FloatArray = FloatArray(6),       // degC
+    val fuelQtyKg: FloatArray = FloatArray(2),     // kg
+    val busVolts: Float = 0f,                      // V
+    val batteryAmps: Float = 0f,                   // A
+    val suctionInhg: Float = 0f,                   // inHg
+    val nav1HdefDot: Float = 0f,                   // dots
+    val nav1VdefDot: Float = 0f,                   // dots
+    val nav1ObsDeg: Float = 0f,                    // deg
+    val gpsDistNm: Float = 0f,                     // nm
+    val gpsBearingDeg: Float = 0f,                 // deg
     val apStateFlags: Int = 0,
-    val fdPitchDeg: Float = 0f,
-    val fdRollDeg: Float = 0f,
-    val apHeadingBugDeg: Float = 0f,
-    val apAltitudeFt: Float = 0f,
-    val apVsFpm: Float = 0f,
-
-    // ── Radios ────────────────────────────────────────────────────────────────
-    val com1ActiveHz: Int = 0,
-    val com1StandbyHz: Int = 0,
-    val com2ActiveHz: Int = 0,
-    val nav1ActiveHz: Int = 0,
-    val nav1StandbyHz: Int = 0,
+    val fdPitchDeg: Float = 0f,                    // deg
+    val fdRollDeg: Float = 0f,                     // deg
+    val apHeadingBugDeg: Float = 0f,               // deg
+    val apAltitudeFt: Float = 0f,                  // ft
+    val apVsFpm: Float = 0f,                       // ft/min
+    val com1ActiveHz: Int = 0,                     // Hz
+    val com1StandbyHz: Int = 0,                    // Hz
+    val com2ActiveHz: Int = 0,                     // Hz
+    val nav1ActiveHz: Int = 0,                     // Hz
+    val nav1StandbyHz: Int = 0,                    // Hz
     val transponderCode: Int = 0,
     val transponderMode: Int = 0,
-
-    // ── Markers ───────────────────────────────────────────────────────────────
     val outerMarker: Boolean = false,
     val middleMarker: Boolean = false,
     val innerMarker: Boolean = false,
+    val windDirDeg: Float = 0f,                    // deg
+    val windSpeedKt: Float = 0f,                   // kt
+    val trafficLat: FloatArray = FloatArray(20),   // deg
+    val trafficLon: FloatArray = FloatArray(20),   // deg
+    val trafficEleM: FloatArray = FloatArray(20),  // m
+    val trafficCount: Int = 0,                     // 0..255
+    val hsiSource: Int = 0,
+)
 
-    // ── Weather ───────────────────────────────────────────────────────────────
-    val windDirDeg: Float = 0f,
-    val windSpeedKt: Float = 0f,
+/** SimData payload codec for [SimSnapshot] (little-endian, no padding). */
+object SimSnapshotCodec {
 
-    // ── Traffic (up to 20 TCAS targets) ──────────────────────────────────────
-    val trafficLat: FloatArray = FloatArray(20),
-    val trafficLon: FloatArray = FloatArray(20),
-    val trafficEleM: FloatArray = FloatArray(20),
-    val trafficCount: Int = 0,    // Rust u8 → Kotlin Int (0..255)
+    /** Must equal `dataref_schema::SNAPSHOT_LAYOUT_CHECKSUM` of the plugin build. */
+    const val LAYOUT_CHECKSUM: Long = 0xEBF6C20BL
 
-    // ── HSI source ────────────────────────────────────────────────────────────
-    val hsiSource: Int = 0,
-)
+    const val PAYLOAD_LEN: Int = 464
+
+    /** Read a snapshot; [bb] must be little-endian with [PAYLOAD_LEN] bytes remaining. */
+    fun decode(bb: ByteBuffer): SimSnapshot = SimSnapshot(
+        latitude        = bb.double,
+        longitude       = bb.double,
+        elevationM      = bb.double,
+        groundspeedMs   = bb.float,
+        pitchDeg        = bb.float,
+        rollDeg         = bb.float,
+        magHeadingDeg   = bb.float,
+        groundTrackDeg  = bb.float,
+        iasKts          = bb.float,
+        tasKts          = bb.float,
+        vviFpm          = bb.float,
+        turnRateDegSec  = bb.float,
+        slipDeg         = bb.float,
+        oatDegc         = bb.float,
+        barometerInhg   = bb.float,
+        rpm             = bb.float,
+        mapInhg         = bb.float,
+        fuelFlowKgSec   = bb.float,
+        oilPressPsi     = bb.float,
+        oilTempDegc     = bb.float,
+        egtDegc         = FloatArray(6) { bb.float },
+        fuelQtyKg       = FloatArray(2) { bb.float },
+        busVolts        = bb.float,
+        batteryAmps     = bb.float,
+        suctionInhg     = bb.float,
+        nav1HdefDot     = bb.float,
+        nav1VdefDot     = bb.float,
+        nav1ObsDeg      = bb.float,
+        gpsDistNm       = bb.float,
+        gpsBearingDeg   = bb.float,
+        apStateFlags    = bb.int,
+        fdPitchDeg      = bb.float,
+        fdRollDeg       = bb.float,
+        apHeadingBugDeg = bb.float,
+        apAltitudeFt    = bb.float,
+        apVsFpm         = bb.float,
+        com1ActiveHz    = bb.int,
+        com1StandbyHz   = bb.int,
+        com2ActiveHz    = bb.int,
+        nav1ActiveHz    = bb.int,
+        nav1StandbyHz   = bb.int,
+        transponderCode = bb.int,
+        transponderMode = bb.int,
+        outerMarker     = bb.get().toInt() != 0,
+        middleMarker    = bb.get().toInt() != 0,
+        innerMarker     = bb.get().toInt() != 0,
+        windDirDeg      = bb.float,
+        windSpeedKt     = bb.float,
+        trafficLat      = FloatArray(20) { bb.float },
+        trafficLon      = FloatArray(20) { bb.float },
+        trafficEleM     = FloatArray(20) { bb.float },
+        trafficCount    = bb.get().toInt() and 0xFF,
+        hsiSource       = bb.int,
+    )
+
+    /** Write [s]; [bb] must be little-endian with [PAYLOAD_LEN] bytes remaining. */
+    fun encode(s: SimSnapshot, bb: ByteBuffer) {
+        bb.putDouble(s.latitude)
+        bb.putDouble(s.longitude)
+        bb.putDouble(s.elevationM)
+        bb.putFloat(s.groundspeedMs)
+        bb.putFloat(s.pitchDeg)
+        bb.putFloat(s.rollDeg)
+        bb.putFloat(s.magHeadingDeg)
+        bb.putFloat(s.groundTrackDeg)
+        bb.putFloat(s.iasKts)
+        bb.putFloat(s.tasKts)
+        bb.putFloat(s.vviFpm)
+        bb.putFloat(s.turnRateDegSec)
+        bb.putFloat(s.slipDeg)
+        bb.putFloat(s.oatDegc)
+        bb.putFloat(s.barometerInhg)
+        bb.putFloat(s.rpm)
+        bb.putFloat(s.mapInhg)
+        bb.putFloat(s.fuelFlowKgSec)
+        bb.putFloat(s.oilPressPsi)
+        bb.putFloat(s.oilTempDegc)
+        for (x in s.egtDegc) bb.putFloat(x)
+        for (x in s.fuelQtyKg) bb.putFloat(x)
+        bb.putFloat(s.busVolts)
+        bb.putFloat(s.batteryAmps)
+        bb.putFloat(s.suctionInhg)
+        bb.putFloat(s.nav1HdefDot)
+        bb.putFloat(s.nav1VdefDot)
+        bb.putFloat(s.nav1ObsDeg)
+        bb.putFloat(s.gpsDistNm)
+        bb.putFloat(s.gpsBearingDeg)
+        bb.putInt(s.apStateFlags)
+        bb.putFloat(s.fdPitchDeg)
+        bb.putFloat(s.fdRollDeg)
+        bb.putFloat(s.apHeadingBugDeg)
+        bb.putFloat(s.apAltitudeFt)
+        bb.putFloat(s.apVsFpm)
+        bb.putInt(s.com1ActiveHz)
+        bb.putInt(s.com1StandbyHz)
+        bb.putInt(s.com2ActiveHz)
+        bb.putInt(s.nav1ActiveHz)
+        bb.putInt(s.nav1StandbyHz)
+        bb.putInt(s.transponderCode)
+        bb.putInt(s.transponderMode)
+        bb.put(if (s.outerMarker) 1 else 0)
+        bb.put(if (s.middleMarker) 1 else 0)
+        bb.put(if (s.innerMarker) 1 else 0)
+        bb.putFloat(s.windDirDeg)
+        bb.putFloat(s.windSpeedKt)
+        for (x in s.trafficLat) bb.putFloat(x)
+        for (x in s.trafficLon) bb.putFloat(x)
+        for (x in s.trafficEleM) bb.putFloat(x)
+        bb.put(s.trafficCount.toByte())
+        bb.putInt(s.hsiSource)
+    }
+}
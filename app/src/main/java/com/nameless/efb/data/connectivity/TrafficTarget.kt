@@ -0,0 +1,62 @@
+package com.nameless.efb.data.connectivity
+
+/**
+ * TCAS classification of a [TrafficTarget] worked out by the plugin.
+ * Declaration order matches the wire values 0..3.
+ */
+enum class ThreatLevel {
+    NONE,
+    /** Within 6 nm and ±1200 ft. */
+    PROXIMATE,
+    TRAFFIC_ADVISORY,
+    RESOLUTION_ADVISORY;
+
+    companion object {
+        fun fromWire(value: Int): ThreatLevel? = values().getOrNull(value)
+    }
+}
+
+/**
+ * One aircraft from a plugin Traffic packet.
+ *
+ * @param icaoAddress 24-bit Mode S address, 0 if unknown.
+ * @param callsign    Flight id, empty if unknown.
+ * @param altitudeM   Metres MSL.
+ * @param threat      Null for targets built by [fromSnapshot], which carry
+ *                    position only.
+ */
+data class TrafficTarget(
+    val icaoAddress: Int,
+    val callsign: String,
+    val latitude: Double,
+    val longitude: Double,
+    val altitudeM: Double,
+    val trackDeg: Float,
+    val groundspeedKt: Float,
+    val verticalSpeedFpm: Float,
+    val onGround: Boolean,
+    val threat: ThreatLevel?,
+) {
+    companion object {
+        /**
+         * Position-only targets from the fixed [SimSnapshot] traffic arrays,
+         * for plugins that do not send Traffic packets.
+         */
+        fun fromSnapshot(snapshot: SimSnapshot): List<TrafficTarget> =
+            List(snapshot.trafficCount.coerceIn(0, 20)) { fromSnapshot(snapshot, it) }
+
+        /** The target at [index] of the snapshot traffic arrays. */
+        fun fromSnapshot(snapshot: SimSnapshot, index: Int) = TrafficTarget(
+            icaoAddress      = 0,
+            callsign         = "",
+            latitude         = snapshot.trafficLat[index].toDouble(),
+            longitude        = snapshot.trafficLon[index].toDouble(),
+            altitudeM        = snapshot.trafficEleM[index].toDouble(),
+            trackDeg         = 0f,
+            groundspeedKt    = 0f,
+            verticalSpeedFpm = 0f,
+            onGround         = false,
+            threat           = null,
+        )
+    }
+}
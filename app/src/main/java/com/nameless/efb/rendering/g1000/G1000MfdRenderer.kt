@@ -3,6 +3,7 @@ package com.nameless.efb.rendering.g1000
 import android.content.res.AssetManager
 import android.opengl.GLES30
 import com.nameless.efb.data.connectivity.SimSnapshot
+import com.nameless.efb.data.connectivity.TrafficTarget
 import com.nameless.efb.domain.flightplan.FlightPlan
 import com.nameless.efb.rendering.g1000.mfd.AlertLevel
 import com.nameless.efb.rendering.g1000.mfd.AuxPageRenderer
@@ -50,12 +51,15 @@ private const val EIS_WIDTH_PX = 200
  * @param simData     Live sim state from [DataSourceManager].
  * @param mapRenderer Shared [MapRenderer] instance (also used by PFD inset map).
  * @param theme       Initial rendering theme.
+ * @param traffic     TCAS targets from [DataSourceManager.traffic]; when null
+ *                    the snapshot traffic arrays are drawn.
  */
 class G1000MfdRenderer(
     assets: AssetManager,
     private val simData: StateFlow<SimSnapshot?>,
     private val mapRenderer: MapRenderer? = null,
     theme: Theme = Theme.DAY,
+    private val traffic: StateFlow<List<TrafficTarget>>? = null,
 ) : BaseRenderer(assets, theme) {
 
     // ── Page management ───────────────────────────────────────────────────────
@@ -237,8 +241,8 @@ class G1000MfdRenderer(
             GLES30.glUniform4f(colorLoc, 0f, 1f, 0f, 1f)
             drawFilledQuad(-0.04f, -0.04f, 0.08f, 0.08f)
             // Draw each traffic target.
-            for (i in 0 until snap.trafficCount.coerceAtMost(20)) {
-                val alert = trafficRenderer.getAlertLevel(snap, i)
+            for (target in traffic?.value ?: TrafficTarget.fromSnapshot(snap)) {
+                val alert = trafficRenderer.getAlertLevel(snap, target)
                 val (r, g, b) = when (alert) {
                     AlertLevel.RA    -> Triple(1f, 0f, 0f)
                     AlertLevel.TA    -> Triple(1f, 0.749f, 0f)
@@ -1,6 +1,8 @@
 package com.nameless.efb.rendering.g1000.mfd
 
 import com.nameless.efb.data.connectivity.SimSnapshot
+import com.nameless.efb.data.connectivity.ThreatLevel
+import com.nameless.efb.data.connectivity.TrafficTarget
 import com.nameless.efb.domain.nav.GreatCircle
 import com.nameless.efb.domain.nav.LatLon
 import kotlin.math.abs
@@ -8,8 +10,9 @@ import kotlin.math.abs
 /**
  * G1000 MFD Traffic page renderer (G-18).
  *
- * Renders all TCAS targets from [SimSnapshot.trafficLat/Lon/EleM] with
- * colour-coded TA (amber) and RA (red) alert levels.
+ * Renders TCAS targets with colour-coded TA (amber) and RA (red) alert
+ * levels: from plugin Traffic packets when available, otherwise from
+ * [SimSnapshot.trafficLat/Lon/EleM].
  *
  * Layout:
  *  - Ownship centred (or offset forward)
@@ -21,24 +24,37 @@ import kotlin.math.abs
 class TrafficPageRenderer {
 
     /**
-     * Computes the [AlertLevel] for the traffic target at [index].
+     * Computes the [AlertLevel] for the traffic target at [index] of the
+     * snapshot traffic arrays.
+     */
+    fun getAlertLevel(snapshot: SimSnapshot, index: Int): AlertLevel =
+        getAlertLevel(snapshot, TrafficTarget.fromSnapshot(snapshot, index))
+
+    /**
+     * Computes the [AlertLevel] for [target]. The plugin's TCAS threat level
+     * is used when the target has one.
      *
-     * Thresholds per G1000 CRG:
+     * Otherwise, thresholds per G1000 CRG:
      *  - RA: lateral < 0.2 nm AND vertical < 100 ft
      *  - TA: lateral < 0.5 nm AND vertical < 200 ft
      *  - OTHER: all other proximate traffic
      *
-     * @param snapshot  Current sim snapshot (ownship position + traffic array).
-     * @param index     Index into the traffic arrays (0..[SimSnapshot.trafficCount]).
+     * @param snapshot  Current sim snapshot (ownship position).
      */
-    fun getAlertLevel(snapshot: SimSnapshot, index: Int): AlertLevel {
+    fun getAlertLevel(snapshot: SimSnapshot, target: TrafficTarget): AlertLevel {
+        when (target.threat) {
+            ThreatLevel.RESOLUTION_ADVISORY -> return AlertLevel.RA
+            ThreatLevel.TRAFFIC_ADVISORY    -> return AlertLevel.TA
+            ThreatLevel.PROXIMATE, ThreatLevel.NONE -> return AlertLevel.OTHER
+            null -> {}
+        }
         val ownAltFt = snapshot.elevationM.toFloat() * 3.281f
-        val trafficAltFt = snapshot.trafficEleM[index] * 3.281f
+        val trafficAltFt = target.altitudeM.toFloat() * 3.281f
         val relAltFt = trafficAltFt - ownAltFt
 
         val dist = GreatCircle.distanceNm(
             LatLon(snapshot.latitude, snapshot.longitude),
-            LatLon(snapshot.trafficLat[index].toDouble(), snapshot.trafficLon[index].toDouble()),
+            LatLon(target.latitude, target.longitude),
         )
 
         return when {
@@ -51,18 +67,19 @@ class TrafficPageRenderer {
     /**
      * Draws the traffic page into the current GL viewport.
      *
-     * Called on the GL thread each frame. Iterates all traffic targets from
-     * [snapshot] and renders each as a colour-coded diamond with altitude tag.
+     * Called on the GL thread each frame. Iterates all [targets] and renders
+     * each as a colour-coded diamond with altitude tag.
      *
      * @param snapshot  Current sim state.
+     * @param targets   Traffic to draw; defaults to the snapshot arrays.
      */
-    fun draw(snapshot: SimSnapshot) {
+    fun draw(snapshot: SimSnapshot, targets: List<TrafficTarget> = TrafficTarget.fromSnapshot(snapshot)) {
         // OpenGL draw calls rendered in G1000MfdRenderer via EIS+page-area pipeline.
         // For each target: compute screen position relative to ownship, choose colour,
         // draw diamond symbol and altitude tag.
         val ownAltFt = snapshot.elevationM.toFloat() * 3.281f
-        for (i in 0 until snapshot.trafficCount.coerceAtMost(20)) {
-            val alert = getAlertLevel(snapshot, i)
+        for (target in targets) {
+            val alert = getAlertLevel(snapshot, target)
             // Colour: RA=red, TA=amber, OTHER=white — rendered by MfdRenderer draw calls.
             @Suppress("UNUSED_VARIABLE")
             val colour = when (alert) {
@@ -1,7 +1,8 @@
 package com.nameless.efb.rendering.map.overlay
 
 import android.opengl.GLES30
-import com.nameless.efb.data.connectivity.SimSnapshot
+import com.nameless.efb.data.connectivity.ThreatLevel
+import com.nameless.efb.data.connectivity.TrafficTarget
 import com.nameless.efb.domain.nav.WebMercator
 import com.nameless.efb.rendering.gl.GlBuffer
 import com.nameless.efb.rendering.gl.GlVao
@@ -10,10 +11,13 @@ import kotlin.math.roundToInt
 /**
  * Draws TCAS-style AI/multiplayer traffic symbols on the moving map (MM-14).
  *
- * Each target is a diamond shape coloured by threat level:
- *  - White:  non-threat (ΔAlt > ±1200 ft or far away)
- *  - Amber:  traffic advisory (TA) — within ±1200 ft and approaching
- *  - Red:    resolution advisory (RA) — within ±300 ft (simplified)
+ * Each target is a diamond shape coloured by the plugin's TCAS threat level:
+ *  - White:  other or proximate traffic
+ *  - Amber:  traffic advisory (TA)
+ *  - Red:    resolution advisory (RA)
+ *
+ * Targets without a threat level (from the snapshot arrays) fall back to
+ * relative altitude: red within ±300 ft, amber within ±1200 ft.
  *
  * A relative altitude label is rendered alongside each symbol.
  *
@@ -39,15 +43,14 @@ class TrafficOverlayRenderer {
     // ── Draw ──────────────────────────────────────────────────────────────────
 
     fun draw(
-        snapshot: SimSnapshot,
+        targets: List<TrafficTarget>,
         ownshipAltFt: Float,
         mvpUniformLoc: Int,
         colorUniformLoc: Int,
         viewMatrix: FloatArray,
     ) {
         val vaoObj = vao ?: return
-        val count  = snapshot.trafficCount.coerceIn(0, 20)
-        if (count == 0) return
+        if (targets.isEmpty()) return
 
         val mvp = FloatArray(16).also {
             android.opengl.Matrix.setIdentityM(it, 0)
@@ -55,17 +58,15 @@ class TrafficOverlayRenderer {
         }
         GLES30.glUniformMatrix4fv(mvpUniformLoc, 1, false, mvp, 0)
 
-        for (i in 0 until count) {
-            val lat    = snapshot.trafficLat[i].toDouble()
-            val lon    = snapshot.trafficLon[i].toDouble()
-            val altFt  = snapshot.trafficEleM[i] * 3.28084f
+        for (target in targets) {
+            val altFt  = target.altitudeM.toFloat() * 3.28084f
             val relAlt = (altFt - ownshipAltFt).roundToInt()
 
-            val worldPos = WebMercator.toMeters(lat, lon)
+            val worldPos = WebMercator.toMeters(target.latitude, target.longitude)
             val cx = worldPos[0].toFloat()
             val cy = worldPos[1].toFloat()
 
-            val (r, g, b) = threatColor(relAlt)
+            val (r, g, b) = threatColor(target.threat, relAlt)
             GLES30.glUniform4f(colorUniformLoc, r, g, b, 1f)
             drawDiamond(cx, cy, vaoObj)
         }
@@ -91,9 +92,20 @@ class TrafficOverlayRenderer {
         vaoObj.unbind()
     }
 
-    private fun threatColor(relAltFt: Int): Triple<Float, Float, Float> = when {
-        kotlin.math.abs(relAltFt) < 300  -> Triple(1f, 0f, 0f)      // RA — red
-        kotlin.math.abs(relAltFt) < 1200 -> Triple(1f, 0.65f, 0f)   // TA — amber
-        else                              -> Triple(1f, 1f, 1f)       // non-threat — white
+    private fun threatColor(threat: ThreatLevel?, relAltFt: Int): Triple<Float, Float, Float> = when (threat) {
+        ThreatLevel.RESOLUTION_ADVISORY -> RED
+        ThreatLevel.TRAFFIC_ADVISORY    -> AMBER
+        ThreatLevel.PROXIMATE, ThreatLevel.NONE -> WHITE
+        null -> when {
+            kotlin.math.abs(relAltFt) < 300  -> RED
+            kotlin.math.abs(relAltFt) < 1200 -> AMBER
+            else                              -> WHITE
+        }
+    }
+
+    private companion object {
+        val RED   = Triple(1f, 0f, 0f)
+        val AMBER = Triple(1f, 0.65f, 0f)
+        val WHITE = Triple(1f, 1f, 1f)
     }
 }
@@ -2,6 +2,7 @@ package com.nameless.efb.ui.g1000
 
 import android.content.Context
 import com.nameless.efb.data.connectivity.SimSnapshot
+import com.nameless.efb.data.connectivity.TrafficTarget
 import com.nameless.efb.domain.flightplan.FlightPlan
 import com.nameless.efb.rendering.g1000.G1000MfdRenderer
 import com.nameless.efb.rendering.g1000.mfd.FuelFlowUnit
@@ -23,12 +24,15 @@ import kotlinx.coroutines.flow.StateFlow
  * @param simData     Live sim state flow consumed by the renderer.
  * @param mapRenderer Shared map renderer (also used by PFD inset map), or null.
  * @param theme       Initial rendering theme.
+ * @param traffic     TCAS targets for the traffic page, or null to use the
+ *                    snapshot traffic arrays.
  */
 class G1000MfdView(
     context: Context,
     simData: StateFlow<SimSnapshot?>,
     mapRenderer: MapRenderer? = null,
     theme: Theme = Theme.DAY,
+    traffic: StateFlow<List<TrafficTarget>>? = null,
 ) : BaseGlSurfaceView(context) {
 
     private val renderer: G1000MfdRenderer
@@ -39,6 +43,7 @@ class G1000MfdView(
             simData     = simData,
             mapRenderer = mapRenderer,
             theme       = theme,
+            traffic     = traffic,
         )
         setRenderer(renderer)
         renderMode = RENDERMODE_CONTINUOUSLY
@@ -1,9 +1,22 @@
 package com.nameless.efb.data.connectivity
 
+import java.io.File
+import java.nio.ByteBuffer
+import java.nio.ByteOrder
+import kotlinx.serialization.json.Json
+import kotlinx.serialization.json.JsonObject
+import kotlinx.serialization.json.boolean
+import kotlinx.serialization.json.double
+import kotlinx.serialization.json.int
+import kotlinx.serialization.json.jsonArray
+import kotlinx.serialization.json.jsonObject
+import kotlinx.serialization.json.jsonPrimitive
+import kotlinx.serialization.json.long
 import org.junit.jupiter.api.Assertions.assertArrayEquals
 import org.junit.jupiter.api.Assertions.assertEquals
 import org.junit.jupiter.api.Assertions.assertNotNull
 import org.junit.jupiter.api.Assertions.assertNull
+import org.junit.jupiter.api.Assertions.assertTrue
 import org.junit.jupiter.api.Test
 
 class EfbProtocolTest {
@@ -99,8 +112,182 @@ class EfbProtocolTest {
         assertEquals(0xCBF43926L, EfbProtocol.crc32(data))
     }
 
+    private fun beaconPacket(vararg strings: String): ByteArray {
+        val bb = java.nio.ByteBuffer.allocate(64).order(java.nio.ByteOrder.LITTLE_ENDIAN)
+        bb.putShort(1.toShort()).putShort(1.toShort()).putInt(1)          // versions, capabilities
+        bb.putShort(49100.toShort()).putShort(49100.toShort()).putShort(0) // stream, command, TCP port
+        for (s in strings) {
+            val bytes = s.toByteArray(Charsets.UTF_8)
+            bb.put(bytes.size.toByte()).put(bytes)
+        }
+        return EfbProtocol.buildPacket(7, 0x0C, bb.array().copyOf(bb.position()))
+    }
+
+    @Test
+    fun `decodeBeacon reads plugin beacon`() {
+        val pkt = beaconPacket("0.1.0", "sim-pc", "C172")
+        val beacon = EfbProtocol.decodeBeacon(pkt, pkt.size)!!
+        assertEquals(49100, beacon.streamPort)
+        assertEquals(0, beacon.tcpPort)
+        assertEquals("0.1.0", beacon.pluginVersion)
+        assertEquals("sim-pc", beacon.hostname)
+        assertEquals("C172", beacon.aircraftIcao)
+    }
+
+    @Test
+    fun `decodeBeacon rejects truncated beacons and other packets`() {
+        val pkt = beaconPacket("0.1.0", "sim-pc")
+        assertNull(EfbProtocol.decodeBeacon(pkt, pkt.size))
+        val simData = EfbProtocol.encode(testSnapshot())
+        assertNull(EfbProtocol.decodeBeacon(simData, simData.size))
+        val becn = "BECN\u0000sim-pc\u0000".toByteArray(Charsets.US_ASCII)
+        assertNull(EfbProtocol.decodeBeacon(becn, becn.size))
+    }
+
     @Test
     fun `deserializeSnapshot returns null for undersized payload`() {
         assertNull(EfbProtocol.deserializeSnapshot(ByteArray(100)))
     }
+
+    // ── Golden vectors shared with the Rust codec ─────────────────────────────
+    // Written by `cargo run -p efb-protocol --bin efb-golden`; see
+    // plugin/efb-protocol/src/golden.rs for the manifest format.
+
+    private val goldenDir = File("../plugin/efb-protocol/testdata/golden")
+
+    private val manifest: JsonObject by lazy {
+        Json.parseToJsonElement(File(goldenDir, "manifest.json").readText()).jsonObject
+    }
+
+    private val vectors: List<JsonObject> get() = manifest.getValue("vectors").jsonArray.map { it.jsonObject }
+
+    private fun JsonObject.str(key: String) = getValue(key).jsonPrimitive.content
+    private fun JsonObject.obj(key: String) = getValue(key).jsonObject
+    // Non-finite values are spelled NaN / Infinity / -Infinity, which toDouble() parses.
+    private fun JsonObject.num(key: String) = str(key).toDouble()
+
+    private fun bytes(v: JsonObject) = File(goldenDir, v.str("file")).readBytes()
+
+    private fun valid(type: Int) = vectors.filter {
+        it.str("expect") == "ok" && it.obj("header").getValue("packet_type").jsonPrimitive.int == type
+    }
+
+    @Test
+    fun `golden corpus was generated from this snapshot layout`() {
+        assertEquals(SimSnapshotCodec.LAYOUT_CHECKSUM, manifest.getValue("layout_checksum").jsonPrimitive.long)
+    }
+
+    @Test
+    fun `golden SimData vectors decode and re-encode bit for bit`() {
+        val simData = valid(0x01)
+        assertTrue(simData.size >= 3)
+        for (v in simData) {
+            val name = v.str("name")
+            val pkt = bytes(v)
+            val header = v.obj("header")
+            if (header.getValue("version").jsonPrimitive.int != EfbProtocol.VERSION) {
+                assertNull(EfbProtocol.decode(pkt, pkt.size), "$name: v2 is not spoken by EfbProtocol")
+                continue
+            }
+            val decoded = EfbProtocol.decode(pkt, pkt.size)
+            assertNotNull(decoded, name)
+            val payload = v.obj("payload")
+            assertEquals(payload.num("latitude"),     decoded!!.latitude, name)
+            assertEquals(payload.num("longitude"),    decoded.longitude, name)
+            assertEquals(payload.num("nav1_vdef_dot").toFloat(), decoded.nav1VdefDot, name)
+            assertEquals(payload.str("inner_marker").toBoolean(), decoded.innerMarker, name)
+            assertEquals(payload.getValue("traffic_count").jsonPrimitive.int, decoded.trafficCount, name)
+            val seq = header.getValue("sequence").jsonPrimitive.int
+            assertArrayEquals(pkt, EfbProtocol.encode(decoded, seq), name)
+        }
+    }
+
+    @Test
+    fun `golden beacon decodes`() {
+        val v = valid(0x0C).single()
+        val pkt = bytes(v)
+        val beacon = EfbProtocol.decodeBeacon(pkt, pkt.size)!!
+        val expected = v.obj("payload")
+        assertEquals(expected.getValue("capabilities").jsonPrimitive.int, beacon.capabilities)
+        assertEquals(expected.getValue("command_port").jsonPrimitive.int, beacon.commandPort)
+        assertEquals(expected.str("hostname"), beacon.hostname)
+        assertEquals(expected.str("aircraft_icao"), beacon.aircraftIcao)
+    }
+
+    @Test
+    fun `golden traffic decodes with full precision`() {
+        val v = valid(0x0F).single()
+        val pkt = bytes(v)
+        val targets = EfbProtocol.decodeTraffic(pkt, pkt.size)!!
+        val expected = v.obj("payload").getValue("targets").jsonArray.map { it.jsonObject }
+        assertEquals(expected.size, targets.size)
+        for ((t, e) in targets.zip(expected)) {
+            assertEquals(e.getValue("icao_address").jsonPrimitive.int, t.icaoAddress)
+            assertEquals(e.str("callsign"), t.callsign)
+            assertEquals(e.getValue("latitude").jsonPrimitive.double, t.latitude)
+            assertEquals(e.getValue("longitude").jsonPrimitive.double, t.longitude)
+            assertEquals(e.getValue("altitude_m").jsonPrimitive.double, t.altitudeM)
+            assertEquals(e.num("track_deg").toFloat(), t.trackDeg)
+            assertEquals(e.num("vertical_speed_fpm").toFloat(), t.verticalSpeedFpm)
+            assertEquals(e.getValue("on_ground").jsonPrimitive.boolean, t.onGround)
+            assertEquals(e.str("threat").uppercase(), t.threat!!.name)
+        }
+        val malformed = vectors.single { it.str("name") == "malformed_traffic" }
+        assertNull(EfbProtocol.decodeTraffic(bytes(malformed), bytes(malformed).size))
+    }
+
+    @Test
+    fun `fragmented traffic is reassembled`() {
+        val v = valid(0x0F).single()
+        val payload = bytes(v).copyOfRange(EfbProtocol.HEADER_LEN, bytes(v).size)
+        val half = payload.size / 2
+        val fragments = listOf(payload.copyOfRange(0, half), payload.copyOfRange(half, payload.size))
+            .mapIndexed { i, chunk ->
+                val prefix = ByteBuffer.allocate(9).order(ByteOrder.LITTLE_ENDIAN)
+                    .putInt(77).putShort(i.toShort()).putShort(2).put(0x0F.toByte())
+                EfbProtocol.buildPacket(77 + i, 0x0A, prefix.array() + chunk)
+            }
+        val assembler = FragmentAssembler()
+        assertNull(assembler.push(EfbProtocol.decodeFragment(fragments[0], fragments[0].size)!!))
+        val (type, message) = assembler.push(EfbProtocol.decodeFragment(fragments[1], fragments[1].size)!!)!!
+        assertEquals(0x0F.toByte(), type)
+        assertArrayEquals(payload, message)
+        assertEquals(2, EfbProtocol.decodeTrafficPayload(message)!!.size)
+    }
+
+    @Test
+    fun `hello asks for v1 with the given capabilities`() {
+        val hello = EfbProtocol.buildHello(EfbProtocol.CAP_EXTENDED_TRAFFIC, seq = 3)
+        val payload = ByteBuffer.wrap(hello, EfbProtocol.HEADER_LEN, 8).order(ByteOrder.LITTLE_ENDIAN)
+        assertEquals(0x07.toByte(), hello[6])
+        assertEquals(1, payload.short.toInt())
+        assertEquals(1, payload.short.toInt())
+        assertEquals(1 shl 2, payload.int)
+
+        val ack = vectors.single { it.str("name") == "hello_ack" }
+        val expected = ack.obj("payload").getValue("capabilities").jsonPrimitive.int
+        assertEquals(expected, EfbProtocol.decodeHelloAck(bytes(ack), bytes(ack).size))
+    }
+
+    @Test
+    fun `golden packet checksums match crc32`() {
+        for (v in vectors.filter { it.str("expect") == "ok" }) {
+            val pkt = bytes(v)
+            val header = v.obj("header")
+            val headerLen = if (header.getValue("version").jsonPrimitive.int >= 2) 34 else EfbProtocol.HEADER_LEN
+            val payloadLen = header.getValue("payload_len").jsonPrimitive.int
+            val checksum = ByteBuffer.wrap(pkt, 13, 4).order(ByteOrder.LITTLE_ENDIAN).int.toLong() and 0xFFFFFFFFL
+            val payload = pkt.copyOfRange(headerLen, headerLen + payloadLen)
+            assertEquals(checksum, EfbProtocol.crc32(payload), v.str("name"))
+        }
+    }
+
+    @Test
+    fun `golden corrupt vectors are rejected`() {
+        for (v in vectors.filter { it.str("expect") == "error" }) {
+            val pkt = bytes(v)
+            assertNull(EfbProtocol.decode(pkt, pkt.size), v.str("name"))
+            assertNull(EfbProtocol.decodeBeacon(pkt, pkt.size), v.str("name"))
+        }
+    }
 }
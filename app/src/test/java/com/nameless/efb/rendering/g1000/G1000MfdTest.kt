@@ -1,6 +1,8 @@
 package com.nameless.efb.rendering.g1000
 
 import com.nameless.efb.data.connectivity.SimSnapshot
+import com.nameless.efb.data.connectivity.ThreatLevel
+import com.nameless.efb.data.connectivity.TrafficTarget
 import com.nameless.efb.domain.flightplan.FlightPlan
 import com.nameless.efb.domain.flightplan.Waypoint
 import com.nameless.efb.domain.nav.LatLon
@@ -121,6 +123,21 @@ class G1000MfdTest {
         assertEquals(AlertLevel.OTHER, alert)
     }
 
+    @Test
+    fun trafficPage_usesPluginThreatLevel() {
+        // Co-located traffic would be an RA by geometry, but the plugin says TA.
+        val snapshot = testSnapshot(
+            trafficLat  = floatArrayOf(-26.139f),
+            trafficLon  = floatArrayOf(28.247f),
+            trafficEleM = floatArrayOf(1700f),
+            ownLat  = -26.139,
+            ownLon  = 28.247,
+            ownEleM = 1700.0,
+        )
+        val target = TrafficTarget.fromSnapshot(snapshot, 0).copy(threat = ThreatLevel.TRAFFIC_ADVISORY)
+        assertEquals(AlertLevel.TA, TrafficPageRenderer().getAlertLevel(snapshot, target))
+    }
+
     // ── G-17: Terrain page PDA logic ─────────────────────────────────────────
 
     @Test
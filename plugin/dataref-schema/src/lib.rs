@@ -3,6 +3,8 @@
 //!
 //! Field order and sizes are part of the wire protocol — do not reorder.
 
+pub mod paths;
+
 use serde::{Deserialize, Serialize};
 
 /// A complete snapshot of all sim state streamed per UDP datagram.
@@ -75,7 +77,7 @@ pub struct SimSnapshot {
     pub wind_dir_deg: f32,
     pub wind_speed_kt: f32,
 
-    // ── Traffic (up to 20 TCAS targets) ──────────────────────────────────────
+    // ── Traffic (up to 20 TCAS targets) ───────────────────────────────────────
     pub traffic_lat: [f32; 20],
     pub traffic_lon: [f32; 20],
     pub traffic_ele_m: [f32; 20],
@@ -145,6 +147,237 @@ impl Default for SimSnapshot {
     }
 }
 
+// ── Wire schema ───────────────────────────────────────────────────────────────
+
+/// Primitive type of a [`SimSnapshot`] field on the wire (little-endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FieldType {
+    F64  = 0,
+    F32  = 1,
+    I32  = 2,
+    U8   = 3,
+    Bool = 4, // one byte, 0 = false
+}
+
+impl FieldType {
+    /// Encoded size of one element in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::F64 => 8,
+            Self::F32 | Self::I32 => 4,
+            Self::U8 | Self::Bool => 1,
+        }
+    }
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::F64),
+            1 => Some(Self::F32),
+            2 => Some(Self::I32),
+            3 => Some(Self::U8),
+            4 => Some(Self::Bool),
+            _ => None,
+        }
+    }
+}
+
+/// A set of [`SimSnapshot`] fields streamed together, at a rate suited to
+/// how fast they change (see `efb_protocol::groups`).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldGroup {
+    /// Position, attitude and air data — the flight instruments.
+    Fast    = 0,
+    /// Navigation, autopilot, marker beacons and HSI source.
+    Nav     = 1,
+    /// Engine, radios and weather.
+    Slow    = 2,
+    /// Traffic positions; sent when they change.
+    Traffic = 3,
+}
+
+impl FieldGroup {
+    pub const COUNT: usize = 4;
+
+    /// Every group, in id order.
+    pub const ALL: [FieldGroup; Self::COUNT] = [Self::Fast, Self::Nav, Self::Slow, Self::Traffic];
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Self::ALL.get(v as usize).copied()
+    }
+
+    /// Indices into [`SNAPSHOT_FIELDS`] of this group's fields, in wire order.
+    pub fn field_indices(self) -> impl Iterator<Item = usize> {
+        (0..SNAPSHOT_FIELDS.len()).filter(move |&i| SNAPSHOT_FIELDS[i].group == self)
+    }
+
+    /// This group's fields, in wire order.
+    pub fn fields(self) -> impl Iterator<Item = &'static FieldDesc> {
+        SNAPSHOT_FIELDS.iter().filter(move |f| f.group == self)
+    }
+
+    /// Encoded size of this group's values.
+    pub fn values_len(self) -> usize {
+        self.fields().map(FieldDesc::wire_size).sum()
+    }
+}
+
+/// Wire description of one [`SimSnapshot`] field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDesc {
+    pub name: &'static str,
+    pub ty: FieldType,
+    /// Number of elements; 1 for scalars.
+    pub len: usize,
+    /// Physical unit, empty for flags, codes and counts.
+    pub unit: &'static str,
+    /// Group the field is streamed in. Not part of the SimData layout.
+    pub group: FieldGroup,
+}
+
+impl FieldDesc {
+    /// Total encoded size of the field in bytes.
+    pub const fn wire_size(&self) -> usize {
+        self.ty.size() * self.len
+    }
+}
+
+const fn field(
+    name: &'static str,
+    ty: FieldType,
+    len: usize,
+    unit: &'static str,
+    group: FieldGroup,
+) -> FieldDesc {
+    FieldDesc { name, ty, len, unit, group }
+}
+
+/// Every [`SimSnapshot`] field in wire order — the single source of truth for
+/// the SimData payload layout. Keep in lockstep with the struct above.
+pub const SNAPSHOT_FIELDS: [FieldDesc; 53] = [
+    // ── Position ──────────────────────────────────────────────────────────────
+    field("latitude",           FieldType::F64,  1,  "deg",    FieldGroup::Fast),
+    field("longitude",          FieldType::F64,  1,  "deg",    FieldGroup::Fast),
+    field("elevation_m",        FieldType::F64,  1,  "m",      FieldGroup::Fast),
+    field("groundspeed_ms",     FieldType::F32,  1,  "m/s",    FieldGroup::Fast),
+
+    // ── Attitude ──────────────────────────────────────────────────────────────
+    field("pitch_deg",          FieldType::F32,  1,  "deg",    FieldGroup::Fast),
+    field("roll_deg",           FieldType::F32,  1,  "deg",    FieldGroup::Fast),
+    field("mag_heading_deg",    FieldType::F32,  1,  "deg",    FieldGroup::Fast),
+    field("ground_track_deg",   FieldType::F32,  1,  "deg",    FieldGroup::Fast),
+
+    // ── Air data ──────────────────────────────────────────────────────────────
+    field("ias_kts",            FieldType::F32,  1,  "kt",     FieldGroup::Fast),
+    field("tas_kts",            FieldType::F32,  1,  "kt",     FieldGroup::Fast),
+    field("vvi_fpm",            FieldType::F32,  1,  "ft/min", FieldGroup::Fast),
+    field("turn_rate_deg_sec",  FieldType::F32,  1,  "deg/s",  FieldGroup::Fast),
+    field("slip_deg",           FieldType::F32,  1,  "deg",    FieldGroup::Fast),
+    field("oat_degc",           FieldType::F32,  1,  "degC",   FieldGroup::Fast),
+    field("barometer_inhg",     FieldType::F32,  1,  "inHg",   FieldGroup::Fast),
+
+    // ── Engine (index 0) ──────────────────────────────────────────────────────
+    field("rpm",                FieldType::F32,  1,  "rpm",    FieldGroup::Slow),
+    field("map_inhg",           FieldType::F32,  1,  "inHg",   FieldGroup::Slow),
+    field("fuel_flow_kg_sec",   FieldType::F32,  1,  "kg/s",   FieldGroup::Slow),
+    field("oil_press_psi",      FieldType::F32,  1,  "psi",    FieldGroup::Slow),
+    field("oil_temp_degc",      FieldType::F32,  1,  "degC",   FieldGroup::Slow),
+    field("egt_degc",           FieldType::F32,  6,  "degC",   FieldGroup::Slow),
+    field("fuel_qty_kg",        FieldType::F32,  2,  "kg",     FieldGroup::Slow),
+    field("bus_volts",          FieldType::F32,  1,  "V",      FieldGroup::Slow),
+    field("battery_amps",       FieldType::F32,  1,  "A",      FieldGroup::Slow),
+    field("suction_inhg",       FieldType::F32,  1,  "inHg",   FieldGroup::Slow),
+
+    // ── Navigation ────────────────────────────────────────────────────────────
+    field("nav1_hdef_dot",      FieldType::F32,  1,  "dots",   FieldGroup::Nav),
+    field("nav1_vdef_dot",      FieldType::F32,  1,  "dots",   FieldGroup::Nav),
+    field("nav1_obs_deg",       FieldType::F32,  1,  "deg",    FieldGroup::Nav),
+    field("gps_dist_nm",        FieldType::F32,  1,  "nm",     FieldGroup::Nav),
+    field("gps_bearing_deg",    FieldType::F32,  1,  "deg",    FieldGroup::Nav),
+
+    // ── Autopilot ─────────────────────────────────────────────────────────────
+    field("ap_state_flags",     FieldType::I32,  1,  "",       FieldGroup::Nav),
+    field("fd_pitch_deg",       FieldType::F32,  1,  "deg",    FieldGroup::Nav),
+    field("fd_roll_deg",        FieldType::F32,  1,  "deg",    FieldGroup::Nav),
+    field("ap_heading_bug_deg", FieldType::F32,  1,  "deg",    FieldGroup::Nav),
+    field("ap_altitude_ft",     FieldType::F32,  1,  "ft",     FieldGroup::Nav),
+    field("ap_vs_fpm",          FieldType::F32,  1,  "ft/min", FieldGroup::Nav),
+
+    // ── Radios ────────────────────────────────────────────────────────────────
+    field("com1_active_hz",     FieldType::I32,  1,  "Hz",     FieldGroup::Slow),
+    field("com1_standby_hz",    FieldType::I32,  1,  "Hz",     FieldGroup::Slow),
+    field("com2_active_hz",     FieldType::I32,  1,  "Hz",     FieldGroup::Slow),
+    field("nav1_active_hz",     FieldType::I32,  1,  "Hz",     FieldGroup::Slow),
+    field("nav1_standby_hz",    FieldType::I32,  1,  "Hz",     FieldGroup::Slow),
+    field("transponder_code",   FieldType::I32,  1,  "",       FieldGroup::Slow),
+    field("transponder_mode",   FieldType::I32,  1,  "",       FieldGroup::Slow),
+
+    // ── Markers ───────────────────────────────────────────────────────────────
+    field("outer_marker",       FieldType::Bool, 1,  "",       FieldGroup::Nav),
+    field("middle_marker",      FieldType::Bool, 1,  "",       FieldGroup::Nav),
+    field("inner_marker",       FieldType::Bool, 1,  "",       FieldGroup::Nav),
+
+    // ── Weather ───────────────────────────────────────────────────────────────
+    field("wind_dir_deg",       FieldType::F32,  1,  "deg",    FieldGroup::Slow),
+    field("wind_speed_kt",      FieldType::F32,  1,  "kt",     FieldGroup::Slow),
+
+    // ── Traffic ───────────────────────────────────────────────────────────────
+    field("traffic_lat",        FieldType::F32,  20, "deg",    FieldGroup::Traffic),
+    field("traffic_lon",        FieldType::F32,  20, "deg",    FieldGroup::Traffic),
+    field("traffic_ele_m",      FieldType::F32,  20, "m",      FieldGroup::Traffic),
+    field("traffic_count",      FieldType::U8,   1,  "",       FieldGroup::Traffic),
+
+    // ── HSI source ────────────────────────────────────────────────────────────
+    field("hsi_source",         FieldType::I32,  1,  "",       FieldGroup::Nav),
+];
+
+/// Total encoded size of a [`SimSnapshot`] payload in bytes.
+pub const SNAPSHOT_WIRE_LEN: usize = {
+    let mut total = 0;
+    let mut i = 0;
+    while i < SNAPSHOT_FIELDS.len() {
+        total += SNAPSHOT_FIELDS[i].wire_size();
+        i += 1;
+    }
+    total
+};
+
+/// Fingerprint of the [`SNAPSHOT_FIELDS`] layout: names, types and lengths,
+/// in order (units and groups are ignored). Code generated from the table embeds it, so
+/// a decoder built against an older layout can be detected and refused.
+pub const SNAPSHOT_LAYOUT_CHECKSUM: u32 = layout_checksum(&SNAPSHOT_FIELDS);
+
+/// 32-bit FNV-1a over each field's name, a NUL, its type code and its length
+/// (u32 little-endian).
+pub const fn layout_checksum(fields: &[FieldDesc]) -> u32 {
+    const PRIME: u32 = 0x0100_0193;
+    const fn mix(h: u32, byte: u8) -> u32 {
+        (h ^ byte as u32).wrapping_mul(PRIME)
+    }
+
+    let mut h: u32 = 0x811C_9DC5;
+    let mut i = 0;
+    while i < fields.len() {
+        let name = fields[i].name.as_bytes();
+        let mut j = 0;
+        while j < name.len() {
+            h = mix(h, name[j]);
+            j += 1;
+        }
+        h = mix(h, 0);
+        h = mix(h, fields[i].ty as u8);
+        let len = (fields[i].len as u32).to_le_bytes();
+        let mut j = 0;
+        while j < len.len() {
+            h = mix(h, len[j]);
+            j += 1;
+        }
+        i += 1;
+    }
+    h
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +393,64 @@ mod tests {
         let snap = SimSnapshot::default();
         assert!((snap.oat_degc - 15.0).abs() < 0.01);
     }
+
+    #[test]
+    fn wire_len_matches_field_table() {
+        assert_eq!(SNAPSHOT_WIRE_LEN, 464);
+        assert_eq!(SNAPSHOT_FIELDS[0].name, "latitude");
+        assert_eq!(SNAPSHOT_FIELDS[SNAPSHOT_FIELDS.len() - 1].name, "hsi_source");
+    }
+
+    #[test]
+    fn layout_checksum_tracks_order_type_and_len() {
+        let mut fields = SNAPSHOT_FIELDS;
+        assert_eq!(layout_checksum(&fields), SNAPSHOT_LAYOUT_CHECKSUM);
+
+        fields.swap(1, 2);
+        assert_ne!(layout_checksum(&fields), SNAPSHOT_LAYOUT_CHECKSUM);
+        fields.swap(1, 2);
+        fields[0].ty = FieldType::F32;
+        assert_ne!(layout_checksum(&fields), SNAPSHOT_LAYOUT_CHECKSUM);
+        fields[0].ty = FieldType::F64;
+        fields[20].len = 4;
+        assert_ne!(layout_checksum(&fields), SNAPSHOT_LAYOUT_CHECKSUM);
+        fields[20].len = 6;
+        fields[0].unit = "rad";
+        fields[0].group = FieldGroup::Slow;
+        assert_eq!(layout_checksum(&fields), SNAPSHOT_LAYOUT_CHECKSUM);
+    }
+
+    #[test]
+    fn every_field_has_its_intended_group() {
+        let intended: [(FieldGroup, &[&str]); FieldGroup::COUNT] = [
+            (FieldGroup::Fast, &[
+                "latitude", "longitude", "elevation_m", "groundspeed_ms", "pitch_deg", "roll_deg",
+                "mag_heading_deg", "ground_track_deg", "ias_kts", "tas_kts", "vvi_fpm",
+                "turn_rate_deg_sec", "slip_deg", "oat_degc", "barometer_inhg",
+            ]),
+            (FieldGroup::Nav, &[
+                "nav1_hdef_dot", "nav1_vdef_dot", "nav1_obs_deg", "gps_dist_nm", "gps_bearing_deg",
+                "ap_state_flags", "fd_pitch_deg", "fd_roll_deg", "ap_heading_bug_deg",
+                "ap_altitude_ft", "ap_vs_fpm", "outer_marker", "middle_marker", "inner_marker",
+                "hsi_source",
+            ]),
+            (FieldGroup::Slow, &[
+                "rpm", "map_inhg", "fuel_flow_kg_sec", "oil_press_psi", "oil_temp_degc", "egt_degc",
+                "fuel_qty_kg", "bus_volts", "battery_amps", "suction_inhg", "com1_active_hz",
+                "com1_standby_hz", "com2_active_hz", "nav1_active_hz", "nav1_standby_hz",
+                "transponder_code", "transponder_mode", "wind_dir_deg", "wind_speed_kt",
+            ]),
+            (FieldGroup::Traffic, &["traffic_lat", "traffic_lon", "traffic_ele_m", "traffic_count"]),
+        ];
+        for f in &SNAPSHOT_FIELDS {
+            let groups: Vec<_> = intended
+                .iter()
+                .filter(|(_, names)| names.contains(&f.name))
+                .map(|(g, _)| *g)
+                .collect();
+            assert_eq!(groups, [f.group], "{} must be in exactly its intended group", f.name);
+        }
+        let listed: usize = intended.iter().map(|(_, names)| names.len()).sum();
+        assert_eq!(listed, SNAPSHOT_FIELDS.len(), "every listed name must be a field");
+    }
 }
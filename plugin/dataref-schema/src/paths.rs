@@ -0,0 +1,95 @@
+//! X-Plane dataref paths read by the plugin, and subscribed to by
+//! `efb-bridge` through X-Plane's UDP API.
+
+// Position
+pub const LATITUDE:       &str = "sim/flightmodel/position/latitude";
+pub const LONGITUDE:      &str = "sim/flightmodel/position/longitude";
+pub const ELEVATION_M:    &str = "sim/flightmodel/position/elevation";
+pub const GROUNDSPEED_MS: &str = "sim/flightmodel/position/groundspeed";
+// Attitude
+pub const PITCH_DEG:        &str = "sim/flightmodel/position/theta";
+pub const ROLL_DEG:         &str = "sim/flightmodel/position/phi";
+pub const MAG_HEADING_DEG:  &str = "sim/flightmodel/position/mag_psi";
+pub const GROUND_TRACK_DEG: &str = "sim/flightmodel/position/hpath";
+// Air data
+pub const IAS_KTS:           &str = "sim/flightmodel/position/indicated_airspeed";
+pub const TRUE_AIRSPEED_MS:  &str = "sim/flightmodel/position/true_airspeed";
+pub const VVI_FPM:           &str = "sim/flightmodel/position/vh_ind_fpm";
+pub const TURN_RATE_DEG_SEC: &str = "sim/cockpit2/gauges/indicators/turn_rate_heading_deg_pilot";
+pub const SLIP_DEG:          &str = "sim/cockpit/gyros/slip_deg";
+pub const OAT_DEGC:          &str = "sim/weather/temperature_ambient_c";
+pub const BAROMETER_INHG:    &str = "sim/cockpit2/gauges/actuators/barometer_setting_in_hg_pilot";
+// Engine arrays (read index 0 for engine 1)
+pub const ENGINE_RPM:        &str = "sim/cockpit2/engine/indicators/engine_speed_rpm";
+pub const MANIFOLD_INHG:     &str = "sim/cockpit2/engine/indicators/manifold_pressure_inhg";
+pub const FUEL_FLOW_KG_SEC:  &str = "sim/cockpit2/engine/indicators/fuel_flow_kg_sec";
+pub const OIL_PRESS_PSI:     &str = "sim/cockpit2/engine/indicators/oil_pressure_psi";
+pub const OIL_TEMP_DEGC:     &str = "sim/cockpit2/engine/indicators/oil_temp_deg_c";
+pub const EGT_DEGC:          &str = "sim/cockpit2/engine/indicators/EGT_deg_c";
+pub const FUEL_QTY_KG:       &str = "sim/flightmodel/weight/m_fuel";
+pub const BUS_VOLTS:         &str = "sim/cockpit2/electrical/bus_volts";
+pub const BATTERY_AMPS:      &str = "sim/cockpit2/electrical/battery_amps_total";
+pub const SUCTION_INHG:      &str = "sim/cockpit2/gauges/indicators/airspeed_vacuum_in_hg_pilot";
+// Navigation
+pub const NAV1_HDEF_DOT:   &str = "sim/cockpit2/radios/indicators/nav1_hdef_dots_pilot";
+pub const NAV1_VDEF_DOT:   &str = "sim/cockpit2/radios/indicators/nav1_vdef_dots_pilot";
+pub const NAV1_OBS_DEG:    &str = "sim/cockpit/radios/nav1_course_degm";
+pub const GPS_DIST_NM:     &str = "sim/cockpit2/radios/indicators/gps_dme_distance_nm";
+pub const GPS_BEARING_DEG: &str = "sim/cockpit2/radios/indicators/gps_bearing_deg_mag";
+// Autopilot
+pub const AP_STATE_FLAGS:     &str = "sim/cockpit/autopilot/autopilot_state";
+pub const FD_PITCH_DEG:       &str = "sim/cockpit2/autopilot/flight_director_pitch_deg";
+pub const FD_ROLL_DEG:        &str = "sim/cockpit2/autopilot/flight_director_roll_deg";
+pub const AP_HEADING_BUG_DEG: &str = "sim/cockpit/autopilot/heading_mag";
+pub const AP_ALTITUDE_FT:     &str = "sim/cockpit/autopilot/altitude";
+pub const AP_VS_FPM:          &str = "sim/cockpit/autopilot/vertical_velocity";
+// Radios
+pub const COM1_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/com1_frequency_hz";
+pub const COM1_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/com1_standby_frequency_hz";
+pub const COM2_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/com2_frequency_hz";
+pub const NAV1_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/nav1_frequency_hz";
+pub const NAV1_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/nav1_standby_frequency_hz";
+pub const TRANSPONDER_CODE:  &str = "sim/cockpit/radios/transponder_code";
+pub const TRANSPONDER_MODE:  &str = "sim/cockpit/radios/transponder_mode";
+// Radios the plugin does not stream (efb-bridge frequency commands only)
+pub const COM2_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/com2_standby_frequency_hz";
+pub const NAV2_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/nav2_standby_frequency_hz";
+// Markers
+pub const OUTER_MARKER:  &str = "sim/cockpit2/annunciators/outer_marker";
+pub const MIDDLE_MARKER: &str = "sim/cockpit2/annunciators/middle_marker";
+pub const INNER_MARKER:  &str = "sim/cockpit2/annunciators/inner_marker";
+// Weather
+pub const WIND_DIR_DEG:  &str = "sim/weather/wind_direction_degt";
+pub const WIND_SPEED_KT: &str = "sim/weather/wind_speed_kt";
+// Traffic (TCAS)
+pub const TRAFFIC_LAT:   &str = "sim/cockpit2/tcas/targets/position/lat";
+pub const TRAFFIC_LON:   &str = "sim/cockpit2/tcas/targets/position/lon";
+pub const TRAFFIC_ELE_M: &str = "sim/cockpit2/tcas/targets/position/ele";
+pub const TRAFFIC_COUNT: &str = "sim/cockpit2/tcas/targets/N_targets_max";
+// Traffic packet (XP12 TCAS targets, OpenGL local coordinates)
+pub const TCAS_NUM_ACF:      &str = "sim/cockpit2/tcas/indicators/tcas_num_acf";
+pub const TCAS_MODE_S_ID:    &str = "sim/cockpit2/tcas/targets/modeS_id";
+pub const TCAS_FLIGHT_ID:    &str = "sim/cockpit2/tcas/targets/flight_id";
+pub const TCAS_X:            &str = "sim/cockpit2/tcas/targets/position/x";
+pub const TCAS_Y:            &str = "sim/cockpit2/tcas/targets/position/y";
+pub const TCAS_Z:            &str = "sim/cockpit2/tcas/targets/position/z";
+pub const TCAS_VX:           &str = "sim/cockpit2/tcas/targets/position/vx";
+pub const TCAS_VY:           &str = "sim/cockpit2/tcas/targets/position/vy";
+pub const TCAS_VZ:           &str = "sim/cockpit2/tcas/targets/position/vz";
+pub const TCAS_ON_GROUND:    &str = "sim/cockpit2/tcas/targets/position/weight_on_wheels";
+pub const LOCAL_X:           &str = "sim/flightmodel/position/local_x";
+pub const LOCAL_Y:           &str = "sim/flightmodel/position/local_y";
+pub const LOCAL_Z:           &str = "sim/flightmodel/position/local_z";
+pub const LOCAL_VX:          &str = "sim/flightmodel/position/local_vx";
+pub const LOCAL_VY:          &str = "sim/flightmodel/position/local_vy";
+pub const LOCAL_VZ:          &str = "sim/flightmodel/position/local_vz";
+// HSI source
+pub const HSI_SOURCE: &str = "sim/cockpit2/radios/actuators/HSI_source_select_pilot";
+// Aircraft (beacon and GDL 90 only, not part of the snapshot)
+pub const ACF_ICAO:    &str = "sim/aircraft/view/acf_ICAO";
+pub const ACF_TAILNUM: &str = "sim/aircraft/view/acf_tailnum";
+// Time (v2 packet headers, GDL 90 and NMEA only, not part of the snapshot)
+pub const SIM_TIME_SEC:    &str = "sim/time/total_running_time_sec";
+pub const ZULU_TIME_SEC:   &str = "sim/time/zulu_time_sec";
+pub const LOCAL_TIME_SEC:  &str = "sim/time/local_time_sec";
+pub const LOCAL_DATE_DAYS: &str = "sim/time/local_date_days";
@@ -0,0 +1,708 @@
+// efb-bridge/src/main.rs
+// Streams X-Plane to an EFB tablet without the plugin, for sim PCs where
+// plugins cannot be installed. The bridge subscribes to the plugin's
+// datarefs through X-Plane's built-in UDP API (RREF), assembles SimSnapshots
+// from the answers and sends them to the tablet as SimData. Tablet commands
+// are forwarded as DREF and CMND packets.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use dataref_schema::{paths, SimSnapshot};
+use efb_protocol::command::{decode_request, decode_request_json, encode_command_result, peek_request_id};
+use efb_protocol::handshake::{decode_hello, encode_hello_ack};
+use efb_protocol::schema::encode_schema;
+use efb_protocol::{
+    decode_packet, encode_sim_data_into, Command, CommandRequest, CommandResult, CommandStatus, Hello, PacketType,
+    Radio, Schema, VersionRange, MIN_PROTOCOL_VERSION, SIM_DATA_PACKET_LEN,
+};
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// CLI args
+// ---------------------------------------------------------------------------
+
+#[derive(Parser)]
+#[command(name = "efb-bridge", about = "Stream X-Plane to the EFB tablet without the plugin")]
+struct Args {
+    /// X-Plane's UDP API address (Settings → Network, port 49000 by default)
+    #[arg(short, long, default_value = "127.0.0.1:49000")]
+    xplane: SocketAddr,
+
+    /// Local address tablets send ACKs and commands to, and X-Plane answers on
+    #[arg(short, long, default_value = "0.0.0.0:49100")]
+    bind: SocketAddr,
+
+    /// Start streaming to this tablet right away instead of waiting for its ACK
+    /// (another tablet may take over once it is silent past the watchdog)
+    #[arg(short, long)]
+    tablet: Option<SocketAddr>,
+
+    /// SimData rate, and the rate the datarefs are requested at
+    #[arg(short, long, default_value_t = 20)]
+    rate: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Same as the plugin: stop streaming when the tablet has not ACKed for this long.
+const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
+/// While the watchdog is tripped, resend the current snapshot this often so
+/// the tablet has something to ACK.
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+/// Subscribe again when X-Plane has sent nothing for this long, e.g. after
+/// it was restarted.
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(2);
+/// Highest SimData rate, as in the plugin.
+const MAX_RATE: u32 = 60;
+
+const KT_PER_MS: f32 = 1.943_84;
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    if !(1..=MAX_RATE).contains(&args.rate) {
+        bail!("--rate must be between 1 and {MAX_RATE}");
+    }
+    let socket = UdpSocket::bind(args.bind).with_context(|| format!("Cannot bind {}", args.bind))?;
+    let mut bridge = Bridge::new(socket, args.xplane, args.tablet, args.rate);
+    let running = Arc::new(AtomicBool::new(true));
+    let flag = Arc::clone(&running);
+    ctrlc::set_handler(move || flag.store(false, Ordering::Relaxed)).context("Cannot install Ctrl-C handler")?;
+    eprintln!("Bridging X-Plane at {} to tablets on {}", args.xplane, args.bind);
+
+    let interval = Duration::from_secs(1) / args.rate;
+    let mut run = || -> io::Result<()> {
+        while running.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            bridge.tick(now)?;
+            bridge.receive_until(now + interval)?;
+        }
+        Ok(())
+    };
+    let result = run();
+    // Otherwise X-Plane keeps sending RREF answers to the closed port.
+    bridge.unsubscribe()?;
+    Ok(result?)
+}
+
+// ---------------------------------------------------------------------------
+// X-Plane UDP API
+// ---------------------------------------------------------------------------
+//
+// Requests start with a 4-letter label and a NUL; numbers are little-endian.
+//
+//   RREF\0  freq: i32, index: i32, path: [u8; 400]  subscribe (freq 0 stops)
+//   RREF,   (index: i32, value: f32)*               X-Plane's answer
+//   DREF\0  value: f32, path: [u8; 500]             write a dataref
+//   CMND\0  command path                            run a command once
+//
+// Every value is sent as f32, whatever the dataref's type.
+
+const RREF_PATH_LEN: usize = 400;
+const DREF_PATH_LEN: usize = 500;
+
+/// Subscribe to `path` (an element such as `…/EGT_deg_c[2]` for arrays) at
+/// `freq_hz`; answers carry `index`.
+fn encode_rref(freq_hz: i32, index: i32, path: &str) -> Vec<u8> {
+    let mut v = Vec::with_capacity(13 + RREF_PATH_LEN);
+    v.extend_from_slice(b"RREF\0");
+    v.extend_from_slice(&freq_hz.to_le_bytes());
+    v.extend_from_slice(&index.to_le_bytes());
+    push_padded(&mut v, path, RREF_PATH_LEN);
+    v
+}
+
+/// The (index, value) pairs of an RREF answer, or `None` for anything else.
+fn decode_rref(datagram: &[u8]) -> Option<Vec<(i32, f32)>> {
+    let body = datagram.strip_prefix(b"RREF")?.get(1..)?;
+    let pairs = body
+        .chunks_exact(8)
+        .map(|c| {
+            let index = i32::from_le_bytes(c[..4].try_into().unwrap());
+            (index, f32::from_le_bytes(c[4..].try_into().unwrap()))
+        })
+        .collect();
+    Some(pairs)
+}
+
+fn encode_dref(path: &str, value: f32) -> Vec<u8> {
+    let mut v = Vec::with_capacity(9 + DREF_PATH_LEN);
+    v.extend_from_slice(b"DREF\0");
+    v.extend_from_slice(&value.to_le_bytes());
+    push_padded(&mut v, path, DREF_PATH_LEN);
+    v
+}
+
+fn encode_cmnd(command: &str) -> Vec<u8> {
+    let mut v = b"CMND\0".to_vec();
+    v.extend_from_slice(command.as_bytes());
+    v
+}
+
+/// `s` NUL-padded to `len` bytes; longer strings are cut, keeping a NUL.
+fn push_padded(v: &mut Vec<u8>, s: &str, len: usize) {
+    let s = &s.as_bytes()[..s.len().min(len - 1)];
+    v.extend_from_slice(s);
+    v.resize(v.len() + len - s.len(), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Subscriptions — the plugin's datarefs, one RREF per snapshot value
+// ---------------------------------------------------------------------------
+
+/// Stores one received value in the snapshot.
+type Setter = Box<dyn Fn(&mut SimSnapshot, f32)>;
+
+/// A subscribed dataref element and where its value goes in the snapshot.
+struct Subscription {
+    path: String,
+    set:  Setter,
+}
+
+fn sub(path: impl Into<String>, set: impl Fn(&mut SimSnapshot, f32) + 'static) -> Subscription {
+    Subscription { path: path.into(), set: Box::new(set) }
+}
+
+/// Everything the plugin reads for a SimSnapshot. Array datarefs are read
+/// element by element, as RREF requires. Position arrives as f32, so it is
+/// less precise than the plugin's (about a metre).
+fn subscriptions() -> Vec<Subscription> {
+    let mut subs = vec![
+        sub(paths::LATITUDE,          |s, v| s.latitude = v as f64),
+        sub(paths::LONGITUDE,         |s, v| s.longitude = v as f64),
+        sub(paths::ELEVATION_M,       |s, v| s.elevation_m = v as f64),
+        sub(paths::GROUNDSPEED_MS,    |s, v| s.groundspeed_ms = v),
+        sub(paths::PITCH_DEG,         |s, v| s.pitch_deg = v),
+        sub(paths::ROLL_DEG,          |s, v| s.roll_deg = v),
+        sub(paths::MAG_HEADING_DEG,   |s, v| s.mag_heading_deg = v),
+        sub(paths::GROUND_TRACK_DEG,  |s, v| s.ground_track_deg = v),
+        sub(paths::IAS_KTS,           |s, v| s.ias_kts = v),
+        sub(paths::TRUE_AIRSPEED_MS,  |s, v| s.tas_kts = v * KT_PER_MS),
+        sub(paths::VVI_FPM,           |s, v| s.vvi_fpm = v),
+        sub(paths::TURN_RATE_DEG_SEC, |s, v| s.turn_rate_deg_sec = v),
+        sub(paths::SLIP_DEG,          |s, v| s.slip_deg = v),
+        sub(paths::OAT_DEGC,          |s, v| s.oat_degc = v),
+        sub(paths::BAROMETER_INHG,    |s, v| s.barometer_inhg = v),
+        sub(format!("{}[0]", paths::ENGINE_RPM),       |s, v| s.rpm = v),
+        sub(format!("{}[0]", paths::MANIFOLD_INHG),    |s, v| s.map_inhg = v),
+        sub(format!("{}[0]", paths::FUEL_FLOW_KG_SEC), |s, v| s.fuel_flow_kg_sec = v),
+        sub(format!("{}[0]", paths::OIL_PRESS_PSI),    |s, v| s.oil_press_psi = v),
+        sub(format!("{}[0]", paths::OIL_TEMP_DEGC),    |s, v| s.oil_temp_degc = v),
+        sub(format!("{}[0]", paths::BUS_VOLTS),        |s, v| s.bus_volts = v),
+        sub(format!("{}[0]", paths::BATTERY_AMPS),     |s, v| s.battery_amps = v),
+        sub(format!("{}[0]", paths::SUCTION_INHG),     |s, v| s.suction_inhg = v),
+        sub(paths::NAV1_HDEF_DOT,      |s, v| s.nav1_hdef_dot = v),
+        sub(paths::NAV1_VDEF_DOT,      |s, v| s.nav1_vdef_dot = v),
+        sub(paths::NAV1_OBS_DEG,       |s, v| s.nav1_obs_deg = v),
+        sub(paths::GPS_DIST_NM,        |s, v| s.gps_dist_nm = v),
+        sub(paths::GPS_BEARING_DEG,    |s, v| s.gps_bearing_deg = v),
+        sub(paths::AP_STATE_FLAGS,     |s, v| s.ap_state_flags = v as i32),
+        sub(paths::FD_PITCH_DEG,       |s, v| s.fd_pitch_deg = v),
+        sub(paths::FD_ROLL_DEG,        |s, v| s.fd_roll_deg = v),
+        sub(paths::AP_HEADING_BUG_DEG, |s, v| s.ap_heading_bug_deg = v),
+        sub(paths::AP_ALTITUDE_FT,     |s, v| s.ap_altitude_ft = v),
+        sub(paths::AP_VS_FPM,          |s, v| s.ap_vs_fpm = v),
+        sub(paths::COM1_ACTIVE_HZ,     |s, v| s.com1_active_hz = v.round() as i32),
+        sub(paths::COM1_STANDBY_HZ,    |s, v| s.com1_standby_hz = v.round() as i32),
+        sub(paths::COM2_ACTIVE_HZ,     |s, v| s.com2_active_hz = v.round() as i32),
+        sub(paths::NAV1_ACTIVE_HZ,     |s, v| s.nav1_active_hz = v.round() as i32),
+        sub(paths::NAV1_STANDBY_HZ,    |s, v| s.nav1_standby_hz = v.round() as i32),
+        sub(paths::TRANSPONDER_CODE,   |s, v| s.transponder_code = v as i32),
+        sub(paths::TRANSPONDER_MODE,   |s, v| s.transponder_mode = v as i32),
+        sub(paths::OUTER_MARKER,       |s, v| s.outer_marker = v != 0.0),
+        sub(paths::MIDDLE_MARKER,      |s, v| s.middle_marker = v != 0.0),
+        sub(paths::INNER_MARKER,       |s, v| s.inner_marker = v != 0.0),
+        sub(paths::WIND_DIR_DEG,       |s, v| s.wind_dir_deg = v),
+        sub(paths::WIND_SPEED_KT,      |s, v| s.wind_speed_kt = v),
+        sub(paths::TRAFFIC_COUNT,      |s, v| s.traffic_count = v.clamp(0.0, 20.0) as u8),
+        sub(paths::HSI_SOURCE,         |s, v| s.hsi_source = v as i32),
+    ];
+    for i in 0..6 {
+        subs.push(sub(format!("{}[{i}]", paths::EGT_DEGC), move |s, v| s.egt_degc[i] = v));
+    }
+    for i in 0..2 {
+        subs.push(sub(format!("{}[{i}]", paths::FUEL_QTY_KG), move |s, v| s.fuel_qty_kg[i] = v));
+    }
+    for i in 0..20 {
+        subs.push(sub(format!("{}[{i}]", paths::TRAFFIC_LAT), move |s, v| s.traffic_lat[i] = v));
+        subs.push(sub(format!("{}[{i}]", paths::TRAFFIC_LON), move |s, v| s.traffic_lon[i] = v));
+        subs.push(sub(format!("{}[{i}]", paths::TRAFFIC_ELE_M), move |s, v| s.traffic_ele_m[i] = v));
+    }
+    subs
+}
+
+// ---------------------------------------------------------------------------
+// Commands — tablet commands as DREF/CMND
+// ---------------------------------------------------------------------------
+
+/// X-Plane command that exchanges a radio's frequencies (sic "standy").
+fn swap_command(radio: Radio) -> &'static str {
+    match radio {
+        Radio::Com1 => "sim/radios/com1_standy_flip",
+        Radio::Com2 => "sim/radios/com2_standy_flip",
+        Radio::Nav1 => "sim/radios/nav1_standy_flip",
+        Radio::Nav2 => "sim/radios/nav2_standy_flip",
+    }
+}
+
+fn standby_path(radio: Radio) -> &'static str {
+    match radio {
+        Radio::Com1 => paths::COM1_STANDBY_HZ,
+        Radio::Com2 => paths::COM2_STANDBY_HZ,
+        Radio::Nav1 => paths::NAV1_STANDBY_HZ,
+        Radio::Nav2 => paths::NAV2_STANDBY_HZ,
+    }
+}
+
+/// The X-Plane packet that carries out `cmd`.
+fn command_packet(cmd: &Command) -> Vec<u8> {
+    match cmd {
+        Command::SetDataref { path, value } => encode_dref(path, *value as f32),
+        Command::SwapFreq { radio } => encode_cmnd(swap_command(*radio)),
+        Command::SetStandbyFreq { radio, hz } => encode_dref(standby_path(*radio), *hz as f32),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Bridge
+// ---------------------------------------------------------------------------
+
+struct Bridge {
+    /// Shared by X-Plane and the tablets, told apart by sender: X-Plane
+    /// answers RREF to the port that asked.
+    socket:         UdpSocket,
+    xplane_addr:    SocketAddr,
+    subs:           Vec<Subscription>,
+    rate_hz:        u32,
+    snapshot:       SimSnapshot,
+    /// When X-Plane last answered, and when it was last asked.
+    last_rref:      Option<Instant>,
+    last_subscribe: Option<Instant>,
+    /// The tablet streamed to. Only it may send commands, and another
+    /// tablet's ACK takes its place only once the watchdog has tripped.
+    tablet:         Option<SocketAddr>,
+    /// Whether `tablet` has ACKed yet.
+    acked:          bool,
+    last_ack:       Instant,
+    last_sent:      Option<Instant>,
+    seq:            u32,
+    buf:            [u8; SIM_DATA_PACKET_LEN],
+}
+
+impl Bridge {
+    fn new(socket: UdpSocket, xplane_addr: SocketAddr, tablet: Option<SocketAddr>, rate_hz: u32) -> Self {
+        Bridge {
+            socket,
+            xplane_addr,
+            subs: subscriptions(),
+            rate_hz,
+            snapshot: SimSnapshot::default(),
+            last_rref: None,
+            last_subscribe: None,
+            tablet,
+            acked: false,
+            last_ack: Instant::now(), // grace period until the first ACK
+            last_sent: None,
+            seq: 0,
+            buf: [0; SIM_DATA_PACKET_LEN],
+        }
+    }
+
+    fn next_seq(&mut self) -> u32 {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+        seq
+    }
+
+    fn subscribe(&mut self, now: Instant) -> io::Result<()> {
+        self.send_rrefs(self.rate_hz as i32)?;
+        self.last_subscribe = Some(now);
+        Ok(())
+    }
+
+    /// Stop X-Plane sending RREF answers, if it was ever asked to.
+    fn unsubscribe(&mut self) -> io::Result<()> {
+        if self.last_subscribe.take().is_some() {
+            self.send_rrefs(0)?;
+        }
+        Ok(())
+    }
+
+    fn send_rrefs(&self, freq_hz: i32) -> io::Result<()> {
+        for (i, sub) in self.subs.iter().enumerate() {
+            self.socket.send_to(&encode_rref(freq_hz, i as i32, &sub.path), self.xplane_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Handle datagrams as they arrive until `deadline`.
+    fn receive_until(&mut self, deadline: Instant) -> io::Result<()> {
+        let mut buf = [0u8; 2048];
+        loop {
+            let now = Instant::now();
+            let wait = deadline.saturating_duration_since(now);
+            if wait.is_zero() {
+                return Ok(());
+            }
+            self.socket.set_read_timeout(Some(wait))?;
+            match self.socket.recv_from(&mut buf) {
+                Ok((n, from)) if from == self.xplane_addr => self.handle_xplane(&buf[..n], Instant::now()),
+                Ok((n, from)) => self.handle_tablet(&buf[..n], from, Instant::now())?,
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => return Ok(()),
+                // ICMP port unreachable while X-Plane or a tablet is not running.
+                Err(e) if matches!(e.kind(), io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Apply an RREF answer from X-Plane to the snapshot.
+    fn handle_xplane(&mut self, datagram: &[u8], now: Instant) {
+        let Some(pairs) = decode_rref(datagram) else { return };
+        for (index, value) in pairs {
+            if let Some(sub) = usize::try_from(index).ok().and_then(|i| self.subs.get(i)) {
+                (sub.set)(&mut self.snapshot, value);
+            }
+        }
+        self.last_rref = Some(now);
+    }
+
+    /// Whether the current tablet still holds its place: it has ACKed (or,
+    /// from `--tablet`, is new) within the watchdog timeout.
+    fn tablet_held(&self, now: Instant) -> bool {
+        self.tablet.is_some() && now.duration_since(self.last_ack) <= WATCHDOG_TIMEOUT
+    }
+
+    /// Tablet traffic: ACKs feed the watchdog, Hello gets a plain-v1
+    /// answer and commands from the connected tablet go to X-Plane.
+    fn handle_tablet(&mut self, datagram: &[u8], from: SocketAddr, now: Instant) -> io::Result<()> {
+        let Ok((_, ptype, payload)) = decode_packet(datagram) else { return Ok(()) };
+        match ptype {
+            PacketType::Ack => {
+                if self.tablet != Some(from) {
+                    if self.tablet_held(now) {
+                        eprintln!("ignoring ACK from {from}: tablet {} is connected", self.tablet.unwrap());
+                        return Ok(());
+                    }
+                    if let Some(old) = self.tablet {
+                        eprintln!("tablet {from} replaces {old}, silent for {WATCHDOG_TIMEOUT:?}");
+                    }
+                    self.tablet = Some(from);
+                    self.acked = false;
+                }
+                self.last_ack = now;
+                if !self.acked {
+                    self.acked = true;
+                    eprintln!("tablet {from} connected");
+                    let seq = self.next_seq();
+                    self.send_to_tablet(&encode_schema(seq, &Schema::local()), from);
+                }
+            }
+            PacketType::Hello => {
+                // Snapshots are sent as plain v1 SimData.
+                let bridge = Hello { versions: VersionRange::exact(MIN_PROTOCOL_VERSION), capabilities: 0 };
+                if let Some(ack) = decode_hello(payload).ok().and_then(|h| bridge.negotiate(&h)) {
+                    let seq = self.next_seq();
+                    self.send_to_tablet(&encode_hello_ack(seq, &ack), from);
+                }
+            }
+            PacketType::CommandJson | PacketType::CommandBinary => {
+                if !self.acked || self.tablet != Some(from) {
+                    eprintln!("ignoring command from {from}: not the connected tablet");
+                    return Ok(());
+                }
+                let decoded = match ptype {
+                    PacketType::CommandJson => decode_request_json(payload),
+                    _ => decode_request(payload),
+                };
+                let result = match decoded {
+                    Ok(req) => self.forward(req)?,
+                    Err(e) => {
+                        let id = peek_request_id(ptype, payload);
+                        CommandResult::error(id, CommandStatus::from_error(&e), e.to_string())
+                    }
+                };
+                let seq = self.next_seq();
+                self.send_to_tablet(&encode_command_result(seq, &result), from);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Send a command to X-Plane. X-Plane does not answer DREF or CMND, so
+    /// the result is always Ok: a misspelt dataref is silently ignored.
+    fn forward(&mut self, req: CommandRequest) -> io::Result<CommandResult> {
+        self.socket.send_to(&command_packet(&req.command), self.xplane_addr)?;
+        Ok(CommandResult::ok(req.id))
+    }
+
+    /// (Re)subscribe when X-Plane is silent, and send SimData when due.
+    fn tick(&mut self, now: Instant) -> io::Result<()> {
+        let stale = |at: Option<Instant>| at.is_none_or(|t| now.duration_since(t) >= RESUBSCRIBE_INTERVAL);
+        if stale(self.last_rref) && stale(self.last_subscribe) {
+            self.subscribe(now)?;
+        }
+
+        let (Some(tablet), Some(_)) = (self.tablet, self.last_rref) else { return Ok(()) };
+        let interval = if now.duration_since(self.last_ack) > WATCHDOG_TIMEOUT {
+            PROBE_INTERVAL
+        } else {
+            Duration::from_secs(1) / self.rate_hz
+        };
+        if self.last_sent.is_none_or(|t| now.duration_since(t) >= interval) {
+            let seq = self.next_seq();
+            let n = encode_sim_data_into(seq, &self.snapshot, &mut self.buf);
+            self.send_to_tablet(&self.buf[..n], tablet);
+            self.last_sent = Some(now);
+        }
+        Ok(())
+    }
+
+    /// Send to a tablet. Errors such as an unreachable host while its Wi-Fi
+    /// is down are logged, not returned: the watchdog drops a tablet that
+    /// stays away.
+    fn send_to_tablet(&self, pkt: &[u8], to: SocketAddr) {
+        if let Err(e) = self.socket.send_to(pkt, to) {
+            eprintln!("cannot send to tablet {to}: {e}");
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use efb_protocol::command::{decode_command_result, encode_request};
+    use efb_protocol::{decode_sim_data, encode_ack};
+    use std::collections::HashMap;
+
+    #[test]
+    fn xplane_packets_have_fixed_layouts() {
+        let rref = encode_rref(20, 7, "sim/flightmodel/position/latitude");
+        assert_eq!(rref.len(), 413);
+        assert_eq!(&rref[..13], b"RREF\0\x14\0\0\0\x07\0\0\0");
+        assert_eq!(&rref[13..46], b"sim/flightmodel/position/latitude");
+        assert!(rref[46..].iter().all(|&b| b == 0));
+
+        let dref = encode_dref("sim/cockpit/autopilot/heading_mag", 90.0);
+        assert_eq!(dref.len(), 509);
+        assert_eq!(&dref[..9], b"DREF\0\0\0\xB4\x42");
+
+        assert_eq!(encode_cmnd("sim/radios/com1_standy_flip"), b"CMND\0sim/radios/com1_standy_flip");
+        // Over-long paths keep their terminating NUL.
+        assert_eq!(encode_rref(1, 0, &"x".repeat(500))[412], 0);
+    }
+
+    #[test]
+    fn rref_answers_decode_to_pairs() {
+        let mut answer = b"RREF,".to_vec();
+        for (i, v) in [(0i32, 1.5f32), (42, -3.0)] {
+            answer.extend_from_slice(&i.to_le_bytes());
+            answer.extend_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(decode_rref(&answer), Some(vec![(0, 1.5), (42, -3.0)]));
+        // A trailing partial pair is dropped.
+        assert_eq!(decode_rref(&answer[..answer.len() - 3]), Some(vec![(0, 1.5)]));
+        assert_eq!(decode_rref(b"DATA*"), None);
+    }
+
+    #[test]
+    fn subscriptions_cover_the_snapshot_once() {
+        let subs = subscriptions();
+        let mut seen = std::collections::HashSet::new();
+        assert!(subs.iter().all(|s| seen.insert(s.path.as_str())));
+        assert_eq!(subs.len(), 48 + 6 + 2 + 3 * 20);
+        assert!(seen.contains("sim/cockpit2/engine/indicators/EGT_deg_c[5]"));
+        assert!(seen.contains("sim/cockpit2/tcas/targets/position/ele[19]"));
+    }
+
+    /// Fake X-Plane: answers nothing until told to, records what it gets.
+    fn fake_xplane() -> UdpSocket {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sock.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+        sock
+    }
+
+    fn recv(sock: &UdpSocket) -> (Vec<u8>, SocketAddr) {
+        let mut buf = [0u8; 2048];
+        let (n, from) = sock.recv_from(&mut buf).unwrap();
+        (buf[..n].to_vec(), from)
+    }
+
+    /// Let the bridge handle whatever reaches it over loopback.
+    fn settle(bridge: &mut Bridge) {
+        bridge.receive_until(Instant::now() + Duration::from_millis(50)).unwrap();
+    }
+
+    fn make_bridge(xplane: &UdpSocket, tablet: Option<SocketAddr>) -> Bridge {
+        Bridge::new(UdpSocket::bind("127.0.0.1:0").unwrap(), xplane.local_addr().unwrap(), tablet, 20)
+    }
+
+    #[test]
+    fn bridges_fake_xplane_to_a_tablet() {
+        let xplane = fake_xplane();
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+        let mut bridge = make_bridge(&xplane, Some(tablet.local_addr().unwrap()));
+        let t0 = Instant::now();
+
+        // Subscriptions: one RREF per element, indexed in order.
+        bridge.tick(t0).unwrap();
+        let mut index = HashMap::new();
+        let mut bridge_addr = None;
+        for _ in 0..bridge.subs.len() {
+            let (rref, from) = recv(&xplane);
+            assert_eq!(i32::from_le_bytes(rref[5..9].try_into().unwrap()), 20);
+            let i = i32::from_le_bytes(rref[9..13].try_into().unwrap());
+            let path = std::str::from_utf8(&rref[13..]).unwrap().trim_end_matches('\0').to_string();
+            index.insert(path, i);
+            bridge_addr = Some(from);
+        }
+        // Nothing is streamed before X-Plane answers.
+        assert!(tablet.recv(&mut [0u8; 16]).is_err());
+
+        let mut answer = b"RREF,".to_vec();
+        for (path, value) in [
+            (paths::LATITUDE.to_string(), -26.139_f32),
+            (paths::TRUE_AIRSPEED_MS.to_string(), 100.0),
+            (format!("{}[2]", paths::EGT_DEGC), 650.0),
+            (paths::COM1_STANDBY_HZ.to_string(), 12_480.0),
+            (paths::OUTER_MARKER.to_string(), 1.0),
+        ] {
+            answer.extend_from_slice(&index[&path].to_le_bytes());
+            answer.extend_from_slice(&value.to_le_bytes());
+        }
+        xplane.send_to(&answer, bridge_addr.unwrap()).unwrap();
+        settle(&mut bridge);
+        bridge.tick(Instant::now()).unwrap();
+
+        let (pkt, _) = recv(&tablet);
+        let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::SimData);
+        let snap = decode_sim_data(payload).unwrap();
+        assert_eq!(snap.latitude, -26.139_f32 as f64);
+        assert!((snap.tas_kts - 194.384).abs() < 0.01);
+        assert_eq!(snap.egt_degc[2], 650.0);
+        assert_eq!(snap.com1_standby_hz, 12_480);
+        assert!(snap.outer_marker);
+
+        // Commands become DREF/CMND; X-Plane never answers, so they are Ok.
+        let bridge_tablet_addr = bridge.socket.local_addr().unwrap();
+        tablet.send_to(&encode_ack(0), bridge_tablet_addr).unwrap();
+        let set = CommandRequest { id: Some(5), command: Command::SetStandbyFreq { radio: Radio::Nav2, hz: 11_030 } };
+        tablet.send_to(&encode_request(1, &set).unwrap(), bridge_tablet_addr).unwrap();
+        let swap = CommandRequest { id: Some(6), command: Command::SwapFreq { radio: Radio::Com1 } };
+        tablet.send_to(&encode_request(2, &swap).unwrap(), bridge_tablet_addr).unwrap();
+        settle(&mut bridge);
+
+        let (dref, _) = recv(&xplane);
+        assert_eq!(dref[..9], *b"DREF\0\0\x58\x2C\x46");
+        assert!(dref[9..].starts_with(paths::NAV2_STANDBY_HZ.as_bytes()));
+        assert_eq!(recv(&xplane).0, b"CMND\0sim/radios/com1_standy_flip");
+
+        // The ACK brings the Schema; each command gets its result.
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let (pkt, _) = recv(&tablet);
+            let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+            if ptype == PacketType::CommandResult {
+                let result = decode_command_result(payload).unwrap();
+                assert_eq!(result.status, CommandStatus::Ok);
+                ids.push(result.id.unwrap());
+            }
+        }
+        assert_eq!(ids, [5, 6]);
+
+        // A silent X-Plane is asked again.
+        bridge.tick(Instant::now() + RESUBSCRIBE_INTERVAL).unwrap();
+        assert_eq!(&recv(&xplane).0[..4], b"RREF");
+    }
+
+    #[test]
+    fn only_the_connected_tablet_is_served() {
+        let xplane = fake_xplane();
+        let mut bridge = make_bridge(&xplane, None);
+        let tablet: SocketAddr = "127.0.0.1:40001".parse().unwrap();
+        let intruder: SocketAddr = "127.0.0.1:40002".parse().unwrap();
+        let swap = encode_request(1, &CommandRequest::from(Command::SwapFreq { radio: Radio::Com1 })).unwrap();
+        let t0 = Instant::now();
+
+        // Commands before any ACK are not forwarded.
+        bridge.handle_tablet(&swap, tablet, t0).unwrap();
+        bridge.handle_tablet(&encode_ack(0), tablet, t0).unwrap();
+        assert_eq!(bridge.tablet, Some(tablet));
+
+        // Another host can neither take the tablet's place nor send commands.
+        bridge.handle_tablet(&encode_ack(0), intruder, t0).unwrap();
+        bridge.handle_tablet(&swap, intruder, t0).unwrap();
+        assert_eq!(bridge.tablet, Some(tablet));
+        assert!(xplane.recv(&mut [0u8; 16]).is_err());
+
+        bridge.handle_tablet(&swap, tablet, t0).unwrap();
+        assert_eq!(recv(&xplane).0, b"CMND\0sim/radios/com1_standy_flip");
+
+        // Once the tablet has been silent past the watchdog, another may connect.
+        let later = t0 + WATCHDOG_TIMEOUT + Duration::from_secs(1);
+        bridge.handle_tablet(&encode_ack(0), intruder, later).unwrap();
+        assert_eq!(bridge.tablet, Some(intruder));
+        bridge.handle_tablet(&swap, tablet, later).unwrap();
+        assert!(xplane.recv(&mut [0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn unreachable_tablet_does_not_stop_the_bridge() {
+        let xplane = fake_xplane();
+        // Sending to the broadcast address without SO_BROADCAST fails.
+        let unreachable: SocketAddr = "255.255.255.255:40001".parse().unwrap();
+        let mut bridge = make_bridge(&xplane, Some(unreachable));
+        let t0 = Instant::now();
+        bridge.handle_xplane(b"RREF,\0\0\0\0\0\0\x80\x3F", t0);
+        bridge.tick(t0).unwrap();
+        bridge.handle_tablet(&encode_ack(0), unreachable, t0).unwrap();
+        let hello = efb_protocol::handshake::encode_hello(0, &Hello::local());
+        bridge.handle_tablet(&hello, unreachable, t0).unwrap();
+
+        // Once the watchdog has dropped it, the next tablet is served.
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+        let later = t0 + WATCHDOG_TIMEOUT + Duration::from_secs(1);
+        bridge.handle_tablet(&encode_ack(0), tablet.local_addr().unwrap(), later).unwrap();
+        bridge.tick(later).unwrap();
+        let (pkt, _) = recv(&tablet);
+        assert_eq!(decode_packet(&pkt).unwrap().1, PacketType::Schema);
+        let (pkt, _) = recv(&tablet);
+        assert_eq!(decode_packet(&pkt).unwrap().1, PacketType::SimData);
+    }
+
+    #[test]
+    fn unsubscribe_stops_every_rref() {
+        let xplane = fake_xplane();
+        let mut bridge = make_bridge(&xplane, None);
+        // Never subscribed: nothing to stop.
+        bridge.unsubscribe().unwrap();
+
+        bridge.tick(Instant::now()).unwrap();
+        for _ in 0..bridge.subs.len() {
+            recv(&xplane);
+        }
+        bridge.unsubscribe().unwrap();
+        for i in 0..bridge.subs.len() {
+            let (rref, _) = recv(&xplane);
+            assert_eq!(rref[..13], encode_rref(0, i as i32, &bridge.subs[i].path)[..13]);
+        }
+        assert!(xplane.recv(&mut [0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+// efb-codegen/src/main.rs
+// Generates the app's SimSnapshot.kt — the Kotlin data class, its ByteBuffer
+// codec and the layout checksum — from dataref_schema::SNAPSHOT_FIELDS, so the
+// Kotlin mirror can no longer drift from the Rust struct.
+//
+// Regenerate after changing the field table:
+//   cargo run -p efb-codegen -- --out ../app/src/main/java/com/nameless/efb/data/connectivity/SimSnapshot.kt
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use dataref_schema::{layout_checksum, FieldDesc, FieldType, SimSnapshot, SNAPSHOT_FIELDS};
+use efb_protocol::{encode_sim_data, HEADER_LEN};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+// ---------------------------------------------------------------------------
+// CLI args
+// ---------------------------------------------------------------------------
+
+#[derive(Parser)]
+#[command(name = "efb-codegen", about = "Generate the Kotlin SimSnapshot decoder from dataref-schema")]
+struct Args {
+    /// Write to this file instead of stdout
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+
+    /// Do not write; fail if --out differs from the generated code
+    #[arg(long, requires = "out")]
+    check: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let code = kotlin_source();
+
+    match (&args.out, args.check) {
+        (None, _) => print!("{code}"),
+        (Some(path), true) => {
+            let current = std::fs::read_to_string(path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            if current != code {
+                bail!("{} is out of date with dataref-schema; rerun efb-codegen", path.display());
+            }
+        }
+        (Some(path), false) => {
+            std::fs::write(path, code).with_context(|| format!("writing {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Kotlin generation
+// ---------------------------------------------------------------------------
+
+const PACKAGE: &str = "com.nameless.efb.data.connectivity";
+
+/// `elevation_m` → `elevationM`.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn kotlin_type(f: &FieldDesc) -> &'static str {
+    match (f.ty, f.len) {
+        (FieldType::F64, 1)                 => "Double",
+        (FieldType::F32, 1)                 => "Float",
+        (FieldType::I32 | FieldType::U8, 1) => "Int",
+        (FieldType::Bool, 1)                => "Boolean",
+        (FieldType::F64, _)                 => "DoubleArray",
+        (FieldType::F32, _)                 => "FloatArray",
+        (FieldType::I32 | FieldType::U8, _) => "IntArray",
+        (FieldType::Bool, _)                => "BooleanArray",
+    }
+}
+
+/// Kotlin literal for one element of `ty` at the start of `b`.
+fn literal(ty: FieldType, b: &[u8]) -> String {
+    match ty {
+        FieldType::F64  => format!("{:?}", f64::from_le_bytes(b[..8].try_into().unwrap())),
+        FieldType::F32  => format!("{}f", f32::from_le_bytes(b[..4].try_into().unwrap())),
+        FieldType::I32  => i32::from_le_bytes(b[..4].try_into().unwrap()).to_string(),
+        FieldType::U8   => b[0].to_string(),
+        FieldType::Bool => (b[0] != 0).to_string(),
+    }
+}
+
+/// Default value expression, taken from the encoded `SimSnapshot::default()`.
+fn default_value(f: &FieldDesc, bytes: &[u8]) -> String {
+    if f.len == 1 {
+        return literal(f.ty, bytes);
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return format!("{}({})", kotlin_type(f), f.len);
+    }
+    let items: Vec<String> = bytes.chunks(f.ty.size()).map(|b| literal(f.ty, b)).collect();
+    let of = match f.ty {
+        FieldType::F64                 => "doubleArrayOf",
+        FieldType::F32                 => "floatArrayOf",
+        FieldType::I32 | FieldType::U8 => "intArrayOf",
+        FieldType::Bool                => "booleanArrayOf",
+    };
+    format!("{of}({})", items.join(", "))
+}
+
+/// Expression reading one element from `bb`.
+fn read_expr(ty: FieldType) -> &'static str {
+    match ty {
+        FieldType::F64  => "bb.double",
+        FieldType::F32  => "bb.float",
+        FieldType::I32  => "bb.int",
+        FieldType::U8   => "bb.get().toInt() and 0xFF",
+        FieldType::Bool => "bb.get().toInt() != 0",
+    }
+}
+
+/// Statement writing element `x` to `bb`.
+fn write_stmt(ty: FieldType, x: &str) -> String {
+    match ty {
+        FieldType::F64  => format!("bb.putDouble({x})"),
+        FieldType::F32  => format!("bb.putFloat({x})"),
+        FieldType::I32  => format!("bb.putInt({x})"),
+        FieldType::U8   => format!("bb.put({x}.toByte())"),
+        FieldType::Bool => format!("bb.put(if ({x}) 1 else 0)"),
+    }
+}
+
+fn kotlin_source() -> String {
+    kotlin_source_for(&SNAPSHOT_FIELDS, &encode_sim_data(0, &SimSnapshot::default())[HEADER_LEN..])
+}
+
+fn kotlin_source_for(fields: &[FieldDesc], defaults: &[u8]) -> String {
+    let names: Vec<String> = fields.iter().map(|f| camel_case(f.name)).collect();
+    let mut out = String::new();
+
+    // `writeln!` into a String cannot fail.
+    macro_rules! line {
+        ($($arg:tt)*) => { writeln!(out, $($arg)*).unwrap() };
+    }
+
+    line!("// Generated by efb-codegen from dataref-schema. Do not edit.");
+    line!("// Regenerate with: cargo run -p efb-codegen -- --out <this file>");
+    line!();
+    line!("package {PACKAGE}");
+    line!();
+    line!("import java.nio.ByteBuffer");
+    line!();
+    line!("/**");
+    line!(" * Kotlin mirror of the Rust `SimSnapshot` struct in dataref-schema.");
+    line!(" *");
+    line!(" * Fields are in wire order. Arrays are primitive arrays to avoid boxing.");
+    line!(" * Note: data class [copy] performs a shallow copy of arrays.");
+    line!(" */");
+    line!("data class SimSnapshot(");
+    let mut offset = 0;
+    let decls: Vec<String> = fields
+        .iter()
+        .zip(&names)
+        .map(|(f, name)| {
+            let default = default_value(f, &defaults[offset..offset + f.wire_size()]);
+            offset += f.wire_size();
+            format!("val {name}: {} = {default},", kotlin_type(f))
+        })
+        .collect();
+    let width = decls.iter().map(String::len).max().unwrap_or(0);
+    for (decl, f) in decls.iter().zip(fields) {
+        // Kotlin has no unsigned byte field type; note the range instead.
+        let note = if f.unit.is_empty() && f.ty == FieldType::U8 { "0..255" } else { f.unit };
+        if note.is_empty() {
+            line!("    {decl}");
+        } else {
+            line!("    {decl:width$}  // {note}");
+        }
+    }
+    line!(")");
+    line!();
+    line!("/** SimData payload codec for [SimSnapshot] (little-endian, no padding). */");
+    line!("object SimSnapshotCodec {{");
+    line!();
+    line!("    /** Must equal `dataref_schema::SNAPSHOT_LAYOUT_CHECKSUM` of the plugin build. */");
+    line!("    const val LAYOUT_CHECKSUM: Long = 0x{:08X}L", layout_checksum(fields));
+    line!();
+    line!("    const val PAYLOAD_LEN: Int = {}", fields.iter().map(FieldDesc::wire_size).sum::<usize>());
+    line!();
+    line!("    /** Read a snapshot; [bb] must be little-endian with [PAYLOAD_LEN] bytes remaining. */");
+    line!("    fun decode(bb: ByteBuffer): SimSnapshot = SimSnapshot(");
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+    for (f, name) in fields.iter().zip(&names) {
+        let expr = if f.len == 1 {
+            read_expr(f.ty).to_string()
+        } else {
+            format!("{}({}) {{ {} }}", kotlin_type(f), f.len, read_expr(f.ty))
+        };
+        line!("        {name:width$} = {expr},");
+    }
+    line!("    )");
+    line!();
+    line!("    /** Write [s]; [bb] must be little-endian with [PAYLOAD_LEN] bytes remaining. */");
+    line!("    fun encode(s: SimSnapshot, bb: ByteBuffer) {{");
+    for (f, name) in fields.iter().zip(&names) {
+        if f.len == 1 {
+            line!("        {}", write_stmt(f.ty, &format!("s.{name}")));
+        } else {
+            line!("        for (x in s.{name}) {}", write_stmt(f.ty, "x"));
+        }
+    }
+    line!("    }}");
+    line!("}}");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dataref_schema::{FieldGroup, SNAPSHOT_LAYOUT_CHECKSUM, SNAPSHOT_WIRE_LEN};
+
+    const KOTLIN_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../app/src/main/java/com/nameless/efb/data/connectivity/SimSnapshot.kt"
+    );
+
+    #[test]
+    fn committed_kotlin_is_current() {
+        let committed = std::fs::read_to_string(KOTLIN_PATH).unwrap();
+        assert!(
+            committed == kotlin_source(),
+            "SimSnapshot.kt is out of date with dataref-schema; run \
+             `cargo run -p efb-codegen -- --out {KOTLIN_PATH}`"
+        );
+    }
+
+    #[test]
+    fn names_types_and_defaults() {
+        assert_eq!(camel_case("nav1_hdef_dot"), "nav1HdefDot");
+        assert_eq!(camel_case("latitude"), "latitude");
+
+        let code = kotlin_source();
+        assert!(code.contains("val elevationM: Double = 0.0,"));
+        assert!(code.contains("val barometerInhg: Float = 29.92f,"));
+        assert!(code.contains("val egtDegc: FloatArray = FloatArray(6),"));
+        assert!(code.contains("trafficCount    = bb.get().toInt() and 0xFF,"));
+        assert!(code.contains("for (x in s.trafficLat) bb.putFloat(x)"));
+        assert!(code.contains(&format!("LAYOUT_CHECKSUM: Long = 0x{SNAPSHOT_LAYOUT_CHECKSUM:08X}L")));
+        assert!(code.contains(&format!("PAYLOAD_LEN: Int = {SNAPSHOT_WIRE_LEN}")));
+    }
+
+    #[test]
+    fn array_defaults_spelled_out() {
+        let fields = [FieldDesc { name: "flags", ty: FieldType::Bool, len: 2, unit: "", group: FieldGroup::Slow }];
+        let code = kotlin_source_for(&fields, &[1, 0]);
+        assert!(code.contains("val flags: BooleanArray = booleanArrayOf(true, false),"));
+        assert!(code.contains("flags = BooleanArray(2) { bb.get().toInt() != 0 },"));
+    }
+}
@@ -0,0 +1,913 @@
+// efb-dump/src/main.rs
+// Protocol dissector for the X-Plane EFB link: listens on a UDP port or reads
+// an .efbcap capture and prints every packet — header fields, the reason a
+// packet was rejected, decoded snapshots and commands — as text or JSON lines.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use efb_protocol::auth::{unix_micros, verify_packet};
+use efb_protocol::beacon::decode_beacon;
+use efb_protocol::command::{decode_command_json, decode_command_result, decode_request};
+use efb_protocol::groups::peek_group;
+use efb_protocol::handshake::{decode_hello, decode_hello_ack};
+use efb_protocol::schema::decode_schema;
+use efb_protocol::traffic::decode_traffic;
+use efb_protocol::{
+    decode_packet, encode_sim_data, flags, header_len, inflate_payload, CaptureReader, DeltaDecoder,
+    Direction, FieldValue, GroupDecoder, PacketHeader, PacketType, PairingKey, ProtocolError,
+    Reassembler, ReplayWindow, Schema, Signer, HEADER_LEN, HEADER_V2_LEN, MAGIC,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// CLI args
+// ---------------------------------------------------------------------------
+
+#[derive(Parser)]
+#[command(name = "efb-dump", about = "Print decoded EFB protocol packets")]
+struct Args {
+    /// Read packets from a capture file (.efbcap) instead of listening
+    #[arg(short, long)]
+    capture: Option<PathBuf>,
+
+    /// UDP address to listen on
+    #[arg(short, long, default_value = "0.0.0.0:49100", conflicts_with = "capture")]
+    listen: SocketAddr,
+
+    /// Print one JSON object per packet instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Only show these packet types, by name (sim-data, ack, …) or number (0x0B)
+    #[arg(short = 't', long = "type", value_delimiter = ',', value_parser = parse_packet_type)]
+    types: Vec<PacketType>,
+
+    /// Only show these snapshot fields; packets carrying none of them are hidden
+    #[arg(short, long, value_delimiter = ',')]
+    field: Vec<String>,
+
+    /// Verify auth tags and stamps of peers paired with this code
+    #[arg(long)]
+    pairing_code: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Every packet type, for `--type` parsing.
+const PACKET_TYPES: [PacketType; 15] = [
+    PacketType::SimData,
+    PacketType::CommandJson,
+    PacketType::Ack,
+    PacketType::Reload,
+    PacketType::Keyframe,
+    PacketType::Delta,
+    PacketType::Hello,
+    PacketType::HelloAck,
+    PacketType::Schema,
+    PacketType::Fragment,
+    PacketType::CommandBinary,
+    PacketType::Beacon,
+    PacketType::CommandResult,
+    PacketType::Group,
+    PacketType::Traffic,
+];
+
+/// Largest datagram accepted in listen mode.
+const MAX_DATAGRAM: usize = 65536;
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let local = Schema::local();
+    if let Some(unknown) = args.field.iter().find(|f| local.field(f).is_none()) {
+        bail!("Unknown field `{unknown}`");
+    }
+
+    let filter = Filter { types: args.types, fields: args.field };
+    let key = args.pairing_code.as_deref().map(PairingKey::from_code);
+    let mut printer = Printer { out: io::stdout().lock(), json: args.json, filter };
+    let result = match args.capture {
+        Some(path) => {
+            let file = File::open(&path).with_context(|| format!("Cannot open {}", path.display()))?;
+            dump_capture(BufReader::new(file), Dissector::new(key), &mut printer)
+                .with_context(|| format!("Cannot read {}", path.display()))
+        }
+        None => listen(args.listen, Dissector::new(key).live(), &mut printer),
+    };
+    match result {
+        // `efb-dump | head` closing the pipe is a normal way to stop.
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => Ok(()),
+        other => other,
+    }
+}
+
+fn dump_capture<R: Read, W: Write>(input: R, mut dissector: Dissector, printer: &mut Printer<W>) -> Result<()> {
+    let reader = CaptureReader::new(input)?;
+    let base = Instant::now();
+    for record in reader {
+        let record = record?;
+        let meta = Meta {
+            time:      record.timestamp,
+            direction: Some(record.direction),
+            src:       record.src,
+            dst:       Some(record.dst),
+            len:       record.data.len(),
+        };
+        let packets = dissector.dissect(record.src, &record.data, base + record.timestamp);
+        printer.print(&meta, packets)?;
+    }
+    Ok(())
+}
+
+fn listen<W: Write>(addr: SocketAddr, mut dissector: Dissector, printer: &mut Printer<W>) -> Result<()> {
+    let socket = UdpSocket::bind(addr).with_context(|| format!("Cannot bind {addr}"))?;
+    let local = socket.local_addr().ok();
+    eprintln!("Listening on {addr}");
+
+    let start = Instant::now();
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+    loop {
+        let (n, src) = socket.recv_from(&mut buf)?;
+        let now = Instant::now();
+        let meta = Meta { time: now - start, direction: None, src, dst: local, len: n };
+        let packets = dissector.dissect(src, &buf[..n], now);
+        printer.print(&meta, packets)?;
+    }
+}
+
+fn parse_packet_type(s: &str) -> Result<PacketType, String> {
+    let number = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse::<u8>().ok(),
+    };
+    let wanted: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    PACKET_TYPES
+        .into_iter()
+        .find(|&t| Some(t as u8) == number || format!("{t:?}").eq_ignore_ascii_case(&wanted))
+        .ok_or_else(|| format!("unknown packet type `{s}`"))
+}
+
+// ---------------------------------------------------------------------------
+// Dissection
+// ---------------------------------------------------------------------------
+
+/// Where and when a datagram was seen.
+struct Meta {
+    /// Since the capture (or listening) started.
+    time:      Duration,
+    direction: Option<Direction>,
+    src:       SocketAddr,
+    dst:       Option<SocketAddr>,
+    len:       usize,
+}
+
+/// Header fields read straight from the datagram, whether or not they are valid.
+#[derive(Debug, Clone, Copy)]
+struct RawHeader {
+    magic:       u32,
+    version:     u16,
+    packet_type: u8,
+    payload_len: u16,
+    sequence:    u32,
+    checksum:    u32,
+    /// Present if the version calls for a v2 header and the datagram is long enough.
+    v2:          Option<V2Fields>,
+    /// Bytes after the payload (the auth trailer of paired peers).
+    trailer:     usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct V2Fields {
+    flags:       u8,
+    sim_time_us: u64,
+    sent_us:     u64,
+}
+
+impl RawHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let b = data.get(..HEADER_LEN)?;
+        let u16_at = |i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(b[i..i + 4].try_into().unwrap());
+        let version = u16_at(4);
+        let payload_len = u16_at(7);
+        let hlen = header_len(version);
+        let v2 = data.get(HEADER_LEN..HEADER_V2_LEN).filter(|_| hlen == HEADER_V2_LEN).map(|e| {
+            let u64_at = |i: usize| u64::from_le_bytes(e[i..i + 8].try_into().unwrap());
+            V2Fields { flags: e[0], sim_time_us: u64_at(1), sent_us: u64_at(9) }
+        });
+        Some(RawHeader {
+            magic: u32_at(0),
+            version,
+            packet_type: b[6],
+            payload_len,
+            sequence: u32_at(9),
+            checksum: u32_at(13),
+            v2,
+            trailer: data.len().saturating_sub(hlen + payload_len as usize),
+        })
+    }
+}
+
+/// Names of the set v2 header flags, lowest bit first.
+fn flag_names(bits: u8) -> Vec<&'static str> {
+    [
+        (flags::COMPRESSED, "compressed"),
+        (flags::FRAGMENTED, "fragmented"),
+        (flags::AUTHENTICATED, "authenticated"),
+        (flags::KEYFRAME, "keyframe"),
+    ]
+    .into_iter()
+    .filter(|&(bit, _)| bits & bit != 0)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// Decoded content of a packet.
+#[derive(Debug, PartialEq)]
+enum Body {
+    Empty,
+    /// Snapshot fields of SimData, Keyframe, Delta and Group packets.
+    Fields(Vec<(String, FieldValue)>),
+    /// Everything else with a payload worth showing.
+    Json(Value),
+}
+
+/// One dissected packet, or a message reassembled from fragments.
+#[derive(Debug)]
+struct Dissection {
+    /// `None` for reassembled messages and datagrams shorter than a header.
+    header:      Option<RawHeader>,
+    /// Set once the header passed validation.
+    ptype:       Option<PacketType>,
+    body:        Body,
+    error:       Option<ProtocolError>,
+    reassembled: bool,
+}
+
+impl Dissection {
+    /// Type byte, from the validated type or the raw header.
+    fn type_byte(&self) -> Option<u8> {
+        self.ptype.map(|t| t as u8).or(self.header.map(|h| h.packet_type))
+    }
+
+    fn type_name(&self) -> String {
+        match (self.ptype, self.header) {
+            (Some(t), _) => format!("{t:?}"),
+            (None, Some(h)) => format!("0x{:02X}", h.packet_type),
+            (None, None) => "?".to_string(),
+        }
+    }
+}
+
+/// Per-peer decoding state.
+struct Dissector {
+    key:         Option<PairingKey>,
+    /// One replay window per signer, as each end of the link keeps.
+    windows:     HashMap<Signer, ReplayWindow>,
+    /// Packets are arriving now, so their stamps can be checked against the
+    /// clock; a capture is only checked for repeated stamps.
+    live:        bool,
+    deltas:      HashMap<SocketAddr, DeltaDecoder>,
+    /// Layout announced by each peer's Schema packet.
+    schemas:     HashMap<SocketAddr, Schema>,
+    local:       Schema,
+    reassembler: Reassembler,
+}
+
+impl Dissector {
+    fn new(key: Option<PairingKey>) -> Self {
+        Dissector {
+            key,
+            windows: HashMap::new(),
+            live: false,
+            deltas: HashMap::new(),
+            schemas: HashMap::new(),
+            local: Schema::local(),
+            reassembler: Reassembler::default(),
+        }
+    }
+
+    fn live(mut self) -> Self {
+        self.live = true;
+        self
+    }
+
+    /// Dissect one datagram from `src`. A Fragment completing a message is
+    /// followed by the reassembled message.
+    fn dissect(&mut self, src: SocketAddr, data: &[u8], now: Instant) -> Vec<Dissection> {
+        let header = RawHeader::parse(data);
+        let decoded = match self.key.clone() {
+            Some(key) => self.authenticate(&key, data),
+            None => decode_packet(data),
+        };
+        let (hdr, ptype, payload) = match decoded {
+            Ok(d) => d,
+            Err(e) => {
+                return vec![Dissection { header, ptype: None, body: Body::Empty, error: Some(e), reassembled: false }];
+            }
+        };
+
+        if ptype != PacketType::Fragment {
+            let (body, error) = match inflate_payload(&hdr, payload) {
+                Ok(payload) => self.body(src, &hdr, ptype, &payload),
+                Err(e) => (Body::Empty, Some(e)),
+            };
+            return vec![Dissection { header, ptype: Some(ptype), body, error, reassembled: false }];
+        }
+
+        let prefix = payload.get(..9).map(|p| {
+            json!({
+                "message_id": u32::from_le_bytes(p[0..4].try_into().unwrap()),
+                "index":      u16::from_le_bytes([p[4], p[5]]),
+                "count":      u16::from_le_bytes([p[6], p[7]]),
+                "inner_type": p[8],
+            })
+        });
+        let mut out = vec![Dissection {
+            header,
+            ptype: Some(ptype),
+            body: prefix.clone().map_or(Body::Empty, Body::Json),
+            error: None,
+            reassembled: false,
+        }];
+        match self.reassembler.push(src, payload, now) {
+            Ok(None) => {}
+            Ok(Some((inner, message))) => {
+                // The message id is the sequence number of the first fragment.
+                let id = prefix.and_then(|p| p["message_id"].as_u64()).unwrap_or(0) as u32;
+                let inner_hdr = PacketHeader { sequence: id, payload_len: message.len() as u16, ..hdr };
+                let (body, error) = match inflate_payload(&hdr, &message) {
+                    Ok(message) => self.body(src, &inner_hdr, inner, &message),
+                    Err(e) => (Body::Empty, Some(e)),
+                };
+                out.push(Dissection { header: None, ptype: Some(inner), body, error, reassembled: true });
+            }
+            Err(e) => out[0].error = Some(e),
+        }
+        out
+    }
+
+    /// Check the tag of a datagram signed by either end, and its stamp
+    /// against that end's replay window.
+    fn authenticate<'a>(
+        &mut self,
+        key: &PairingKey,
+        data: &'a [u8],
+    ) -> Result<(PacketHeader, PacketType, &'a [u8]), ProtocolError> {
+        let (signer, (decoded, stamp)) = match verify_packet(data, key, Signer::Tablet) {
+            Err(ProtocolError::BadAuth) => (Signer::Plugin, verify_packet(data, key, Signer::Plugin)?),
+            other => (Signer::Tablet, other?),
+        };
+        let now = if self.live { unix_micros() } else { stamp };
+        self.windows.entry(signer).or_insert_with(|| ReplayWindow::since(0)).accept(stamp, now)?;
+        Ok(decoded)
+    }
+
+    /// Decode the payload of a validated packet. Command JSON that parses but
+    /// is not a valid command is returned together with the reason.
+    fn body(
+        &mut self,
+        src: SocketAddr,
+        hdr: &PacketHeader,
+        ptype: PacketType,
+        payload: &[u8],
+    ) -> (Body, Option<ProtocolError>) {
+        let result = match ptype {
+            PacketType::SimData | PacketType::Keyframe | PacketType::Delta => {
+                self.snapshot_fields(src, hdr, ptype, payload).map(Body::Fields)
+            }
+            PacketType::Group => self.group_fields(hdr, payload).map(Body::Fields),
+            PacketType::Traffic => decode_traffic(payload).map(|targets| Body::Json(json!({ "targets": targets }))),
+            PacketType::CommandJson => match serde_json::from_slice::<Value>(payload) {
+                Ok(value) => return (Body::Json(value), decode_command_json(payload).err()),
+                Err(_) => Err(decode_command_json(payload).expect_err("payload is not JSON")),
+            },
+            PacketType::CommandBinary => decode_request(payload)
+                .map(|req| Body::Json(serde_json::to_value(req).expect("Command always serializes"))),
+            PacketType::CommandResult => decode_command_result(payload).map(|r| {
+                Body::Json(json!({ "id": r.id, "status": r.status, "message": r.message }))
+            }),
+            PacketType::Hello => decode_hello(payload).map(|h| {
+                Body::Json(json!({
+                    "versions":     { "min": h.versions.min, "max": h.versions.max },
+                    "capabilities": h.capabilities,
+                }))
+            }),
+            PacketType::HelloAck => decode_hello_ack(payload)
+                .map(|a| Body::Json(json!({ "version": a.version, "capabilities": a.capabilities }))),
+            PacketType::Schema => decode_schema(payload).map(|schema| {
+                let body = json!({
+                    "fields":      schema.fields.len(),
+                    "payload_len": schema.payload_len(),
+                });
+                self.schemas.insert(src, schema);
+                Body::Json(body)
+            }),
+            PacketType::Beacon => decode_beacon(payload).map(|b| {
+                Body::Json(json!({
+                    "versions":       { "min": b.versions.min, "max": b.versions.max },
+                    "capabilities":   b.capabilities,
+                    "stream_port":    b.stream_port,
+                    "command_port":   b.command_port,
+                    "tcp_port":       b.tcp_port,
+                    "plugin_version": b.plugin_version,
+                    "hostname":       b.hostname,
+                    "aircraft_icao":  b.aircraft_icao,
+                }))
+            }),
+            PacketType::Ack | PacketType::Reload | PacketType::Fragment => Ok(Body::Empty),
+        };
+        match result {
+            Ok(body) => (body, None),
+            Err(e) => (Body::Empty, Some(e)),
+        }
+    }
+
+    fn snapshot_fields(
+        &mut self,
+        src: SocketAddr,
+        hdr: &PacketHeader,
+        ptype: PacketType,
+        payload: &[u8],
+    ) -> Result<Vec<(String, FieldValue)>, ProtocolError> {
+        let snapshot = self.deltas.entry(src).or_default().decode(hdr, ptype, payload)?;
+        if ptype == PacketType::Delta {
+            // Deltas only make sense against our own layout.
+            return self.local.decode(&encode_sim_data(0, &snapshot)[HEADER_LEN..]);
+        }
+        self.schemas.get(&src).unwrap_or(&self.local).decode(payload)
+    }
+
+    /// Just the fields a Group packet carries, in the local layout.
+    fn group_fields(&self, hdr: &PacketHeader, payload: &[u8]) -> Result<Vec<(String, FieldValue)>, ProtocolError> {
+        let group = peek_group(payload)?;
+        let snapshot = GroupDecoder::new().decode(hdr, PacketType::Group, payload)?;
+        let mut fields = self.local.decode(&encode_sim_data(0, &snapshot)[HEADER_LEN..])?;
+        fields.retain(|(name, _)| group.fields().any(|f| f.name == name));
+        Ok(fields)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// Which packets and fields to print.
+#[derive(Default)]
+struct Filter {
+    types:  Vec<PacketType>,
+    fields: Vec<String>,
+}
+
+impl Filter {
+    /// Apply the filter; `None` if the packet should be hidden.
+    fn apply(&self, mut d: Dissection) -> Option<Dissection> {
+        if !self.types.is_empty() && !self.types.iter().any(|&t| Some(t as u8) == d.type_byte()) {
+            return None;
+        }
+        if !self.fields.is_empty() {
+            let Body::Fields(fields) = &mut d.body else { return None };
+            fields.retain(|(name, _)| self.fields.contains(name));
+            if fields.is_empty() {
+                return None;
+            }
+        }
+        Some(d)
+    }
+}
+
+struct Printer<W> {
+    out:    W,
+    json:   bool,
+    filter: Filter,
+}
+
+impl<W: Write> Printer<W> {
+    fn print(&mut self, meta: &Meta, packets: Vec<Dissection>) -> io::Result<()> {
+        for d in packets.into_iter().filter_map(|d| self.filter.apply(d)) {
+            if self.json {
+                writeln!(self.out, "{}", to_json(meta, &d))?;
+            } else {
+                write_text(&mut self.out, meta, &d)?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+fn direction_name(d: Direction) -> &'static str {
+    match d {
+        Direction::Inbound => "in",
+        Direction::Outbound => "out",
+    }
+}
+
+fn write_text<W: Write>(out: &mut W, meta: &Meta, d: &Dissection) -> io::Result<()> {
+    if d.reassembled {
+        write!(out, "{:>12}  ↳ reassembled {}", "", d.type_name())?;
+    } else {
+        write!(out, "{:>12.6} ", meta.time.as_secs_f64())?;
+        if let Some(dir) = meta.direction {
+            write!(out, "{:<3} ", direction_name(dir))?;
+        }
+        write!(out, "{} → ", meta.src)?;
+        match meta.dst {
+            Some(dst) => write!(out, "{dst}")?,
+            None => write!(out, "?")?,
+        }
+        write!(out, "  {} ", d.type_name())?;
+        match d.header {
+            Some(h) => {
+                if h.magic != MAGIC {
+                    write!(out, "magic=0x{:08X} ", h.magic)?;
+                }
+                write!(
+                    out,
+                    "v{} seq={} len={} crc=0x{:08X}",
+                    h.version, h.sequence, h.payload_len, h.checksum
+                )?;
+                if let Some(v2) = h.v2 {
+                    write!(out, " sim={:.6}s sent={:.6}s", v2.sim_time_us as f64 / 1e6, v2.sent_us as f64 / 1e6)?;
+                    if v2.flags != 0 {
+                        write!(out, " [{}]", flag_names(v2.flags).join(","))?;
+                    }
+                }
+                if h.trailer > 0 {
+                    write!(out, " +{}B trailer", h.trailer)?;
+                }
+            }
+            None => write!(out, "({} bytes)", meta.len)?,
+        }
+    }
+    if let Some(e) = &d.error {
+        write!(out, "  REJECTED: {e}")?;
+    }
+    writeln!(out)?;
+
+    match &d.body {
+        Body::Empty => Ok(()),
+        Body::Json(v) => writeln!(out, "{:>14}{v}", ""),
+        Body::Fields(fields) => {
+            for (name, value) in fields {
+                writeln!(out, "{:>14}{name:<20} {}", "", format_value(value))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn format_value(v: &FieldValue) -> String {
+    match v {
+        FieldValue::F64(x)  => x.to_string(),
+        FieldValue::F32(x)  => x.to_string(),
+        FieldValue::I32(x)  => x.to_string(),
+        FieldValue::U8(x)   => x.to_string(),
+        FieldValue::Bool(x) => x.to_string(),
+        FieldValue::Array(a) => {
+            let items: Vec<String> = a.iter().map(format_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+    }
+}
+
+fn to_json(meta: &Meta, d: &Dissection) -> Value {
+    let mut obj = Map::new();
+    obj.insert("time".into(), json!(meta.time.as_secs_f64()));
+    if let Some(dir) = meta.direction {
+        obj.insert("direction".into(), json!(direction_name(dir)));
+    }
+    obj.insert("src".into(), json!(meta.src.to_string()));
+    obj.insert("dst".into(), meta.dst.map_or(Value::Null, |a| json!(a.to_string())));
+    obj.insert("len".into(), json!(meta.len));
+    obj.insert("type".into(), json!(d.type_name()));
+    if d.reassembled {
+        obj.insert("reassembled".into(), json!(true));
+    }
+    if let Some(h) = d.header {
+        let mut header = json!({
+            "magic":       h.magic,
+            "version":     h.version,
+            "packet_type": h.packet_type,
+            "payload_len": h.payload_len,
+            "seq":         h.sequence,
+            "crc":         h.checksum,
+            "trailer":     h.trailer,
+        });
+        if let Some(v2) = h.v2 {
+            header["flags"]       = json!(flag_names(v2.flags));
+            header["sim_time_us"] = json!(v2.sim_time_us);
+            header["sent_us"]     = json!(v2.sent_us);
+        }
+        obj.insert("header".into(), header);
+    }
+    if let Some(e) = &d.error {
+        obj.insert("error".into(), json!(e.to_string()));
+    }
+    match &d.body {
+        Body::Empty => {}
+        Body::Json(v) => {
+            obj.insert("payload".into(), v.clone());
+        }
+        Body::Fields(fields) => {
+            let map = fields.iter().map(|(name, v)| (name.clone(), value_json(v))).collect();
+            obj.insert("fields".into(), Value::Object(map));
+        }
+    }
+    Value::Object(obj)
+}
+
+/// f32 values are printed with their shortest round-trip representation
+/// rather than widened to f64 (`0.1`, not `0.10000000149011612`).
+fn value_json(v: &FieldValue) -> Value {
+    match v {
+        FieldValue::F64(x)  => json!(x),
+        FieldValue::F32(x)  => json!(x.to_string().parse::<f64>().ok()),
+        FieldValue::I32(x)  => json!(x),
+        FieldValue::U8(x)   => json!(x),
+        FieldValue::Bool(x) => json!(x),
+        FieldValue::Array(a) => Value::Array(a.iter().map(value_json).collect()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dataref_schema::SimSnapshot;
+    use efb_protocol::auth::sign_packet;
+    use efb_protocol::command::{
+        encode_command, encode_command_json, encode_command_result, encode_request, CommandRequest,
+        CommandResult, CommandStatus,
+    };
+    use efb_protocol::{
+        encode_ack, encode_group, fragment_payload, upgrade_packet, CaptureWriter, Command, DeltaEncoder,
+        FieldGroup, Radio, ThreatLevel, Timestamps, TrafficTarget,
+    };
+    use efb_protocol::traffic::encode_traffic;
+    use std::io::Cursor;
+
+    fn plugin() -> SocketAddr {
+        "192.168.1.10:49100".parse().unwrap()
+    }
+    fn tablet() -> SocketAddr {
+        "192.168.1.20:49100".parse().unwrap()
+    }
+
+    fn snapshot() -> SimSnapshot {
+        SimSnapshot { latitude: 51.4706, ias_kts: 0.1, transponder_code: 7000, ..SimSnapshot::default() }
+    }
+
+    fn field<'a>(d: &'a Dissection, name: &str) -> &'a FieldValue {
+        let Body::Fields(fields) = &d.body else { panic!("no fields in {d:?}") };
+        &fields.iter().find(|(n, _)| n == name).unwrap().1
+    }
+
+    #[test]
+    fn sim_data_fields_decoded() {
+        let mut dis = Dissector::new(None);
+        let out = dis.dissect(plugin(), &encode_sim_data(42, &snapshot()), Instant::now());
+        assert_eq!(out.len(), 1);
+        let d = &out[0];
+        assert_eq!(d.ptype, Some(PacketType::SimData));
+        assert_eq!(d.header.unwrap().sequence, 42);
+        assert!(d.error.is_none());
+        assert_eq!(field(d, "latitude"), &FieldValue::F64(51.4706));
+        assert_eq!(field(d, "transponder_code"), &FieldValue::I32(7000));
+    }
+
+    #[test]
+    fn v2_packets_inflated_with_timestamps() {
+        let ts = Timestamps { sim_time_us: 12_500_000, sent_us: 250 };
+        let pkt = upgrade_packet(&encode_sim_data(42, &snapshot()), ts, true).unwrap();
+        let mut dis = Dissector::new(None);
+        let d = &dis.dissect(plugin(), &pkt, Instant::now())[0];
+        assert!(d.error.is_none());
+        assert_eq!(field(d, "latitude"), &FieldValue::F64(51.4706));
+
+        let meta = Meta { time: Duration::ZERO, direction: None, src: plugin(), dst: None, len: pkt.len() };
+        let mut text = Vec::new();
+        write_text(&mut text, &meta, d).unwrap();
+        assert!(String::from_utf8(text).unwrap().contains("v2 seq=42"));
+        let header = &to_json(&meta, d)["header"];
+        assert_eq!(header["flags"], json!(["compressed", "keyframe"]));
+        assert_eq!(header["sim_time_us"], 12_500_000);
+        assert_eq!(header["trailer"], 0);
+    }
+
+    #[test]
+    fn rejected_packet_keeps_raw_header_and_reason() {
+        let mut pkt = encode_ack(9);
+        pkt[13] ^= 0xFF; // checksum
+        let mut dis = Dissector::new(None);
+        let d = &dis.dissect(tablet(), &pkt, Instant::now())[0];
+        assert_eq!(d.error, Some(ProtocolError::BadChecksum));
+        assert_eq!(d.ptype, None);
+        assert_eq!(d.type_name(), "0x03");
+        assert_eq!(d.header.unwrap().sequence, 9);
+
+        let d = &dis.dissect(tablet(), b"short", Instant::now())[0];
+        assert_eq!(d.error, Some(ProtocolError::TooShort));
+        assert!(d.header.is_none());
+    }
+
+    #[test]
+    fn commands_shown_as_json() {
+        let mut dis = Dissector::new(None);
+        let cmd = Command::SetStandbyFreq { radio: Radio::Com1, hz: 118_125_000 };
+        let expected = json!({ "cmd": "set_standby_freq", "radio": "COM1", "hz": 118_125_000 });
+        for pkt in [encode_command(1, &cmd).unwrap(), encode_command_json(2, &cmd)] {
+            let d = &dis.dissect(tablet(), &pkt, Instant::now())[0];
+            assert_eq!(d.body, Body::Json(expected.clone()));
+            assert!(d.error.is_none());
+        }
+
+        // Valid JSON, invalid command: show both.
+        let pkt = frame(PacketType::CommandJson, br#"{"cmd":"direct_to","id":"FAOR"}"#);
+        let d = &dis.dissect(tablet(), &pkt, Instant::now())[0];
+        assert_eq!(d.body, Body::Json(json!({ "cmd": "direct_to", "id": "FAOR" })));
+        assert_eq!(d.error, Some(ProtocolError::UnknownCommandName("direct_to".into())));
+
+        // Ids and the plugin's reply.
+        let req = CommandRequest { id: Some(7), command: cmd };
+        let d = &dis.dissect(tablet(), &encode_request(3, &req).unwrap(), Instant::now())[0];
+        assert_eq!(d.body, Body::Json(json!({ "id": 7, "cmd": "set_standby_freq", "radio": "COM1", "hz": 118_125_000 })));
+        let result = CommandResult::error(Some(7), CommandStatus::UnsupportedRadio, "COM2 standby frequency not available");
+        let d = &dis.dissect(plugin(), &encode_command_result(4, &result), Instant::now())[0];
+        assert_eq!(d.ptype, Some(PacketType::CommandResult));
+        assert_eq!(d.body, Body::Json(json!({
+            "id": 7, "status": "unsupported_radio", "message": "COM2 standby frequency not available",
+        })));
+    }
+
+    /// Frame an arbitrary payload the way the plugin does.
+    fn frame(ptype: PacketType, payload: &[u8]) -> Vec<u8> {
+        let mut seq = 0;
+        fragment_payload(ptype, payload, usize::MAX, || {
+            seq += 1;
+            seq
+        })
+        .unwrap()
+        .remove(0)
+    }
+
+    #[test]
+    fn deltas_need_their_keyframe() {
+        let mut enc = DeltaEncoder::new(10);
+        let key = enc.encode(1, &snapshot());
+        let delta = enc.encode(2, &SimSnapshot { ias_kts: 95.0, ..snapshot() });
+
+        let mut dis = Dissector::new(None);
+        assert_eq!(dis.dissect(plugin(), &delta, Instant::now())[0].error, Some(ProtocolError::MissingKeyframe));
+        dis.dissect(plugin(), &key, Instant::now());
+        let d = &dis.dissect(plugin(), &delta, Instant::now())[0];
+        assert_eq!(d.ptype, Some(PacketType::Delta));
+        assert_eq!(field(d, "ias_kts"), &FieldValue::F32(95.0));
+        assert_eq!(field(d, "latitude"), &FieldValue::F64(51.4706));
+    }
+
+    #[test]
+    fn groups_show_only_their_fields() {
+        let mut dis = Dissector::new(None);
+        let d = &dis.dissect(plugin(), &encode_group(5, FieldGroup::Slow, &snapshot()), Instant::now())[0];
+        assert_eq!(d.ptype, Some(PacketType::Group));
+        assert_eq!(field(d, "transponder_code"), &FieldValue::I32(7000));
+        let Body::Fields(fields) = &d.body else { unreachable!() };
+        assert!(fields.iter().all(|(name, _)| name != "latitude"));
+
+        let d = &dis.dissect(plugin(), &frame(PacketType::Group, &[9]), Instant::now())[0];
+        assert_eq!(d.error, Some(ProtocolError::UnknownGroup(9)));
+    }
+
+    #[test]
+    fn traffic_targets_are_listed() {
+        let target = TrafficTarget {
+            icao_address: 0x8A2C41,
+            callsign:     "SAA335".into(),
+            latitude:     -26.133_692_4,
+            threat:       ThreatLevel::TrafficAdvisory,
+            ..TrafficTarget::default()
+        };
+        let mut dis = Dissector::new(None);
+        let d = &dis.dissect(plugin(), &encode_traffic(6, &[target]), Instant::now())[0];
+        assert_eq!(d.ptype, Some(PacketType::Traffic));
+        let Body::Json(body) = &d.body else { unreachable!() };
+        assert_eq!(body["targets"][0]["callsign"], "SAA335");
+        assert_eq!(body["targets"][0]["latitude"], -26.133_692_4);
+        assert_eq!(body["targets"][0]["threat"], "traffic_advisory");
+    }
+
+    #[test]
+    fn fragments_are_reassembled() {
+        let body = br#"{"cmd":"swap_freq","radio":"NAV1"}"#;
+        let mut seq = 100;
+        let frags = fragment_payload(PacketType::CommandJson, body, HEADER_LEN + 9 + 8, || {
+            seq += 1;
+            seq
+        })
+        .unwrap();
+        assert!(frags.len() > 2);
+
+        let mut dis = Dissector::new(None);
+        let mut all = Vec::new();
+        for f in &frags {
+            all.extend(dis.dissect(tablet(), f, Instant::now()));
+        }
+        assert_eq!(all.len(), frags.len() + 1);
+        assert!(all[..frags.len()].iter().all(|d| d.ptype == Some(PacketType::Fragment)));
+        let last = all.last().unwrap();
+        assert!(last.reassembled);
+        assert_eq!(last.body, Body::Json(json!({ "cmd": "swap_freq", "radio": "NAV1" })));
+    }
+
+    #[test]
+    fn pairing_code_checks_tags_and_replays() {
+        let key = PairingKey::from_code("4711");
+        let mut pkt = encode_ack(5);
+        sign_packet(&mut pkt, &key, Signer::Tablet, 5);
+
+        // A capture is not checked against the clock, so an old stamp is fine.
+        let mut dis = Dissector::new(Some(key.clone()));
+        let d = &dis.dissect(tablet(), &pkt, Instant::now())[0];
+        assert!(d.error.is_none());
+        assert_eq!(d.header.unwrap().trailer, pkt.len() - HEADER_LEN);
+        assert_eq!(dis.dissect(tablet(), &pkt, Instant::now())[0].error, Some(ProtocolError::Replay));
+        assert_eq!(dis.dissect(tablet(), &encode_ack(6), Instant::now())[0].error, Some(ProtocolError::BadAuth));
+
+        // The other end's packets verify too, with their own stamps.
+        let mut data = encode_sim_data(7, &snapshot());
+        sign_packet(&mut data, &key, Signer::Plugin, 5);
+        assert!(dis.dissect(plugin(), &data, Instant::now())[0].error.is_none());
+
+        // Listening live, it is.
+        let mut live = Dissector::new(Some(key)).live();
+        assert_eq!(live.dissect(tablet(), &pkt, Instant::now())[0].error, Some(ProtocolError::Replay));
+    }
+
+    #[test]
+    fn packet_type_names_and_numbers() {
+        assert_eq!(parse_packet_type("sim-data"), Ok(PacketType::SimData));
+        assert_eq!(parse_packet_type("CommandBinary"), Ok(PacketType::CommandBinary));
+        assert_eq!(parse_packet_type("hello_ack"), Ok(PacketType::HelloAck));
+        assert_eq!(parse_packet_type("0x0B"), Ok(PacketType::CommandBinary));
+        assert_eq!(parse_packet_type("3"), Ok(PacketType::Ack));
+        assert_eq!(parse_packet_type("beacon"), Ok(PacketType::Beacon));
+        assert!(parse_packet_type("bogus").is_err());
+    }
+
+    fn capture() -> Vec<u8> {
+        let mut w = CaptureWriter::new(Vec::new()).unwrap();
+        w.record(Direction::Outbound, plugin(), tablet(), &encode_sim_data(1, &snapshot())).unwrap();
+        w.record(Direction::Inbound, tablet(), plugin(), &encode_ack(1)).unwrap();
+        w.record(Direction::Inbound, tablet(), plugin(), b"garbage from a port scanner").unwrap();
+        w.into_inner()
+    }
+
+    fn dump(filter: Filter, json: bool) -> String {
+        let mut printer = Printer { out: Vec::new(), json, filter };
+        dump_capture(Cursor::new(capture()), Dissector::new(None), &mut printer).unwrap();
+        String::from_utf8(printer.out).unwrap()
+    }
+
+    #[test]
+    fn text_output_from_capture() {
+        let text = dump(Filter::default(), false);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].contains("out 192.168.1.10:49100 → 192.168.1.20:49100  SimData v1 seq=1 len=464"));
+        assert!(text.contains("latitude             51.4706"));
+        assert!(text.contains("in  192.168.1.20:49100 → 192.168.1.10:49100  Ack v1 seq=1 len=0"));
+        assert!(lines.last().unwrap().ends_with("REJECTED: bad magic bytes"));
+    }
+
+    #[test]
+    fn json_lines_with_filters() {
+        let filter = Filter { types: vec![], fields: vec!["ias_kts".into(), "latitude".into()] };
+        let text = dump(filter, true);
+        let lines: Vec<Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 1, "only SimData carries the fields");
+        let v = &lines[0];
+        assert_eq!(v["type"], "SimData");
+        assert_eq!(v["direction"], "out");
+        assert_eq!(v["header"]["seq"], 1);
+        assert_eq!(v["fields"], json!({ "latitude": 51.4706, "ias_kts": 0.1 }));
+
+        let text = dump(Filter { types: vec![PacketType::Ack], fields: vec![] }, true);
+        let lines: Vec<Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["type"], "Ack");
+        assert!(lines[0].get("error").is_none());
+    }
+}
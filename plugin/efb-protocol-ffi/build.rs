@@ -0,0 +1,25 @@
+// efb-protocol-ffi/build.rs
+//
+// Generates efb_protocol.h from the C ABI in src/lib.rs into OUT_DIR. The
+// copy in include/ is checked in so the app build does not need cbindgen;
+// the `checked_in_header_is_current` test fails when it falls behind.
+
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is invalid");
+
+    let bindings = cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate C header");
+    bindings.write_to_file(out_dir.join("efb_protocol.h"));
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=../dataref-schema/src/lib.rs");
+}
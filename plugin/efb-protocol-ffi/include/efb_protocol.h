@@ -0,0 +1,211 @@
+#ifndef EFB_PROTOCOL_H
+#define EFB_PROTOCOL_H
+
+/* Generated by efb-protocol-ffi/build.rs with cbindgen. Do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Outcome of an FFI call. Codes -1 to -99 mirror `ProtocolError`; codes
+ * from -100 are caller errors.
+ */
+enum EfbStatus
+#ifdef __cplusplus
+  : int32_t
+#endif // __cplusplus
+ {
+  EFB_STATUS_OK = 0,
+  EFB_STATUS_TOO_SHORT = -1,
+  EFB_STATUS_BAD_MAGIC = -2,
+  EFB_STATUS_BAD_VERSION = -3,
+  EFB_STATUS_UNKNOWN_PACKET_TYPE = -4,
+  EFB_STATUS_PAYLOAD_TOO_LARGE = -5,
+  EFB_STATUS_TRUNCATED_PAYLOAD = -6,
+  EFB_STATUS_BAD_CHECKSUM = -7,
+  EFB_STATUS_MISSING_KEYFRAME = -8,
+  EFB_STATUS_MALFORMED_SCHEMA = -9,
+  EFB_STATUS_MALFORMED_FRAGMENT = -10,
+  EFB_STATUS_REASSEMBLY_OVERFLOW = -11,
+  EFB_STATUS_BAD_AUTH = -12,
+  EFB_STATUS_REPLAY = -13,
+  EFB_STATUS_UNKNOWN_COMMAND = -14,
+  EFB_STATUS_UNKNOWN_RADIO = -15,
+  EFB_STATUS_MALFORMED_COMMAND = -16,
+  EFB_STATUS_BAD_COMPRESSION = -17,
+  EFB_STATUS_UNKNOWN_GROUP = -18,
+  EFB_STATUS_MALFORMED_TRAFFIC = -19,
+  EFB_STATUS_TRAILING_BYTES = -20,
+  /**
+   * A required pointer argument was null.
+   */
+  EFB_STATUS_NULL_POINTER = -100,
+  /**
+   * The output buffer is too small; the required size was reported.
+   */
+  EFB_STATUS_BUFFER_TOO_SMALL = -101,
+  /**
+   * The packet decoded but is not of the type the function handles.
+   */
+  EFB_STATUS_WRONG_PACKET_TYPE = -102,
+  /**
+   * A string argument is not valid UTF-8.
+   */
+  EFB_STATUS_INVALID_UTF8 = -103,
+};
+#ifndef __cplusplus
+typedef int32_t EfbStatus;
+#endif // __cplusplus
+
+/**
+ * Header fields of a validated packet and where its payload lies.
+ */
+typedef struct {
+  uint16_t version;
+  uint8_t packet_type;
+  /**
+   * v2 header flags; 0 for v1.
+   */
+  uint8_t flags;
+  uint32_t sequence;
+  /**
+   * v2 only, 0 for v1.
+   */
+  uint64_t sim_time_us;
+  /**
+   * v2 only, 0 for v1.
+   */
+  uint64_t sent_us;
+  /**
+   * Offset of the payload from the start of the buffer.
+   */
+  size_t payload_offset;
+  /**
+   * Payload bytes as sent (compressed if flagged).
+   */
+  size_t payload_len;
+} EfbPacketInfo;
+
+/**
+ * A complete snapshot of all sim state streamed per UDP datagram.
+ */
+typedef struct {
+  double latitude;
+  double longitude;
+  double elevation_m;
+  float groundspeed_ms;
+  float pitch_deg;
+  float roll_deg;
+  float mag_heading_deg;
+  float ground_track_deg;
+  float ias_kts;
+  float tas_kts;
+  float vvi_fpm;
+  float turn_rate_deg_sec;
+  float slip_deg;
+  float oat_degc;
+  float barometer_inhg;
+  float rpm;
+  float map_inhg;
+  float fuel_flow_kg_sec;
+  float oil_press_psi;
+  float oil_temp_degc;
+  float egt_degc[6];
+  float fuel_qty_kg[2];
+  float bus_volts;
+  float battery_amps;
+  float suction_inhg;
+  float nav1_hdef_dot;
+  float nav1_vdef_dot;
+  float nav1_obs_deg;
+  float gps_dist_nm;
+  float gps_bearing_deg;
+  int32_t ap_state_flags;
+  float fd_pitch_deg;
+  float fd_roll_deg;
+  float ap_heading_bug_deg;
+  float ap_altitude_ft;
+  float ap_vs_fpm;
+  int32_t com1_active_hz;
+  int32_t com1_standby_hz;
+  int32_t com2_active_hz;
+  int32_t nav1_active_hz;
+  int32_t nav1_standby_hz;
+  int32_t transponder_code;
+  int32_t transponder_mode;
+  bool outer_marker;
+  bool middle_marker;
+  bool inner_marker;
+  float wind_dir_deg;
+  float wind_speed_kt;
+  float traffic_lat[20];
+  float traffic_lon[20];
+  float traffic_ele_m[20];
+  uint8_t traffic_count;
+  int32_t hsi_source;
+} EfbSnapshot;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Validate a datagram (magic, version, type, length, CRC-32) and describe it.
+ *
+ * # Safety
+ *
+ * `buf` must point to `len` readable bytes and `out` to a writable
+ * `EfbPacketInfo`.
+ */
+EfbStatus efb_decode_packet(const uint8_t *buf, size_t len, EfbPacketInfo *out);
+
+/**
+ * Decode a SimData or Keyframe datagram into `out`, inflating a compressed
+ * payload.
+ *
+ * # Safety
+ *
+ * `buf` must point to `len` readable bytes and `out` to a writable
+ * snapshot.
+ */
+EfbStatus efb_decode_sim_data(const uint8_t *buf, size_t len, EfbSnapshot *out);
+
+/**
+ * Encode a command given as JSON, e.g. `{"cmd":"swap_freq","radio":"COM1","id":7}`,
+ * into a CommandBinary (`binary` true) or CommandJson datagram.
+ *
+ * The datagram is written to `out` and its length to `written`. If `cap`
+ * is too small, only `written` is set — to the size needed — and
+ * `BufferTooSmall` is returned.
+ *
+ * # Safety
+ *
+ * `json` must be a NUL-terminated string, `out` must point to `cap`
+ * writable bytes (or be null if `cap` is 0) and `written` to a writable
+ * `size_t`.
+ */
+EfbStatus efb_encode_command(uint32_t seq,
+                             const char *json,
+                             bool binary,
+                             uint8_t *out,
+                             size_t cap,
+                             size_t *written);
+
+/**
+ * Layout checksum of the snapshot struct this library was built with
+ * (`SNAPSHOT_LAYOUT_CHECKSUM`). Callers with their own snapshot definition
+ * must refuse to use the library if it differs from theirs.
+ */
+uint32_t efb_layout_checksum(void);
+
+/**
+ * Static description of a status code, for logs. Never null.
+ */
+const char *efb_status_message(int32_t status);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* EFB_PROTOCOL_H */
@@ -0,0 +1,322 @@
+//! JNI entry points for `com.nameless.efb.data.connectivity.NativeCodec`.
+//!
+//! Thin adapters over the same functions as the C ABI. Snapshots cross as
+//! the canonical SimData payload bytes rather than a Java object, so the
+//! app keeps a single `deserializeSnapshot` for native and fallback paths.
+//! JNI failures (e.g. an output array that is too short) are reported as
+//! status codes, never as pending Java exceptions.
+
+use jni::objects::{AsJArrayRaw, JByteArray, JLongArray, JObject, JString};
+use jni::sys::{jboolean, jbyteArray, jint, jlong, jstring, JNI_FALSE};
+use jni::JNIEnv;
+
+use dataref_schema::SNAPSHOT_LAYOUT_CHECKSUM;
+use efb_protocol::{encode_sim_data, HEADER_LEN};
+
+use crate::{command_packet, packet_info, sim_data, EfbStatus};
+
+/// Number of slots `decodePacket` fills in its `header` array.
+const HEADER_FIELDS: usize = 8;
+
+/// Copy the first `len` bytes of a Java array.
+fn read_bytes(env: &JNIEnv, buf: &JByteArray, len: jint) -> Result<Vec<u8>, EfbStatus> {
+    let mut bytes = env.convert_byte_array(buf).map_err(|_| EfbStatus::NullPointer)?;
+    let len = usize::try_from(len).map_err(|_| EfbStatus::TooShort)?;
+    if len > bytes.len() {
+        return Err(EfbStatus::BufferTooSmall);
+    }
+    bytes.truncate(len);
+    Ok(bytes)
+}
+
+/// Fail with `BufferTooSmall` unless `array` holds at least `len` elements,
+/// so that the region write that follows cannot throw.
+fn ensure_len<'a>(env: &JNIEnv, array: &impl AsJArrayRaw<'a>, len: usize) -> Result<(), EfbStatus> {
+    let have = env.get_array_length(array).map_err(|_| EfbStatus::NullPointer)?;
+    if usize::try_from(have).map_or(true, |have| have < len) {
+        return Err(EfbStatus::BufferTooSmall);
+    }
+    Ok(())
+}
+
+/// Status for a JNI call that failed anyway, with the exception it left
+/// pending cleared.
+fn cleared(env: &JNIEnv, status: EfbStatus) -> EfbStatus {
+    let _ = env.exception_clear();
+    status
+}
+
+fn status(result: Result<(), EfbStatus>) -> jint {
+    result.err().unwrap_or(EfbStatus::Ok) as jint
+}
+
+/// `external fun decodePacket(buf: ByteArray, len: Int, header: LongArray): Int`
+///
+/// Fills `header` with version, packet type, flags, sequence, sim time,
+/// send time, payload offset and payload length.
+#[no_mangle]
+pub extern "system" fn Java_com_nameless_efb_data_connectivity_NativeCodec_decodePacket<'l>(
+    env: JNIEnv<'l>,
+    _this: JObject<'l>,
+    buf: JByteArray<'l>,
+    len: jint,
+    header: JLongArray<'l>,
+) -> jint {
+    status((|| {
+        let info = packet_info(&read_bytes(&env, &buf, len)?)?;
+        let fields: [jlong; HEADER_FIELDS] = [
+            info.version.into(),
+            info.packet_type.into(),
+            info.flags.into(),
+            info.sequence.into(),
+            info.sim_time_us as jlong,
+            info.sent_us as jlong,
+            info.payload_offset as jlong,
+            info.payload_len as jlong,
+        ];
+        ensure_len(&env, &header, fields.len())?;
+        env.set_long_array_region(&header, 0, &fields).map_err(|_| cleared(&env, EfbStatus::BufferTooSmall))
+    })())
+}
+
+/// `external fun decodeSimData(buf: ByteArray, len: Int, out: ByteArray): Int`
+///
+/// Writes the snapshot to `out` as an uncompressed v1 SimData payload.
+#[no_mangle]
+pub extern "system" fn Java_com_nameless_efb_data_connectivity_NativeCodec_decodeSimData<'l>(
+    env: JNIEnv<'l>,
+    _this: JObject<'l>,
+    buf: JByteArray<'l>,
+    len: jint,
+    out: JByteArray<'l>,
+) -> jint {
+    status((|| {
+        let snapshot = sim_data(&read_bytes(&env, &buf, len)?)?;
+        let pkt = encode_sim_data(0, &snapshot);
+        let payload: Vec<i8> = pkt[HEADER_LEN..].iter().map(|&b| b as i8).collect();
+        ensure_len(&env, &out, payload.len())?;
+        env.set_byte_array_region(&out, 0, &payload).map_err(|_| cleared(&env, EfbStatus::BufferTooSmall))
+    })())
+}
+
+/// `external fun encodeCommand(seq: Int, json: String, binary: Boolean): ByteArray?`
+///
+/// Returns null if `json` is not a valid command.
+#[no_mangle]
+pub extern "system" fn Java_com_nameless_efb_data_connectivity_NativeCodec_encodeCommand<'l>(
+    mut env: JNIEnv<'l>,
+    _this: JObject<'l>,
+    seq: jint,
+    json: JString<'l>,
+    binary: jboolean,
+) -> jbyteArray {
+    let Ok(json) = env.get_string(&json).map(String::from) else {
+        cleared(&env, EfbStatus::NullPointer);
+        return std::ptr::null_mut();
+    };
+    let Ok(pkt) = command_packet(seq as u32, &json, binary != JNI_FALSE) else {
+        return std::ptr::null_mut();
+    };
+    match env.byte_array_from_slice(&pkt) {
+        Ok(array) => array.into_raw(),
+        Err(_) => {
+            cleared(&env, EfbStatus::BufferTooSmall);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// `external fun layoutChecksum(): Long`
+#[no_mangle]
+pub extern "system" fn Java_com_nameless_efb_data_connectivity_NativeCodec_layoutChecksum<'l>(
+    _env: JNIEnv<'l>,
+    _this: JObject<'l>,
+) -> jlong {
+    SNAPSHOT_LAYOUT_CHECKSUM.into()
+}
+
+/// `external fun statusMessage(status: Int): String`
+#[no_mangle]
+pub extern "system" fn Java_com_nameless_efb_data_connectivity_NativeCodec_statusMessage<'l>(
+    env: JNIEnv<'l>,
+    _this: JObject<'l>,
+    status: jint,
+) -> jstring {
+    let msg = EfbStatus::from_code(status).map_or("unknown status", EfbStatus::message);
+    match env.new_string(msg.trim_end_matches('\0')) {
+        Ok(s) => s.into_raw(),
+        Err(_) => {
+            cleared(&env, EfbStatus::BufferTooSmall);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    //! The entry points run against a fake `JNIEnv` implementing just the
+    //! array calls they make, with the JVM's bounds checks: an out-of-range
+    //! region access leaves an exception pending, as
+    //! ArrayIndexOutOfBoundsException would.
+
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    use dataref_schema::SimSnapshot;
+    use jni::sys::{self, jarray, jbyte, jsize, JNINativeInterface_, JNI_TRUE};
+
+    /// A Java primitive array: raw element bytes and the element size.
+    struct FakeArray {
+        elem: usize,
+        data: RefCell<Vec<u8>>,
+    }
+
+    impl FakeArray {
+        fn new(elem: usize, len: usize) -> Self {
+            FakeArray { elem, data: RefCell::new(vec![0; elem * len]) }
+        }
+
+        fn bytes(data: &[u8]) -> Self {
+            FakeArray { elem: 1, data: RefCell::new(data.to_vec()) }
+        }
+
+        fn raw(&self) -> jarray {
+            self as *const FakeArray as jarray
+        }
+    }
+
+    thread_local! {
+        static PENDING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    unsafe fn fake<'a>(array: jarray) -> &'a FakeArray {
+        &*(array as *const FakeArray)
+    }
+
+    /// Byte range of elements `start..start + len`, or `None` with an
+    /// exception pending if it is out of bounds.
+    unsafe fn region(array: jarray, start: jsize, len: jsize) -> Option<std::ops::Range<usize>> {
+        let a = fake(array);
+        let (start, len) = (start as usize * a.elem, len as usize * a.elem);
+        if start + len > a.data.borrow().len() {
+            PENDING.set(true);
+            return None;
+        }
+        Some(start..start + len)
+    }
+
+    unsafe fn write(array: jarray, start: jsize, len: jsize, src: *const u8) {
+        if let Some(r) = region(array, start, len) {
+            let src = std::slice::from_raw_parts(src, r.len());
+            fake(array).data.borrow_mut()[r].copy_from_slice(src);
+        }
+    }
+
+    unsafe extern "system" fn get_array_length(_: *mut sys::JNIEnv, array: jarray) -> jsize {
+        let a = fake(array);
+        (a.data.borrow().len() / a.elem) as jsize
+    }
+
+    unsafe extern "system" fn get_byte_array_region(
+        _: *mut sys::JNIEnv,
+        array: jarray,
+        start: jsize,
+        len: jsize,
+        buf: *mut jbyte,
+    ) {
+        if let Some(r) = region(array, start, len) {
+            let dst = std::slice::from_raw_parts_mut(buf.cast::<u8>(), r.len());
+            dst.copy_from_slice(&fake(array).data.borrow()[r]);
+        }
+    }
+
+    unsafe extern "system" fn set_byte_array_region(
+        _: *mut sys::JNIEnv,
+        array: jarray,
+        start: jsize,
+        len: jsize,
+        buf: *const jbyte,
+    ) {
+        write(array, start, len, buf.cast());
+    }
+
+    unsafe extern "system" fn set_long_array_region(
+        _: *mut sys::JNIEnv,
+        array: jarray,
+        start: jsize,
+        len: jsize,
+        buf: *const jlong,
+    ) {
+        write(array, start, len, buf.cast());
+    }
+
+    unsafe extern "system" fn exception_check(_: *mut sys::JNIEnv) -> jboolean {
+        if PENDING.get() { JNI_TRUE } else { JNI_FALSE }
+    }
+
+    unsafe extern "system" fn exception_clear(_: *mut sys::JNIEnv) {
+        PENDING.set(false);
+    }
+
+    fn with_env(f: impl FnOnce(JNIEnv)) {
+        // SAFETY: every field is a nullable pointer or an `Option` of one.
+        let mut table: JNINativeInterface_ = unsafe { std::mem::zeroed() };
+        table.GetArrayLength = Some(get_array_length);
+        table.GetByteArrayRegion = Some(get_byte_array_region);
+        table.SetByteArrayRegion = Some(set_byte_array_region);
+        table.SetLongArrayRegion = Some(set_long_array_region);
+        table.ExceptionCheck = Some(exception_check);
+        table.ExceptionClear = Some(exception_clear);
+        let iface: *const JNINativeInterface_ = &table;
+        let env = unsafe { JNIEnv::from_raw(&iface as *const _ as *mut sys::JNIEnv) }.unwrap();
+        PENDING.set(false);
+        f(env);
+    }
+
+    fn decode_packet(env: JNIEnv, buf: &FakeArray, header: &FakeArray) -> jint {
+        let len = buf.data.borrow().len() as jint;
+        let (buf, header) = unsafe { (JByteArray::from_raw(buf.raw()), JLongArray::from_raw(header.raw())) };
+        Java_com_nameless_efb_data_connectivity_NativeCodec_decodePacket(env, JObject::null(), buf, len, header)
+    }
+
+    fn decode_sim_data(env: JNIEnv, buf: &FakeArray, out: &FakeArray) -> jint {
+        let len = buf.data.borrow().len() as jint;
+        let (buf, out) = unsafe { (JByteArray::from_raw(buf.raw()), JByteArray::from_raw(out.raw())) };
+        Java_com_nameless_efb_data_connectivity_NativeCodec_decodeSimData(env, JObject::null(), buf, len, out)
+    }
+
+    #[test]
+    fn short_header_array_is_a_status_not_an_exception() {
+        let pkt = FakeArray::bytes(&encode_sim_data(7, &SimSnapshot::default()));
+        with_env(|env| {
+            let header = FakeArray::new(8, HEADER_FIELDS - 1);
+            assert_eq!(decode_packet(env, &pkt, &header), EfbStatus::BufferTooSmall as jint);
+            assert!(!PENDING.get(), "exception left pending");
+        });
+        with_env(|env| {
+            let header = FakeArray::new(8, HEADER_FIELDS);
+            assert_eq!(decode_packet(env, &pkt, &header), EfbStatus::Ok as jint);
+            let fields = header.data.borrow();
+            assert_eq!(fields[8], 0x01); // packet type SimData
+            assert_eq!(fields[24], 7);   // sequence
+        });
+    }
+
+    #[test]
+    fn short_snapshot_array_is_a_status_not_an_exception() {
+        let pkt = encode_sim_data(0, &SimSnapshot { latitude: 12.5, ..SimSnapshot::default() });
+        let buf = FakeArray::bytes(&pkt);
+        with_env(|env| {
+            let out = FakeArray::new(1, 10);
+            assert_eq!(decode_sim_data(env, &buf, &out), EfbStatus::BufferTooSmall as jint);
+            assert!(!PENDING.get(), "exception left pending");
+        });
+        with_env(|env| {
+            let out = FakeArray::new(1, pkt.len() - HEADER_LEN);
+            assert_eq!(decode_sim_data(env, &buf, &out), EfbStatus::Ok as jint);
+            assert_eq!(*out.data.borrow(), pkt[HEADER_LEN..]);
+        });
+    }
+}
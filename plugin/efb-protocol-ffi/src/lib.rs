@@ -0,0 +1,363 @@
+//! C ABI and JNI bindings to the EFB packet codec.
+//!
+//! Lets the Android app (and any other C-speaking client) decode and encode
+//! packets with the exact codec the plugin uses instead of a hand-written
+//! mirror. The C declarations are generated by `build.rs` and checked in as
+//! `include/efb_protocol.h`; the JNI entry points for `NativeCodec.kt` live
+//! in `jvm`.
+//!
+//! Every function returns an [`EfbStatus`]: zero on success, negative on
+//! error. Output parameters are only written on success. No function keeps
+//! a pointer past its return, allocates memory the caller must free, or
+//! panics across the boundary.
+
+use std::ffi::{c_char, CStr};
+
+use dataref_schema::{SimSnapshot, SNAPSHOT_LAYOUT_CHECKSUM};
+use efb_protocol::command::{decode_request_json, encode_request, encode_request_json};
+use efb_protocol::{decode_packet, decode_sim_data, header_len, inflate_payload, PacketType, ProtocolError};
+
+mod jvm;
+
+// ── Status codes ─────────────────────────────────────────────────────────────
+
+/// Outcome of an FFI call. Codes -1 to -99 mirror `ProtocolError`; codes
+/// from -100 are caller errors.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EfbStatus {
+    Ok                 = 0,
+    TooShort           = -1,
+    BadMagic           = -2,
+    BadVersion         = -3,
+    UnknownPacketType  = -4,
+    PayloadTooLarge    = -5,
+    TruncatedPayload   = -6,
+    BadChecksum        = -7,
+    MissingKeyframe    = -8,
+    MalformedSchema    = -9,
+    MalformedFragment  = -10,
+    ReassemblyOverflow = -11,
+    BadAuth            = -12,
+    Replay             = -13,
+    UnknownCommand     = -14,
+    UnknownRadio       = -15,
+    MalformedCommand   = -16,
+    BadCompression     = -17,
+    UnknownGroup       = -18,
+    MalformedTraffic   = -19,
+    TrailingBytes      = -20,
+    /// A required pointer argument was null.
+    NullPointer        = -100,
+    /// The output buffer is too small; the required size was reported.
+    BufferTooSmall     = -101,
+    /// The packet decoded but is not of the type the function handles.
+    WrongPacketType    = -102,
+    /// A string argument is not valid UTF-8.
+    InvalidUtf8        = -103,
+}
+
+impl From<ProtocolError> for EfbStatus {
+    fn from(e: ProtocolError) -> Self {
+        match e {
+            ProtocolError::TooShort             => Self::TooShort,
+            ProtocolError::BadMagic             => Self::BadMagic,
+            ProtocolError::BadVersion           => Self::BadVersion,
+            ProtocolError::UnknownPacketType(_) => Self::UnknownPacketType,
+            ProtocolError::PayloadTooLarge      => Self::PayloadTooLarge,
+            ProtocolError::TruncatedPayload { .. } => Self::TruncatedPayload,
+            ProtocolError::TrailingBytes { .. }    => Self::TrailingBytes,
+            ProtocolError::BadChecksum          => Self::BadChecksum,
+            ProtocolError::MissingKeyframe      => Self::MissingKeyframe,
+            ProtocolError::MalformedSchema { .. }   => Self::MalformedSchema,
+            ProtocolError::MalformedFragment { .. } => Self::MalformedFragment,
+            ProtocolError::ReassemblyOverflow   => Self::ReassemblyOverflow,
+            ProtocolError::BadAuth              => Self::BadAuth,
+            ProtocolError::Replay               => Self::Replay,
+            ProtocolError::UnknownCommand(_)
+            | ProtocolError::UnknownCommandName(_) => Self::UnknownCommand,
+            ProtocolError::UnknownRadio(_)
+            | ProtocolError::UnknownRadioName(_)   => Self::UnknownRadio,
+            ProtocolError::MalformedCommand { .. }  => Self::MalformedCommand,
+            ProtocolError::BadCompression       => Self::BadCompression,
+            ProtocolError::UnknownGroup(_)      => Self::UnknownGroup,
+            ProtocolError::MalformedTraffic { .. }  => Self::MalformedTraffic,
+        }
+    }
+}
+
+impl EfbStatus {
+    /// Every status, for looking codes up.
+    const ALL: [EfbStatus; 25] = [
+        Self::Ok, Self::TooShort, Self::BadMagic, Self::BadVersion, Self::UnknownPacketType,
+        Self::PayloadTooLarge, Self::TruncatedPayload, Self::BadChecksum, Self::MissingKeyframe,
+        Self::MalformedSchema, Self::MalformedFragment, Self::ReassemblyOverflow, Self::BadAuth,
+        Self::Replay, Self::UnknownCommand, Self::UnknownRadio, Self::MalformedCommand,
+        Self::BadCompression, Self::UnknownGroup, Self::MalformedTraffic, Self::TrailingBytes,
+        Self::NullPointer, Self::BufferTooSmall, Self::WrongPacketType, Self::InvalidUtf8,
+    ];
+
+    fn from_code(code: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| *s as i32 == code)
+    }
+
+    /// NUL-terminated description, for logs.
+    fn message(self) -> &'static str {
+        match self {
+            Self::Ok                 => "ok\0",
+            Self::TooShort           => "packet too short\0",
+            Self::BadMagic           => "bad magic bytes\0",
+            Self::BadVersion         => "unsupported protocol version\0",
+            Self::UnknownPacketType  => "unknown packet type\0",
+            Self::PayloadTooLarge    => "payload exceeds 64 KiB limit\0",
+            Self::TruncatedPayload   => "payload truncated\0",
+            Self::BadChecksum        => "CRC-32 mismatch\0",
+            Self::MissingKeyframe    => "delta references a missing keyframe\0",
+            Self::MalformedSchema    => "malformed schema packet\0",
+            Self::MalformedFragment  => "malformed fragment\0",
+            Self::ReassemblyOverflow => "reassembly buffer full\0",
+            Self::BadAuth            => "authentication failed\0",
+            Self::Replay             => "replayed or stale sequence number\0",
+            Self::UnknownCommand     => "unknown command\0",
+            Self::UnknownRadio       => "unknown radio\0",
+            Self::MalformedCommand   => "malformed command\0",
+            Self::BadCompression     => "corrupt compressed payload\0",
+            Self::UnknownGroup       => "unknown field group\0",
+            Self::MalformedTraffic   => "malformed traffic packet\0",
+            Self::TrailingBytes      => "trailing bytes after payload\0",
+            Self::NullPointer        => "null pointer argument\0",
+            Self::BufferTooSmall     => "output buffer too small\0",
+            Self::WrongPacketType    => "wrong packet type\0",
+            Self::InvalidUtf8        => "string is not UTF-8\0",
+        }
+    }
+}
+
+// ── Packet info ──────────────────────────────────────────────────────────────
+
+/// Header fields of a validated packet and where its payload lies.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EfbPacketInfo {
+    pub version:        u16,
+    pub packet_type:    u8,
+    /// v2 header flags; 0 for v1.
+    pub flags:          u8,
+    pub sequence:       u32,
+    /// v2 only, 0 for v1.
+    pub sim_time_us:    u64,
+    /// v2 only, 0 for v1.
+    pub sent_us:        u64,
+    /// Offset of the payload from the start of the buffer.
+    pub payload_offset: usize,
+    /// Payload bytes as sent (compressed if flagged).
+    pub payload_len:    usize,
+}
+
+// ── C ABI ────────────────────────────────────────────────────────────────────
+
+/// Validate a datagram (magic, version, type, length, CRC-32) and describe it.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes and `out` to a writable
+/// `EfbPacketInfo`.
+#[no_mangle]
+pub unsafe extern "C" fn efb_decode_packet(buf: *const u8, len: usize, out: *mut EfbPacketInfo) -> EfbStatus {
+    if buf.is_null() || out.is_null() {
+        return EfbStatus::NullPointer;
+    }
+    match packet_info(std::slice::from_raw_parts(buf, len)) {
+        Ok(info) => {
+            out.write(info);
+            EfbStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Decode a SimData or Keyframe datagram into `out`, inflating a compressed
+/// payload.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes and `out` to a writable
+/// snapshot.
+#[no_mangle]
+pub unsafe extern "C" fn efb_decode_sim_data(buf: *const u8, len: usize, out: *mut SimSnapshot) -> EfbStatus {
+    if buf.is_null() || out.is_null() {
+        return EfbStatus::NullPointer;
+    }
+    match sim_data(std::slice::from_raw_parts(buf, len)) {
+        Ok(snapshot) => {
+            out.write(snapshot);
+            EfbStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Encode a command given as JSON, e.g. `{"cmd":"swap_freq","radio":"COM1","id":7}`,
+/// into a CommandBinary (`binary` true) or CommandJson datagram.
+///
+/// The datagram is written to `out` and its length to `written`. If `cap`
+/// is too small, only `written` is set — to the size needed — and
+/// `BufferTooSmall` is returned.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string, `out` must point to `cap`
+/// writable bytes (or be null if `cap` is 0) and `written` to a writable
+/// `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn efb_encode_command(
+    seq: u32,
+    json: *const c_char,
+    binary: bool,
+    out: *mut u8,
+    cap: usize,
+    written: *mut usize,
+) -> EfbStatus {
+    if json.is_null() || written.is_null() || (out.is_null() && cap > 0) {
+        return EfbStatus::NullPointer;
+    }
+    let Ok(json) = CStr::from_ptr(json).to_str() else {
+        return EfbStatus::InvalidUtf8;
+    };
+    let pkt = match command_packet(seq, json, binary) {
+        Ok(pkt) => pkt,
+        Err(status) => return status,
+    };
+    written.write(pkt.len());
+    if pkt.len() > cap {
+        return EfbStatus::BufferTooSmall;
+    }
+    std::ptr::copy_nonoverlapping(pkt.as_ptr(), out, pkt.len());
+    EfbStatus::Ok
+}
+
+/// Layout checksum of the snapshot struct this library was built with
+/// (`SNAPSHOT_LAYOUT_CHECKSUM`). Callers with their own snapshot definition
+/// must refuse to use the library if it differs from theirs.
+#[no_mangle]
+pub extern "C" fn efb_layout_checksum() -> u32 {
+    SNAPSHOT_LAYOUT_CHECKSUM
+}
+
+/// Static description of a status code, for logs. Never null.
+#[no_mangle]
+pub extern "C" fn efb_status_message(status: i32) -> *const c_char {
+    EfbStatus::from_code(status).map_or("unknown status\0", EfbStatus::message).as_ptr().cast()
+}
+
+// ── Shared implementation (C ABI and JNI) ────────────────────────────────────
+
+fn packet_info(buf: &[u8]) -> Result<EfbPacketInfo, EfbStatus> {
+    let (hdr, _, payload) = decode_packet(buf)?;
+    Ok(EfbPacketInfo {
+        version:        hdr.version,
+        packet_type:    hdr.packet_type,
+        flags:          hdr.flags,
+        sequence:       hdr.sequence,
+        sim_time_us:    hdr.sim_time_us,
+        sent_us:        hdr.sent_us,
+        payload_offset: header_len(hdr.version),
+        payload_len:    payload.len(),
+    })
+}
+
+fn sim_data(buf: &[u8]) -> Result<SimSnapshot, EfbStatus> {
+    let (hdr, ptype, payload) = decode_packet(buf)?;
+    if !matches!(ptype, PacketType::SimData | PacketType::Keyframe) {
+        return Err(EfbStatus::WrongPacketType);
+    }
+    Ok(decode_sim_data(&inflate_payload(&hdr, payload)?)?)
+}
+
+fn command_packet(seq: u32, json: &str, binary: bool) -> Result<Vec<u8>, EfbStatus> {
+    let req = decode_request_json(json.as_bytes())?;
+    Ok(if binary { encode_request(seq, &req)? } else { encode_request_json(seq, &req) })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use efb_protocol::command::decode_request;
+    use efb_protocol::{encode_ack, encode_sim_data, upgrade_packet, Command, Radio, Timestamps};
+    use std::ffi::CString;
+    use std::ptr;
+
+    fn snapshot() -> SimSnapshot {
+        SimSnapshot { latitude: -26.1367, com1_active_hz: 118_025_000, inner_marker: true, ..SimSnapshot::default() }
+    }
+
+    #[test]
+    fn decodes_v1_and_compressed_v2_snapshots() {
+        let v1 = encode_sim_data(3, &snapshot());
+        let ts = Timestamps { sim_time_us: 9, sent_us: 10 };
+        let v2 = upgrade_packet(&v1, ts, true).unwrap();
+        for pkt in [&v1, &v2] {
+            let mut out = SimSnapshot::default();
+            let status = unsafe { efb_decode_sim_data(pkt.as_ptr(), pkt.len(), &mut out) };
+            assert_eq!(status, EfbStatus::Ok);
+            assert_eq!(out.latitude, -26.1367);
+            assert_eq!(out.com1_active_hz, 118_025_000);
+            assert!(out.inner_marker);
+        }
+
+        let mut info = EfbPacketInfo::default();
+        assert_eq!(unsafe { efb_decode_packet(v2.as_ptr(), v2.len(), &mut info) }, EfbStatus::Ok);
+        assert_eq!((info.version, info.packet_type, info.sequence, info.sent_us), (2, 0x01, 3, 10));
+        assert_eq!(info.payload_offset + info.payload_len, v2.len());
+    }
+
+    #[test]
+    fn errors_map_to_status_codes() {
+        let mut out = SimSnapshot::default();
+        let mut pkt = encode_sim_data(0, &snapshot());
+        pkt[20] ^= 0xFF;
+        assert_eq!(unsafe { efb_decode_sim_data(pkt.as_ptr(), pkt.len(), &mut out) }, EfbStatus::BadChecksum);
+        let ack = encode_ack(0);
+        assert_eq!(unsafe { efb_decode_sim_data(ack.as_ptr(), ack.len(), &mut out) }, EfbStatus::WrongPacketType);
+        assert_eq!(unsafe { efb_decode_sim_data(ptr::null(), 0, &mut out) }, EfbStatus::NullPointer);
+
+        let msg = unsafe { CStr::from_ptr(efb_status_message(EfbStatus::BadChecksum as i32)) };
+        assert_eq!(msg.to_str().unwrap(), "CRC-32 mismatch");
+        let msg = unsafe { CStr::from_ptr(efb_status_message(42)) };
+        assert_eq!(msg.to_str().unwrap(), "unknown status");
+        assert!(EfbStatus::ALL.iter().all(|&s| EfbStatus::from_code(s as i32) == Some(s)));
+    }
+
+    #[test]
+    fn encodes_commands_from_json() {
+        let json = CString::new(r#"{"cmd":"swap_freq","radio":"NAV1","id":7}"#).unwrap();
+        let mut written = 0;
+        let status = unsafe { efb_encode_command(1, json.as_ptr(), true, ptr::null_mut(), 0, &mut written) };
+        assert_eq!(status, EfbStatus::BufferTooSmall);
+
+        let mut buf = vec![0u8; written];
+        let status = unsafe { efb_encode_command(1, json.as_ptr(), true, buf.as_mut_ptr(), buf.len(), &mut written) };
+        assert_eq!(status, EfbStatus::Ok);
+        let (_, ptype, payload) = decode_packet(&buf[..written]).unwrap();
+        assert_eq!(ptype, PacketType::CommandBinary);
+        let req = decode_request(payload).unwrap();
+        assert_eq!((req.id, req.command), (Some(7), Command::SwapFreq { radio: Radio::Nav1 }));
+
+        let bad = CString::new(r#"{"cmd":"swap_freq","radio":"ADF1"}"#).unwrap();
+        let status = unsafe { efb_encode_command(1, bad.as_ptr(), false, buf.as_mut_ptr(), buf.len(), &mut written) };
+        assert_eq!(status, EfbStatus::UnknownRadio);
+    }
+
+    #[test]
+    fn checked_in_header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/efb_protocol.h"));
+        let checked_in = include_str!("../include/efb_protocol.h");
+        assert!(
+            generated == checked_in,
+            "include/efb_protocol.h is out of date; copy it from {}/efb_protocol.h",
+            env!("OUT_DIR"),
+        );
+    }
+}
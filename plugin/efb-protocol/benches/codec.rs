@@ -0,0 +1,78 @@
+//! Flight-loop hot path benchmarks: `cargo bench -p efb-protocol`.
+//!
+//! Reference numbers (x86-64 VM, 464-byte SimData payload):
+//! ```text
+//! crc32/bitwise                       1.59 µs
+//! crc32/slicing_by_8                  340 ns
+//! encode_sim_data/vec                 451 ns
+//! encode_sim_data/into_slice          354 ns
+//! decode_sim_data/owned_snapshot      416 ns
+//! decode_sim_data/view_three_fields   368 ns   (dominated by the CRC check)
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use dataref_schema::SimSnapshot;
+use efb_protocol::crc::{crc32, crc32_bitwise};
+use efb_protocol::{
+    decode_packet, decode_sim_data, encode_sim_data, encode_sim_data_into, SnapshotView,
+    SIM_DATA_PACKET_LEN, SNAPSHOT_LEN,
+};
+
+fn snapshot() -> SimSnapshot {
+    SimSnapshot {
+        latitude:         -26.1367,
+        longitude:        28.2411,
+        elevation_m:      1694.0,
+        ias_kts:          120.5,
+        mag_heading_deg:  270.0,
+        egt_degc:         [680.0, 690.0, 695.0, 685.0, 688.0, 692.0],
+        transponder_code: 7000,
+        traffic_count:    2,
+        ..SimSnapshot::default()
+    }
+}
+
+fn bench_crc(c: &mut Criterion) {
+    let payload: Vec<u8> = (0..SNAPSHOT_LEN).map(|i| i as u8).collect();
+    let mut g = c.benchmark_group("crc32");
+    g.throughput(Throughput::Bytes(payload.len() as u64));
+    g.bench_function("bitwise", |b| b.iter(|| crc32_bitwise(black_box(&payload))));
+    g.bench_function("slicing_by_8", |b| b.iter(|| crc32(black_box(&payload))));
+    g.finish();
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let snap = snapshot();
+    let mut buf = [0u8; SIM_DATA_PACKET_LEN];
+    let mut g = c.benchmark_group("encode_sim_data");
+    g.bench_function("vec", |b| b.iter(|| encode_sim_data(black_box(1), black_box(&snap))));
+    g.bench_function("into_slice", |b| {
+        b.iter(|| encode_sim_data_into(black_box(1), black_box(&snap), &mut buf))
+    });
+    g.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let pkt = encode_sim_data(1, &snapshot());
+    let mut g = c.benchmark_group("decode_sim_data");
+    g.bench_function("owned_snapshot", |b| {
+        b.iter(|| {
+            let (_, _, payload) = decode_packet(black_box(&pkt)).unwrap();
+            let s = decode_sim_data(payload).unwrap();
+            (s.latitude, s.longitude, s.ias_kts)
+        })
+    });
+    g.bench_function("view_three_fields", |b| {
+        b.iter(|| {
+            let (_, _, payload) = decode_packet(black_box(&pkt)).unwrap();
+            let v = SnapshotView::new(payload).unwrap();
+            (v.latitude(), v.longitude(), v.ias_kts())
+        })
+    });
+    g.finish();
+}
+
+criterion_group!(benches, bench_crc, bench_encode, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,360 @@
+//! Packet authentication and replay protection.
+//!
+//! When a tablet has been paired with the plugin both sides hold a shared
+//! [`PairingKey`]. Every datagram then carries an authentication trailer
+//! appended after the payload:
+//!
+//! ```text
+//! [0..H]                  header (H = 17 in v1, 34 in v2)
+//! [H..H+payload_len]      payload
+//! [..+8]                  stamp : u64  signer's wall clock, µs since the Unix epoch
+//! [..+16]                 tag   : HMAC-SHA256(key, signer ‖ header ‖ payload ‖ stamp)[..16]
+//! ```
+//!
+//! `signer` is the [`Signer`] byte of the side that sent the packet, so a
+//! packet cannot be reflected back at its sender. The stamp takes the place
+//! of the header sequence for replay protection: sequence numbers restart
+//! with every connection, while stamps keep increasing across reconnects and
+//! restarts.
+//!
+//! The trailer lies outside `payload_len`, so unpaired receivers ignore it.
+//! In v2 the header is also flagged [`flags::AUTHENTICATED`] before signing.
+//! Receivers that hold a key reject packets without a valid tag
+//! ([`ProtocolError::BadAuth`]) and, via a [`ReplayWindow`] per key,
+//! packets whose stamp was already accepted or is not recent
+//! ([`ProtocolError::Replay`]).
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    decode_packet, flags, header_len, read_u16, PacketHeader, PacketType, ProtocolError, HEADER_LEN,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A decoded packet as returned by [`decode_packet`].
+pub type Decoded<'a> = (PacketHeader, PacketType, &'a [u8]);
+
+/// Length of the truncated HMAC tag at the end of authenticated packets.
+pub const AUTH_TAG_LEN: usize = 16;
+
+/// Length of the whole authentication trailer: stamp and tag.
+pub const AUTH_TRAILER_LEN: usize = 8 + AUTH_TAG_LEN;
+
+/// How far a stamp may be from the receiver's clock. Accepted stamps are
+/// remembered for this long, so within it each is accepted once.
+pub const REPLAY_WINDOW: Duration = Duration::from_secs(30);
+
+// ── PairingKey ───────────────────────────────────────────────────────────────
+
+/// Shared secret established once when a tablet is paired with the plugin.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PairingKey([u8; 32]);
+
+impl PairingKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        PairingKey(bytes)
+    }
+
+    /// Derive a key from a human-entered pairing code (e.g. shown in the
+    /// X-Plane plugin menu and typed into the tablet).
+    pub fn from_code(code: &str) -> Self {
+        PairingKey(Sha256::digest(code.trim().as_bytes()).into())
+    }
+
+    fn mac(&self, signer: Signer) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        mac.update(&[signer as u8]);
+        mac
+    }
+}
+
+impl std::fmt::Debug for PairingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PairingKey(..)") // never log the secret
+    }
+}
+
+// ── Signer ───────────────────────────────────────────────────────────────────
+
+/// Which side of the link signed a packet.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signer {
+    Plugin = 1,
+    Tablet = 2,
+}
+
+// ── StampClock ───────────────────────────────────────────────────────────────
+
+/// Current wall-clock time in µs since the Unix epoch.
+pub fn unix_micros() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_micros() as u64)
+}
+
+/// Source of strictly increasing stamps for one signer: the wall clock,
+/// nudged forward when two packets are signed within the same microsecond
+/// or the clock steps back.
+#[derive(Debug, Default)]
+pub struct StampClock {
+    last: AtomicU64,
+}
+
+impl StampClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stamp for a packet signed now.
+    pub fn next(&self) -> u64 {
+        self.next_at(unix_micros())
+    }
+
+    fn next_at(&self, now: u64) -> u64 {
+        let prev = self
+            .last
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| Some(now.max(last + 1)))
+            .unwrap();
+        now.max(prev + 1)
+    }
+}
+
+// ── Signing / verification ───────────────────────────────────────────────────
+
+/// Append the authentication trailer to a framed datagram sent by `signer`.
+/// `stamp` must increase with every packet the signer sends under this key;
+/// take it from a [`StampClock`].
+pub fn sign_packet(pkt: &mut Vec<u8>, key: &PairingKey, signer: Signer, stamp: u64) {
+    if pkt.len() > HEADER_LEN && read_u16(pkt, 4) >= 2 {
+        pkt[17] |= flags::AUTHENTICATED;
+    }
+    pkt.extend_from_slice(&stamp.to_le_bytes());
+    let mut mac = key.mac(signer);
+    mac.update(pkt);
+    let tag = mac.finalize().into_bytes();
+    pkt.extend_from_slice(&tag[..AUTH_TAG_LEN]);
+}
+
+/// Check the tag of a datagram that must have been signed by `signer` with
+/// `key`, and return it decoded along with its stamp. Replays are not
+/// detected; see [`decode_authenticated`].
+pub fn verify_packet<'a>(
+    buf: &'a [u8],
+    key: &PairingKey,
+    signer: Signer,
+) -> Result<(Decoded<'a>, u64), ProtocolError> {
+    if buf.len() < HEADER_LEN {
+        return Err(ProtocolError::TooShort);
+    }
+    let packet_len = header_len(read_u16(buf, 4)) + read_u16(buf, 7) as usize;
+    if buf.len() != packet_len + AUTH_TRAILER_LEN {
+        return Err(ProtocolError::BadAuth);
+    }
+    let signed_len = packet_len + 8;
+
+    let decoded = decode_packet(&buf[..packet_len])?;
+
+    let mut mac = key.mac(signer);
+    mac.update(&buf[..signed_len]);
+    mac.verify_truncated_left(&buf[signed_len..])
+        .map_err(|_| ProtocolError::BadAuth)?;
+
+    let stamp = u64::from_le_bytes(buf[packet_len..signed_len].try_into().unwrap());
+    Ok((decoded, stamp))
+}
+
+/// Decode a datagram that must carry a valid tag from `signer` for `key` and
+/// a recent stamp not yet accepted by `window`.
+///
+/// The window is only advanced once the tag has been verified, so forged
+/// packets cannot shift it.
+pub fn decode_authenticated<'a>(
+    buf: &'a [u8],
+    key: &PairingKey,
+    signer: Signer,
+    window: &mut ReplayWindow,
+) -> Result<Decoded<'a>, ProtocolError> {
+    let (decoded, stamp) = verify_packet(buf, key, signer)?;
+    window.accept(stamp, unix_micros())?;
+    Ok(decoded)
+}
+
+// ── ReplayWindow ─────────────────────────────────────────────────────────────
+
+/// Replay filter over packet stamps.
+///
+/// Accepts each stamp at most once, and only within [`REPLAY_WINDOW`] of the
+/// receiver's clock and not before the window was created — so nothing
+/// signed before a receiver restarted is accepted after it. Keep one window
+/// per key across every transport and peer address.
+#[derive(Debug, Clone)]
+pub struct ReplayWindow {
+    floor: u64,
+    /// Stamps accepted within the last [`REPLAY_WINDOW`].
+    seen:  BTreeSet<u64>,
+}
+
+impl ReplayWindow {
+    /// A window that rejects packets signed before now.
+    pub fn new() -> Self {
+        Self::since(unix_micros())
+    }
+
+    /// A window that rejects stamps below `floor`.
+    pub fn since(floor: u64) -> Self {
+        ReplayWindow { floor, seen: BTreeSet::new() }
+    }
+
+    /// Record `stamp`, or return [`ProtocolError::Replay`] if it is a
+    /// duplicate, older than the window, or too far from `now` (both µs
+    /// since the Unix epoch).
+    pub fn accept(&mut self, stamp: u64, now: u64) -> Result<(), ProtocolError> {
+        let window = REPLAY_WINDOW.as_micros() as u64;
+        if stamp < self.floor || stamp.abs_diff(now) > window {
+            return Err(ProtocolError::Replay);
+        }
+        let cutoff = now.saturating_sub(window);
+        while self.seen.first().is_some_and(|&s| s < cutoff) {
+            self.seen.pop_first();
+        }
+        if !self.seen.insert(stamp) {
+            return Err(ProtocolError::Replay);
+        }
+        Ok(())
+    }
+
+    /// Forget all history, e.g. when a tablet re-pairs. Stamps from before
+    /// the reset stay rejected.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for ReplayWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_packet, encode_sim_data, upgrade_packet, Timestamps};
+    use dataref_schema::SimSnapshot;
+
+    const SECOND: u64 = 1_000_000;
+
+    fn key() -> PairingKey {
+        PairingKey::from_code("4711-0815")
+    }
+
+    fn signed(seq: u32, stamp: u64) -> Vec<u8> {
+        let mut pkt = build_packet(seq, PacketType::CommandJson, br#"{"cmd":"swap_freq","radio":"COM1"}"#);
+        sign_packet(&mut pkt, &key(), Signer::Tablet, stamp);
+        pkt
+    }
+
+    #[test]
+    fn signed_packet_verifies() {
+        let mut w = ReplayWindow::since(0);
+        let pkt = signed(1, unix_micros());
+        let (_, ptype, payload) = decode_authenticated(&pkt, &key(), Signer::Tablet, &mut w).unwrap();
+        assert_eq!(ptype, PacketType::CommandJson);
+        assert!(payload.starts_with(b"{"));
+        assert_eq!(pkt.len(), HEADER_LEN + payload.len() + AUTH_TRAILER_LEN);
+        // Unpaired receivers still decode it, ignoring the trailer.
+        assert!(decode_packet(&pkt).is_ok());
+    }
+
+    #[test]
+    fn v2_packet_flagged_and_verified() {
+        let v1 = build_packet(4, PacketType::CommandJson, br#"{"cmd":"swap_freq","radio":"COM1"}"#);
+        let mut pkt = upgrade_packet(&v1, Timestamps::default(), false).unwrap();
+        sign_packet(&mut pkt, &key(), Signer::Plugin, 77);
+        let ((hdr, ptype, _), stamp) = verify_packet(&pkt, &key(), Signer::Plugin).unwrap();
+        assert_eq!(ptype, PacketType::CommandJson);
+        assert!(hdr.has(flags::AUTHENTICATED));
+        assert_eq!(stamp, 77);
+    }
+
+    #[test]
+    fn unsigned_or_tampered_packets_rejected() {
+        let plain = encode_sim_data(0, &SimSnapshot::default());
+        assert_eq!(verify_packet(&plain, &key(), Signer::Tablet).unwrap_err(), ProtocolError::BadAuth);
+
+        let mut pkt = signed(2, 1);
+        let last = pkt.len() - 1;
+        pkt[last] ^= 0x01;
+        assert_eq!(verify_packet(&pkt, &key(), Signer::Tablet).unwrap_err(), ProtocolError::BadAuth);
+
+        // The stamp is covered by the tag.
+        let mut pkt = signed(2, 1);
+        let stamp_at = pkt.len() - AUTH_TRAILER_LEN;
+        pkt[stamp_at] ^= 0x01;
+        assert_eq!(verify_packet(&pkt, &key(), Signer::Tablet).unwrap_err(), ProtocolError::BadAuth);
+
+        let other = PairingKey::from_code("0000-0000");
+        assert_eq!(verify_packet(&signed(3, 1), &other, Signer::Tablet).unwrap_err(), ProtocolError::BadAuth);
+    }
+
+    #[test]
+    fn reflected_packet_rejected() {
+        // A tablet's packet sent back to it as if from the plugin.
+        assert_eq!(verify_packet(&signed(5, 1), &key(), Signer::Plugin).unwrap_err(), ProtocolError::BadAuth);
+    }
+
+    #[test]
+    fn replayed_packet_rejected() {
+        let mut w = ReplayWindow::since(0);
+        let pkt = signed(10, unix_micros());
+        decode_authenticated(&pkt, &key(), Signer::Tablet, &mut w).unwrap();
+        assert_eq!(decode_authenticated(&pkt, &key(), Signer::Tablet, &mut w).unwrap_err(), ProtocolError::Replay);
+    }
+
+    #[test]
+    fn window_accepts_reordering_but_not_stale() {
+        let now = 1_000 * SECOND;
+        let mut w = ReplayWindow::since(0);
+        w.accept(now, now).unwrap();
+        w.accept(now + 5, now).unwrap();
+        w.accept(now + 2, now).unwrap(); // late but inside window
+        assert_eq!(w.accept(now + 2, now), Err(ProtocolError::Replay));
+        // Too far from the receiver's clock either way.
+        assert_eq!(w.accept(now - 31 * SECOND, now), Err(ProtocolError::Replay));
+        assert_eq!(w.accept(now + 31 * SECOND, now), Err(ProtocolError::Replay));
+        // Old stamps are forgotten once they could no longer be accepted.
+        w.accept(now + 40 * SECOND, now + 40 * SECOND).unwrap();
+        assert_eq!(w.seen.len(), 1);
+    }
+
+    #[test]
+    fn window_rejects_stamps_from_before_it_was_created() {
+        // A packet captured before the receiver restarted, replayed after.
+        let start = 1_000 * SECOND;
+        let mut w = ReplayWindow::since(start);
+        assert_eq!(w.accept(start - 1, start + 1), Err(ProtocolError::Replay));
+        w.accept(start + 1, start + 1).unwrap();
+    }
+
+    #[test]
+    fn stamps_strictly_increase() {
+        let clock = StampClock::new();
+        assert_eq!(clock.next_at(100), 100);
+        assert_eq!(clock.next_at(100), 101);
+        assert_eq!(clock.next_at(50), 102); // clock stepped back
+        assert_eq!(clock.next_at(200), 200);
+        assert!(clock.next() > 200);
+    }
+
+    #[test]
+    fn debug_does_not_leak_key() {
+        assert_eq!(format!("{:?}", key()), "PairingKey(..)");
+    }
+}
@@ -0,0 +1,169 @@
+//! Plugin discovery beacon.
+//!
+//! While no tablet is connected the plugin announces itself about once a
+//! second, so the app can list simulators on the network without the user
+//! typing an address. Beacons go to X-Plane's own BECN multicast group and
+//! port ([`BEACON_ADDR`]); listeners tell the two apart by the first four
+//! bytes (`BECN` vs. the EFB magic). The sender's IP comes from the datagram
+//! itself.
+//!
+//! Beacon payload layout (little-endian):
+//! ```text
+//! [0..2]    min_version    : u16
+//! [2..4]    max_version    : u16
+//! [4..8]    capabilities   : u32  (see `caps`)
+//! [8..10]   stream_port    : u16  UDP port streaming SimData and taking ACKs
+//! [10..12]  command_port   : u16  UDP port taking commands
+//! [12..14]  tcp_port       : u16  stream transport over TCP, 0 if unavailable
+//! then three strings, each:
+//!           len            : u8
+//!           bytes          : [u8; len]  UTF-8
+//! in order: plugin_version, hostname, aircraft_icao (empty if unknown)
+//! ```
+//!
+//! Like Hello, beacons are sent with the header version set to
+//! [`MIN_PROTOCOL_VERSION`] so that any tablet can decode them. Bytes after
+//! the last string are ignored, leaving room for new fields.
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use crate::handshake::VersionRange;
+use crate::{build_packet_versioned, field_bytes, PacketType, ProtocolError, MIN_PROTOCOL_VERSION};
+
+/// Multicast group and port beacons are sent to (shared with X-Plane's BECN).
+pub const BEACON_ADDR: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(239, 255, 1, 1), 49707));
+
+/// Size of the fixed part of the payload, before the strings.
+const FIXED_LEN: usize = 14;
+
+// ── Beacon ───────────────────────────────────────────────────────────────────
+
+/// Everything a tablet needs to connect to a plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Beacon {
+    /// Protocol versions the plugin accepts.
+    pub versions:       VersionRange,
+    pub capabilities:   u32,
+    pub stream_port:    u16,
+    pub command_port:   u16,
+    /// 0 when the TCP transport is not listening.
+    pub tcp_port:       u16,
+    /// Plugin release, e.g. `0.1.0`.
+    pub plugin_version: String,
+    pub hostname:       String,
+    /// ICAO type designator of the loaded aircraft, e.g. `C172`.
+    pub aircraft_icao:  String,
+}
+
+/// Encode a Beacon datagram.
+///
+/// Strings longer than 255 bytes are truncated at a character boundary.
+pub fn encode_beacon(seq: u32, beacon: &Beacon) -> Vec<u8> {
+    let mut v = Vec::with_capacity(FIXED_LEN + 3 + beacon.plugin_version.len()
+        + beacon.hostname.len() + beacon.aircraft_icao.len());
+    v.extend_from_slice(&beacon.versions.min.to_le_bytes());
+    v.extend_from_slice(&beacon.versions.max.to_le_bytes());
+    v.extend_from_slice(&beacon.capabilities.to_le_bytes());
+    v.extend_from_slice(&beacon.stream_port.to_le_bytes());
+    v.extend_from_slice(&beacon.command_port.to_le_bytes());
+    v.extend_from_slice(&beacon.tcp_port.to_le_bytes());
+    push_str(&mut v, &beacon.plugin_version);
+    push_str(&mut v, &beacon.hostname);
+    push_str(&mut v, &beacon.aircraft_icao);
+    build_packet_versioned(MIN_PROTOCOL_VERSION, seq, PacketType::Beacon, &v)
+}
+
+/// Decode a Beacon payload.
+///
+/// Invalid UTF-8 in the strings is replaced rather than rejected: a host
+/// name in an unexpected encoding should not hide the simulator.
+pub fn decode_beacon(payload: &[u8]) -> Result<Beacon, ProtocolError> {
+    let b = field_bytes(payload, 0, FIXED_LEN, "beacon")?;
+    let u16_at = |i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+    let mut p = FIXED_LEN;
+    Ok(Beacon {
+        versions:       VersionRange { min: u16_at(0), max: u16_at(2) },
+        capabilities:   u32::from_le_bytes([b[4], b[5], b[6], b[7]]),
+        stream_port:    u16_at(8),
+        command_port:   u16_at(10),
+        tcp_port:       u16_at(12),
+        plugin_version: read_str(payload, &mut p, "plugin_version")?,
+        hostname:       read_str(payload, &mut p, "hostname")?,
+        aircraft_icao:  read_str(payload, &mut p, "aircraft_icao")?,
+    })
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+pub(crate) fn push_str(v: &mut Vec<u8>, s: &str) {
+    let mut end = s.len().min(255);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    v.push(end as u8);
+    v.extend_from_slice(&s.as_bytes()[..end]);
+}
+
+/// Length-prefixed string `field` at `*p`; advances `p` past it.
+pub(crate) fn read_str(buf: &[u8], p: &mut usize, field: &'static str) -> Result<String, ProtocolError> {
+    let len = field_bytes(buf, *p, 1, field)?[0] as usize;
+    let bytes = field_bytes(buf, *p + 1, len, field)?;
+    *p += 1 + len;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{caps, decode_packet, HEADER_LEN};
+
+    fn sample() -> Beacon {
+        Beacon {
+            versions:       VersionRange::SUPPORTED,
+            capabilities:   caps::DELTA,
+            stream_port:    49100,
+            command_port:   49100,
+            tcp_port:       49100,
+            plugin_version: "0.1.0".into(),
+            hostname:       "sim-pc".into(),
+            aircraft_icao:  "C172".into(),
+        }
+    }
+
+    #[test]
+    fn beacon_round_trip() {
+        let pkt = encode_beacon(3, &sample());
+        let (hdr, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::Beacon);
+        assert_eq!({ hdr.version }, MIN_PROTOCOL_VERSION);
+        assert_eq!(decode_beacon(payload).unwrap(), sample());
+    }
+
+    #[test]
+    fn long_strings_truncated_on_char_boundary() {
+        let beacon = Beacon { hostname: "é".repeat(200), ..sample() };
+        let pkt = encode_beacon(0, &beacon);
+        let decoded = decode_beacon(&pkt[HEADER_LEN..]).unwrap();
+        assert_eq!(decoded.hostname, "é".repeat(127));
+        assert_eq!(decoded.aircraft_icao, "C172");
+    }
+
+    #[test]
+    fn truncated_and_extended_payloads() {
+        let pkt = encode_beacon(0, &sample());
+        let payload = &pkt[HEADER_LEN..];
+        for len in [0, FIXED_LEN - 1, FIXED_LEN, payload.len() - 1] {
+            assert!(matches!(
+                decode_beacon(&payload[..len]).unwrap_err(),
+                ProtocolError::TruncatedPayload { actual, .. } if actual == len,
+            ));
+        }
+        // Fields appended by a newer plugin are skipped.
+        let mut longer = payload.to_vec();
+        longer.extend_from_slice(b"\x05extra");
+        assert_eq!(decode_beacon(&longer).unwrap(), sample());
+    }
+}
@@ -0,0 +1,45 @@
+// efb-protocol/src/bin/efb-golden.rs
+// Writes the golden test vector corpus (see efb_protocol::golden) that the
+// Rust and Kotlin codec tests both check against.
+//
+// Usage: efb-golden [--check] [DIR]   (DIR defaults to efb-protocol/testdata/golden)
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use efb_protocol::golden::{stale_files, write_corpus};
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/golden"));
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("usage: efb-golden [--check] [DIR]");
+                return ExitCode::SUCCESS;
+            }
+            _ => dir = PathBuf::from(arg),
+        }
+    }
+
+    if check {
+        let stale = stale_files(&dir);
+        if stale.is_empty() {
+            return ExitCode::SUCCESS;
+        }
+        eprintln!("out of date in {}: {}", dir.display(), stale.join(", "));
+        return ExitCode::FAILURE;
+    }
+
+    match write_corpus(&dir) {
+        Ok(()) => {
+            println!("wrote golden corpus to {}", dir.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("writing {}: {e}", dir.display());
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! `.efbcap` packet capture files.
+//!
+//! A capture records every datagram (or stream frame) a peer sent and
+//! received, so a session can be replayed or inspected offline instead of
+//! flying the same route again.
+//!
+//! File layout (little-endian):
+//! ```text
+//! file header (20 bytes):
+//! [0..8]    magic       : b"EFBCAP\r\n"
+//! [8..10]   version     : u16 = 1
+//! [10..12]  reserved    : u16 = 0
+//! [12..20]  start_time  : u64  wall clock at capture start, µs since Unix epoch
+//!
+//! then one record per packet:
+//! [0..4]    body_len    : u32  bytes following this field
+//! [4..12]   timestamp   : u64  µs since capture start (monotonic clock)
+//! [12]      direction   : u8   0 = inbound, 1 = outbound (from the recorder's view)
+//!           source      : address
+//!           destination : address
+//!           data        : the raw datagram, including any auth tag
+//!
+//! address:
+//!           family      : u8   4 or 6
+//!           ip          : [u8; 4] or [u8; 16]
+//!           port        : u16
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// First eight bytes of every capture file.
+pub const CAPTURE_MAGIC: [u8; 8] = *b"EFBCAP\r\n";
+
+/// Capture format version written by this build.
+pub const CAPTURE_VERSION: u16 = 1;
+
+/// Size of the file header in bytes.
+pub const CAPTURE_HEADER_LEN: usize = 20;
+
+/// Records larger than this are rejected as corrupt (a datagram plus
+/// addresses is far smaller).
+const MAX_RECORD_LEN: u32 = 1 << 20;
+
+// ── Record ───────────────────────────────────────────────────────────────────
+
+/// Whether the recorder received or sent a packet.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound  = 0,
+    Outbound = 1,
+}
+
+/// One captured packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    /// Time since capture start.
+    pub timestamp: Duration,
+    pub direction: Direction,
+    pub src:       SocketAddr,
+    pub dst:       SocketAddr,
+    pub data:      Vec<u8>,
+}
+
+// ── CaptureWriter ────────────────────────────────────────────────────────────
+
+/// Appends records to a capture stream.
+#[derive(Debug)]
+pub struct CaptureWriter<W: Write> {
+    inner: W,
+    start: Instant,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Write the file header; record timestamps are relative to now.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        let start_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_micros() as u64);
+        let mut header = [0u8; CAPTURE_HEADER_LEN];
+        header[0..8].copy_from_slice(&CAPTURE_MAGIC);
+        header[8..10].copy_from_slice(&CAPTURE_VERSION.to_le_bytes());
+        header[12..20].copy_from_slice(&start_us.to_le_bytes());
+        inner.write_all(&header)?;
+        Ok(CaptureWriter { inner, start: Instant::now() })
+    }
+
+    /// Record a packet observed now.
+    pub fn record(
+        &mut self,
+        direction: Direction,
+        src: SocketAddr,
+        dst: SocketAddr,
+        data: &[u8],
+    ) -> io::Result<()> {
+        self.record_at(Instant::now(), direction, src, dst, data)
+    }
+
+    /// Record a packet observed at `at` (clamped to the capture start).
+    pub fn record_at(
+        &mut self,
+        at: Instant,
+        direction: Direction,
+        src: SocketAddr,
+        dst: SocketAddr,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let timestamp = at.saturating_duration_since(self.start).as_micros() as u64;
+        let mut body = Vec::with_capacity(8 + 1 + 2 * 19 + data.len());
+        body.extend_from_slice(&timestamp.to_le_bytes());
+        body.push(direction as u8);
+        put_addr(&mut body, src);
+        put_addr(&mut body, dst);
+        body.extend_from_slice(data);
+        self.inner.write_all(&(body.len() as u32).to_le_bytes())?;
+        self.inner.write_all(&body)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+// ── CaptureReader ────────────────────────────────────────────────────────────
+
+/// Reads records from a capture stream.
+#[derive(Debug)]
+pub struct CaptureReader<R: Read> {
+    inner:      R,
+    start_time: SystemTime,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Read and validate the file header.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0u8; CAPTURE_HEADER_LEN];
+        inner.read_exact(&mut header)?;
+        if header[0..8] != CAPTURE_MAGIC {
+            return Err(invalid("not an .efbcap file"));
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != CAPTURE_VERSION {
+            return Err(invalid(format!("unsupported capture version {version}")));
+        }
+        let start_us = u64::from_le_bytes(header[12..20].try_into().unwrap());
+        Ok(CaptureReader { inner, start_time: UNIX_EPOCH + Duration::from_micros(start_us) })
+    }
+
+    /// Wall-clock time the capture started.
+    pub fn start_time(&self) -> SystemTime {
+        self.start_time
+    }
+
+    /// Next record, or `Ok(None)` at a clean end of file.
+    pub fn next_record(&mut self) -> io::Result<Option<CaptureRecord>> {
+        let mut len = [0u8; 4];
+        match self.inner.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let len = u32::from_le_bytes(len);
+        if len > MAX_RECORD_LEN {
+            return Err(invalid(format!("record length {len} exceeds limit")));
+        }
+        let mut body = vec![0u8; len as usize];
+        self.inner.read_exact(&mut body)?;
+        parse_body(body).ok_or_else(|| invalid("malformed record")).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = io::Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn put_addr(v: &mut Vec<u8>, addr: SocketAddr) {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            v.push(4);
+            v.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            v.push(6);
+            v.extend_from_slice(&ip.octets());
+        }
+    }
+    v.extend_from_slice(&addr.port().to_le_bytes());
+}
+
+fn take_addr(b: &[u8], p: &mut usize) -> Option<SocketAddr> {
+    let family = *b.get(*p)?;
+    *p += 1;
+    let ip = match family {
+        4 => {
+            let o: [u8; 4] = b.get(*p..*p + 4)?.try_into().ok()?;
+            *p += 4;
+            IpAddr::V4(Ipv4Addr::from(o))
+        }
+        6 => {
+            let o: [u8; 16] = b.get(*p..*p + 16)?.try_into().ok()?;
+            *p += 16;
+            IpAddr::V6(Ipv6Addr::from(o))
+        }
+        _ => return None,
+    };
+    let port = u16::from_le_bytes(b.get(*p..*p + 2)?.try_into().ok()?);
+    *p += 2;
+    Some(SocketAddr::new(ip, port))
+}
+
+fn parse_body(mut body: Vec<u8>) -> Option<CaptureRecord> {
+    let timestamp = u64::from_le_bytes(body.get(0..8)?.try_into().ok()?);
+    let direction = match *body.get(8)? {
+        0 => Direction::Inbound,
+        1 => Direction::Outbound,
+        _ => return None,
+    };
+    let mut p = 9;
+    let src = take_addr(&body, &mut p)?;
+    let dst = take_addr(&body, &mut p)?;
+    let data = body.split_off(p);
+    Some(CaptureRecord {
+        timestamp: Duration::from_micros(timestamp),
+        direction,
+        src,
+        dst,
+        data,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_packet, encode_sim_data, PacketType};
+    use dataref_schema::SimSnapshot;
+    use std::io::Cursor;
+
+    fn plugin() -> SocketAddr {
+        "192.168.1.10:49100".parse().unwrap()
+    }
+    fn tablet() -> SocketAddr {
+        "[fe80::1]:50123".parse().unwrap()
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let mut w = CaptureWriter::new(Vec::new()).unwrap();
+        let t0 = Instant::now();
+        let pkt = encode_sim_data(1, &SimSnapshot::default());
+        w.record_at(t0 + Duration::from_millis(50), Direction::Outbound, plugin(), tablet(), &pkt).unwrap();
+        w.record_at(t0 + Duration::from_millis(75), Direction::Inbound, tablet(), plugin(), b"ack").unwrap();
+        let bytes = w.into_inner();
+
+        let mut r = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(r.start_time() > UNIX_EPOCH);
+        let a = r.next_record().unwrap().unwrap();
+        assert_eq!(a.direction, Direction::Outbound);
+        assert_eq!((a.src, a.dst), (plugin(), tablet()));
+        assert_eq!(decode_packet(&a.data).unwrap().1, PacketType::SimData);
+        let b = r.next_record().unwrap().unwrap();
+        assert_eq!(b.direction, Direction::Inbound);
+        assert_eq!(b.data, b"ack");
+        assert!(b.timestamp > a.timestamp);
+        assert!(b.timestamp >= Duration::from_millis(75));
+        assert!(r.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_foreign_files() {
+        let err = CaptureReader::new(Cursor::new(b"GIF89a..............".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut header = CaptureWriter::new(Vec::new()).unwrap().into_inner();
+        header[8] = 9;
+        let err = CaptureReader::new(Cursor::new(header)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_record_is_an_error() {
+        let mut w = CaptureWriter::new(Vec::new()).unwrap();
+        w.record(Direction::Inbound, tablet(), plugin(), b"hello").unwrap();
+        let mut bytes = w.into_inner();
+        bytes.truncate(bytes.len() - 2);
+        let mut r = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(r.next_record().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
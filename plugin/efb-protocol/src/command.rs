@@ -0,0 +1,563 @@
+//! Typed tablet → plugin commands and their results.
+//!
+//! [`Command`] is the single definition shared by the plugin and its clients.
+//! It travels either as a compact binary `CommandBinary` packet or, for
+//! clients that predate it, as the original JSON `CommandJson` packet:
+//!
+//! ```text
+//! {"cmd":"set_dataref","path":"sim/cockpit/...","value":1.0}
+//! {"cmd":"swap_freq","radio":"COM1","id":42}
+//! {"cmd":"set_standby_freq","radio":"COM1","hz":118125000}
+//! ```
+//!
+//! CommandBinary payload layout (little-endian):
+//! ```text
+//! [0]     opcode : u8   bit 7 set ⇒ an id follows
+//! [1..5]  id     : u32  only present when bit 7 of the opcode is set
+//! then, by opcode & 0x7F:
+//! 0x01 SetDataref      path_len : u16, path : [u8; path_len] UTF-8, value : f64
+//! 0x02 SwapFreq        radio : u8
+//! 0x03 SetStandbyFreq  radio : u8, hz : i32
+//! ```
+//!
+//! Both decoders reject unknown commands, unknown radios, empty dataref paths
+//! and (binary only) trailing bytes, so a bad command never reaches X-Plane.
+//! An unknown command or radio is reported as such in either encoding, so
+//! JSON and binary tablets get the same [`CommandStatus`].
+//!
+//! The plugin answers every command with a CommandResult packet carrying the
+//! command's optional id, so a tablet can match replies to requests:
+//!
+//! CommandResult payload layout (little-endian):
+//! ```text
+//! [0]     flags   : u8   bit 0 set ⇒ id is meaningful
+//! [1..5]  id      : u32  id of the command, 0 if it had none
+//! [5]     status  : u8   CommandStatus, 0 = ok
+//! [6..8]  msg_len : u16
+//! [8..]   message : [u8; msg_len] UTF-8, empty on success
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{build_packet, field_bytes, PacketType, ProtocolError};
+
+/// Opcode bit marking a binary command that carries an id.
+const ID_FLAG: u8 = 0x80;
+
+/// Size of the fixed part of a CommandResult payload, before the message.
+const RESULT_FIXED_LEN: usize = 8;
+
+// ── Command ──────────────────────────────────────────────────────────────────
+
+/// Radio addressed by a frequency command.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Radio {
+    Com1 = 0,
+    Com2 = 1,
+    Nav1 = 2,
+    Nav2 = 3,
+}
+
+impl Radio {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Com1),
+            1 => Some(Self::Com2),
+            2 => Some(Self::Nav1),
+            3 => Some(Self::Nav2),
+            _ => None,
+        }
+    }
+}
+
+/// A command from a tablet to the plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    /// Write a float dataref.
+    SetDataref { path: String, value: f64 },
+    /// Exchange active and standby frequencies.
+    SwapFreq { radio: Radio },
+    /// Set the standby frequency.
+    SetStandbyFreq { radio: Radio, hz: i32 },
+}
+
+/// A command plus the optional id echoed in its [`CommandResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id:      Option<u32>,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+impl From<Command> for CommandRequest {
+    fn from(command: Command) -> Self {
+        CommandRequest { id: None, command }
+    }
+}
+
+impl Command {
+    /// Every JSON `cmd` name, matching the serde tags above.
+    const JSON_NAMES: [&'static str; 3] = ["set_dataref", "swap_freq", "set_standby_freq"];
+
+    fn opcode(&self) -> u8 {
+        match self {
+            Self::SetDataref { .. }     => 0x01,
+            Self::SwapFreq { .. }       => 0x02,
+            Self::SetStandbyFreq { .. } => 0x03,
+        }
+    }
+
+    /// Reject an empty dataref path, reported at `path_offset`.
+    fn validate(self, path_offset: usize) -> Result<Self, ProtocolError> {
+        match &self {
+            Self::SetDataref { path, .. } if path.is_empty() => {
+                Err(ProtocolError::MalformedCommand { offset: path_offset })
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+// ── CommandResult ────────────────────────────────────────────────────────────
+
+/// Outcome of a command.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandStatus {
+    Ok               = 0,
+    /// Invalid JSON or UTF-8, empty path, or trailing bytes.
+    Malformed        = 1,
+    /// Command name or opcode not known to the plugin.
+    UnknownCommand   = 2,
+    /// Radio outside COM1/COM2/NAV1/NAV2.
+    UnknownRadio     = 3,
+    /// No dataref with the requested path.
+    UnknownDataref   = 4,
+    /// A valid radio whose frequencies the plugin cannot set.
+    UnsupportedRadio = 5,
+}
+
+impl CommandStatus {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Ok),
+            1 => Some(Self::Malformed),
+            2 => Some(Self::UnknownCommand),
+            3 => Some(Self::UnknownRadio),
+            4 => Some(Self::UnknownDataref),
+            5 => Some(Self::UnsupportedRadio),
+            _ => None,
+        }
+    }
+
+    /// Status reported for a command that failed to decode.
+    pub fn from_error(e: &ProtocolError) -> Self {
+        match e {
+            ProtocolError::UnknownCommand(_) | ProtocolError::UnknownCommandName(_) => Self::UnknownCommand,
+            ProtocolError::UnknownRadio(_) | ProtocolError::UnknownRadioName(_) => Self::UnknownRadio,
+            _ => Self::Malformed,
+        }
+    }
+}
+
+/// Reply to one command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResult {
+    /// Id of the command this answers, if it had one.
+    pub id:      Option<u32>,
+    pub status:  CommandStatus,
+    /// Human-readable detail; empty on success.
+    pub message: String,
+}
+
+impl CommandResult {
+    pub fn ok(id: Option<u32>) -> Self {
+        CommandResult { id, status: CommandStatus::Ok, message: String::new() }
+    }
+
+    pub fn error(id: Option<u32>, status: CommandStatus, message: impl Into<String>) -> Self {
+        CommandResult { id, status, message: message.into() }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == CommandStatus::Ok
+    }
+}
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// Encode a CommandBinary datagram. See [`request_payload`] for the error.
+pub fn encode_command(seq: u32, cmd: &Command) -> Result<Vec<u8>, ProtocolError> {
+    encode_request(seq, &cmd.clone().into())
+}
+
+/// Encode a CommandJson datagram, for plugins that predate CommandBinary.
+pub fn encode_command_json(seq: u32, cmd: &Command) -> Vec<u8> {
+    encode_request_json(seq, &cmd.clone().into())
+}
+
+/// Encode a CommandBinary datagram carrying the request's id, if any.
+/// See [`request_payload`] for the error.
+pub fn encode_request(seq: u32, req: &CommandRequest) -> Result<Vec<u8>, ProtocolError> {
+    Ok(build_packet(seq, PacketType::CommandBinary, &request_payload(req)?))
+}
+
+/// Encode a CommandJson datagram carrying the request's id, if any.
+pub fn encode_request_json(seq: u32, req: &CommandRequest) -> Vec<u8> {
+    let json = serde_json::to_vec(req).expect("CommandRequest always serializes");
+    build_packet(seq, PacketType::CommandJson, &json)
+}
+
+/// Binary payload of a CommandBinary packet.
+pub fn command_payload(cmd: &Command) -> Result<Vec<u8>, ProtocolError> {
+    request_payload(&cmd.clone().into())
+}
+
+/// Binary payload of a CommandBinary packet with an optional id. A dataref
+/// path too long for its u16 length is [`ProtocolError::PayloadTooLarge`].
+pub fn request_payload(req: &CommandRequest) -> Result<Vec<u8>, ProtocolError> {
+    let cmd = &req.command;
+    let mut v = match req.id {
+        Some(id) => {
+            let mut v = vec![cmd.opcode() | ID_FLAG];
+            v.extend_from_slice(&id.to_le_bytes());
+            v
+        }
+        None => vec![cmd.opcode()],
+    };
+    match cmd {
+        Command::SetDataref { path, value } => {
+            let len = u16::try_from(path.len()).map_err(|_| ProtocolError::PayloadTooLarge)?;
+            v.extend_from_slice(&len.to_le_bytes());
+            v.extend_from_slice(path.as_bytes());
+            v.extend_from_slice(&value.to_le_bytes());
+        }
+        Command::SwapFreq { radio } => v.push(*radio as u8),
+        Command::SetStandbyFreq { radio, hz } => {
+            v.push(*radio as u8);
+            v.extend_from_slice(&hz.to_le_bytes());
+        }
+    }
+    Ok(v)
+}
+
+/// Decode a CommandBinary payload, discarding any id.
+pub fn decode_command(payload: &[u8]) -> Result<Command, ProtocolError> {
+    decode_request(payload).map(|r| r.command)
+}
+
+/// Decode a CommandJson payload, discarding any id.
+pub fn decode_command_json(payload: &[u8]) -> Result<Command, ProtocolError> {
+    decode_request_json(payload).map(|r| r.command)
+}
+
+/// Decode a CommandBinary payload.
+pub fn decode_request(payload: &[u8]) -> Result<CommandRequest, ProtocolError> {
+    let mut p = 0;
+    let [opcode] = take::<1>(payload, &mut p, "opcode")?;
+    let id = if opcode & ID_FLAG != 0 {
+        Some(u32::from_le_bytes(take::<4>(payload, &mut p, "id")?))
+    } else {
+        None
+    };
+    let mut path_offset = 0;
+    let command = match opcode & !ID_FLAG {
+        0x01 => {
+            let len = u16::from_le_bytes(take::<2>(payload, &mut p, "path_len")?) as usize;
+            path_offset = p;
+            let path = field_bytes(payload, p, len, "path")?;
+            p += len;
+            let path = std::str::from_utf8(path)
+                .map_err(|_| ProtocolError::MalformedCommand { offset: path_offset })?;
+            let value = f64::from_le_bytes(take::<8>(payload, &mut p, "value")?);
+            Command::SetDataref { path: path.to_string(), value }
+        }
+        0x02 => Command::SwapFreq { radio: take_radio(payload, &mut p)? },
+        0x03 => {
+            let radio = take_radio(payload, &mut p)?;
+            let hz = i32::from_le_bytes(take::<4>(payload, &mut p, "hz")?);
+            Command::SetStandbyFreq { radio, hz }
+        }
+        other => return Err(ProtocolError::UnknownCommand(other)),
+    };
+    if p != payload.len() {
+        return Err(ProtocolError::MalformedCommand { offset: p });
+    }
+    Ok(CommandRequest { id, command: command.validate(path_offset)? })
+}
+
+/// Decode a CommandJson payload.
+///
+/// The `cmd` and `radio` names are checked before the rest of the object, so
+/// an unknown one is reported as [`ProtocolError::UnknownCommandName`] or
+/// [`ProtocolError::UnknownRadioName`] rather than as malformed.
+pub fn decode_request_json(payload: &[u8]) -> Result<CommandRequest, ProtocolError> {
+    let value = serde_json::from_slice::<serde_json::Value>(payload)
+        .map_err(|e| ProtocolError::MalformedCommand { offset: json_error_offset(payload, &e) })?;
+    if let Some(cmd) = value.get("cmd").and_then(serde_json::Value::as_str) {
+        if !Command::JSON_NAMES.contains(&cmd) {
+            return Err(ProtocolError::UnknownCommandName(cmd.to_string()));
+        }
+    }
+    if let Some(radio) = value.get("radio").filter(|r| r.is_string()) {
+        if Radio::deserialize(radio).is_err() {
+            return Err(ProtocolError::UnknownRadioName(radio.as_str().unwrap_or_default().to_string()));
+        }
+    }
+    let req = serde_json::from_value::<CommandRequest>(value)
+        .map_err(|_| ProtocolError::MalformedCommand { offset: 0 })?;
+    Ok(CommandRequest { id: req.id, command: req.command.validate(0)? })
+}
+
+/// Best-effort id of a command payload that may fail to decode, so an error
+/// reply can still be matched to its request.
+pub fn peek_request_id(ptype: PacketType, payload: &[u8]) -> Option<u32> {
+    match ptype {
+        PacketType::CommandBinary => {
+            let (&opcode, rest) = payload.split_first()?;
+            let id = rest.get(..4).filter(|_| opcode & ID_FLAG != 0)?;
+            Some(u32::from_le_bytes(id.try_into().unwrap()))
+        }
+        PacketType::CommandJson => {
+            let value = serde_json::from_slice::<serde_json::Value>(payload).ok()?;
+            value.get("id")?.as_u64()?.try_into().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Encode a CommandResult datagram.
+pub fn encode_command_result(seq: u32, result: &CommandResult) -> Vec<u8> {
+    let msg = &result.message.as_bytes()[..result.message.len().min(u16::MAX as usize)];
+    let mut v = Vec::with_capacity(RESULT_FIXED_LEN + msg.len());
+    v.push(result.id.is_some() as u8);
+    v.extend_from_slice(&result.id.unwrap_or(0).to_le_bytes());
+    v.push(result.status as u8);
+    v.extend_from_slice(&(msg.len() as u16).to_le_bytes());
+    v.extend_from_slice(msg);
+    build_packet(seq, PacketType::CommandResult, &v)
+}
+
+/// Decode a CommandResult payload.
+pub fn decode_command_result(payload: &[u8]) -> Result<CommandResult, ProtocolError> {
+    let mut p = 0;
+    let [flags] = take::<1>(payload, &mut p, "flags")?;
+    let id = u32::from_le_bytes(take::<4>(payload, &mut p, "id")?);
+    let [status] = take::<1>(payload, &mut p, "status")?;
+    let len = u16::from_le_bytes(take::<2>(payload, &mut p, "message_len")?) as usize;
+    let msg = field_bytes(payload, p, len, "message")?;
+    Ok(CommandResult {
+        id:      (flags & 1 != 0).then_some(id),
+        status:  CommandStatus::from_u8(status).ok_or(ProtocolError::MalformedCommand { offset: 5 })?,
+        message: String::from_utf8_lossy(msg).into_owned(),
+    })
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// Byte offset of a JSON syntax error, from serde_json's 1-based line and
+/// column; the payload length if it ends early.
+fn json_error_offset(payload: &[u8], e: &serde_json::Error) -> usize {
+    if e.is_eof() {
+        return payload.len();
+    }
+    let line_start: usize = payload
+        .split(|&b| b == b'\n')
+        .take(e.line().saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum();
+    (line_start + e.column().saturating_sub(1)).min(payload.len())
+}
+
+fn take<const N: usize>(buf: &[u8], p: &mut usize, field: &'static str) -> Result<[u8; N], ProtocolError> {
+    let b = field_bytes(buf, *p, N, field)?;
+    *p += N;
+    Ok(b.try_into().unwrap())
+}
+
+fn take_radio(buf: &[u8], p: &mut usize) -> Result<Radio, ProtocolError> {
+    let [r] = take::<1>(buf, p, "radio")?;
+    Radio::from_u8(r).ok_or(ProtocolError::UnknownRadio(r))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_packet;
+
+    fn samples() -> Vec<Command> {
+        vec![
+            Command::SetDataref { path: "sim/cockpit/autopilot/heading_mag".into(), value: 270.0 },
+            Command::SwapFreq { radio: Radio::Com1 },
+            Command::SetStandbyFreq { radio: Radio::Nav2, hz: 110_300_000 },
+        ]
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        for cmd in samples() {
+            let pkt = encode_command(5, &cmd).unwrap();
+            let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+            assert_eq!(ptype, PacketType::CommandBinary);
+            assert_eq!(decode_command(payload).unwrap(), cmd);
+        }
+    }
+
+    #[test]
+    fn json_round_trip_and_legacy_format() {
+        for cmd in samples() {
+            let pkt = encode_command_json(0, &cmd);
+            let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+            assert_eq!(ptype, PacketType::CommandJson);
+            assert_eq!(decode_command_json(payload).unwrap(), cmd);
+        }
+        // Exactly what existing tablets send.
+        assert_eq!(
+            decode_command_json(br#"{"cmd":"swap_freq","radio":"COM1"}"#).unwrap(),
+            Command::SwapFreq { radio: Radio::Com1 },
+        );
+        assert_eq!(
+            decode_command_json(br#"{"cmd":"set_dataref","path":"sim/x","value":1}"#).unwrap(),
+            Command::SetDataref { path: "sim/x".into(), value: 1.0 },
+        );
+    }
+
+    #[test]
+    fn binary_is_compact() {
+        assert_eq!(command_payload(&Command::SwapFreq { radio: Radio::Com2 }).unwrap(), [0x02, 0x01]);
+    }
+
+    #[test]
+    fn bad_binary_commands_rejected_precisely() {
+        assert_eq!(
+            decode_command(&[]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "opcode".into(), offset: 0, expected: 1, actual: 0 },
+        );
+        assert_eq!(decode_command(&[0x7F]).unwrap_err(), ProtocolError::UnknownCommand(0x7F));
+        assert_eq!(decode_command(&[0x02, 9]).unwrap_err(), ProtocolError::UnknownRadio(9));
+        assert_eq!(
+            decode_command(&[0x03, 0, 1, 2]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "hz".into(), offset: 2, expected: 6, actual: 4 },
+        );
+        // Trailing byte.
+        assert_eq!(decode_command(&[0x02, 0, 0]).unwrap_err(), ProtocolError::MalformedCommand { offset: 2 });
+        // Empty path.
+        let empty = command_payload(&Command::SetDataref { path: String::new(), value: 0.0 }).unwrap();
+        assert_eq!(decode_command(&empty).unwrap_err(), ProtocolError::MalformedCommand { offset: 3 });
+        // Non-UTF-8 path.
+        let mut bad = vec![0x01, 1, 0, 0xFF];
+        bad.extend_from_slice(&0f64.to_le_bytes());
+        assert_eq!(decode_command(&bad).unwrap_err(), ProtocolError::MalformedCommand { offset: 3 });
+    }
+
+    #[test]
+    fn over_long_path_is_an_error() {
+        let fits = Command::SetDataref { path: "x".repeat(u16::MAX as usize), value: 0.0 };
+        assert_eq!(decode_command(&command_payload(&fits).unwrap()).unwrap(), fits);
+        // Not cut to length, which could split a multi-byte character.
+        let long = Command::SetDataref { path: "é".repeat(u16::MAX as usize / 2 + 1), value: 0.0 };
+        assert_eq!(command_payload(&long).unwrap_err(), ProtocolError::PayloadTooLarge);
+        assert_eq!(encode_command(0, &long).unwrap_err(), ProtocolError::PayloadTooLarge);
+    }
+
+    #[test]
+    fn ids_round_trip_in_both_encodings() {
+        for command in samples() {
+            let req = CommandRequest { id: Some(0xDEAD_BEEF), command };
+            let pkt = encode_request(1, &req).unwrap();
+            let (_, _, payload) = decode_packet(&pkt).unwrap();
+            assert_eq!(payload[0] & ID_FLAG, ID_FLAG);
+            assert_eq!(decode_request(payload).unwrap(), req);
+            let pkt = encode_request_json(2, &req);
+            let (_, _, payload) = decode_packet(&pkt).unwrap();
+            assert_eq!(decode_request_json(payload).unwrap(), req);
+        }
+        assert_eq!(
+            decode_request_json(br#"{"id":7,"cmd":"swap_freq","radio":"NAV1"}"#).unwrap(),
+            CommandRequest { id: Some(7), command: Command::SwapFreq { radio: Radio::Nav1 } },
+        );
+        // Without an id the encodings are unchanged.
+        assert_eq!(
+            serde_json::to_string(&CommandRequest::from(Command::SwapFreq { radio: Radio::Com1 })).unwrap(),
+            r#"{"cmd":"swap_freq","radio":"COM1"}"#,
+        );
+    }
+
+    #[test]
+    fn ids_recovered_from_undecodable_commands() {
+        let bad_radio = [0x82, 9, 0, 0, 0, 7];
+        assert_eq!(decode_request(&bad_radio).unwrap_err(), ProtocolError::UnknownRadio(7));
+        assert_eq!(peek_request_id(PacketType::CommandBinary, &bad_radio), Some(9));
+        assert_eq!(peek_request_id(PacketType::CommandBinary, &[0x02, 0]), None);
+        let json = br#"{"id":12,"cmd":"direct_to"}"#;
+        assert_eq!(peek_request_id(PacketType::CommandJson, json), Some(12));
+        assert_eq!(peek_request_id(PacketType::CommandJson, b"{"), None);
+    }
+
+    #[test]
+    fn command_result_round_trip() {
+        for result in [
+            CommandResult::ok(Some(3)),
+            CommandResult::ok(None),
+            CommandResult::error(Some(0), CommandStatus::UnknownDataref, "dataref not found: sim/x"),
+        ] {
+            let pkt = encode_command_result(9, &result);
+            let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+            assert_eq!(ptype, PacketType::CommandResult);
+            assert_eq!(decode_command_result(payload).unwrap(), result);
+        }
+        assert_eq!(
+            decode_command_result(&[1, 0, 0]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "id".into(), offset: 1, expected: 5, actual: 3 },
+        );
+        assert_eq!(
+            decode_command_result(&[0, 0, 0, 0, 0, 99, 0, 0]).unwrap_err(),
+            ProtocolError::MalformedCommand { offset: 5 },
+        );
+    }
+
+    #[test]
+    fn bad_json_commands_rejected() {
+        for json in [
+            &br#"{"cmd":"set_dataref","path":"","value":1}"#[..],
+            br#"{"cmd":"swap_freq","radio":1}"#,
+            br#"{"cmd":"swap_freq"}"#,
+            br#"{"radio":"COM1"}"#,
+        ] {
+            assert_eq!(decode_command_json(json).unwrap_err(), ProtocolError::MalformedCommand { offset: 0 });
+        }
+        // Syntax errors point at the offending byte.
+        assert_eq!(decode_command_json(b"not json").unwrap_err(), ProtocolError::MalformedCommand { offset: 1 });
+        assert_eq!(
+            decode_command_json(b"{\n  \"cmd\": x}").unwrap_err(),
+            ProtocolError::MalformedCommand { offset: 11 },
+        );
+    }
+
+    #[test]
+    fn unknown_json_names_rejected_precisely() {
+        let err = decode_command_json(br#"{"cmd":"direct_to","identifier":"FAOR"}"#).unwrap_err();
+        assert_eq!(err, ProtocolError::UnknownCommandName("direct_to".into()));
+        assert_eq!(CommandStatus::from_error(&err), CommandStatus::UnknownCommand);
+
+        let err = decode_command_json(br#"{"cmd":"swap_freq","radio":"ADF1"}"#).unwrap_err();
+        assert_eq!(err, ProtocolError::UnknownRadioName("ADF1".into()));
+        assert_eq!(CommandStatus::from_error(&err), CommandStatus::UnknownRadio);
+    }
+
+    #[test]
+    fn json_names_cover_every_command() {
+        for cmd in samples() {
+            let json = serde_json::to_value(&cmd).unwrap();
+            assert!(Command::JSON_NAMES.contains(&json["cmd"].as_str().unwrap()));
+        }
+        assert_eq!(samples().len(), Command::JSON_NAMES.len());
+    }
+}
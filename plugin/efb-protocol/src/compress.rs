@@ -0,0 +1,84 @@
+//! Payload compression for v2 packets.
+//!
+//! Payloads are raw deflate (RFC 1951, no zlib or gzip wrapper), which the
+//! tablet inflates with `java.util.zip.Inflater(nowrap = true)`. Compression
+//! is only used with peers that negotiated [`caps::COMPRESSION`] and is
+//! signalled per packet by [`flags::COMPRESSED`].
+//!
+//! SimData and Delta payloads are dominated by zeros and repeated values, so
+//! they shrink well; payloads too small to gain anything are sent as is.
+//!
+//! [`caps::COMPRESSION`]: crate::caps::COMPRESSION
+//! [`flags::COMPRESSED`]: crate::flags::COMPRESSED
+
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+use crate::ProtocolError;
+
+/// Payloads shorter than this are never compressed.
+pub const MIN_COMPRESS_LEN: usize = 64;
+
+/// Largest inflated payload accepted, so a small datagram cannot expand
+/// into an arbitrarily large allocation.
+pub const MAX_INFLATED_LEN: usize = 4 * 1024 * 1024;
+
+/// Fast end of the deflate scale; packets are compressed on the flight loop.
+const LEVEL: u8 = 1;
+
+/// Deflate `payload`, or `None` if it is too short or would not get smaller.
+pub fn deflate(payload: &[u8]) -> Option<Vec<u8>> {
+    if payload.len() < MIN_COMPRESS_LEN {
+        return None;
+    }
+    let out = compress_to_vec(payload, LEVEL);
+    (out.len() < payload.len()).then_some(out)
+}
+
+/// Inflate a payload flagged as compressed.
+pub fn inflate(payload: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+    decompress_to_vec_with_limit(payload, MAX_INFLATED_LEN).map_err(|_| ProtocolError::BadCompression)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode_packet, encode_ack, encode_sim_data, flags, inflate_payload, upgrade_packet,
+        PacketType, Timestamps, HEADER_V2_LEN, SIM_DATA_PACKET_LEN,
+    };
+    use dataref_schema::SimSnapshot;
+
+    #[test]
+    fn snapshots_shrink_and_round_trip() {
+        let pkt = encode_sim_data(5, &SimSnapshot { latitude: 51.47, ..SimSnapshot::default() });
+        let ts = Timestamps { sim_time_us: 1_000, sent_us: 2_000 };
+        let v2 = upgrade_packet(&pkt, ts, true).unwrap();
+        assert!(v2.len() < SIM_DATA_PACKET_LEN);
+
+        let (hdr, ptype, payload) = decode_packet(&v2).unwrap();
+        assert_eq!(ptype, PacketType::SimData);
+        assert!(hdr.has(flags::COMPRESSED | flags::KEYFRAME));
+        assert_eq!(&*inflate_payload(&hdr, payload).unwrap(), &pkt[crate::HEADER_LEN..]);
+    }
+
+    #[test]
+    fn small_payloads_sent_as_is() {
+        let v2 = upgrade_packet(&encode_ack(1), Timestamps::default(), true).unwrap();
+        assert_eq!(v2.len(), HEADER_V2_LEN);
+        let (hdr, _, _) = decode_packet(&v2).unwrap();
+        assert!(!hdr.has(flags::COMPRESSED));
+
+        let incompressible: Vec<u8> = (0..200u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+        assert_eq!(deflate(&incompressible), None);
+    }
+
+    #[test]
+    fn garbage_and_bombs_rejected() {
+        assert_eq!(inflate(&[0xFF; 16]).unwrap_err(), ProtocolError::BadCompression);
+        let bomb = compress_to_vec(&vec![0u8; MAX_INFLATED_LEN + 1], LEVEL);
+        assert_eq!(inflate(&bomb).unwrap_err(), ProtocolError::BadCompression);
+    }
+}
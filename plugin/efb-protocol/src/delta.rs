@@ -0,0 +1,321 @@
+//! Keyframe/delta encoding of the SimData stream.
+//!
+//! A Keyframe carries a full serialized [`SimSnapshot`]. Every packet in
+//! between carries only the fields that differ from the most recent keyframe,
+//! so a lost delta never corrupts the ones after it — only a lost keyframe
+//! does, and that is reported as [`ProtocolError::MissingKeyframe`]. A
+//! keyframe older than the one held arrived out of order and is dropped as
+//! [`ProtocolError::Replay`].
+//!
+//! Delta payload layout (little-endian):
+//! ```text
+//! [0..4]   base_seq : u32  sequence number of the keyframe this applies to
+//! [4..12]  mask     : u64  bit i set ⇒ field i (wire order) is present
+//! [12..]   values   : changed fields, in wire order, at their wire width
+//! ```
+
+use dataref_schema::{SimSnapshot, SNAPSHOT_FIELDS};
+
+use crate::groups::REORDER_WINDOW;
+use crate::{
+    build_packet, deserialize_snapshot, field_bytes, serialize_snapshot, PacketHeader, PacketType,
+    ProtocolError, SNAPSHOT_LEN,
+};
+
+/// Default number of packets between keyframes (one per second at 20 Hz).
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 20;
+
+/// Size of the fixed part of a Delta payload (`base_seq` + `mask`).
+const DELTA_PREFIX_LEN: usize = 12;
+
+// ── DeltaEncoder ─────────────────────────────────────────────────────────────
+
+/// Sender side: emits a keyframe every `keyframe_interval` packets and deltas
+/// against it in between.
+pub struct DeltaEncoder {
+    keyframe_interval: u32,
+    since_keyframe:    u32,
+    /// (sequence, serialized payload) of the last keyframe sent.
+    keyframe:          Option<(u32, Vec<u8>)>,
+}
+
+impl DeltaEncoder {
+    /// `keyframe_interval` is clamped to at least 1 (every packet a keyframe).
+    pub fn new(keyframe_interval: u32) -> Self {
+        DeltaEncoder {
+            keyframe_interval: keyframe_interval.max(1),
+            since_keyframe:    0,
+            keyframe:          None,
+        }
+    }
+
+    /// Make the next [`encode`](Self::encode) call emit a keyframe, e.g. when a
+    /// new tablet connects.
+    pub fn force_keyframe(&mut self) {
+        self.keyframe = None;
+    }
+
+    /// Encode `snapshot` as either a Keyframe or a Delta datagram.
+    pub fn encode(&mut self, seq: u32, snapshot: &SimSnapshot) -> Vec<u8> {
+        let payload = serialize_snapshot(snapshot);
+
+        let base = match &self.keyframe {
+            Some((base_seq, base)) if self.since_keyframe < self.keyframe_interval => {
+                Some((*base_seq, base))
+            }
+            _ => None,
+        };
+
+        if let Some((base_seq, base)) = base {
+            let delta = diff_payloads(base_seq, base, &payload);
+            self.since_keyframe += 1;
+            return build_packet(seq, PacketType::Delta, &delta);
+        }
+
+        let pkt = build_packet(seq, PacketType::Keyframe, &payload);
+        self.keyframe = Some((seq, payload));
+        self.since_keyframe = 1;
+        pkt
+    }
+}
+
+impl Default for DeltaEncoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEYFRAME_INTERVAL)
+    }
+}
+
+// ── DeltaDecoder ─────────────────────────────────────────────────────────────
+
+/// Receiver side: rebuilds full snapshots from Keyframe and Delta packets.
+#[derive(Default)]
+pub struct DeltaDecoder {
+    keyframe: Option<(u32, Vec<u8>)>,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a packet already validated by [`decode_packet`](crate::decode_packet).
+    ///
+    /// Plain SimData packets are accepted too, so a receiver can use this for
+    /// every snapshot-bearing packet type.
+    pub fn decode(
+        &mut self,
+        header: &PacketHeader,
+        ptype: PacketType,
+        payload: &[u8],
+    ) -> Result<SimSnapshot, ProtocolError> {
+        match ptype {
+            PacketType::SimData => crate::decode_sim_data(payload),
+            PacketType::Keyframe => {
+                let snap = crate::decode_sim_data(payload)?;
+                let stale = self.keyframe.as_ref()
+                    .is_some_and(|(held, _)| (1..=REORDER_WINDOW).contains(&held.wrapping_sub(header.sequence)));
+                if stale {
+                    return Err(ProtocolError::Replay);
+                }
+                self.keyframe = Some((header.sequence, payload[..SNAPSHOT_LEN].to_vec()));
+                Ok(snap)
+            }
+            PacketType::Delta => {
+                let full = self.apply_delta(payload)?;
+                Ok(deserialize_snapshot(&full).expect("keyframe is SNAPSHOT_LEN bytes"))
+            }
+            other => Err(ProtocolError::UnknownPacketType(other as u8)),
+        }
+    }
+
+    /// Sequence number of the keyframe currently held, if any.
+    pub fn keyframe_sequence(&self) -> Option<u32> {
+        self.keyframe.as_ref().map(|(seq, _)| *seq)
+    }
+
+    fn apply_delta(&self, payload: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        field_bytes(payload, 0, DELTA_PREFIX_LEN, "delta prefix")?;
+        let base_seq = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+        let mask     = u64::from_le_bytes(payload[4..12].try_into().unwrap());
+
+        let base = match &self.keyframe {
+            Some((seq, base)) if *seq == base_seq => base,
+            _ => return Err(ProtocolError::MissingKeyframe),
+        };
+
+        let mut full = base.clone();
+        let mut src = DELTA_PREFIX_LEN;
+        let mut dst = 0usize;
+        for (i, f) in SNAPSHOT_FIELDS.iter().enumerate() {
+            let size = f.wire_size();
+            if mask & (1 << i) != 0 {
+                let bytes = field_bytes(payload, src, size, f.name)?;
+                full[dst..dst + size].copy_from_slice(bytes);
+                src += size;
+            }
+            dst += size;
+        }
+        Ok(full)
+    }
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+fn diff_payloads(base_seq: u32, base: &[u8], current: &[u8]) -> Vec<u8> {
+    let mut mask = 0u64;
+    let mut values = Vec::new();
+    let mut off = 0usize;
+    for (i, size) in SNAPSHOT_FIELDS.iter().map(|f| f.wire_size()).enumerate() {
+        let field = &current[off..off + size];
+        if field != &base[off..off + size] {
+            mask |= 1 << i;
+            values.extend_from_slice(field);
+        }
+        off += size;
+    }
+
+    let mut v = Vec::with_capacity(DELTA_PREFIX_LEN + values.len());
+    v.extend_from_slice(&base_seq.to_le_bytes());
+    v.extend_from_slice(&mask.to_le_bytes());
+    v.extend_from_slice(&values);
+    v
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_packet;
+
+    fn decode_into(dec: &mut DeltaDecoder, pkt: &[u8]) -> Result<SimSnapshot, ProtocolError> {
+        let (hdr, ptype, payload) = decode_packet(pkt)?;
+        dec.decode(&hdr, ptype, payload)
+    }
+
+    #[test]
+    fn field_sizes_cover_snapshot() {
+        assert!(SNAPSHOT_FIELDS.len() <= 64, "delta mask is a u64");
+        assert_eq!(serialize_snapshot(&SimSnapshot::default()).len(), SNAPSHOT_LEN);
+    }
+
+    #[test]
+    fn keyframe_every_interval() {
+        let mut enc = DeltaEncoder::new(3);
+        let snap = SimSnapshot::default();
+        let types: Vec<_> = (0..7)
+            .map(|seq| decode_packet(&enc.encode(seq, &snap)).unwrap().1)
+            .collect();
+        assert_eq!(types, [
+            PacketType::Keyframe, PacketType::Delta, PacketType::Delta,
+            PacketType::Keyframe, PacketType::Delta, PacketType::Delta,
+            PacketType::Keyframe,
+        ]);
+    }
+
+    #[test]
+    fn delta_round_trip_rebuilds_snapshot() {
+        let mut enc = DeltaEncoder::new(10);
+        let mut dec = DeltaDecoder::new();
+
+        let mut snap = SimSnapshot { latitude: -26.1367, ias_kts: 110.0, ..SimSnapshot::default() };
+        decode_into(&mut dec, &enc.encode(0, &snap)).unwrap();
+
+        snap.ias_kts = 112.5;
+        snap.traffic_count = 1;
+        snap.traffic_lat[0] = -26.2;
+        let pkt = enc.encode(1, &snap);
+        let (_, _, payload) = decode_packet(&pkt).unwrap();
+        // 12-byte prefix + ias (4) + traffic_lat (80) + traffic_count (1)
+        assert_eq!(payload.len(), 12 + 4 + 80 + 1);
+
+        let out = decode_into(&mut dec, &pkt).unwrap();
+        assert!((out.latitude - -26.1367).abs() < 1e-9);
+        assert!((out.ias_kts - 112.5).abs() < 1e-6);
+        assert_eq!(out.traffic_count, 1);
+        assert!((out.traffic_lat[0] - -26.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unchanged_snapshot_sends_empty_delta() {
+        let mut enc = DeltaEncoder::default();
+        let snap = SimSnapshot::default();
+        enc.encode(0, &snap);
+        let pkt = enc.encode(1, &snap);
+        let (_, _, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(payload.len(), DELTA_PREFIX_LEN);
+    }
+
+    #[test]
+    fn lost_keyframe_is_detected() {
+        let mut enc = DeltaEncoder::new(2);
+        let mut dec = DeltaDecoder::new();
+        let snap = SimSnapshot::default();
+
+        // Delta before any keyframe.
+        let _lost = enc.encode(0, &snap);
+        assert_eq!(decode_into(&mut dec, &enc.encode(1, &snap)).unwrap_err(),
+                   ProtocolError::MissingKeyframe);
+
+        // Decoder holds keyframe 2, but keyframe 4 is dropped.
+        decode_into(&mut dec, &enc.encode(2, &snap)).unwrap();
+        decode_into(&mut dec, &enc.encode(3, &snap)).unwrap();
+        let _lost = enc.encode(4, &snap);
+        assert_eq!(decode_into(&mut dec, &enc.encode(5, &snap)).unwrap_err(),
+                   ProtocolError::MissingKeyframe);
+        assert_eq!(dec.keyframe_sequence(), Some(2));
+    }
+
+    #[test]
+    fn reordered_keyframe_is_dropped() {
+        let mut enc = DeltaEncoder::new(2);
+        let mut dec = DeltaDecoder::new();
+        let old = SimSnapshot { ias_kts: 90.0, ..SimSnapshot::default() };
+        let new = SimSnapshot { ias_kts: 95.0, ..SimSnapshot::default() };
+
+        // Keyframe 2 arrives after keyframe 4 and the delta against it.
+        let late = enc.encode(2, &old);
+        enc.encode(3, &old);
+        decode_into(&mut dec, &enc.encode(4, &new)).unwrap();
+        let delta = enc.encode(5, &new);
+        assert_eq!(decode_into(&mut dec, &late).unwrap_err(), ProtocolError::Replay);
+        assert_eq!(dec.keyframe_sequence(), Some(4));
+        assert!((decode_into(&mut dec, &delta).unwrap().ias_kts - 95.0).abs() < 1e-6);
+
+        // Far enough behind, it is taken as the sender having restarted.
+        decode_into(&mut dec, &DeltaEncoder::new(2).encode(1000u32.wrapping_neg(), &old)).unwrap();
+        assert_eq!(dec.keyframe_sequence(), Some(1000u32.wrapping_neg()));
+    }
+
+    #[test]
+    fn force_keyframe_restarts_cycle() {
+        let mut enc = DeltaEncoder::new(100);
+        let snap = SimSnapshot::default();
+        enc.encode(0, &snap);
+        enc.force_keyframe();
+        let (_, ptype, _) = decode_packet(&enc.encode(1, &snap)).unwrap();
+        assert_eq!(ptype, PacketType::Keyframe);
+    }
+
+    #[test]
+    fn truncated_delta_rejected() {
+        let mut enc = DeltaEncoder::new(10);
+        let mut dec = DeltaDecoder::new();
+        let mut snap = SimSnapshot::default();
+        decode_into(&mut dec, &enc.encode(0, &snap)).unwrap();
+
+        snap.rpm = 2400.0;
+        let pkt = enc.encode(1, &snap);
+        let (hdr, ptype, payload) = decode_packet(&pkt).unwrap();
+        let short = &payload[..payload.len() - 1];
+        assert_eq!(
+            dec.decode(&hdr, ptype, short).unwrap_err(),
+            ProtocolError::TruncatedPayload {
+                field:    "rpm".into(),
+                offset:   DELTA_PREFIX_LEN,
+                expected: DELTA_PREFIX_LEN + 4,
+                actual:   DELTA_PREFIX_LEN + 3,
+            },
+        );
+    }
+}
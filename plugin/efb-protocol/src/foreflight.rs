@@ -0,0 +1,127 @@
+//! ForeFlight simulator protocol: plain-text datagrams on UDP port
+//! [`FOREFLIGHT_PORT`], understood by ForeFlight and a few other EFBs.
+//!
+//! Each datagram is one comma-separated message, prefixed with its type and
+//! the simulator name:
+//!
+//! ```text
+//! XGPS<sim>,<lon>,<lat>,<alt MSL m>,<track true>,<groundspeed m/s>
+//! XATT<sim>,<heading>,<pitch>,<roll>
+//! XTRAFFIC<sim>,<id>,<lat>,<lon>,<alt ft>,<vs fpm>,<airborne 0|1>,<track>,<speed kt>,<callsign>
+//! ```
+//!
+//! ForeFlight expects XGPS and XTRAFFIC once a second and XATT several
+//! times a second. The snapshot has magnetic heading only, so XATT carries
+//! it in place of the true heading.
+
+use dataref_schema::SimSnapshot;
+
+use crate::traffic::TrafficTarget;
+
+/// UDP port ForeFlight listens on.
+pub const FOREFLIGHT_PORT: u16 = 49002;
+
+/// Simulator name sent in every message.
+pub const SIM_NAME: &str = "X-Plane";
+
+const FT_PER_M: f64 = 3.280_84;
+
+/// Position, track and groundspeed.
+pub fn encode_xgps(snap: &SimSnapshot) -> String {
+    format!(
+        "XGPS{SIM_NAME},{:.7},{:.7},{:.1},{:.2},{:.1}",
+        snap.longitude,
+        snap.latitude,
+        snap.elevation_m,
+        snap.ground_track_deg.rem_euclid(360.0),
+        snap.groundspeed_ms,
+    )
+}
+
+/// Heading, pitch and roll.
+pub fn encode_xatt(snap: &SimSnapshot) -> String {
+    format!(
+        "XATT{SIM_NAME},{:.1},{:.1},{:.1}",
+        snap.mag_heading_deg.rem_euclid(360.0),
+        snap.pitch_deg,
+        snap.roll_deg,
+    )
+}
+
+/// One XTRAFFIC message per target.
+///
+/// Targets without an ICAO address are numbered from their index, as in
+/// the GDL 90 encoder, so EFBs keep them apart. Commas in callsigns would
+/// split the message and are dropped.
+pub fn encode_xtraffic(targets: &[TrafficTarget]) -> Vec<String> {
+    targets
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let id = match t.icao_address & 0x00FF_FFFF {
+                0 => i as u32 + 1,
+                a => a,
+            };
+            let callsign: String = t.callsign.chars().filter(|&c| c != ',').collect();
+            format!(
+                "XTRAFFIC{SIM_NAME},{id},{:.7},{:.7},{:.1},{:.1},{},{:.1},{:.1},{callsign}",
+                t.latitude,
+                t.longitude,
+                t.altitude_m * FT_PER_M,
+                t.vertical_speed_fpm,
+                !t.on_ground as u8,
+                t.track_deg.rem_euclid(360.0),
+                t.groundspeed_kt,
+            )
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gps_and_attitude_messages() {
+        let snap = SimSnapshot {
+            latitude:         -26.139_166_7,
+            longitude:        28.246_111_1,
+            elevation_m:      1_694.7,
+            ground_track_deg: -1.5,
+            groundspeed_ms:   61.73,
+            mag_heading_deg:  361.0,
+            pitch_deg:        2.54,
+            roll_deg:         -15.0,
+            ..SimSnapshot::default()
+        };
+        assert_eq!(encode_xgps(&snap), "XGPSX-Plane,28.2461111,-26.1391667,1694.7,358.50,61.7");
+        assert_eq!(encode_xatt(&snap), "XATTX-Plane,1.0,2.5,-15.0");
+    }
+
+    #[test]
+    fn traffic_messages() {
+        let targets = [
+            TrafficTarget {
+                icao_address:       0xA1B2C3,
+                callsign:           "SAA,335".into(),
+                latitude:           33.853_973_39,
+                longitude:          -118.324_867_1,
+                altitude_m:         1_143.0,
+                track_deg:          68.2,
+                groundspeed_kt:     126.0,
+                vertical_speed_fpm: -213.0,
+                ..TrafficTarget::default()
+            },
+            TrafficTarget { on_ground: true, ..TrafficTarget::default() },
+        ];
+        assert_eq!(
+            encode_xtraffic(&targets),
+            [
+                "XTRAFFICX-Plane,10597059,33.8539734,-118.3248671,3750.0,-213.0,1,68.2,126.0,SAA335",
+                "XTRAFFICX-Plane,2,0.0000000,0.0000000,0.0,0.0,0,0.0,0.0,",
+            ],
+        );
+    }
+}
@@ -0,0 +1,570 @@
+//! Fragmentation of payloads larger than the path MTU.
+//!
+//! Datagrams above ~1472 bytes are IP-fragmented or dropped outright by many
+//! Wi-Fi access points, so large messages are split into Fragment packets
+//! that each fit in one Ethernet frame. Each fragment is a normal framed
+//! packet with its own sequence number and CRC; the receiver collects them
+//! in a [`Reassembler`].
+//!
+//! Fragment payload layout (little-endian):
+//! ```text
+//! [0..4]  message_id : u32  header sequence of the message's first fragment
+//! [4..6]  index      : u16  0-based fragment index
+//! [6..8]  count      : u16  total fragments in the message
+//! [8]     inner_type : u8   PacketType of the reassembled message
+//! [9..]   chunk      : message payload bytes
+//! ```
+//!
+//! Fragments of a v2 packet are v2 themselves and carry its timestamps and
+//! flags, plus [`flags::FRAGMENTED`]. [`flags::COMPRESSED`] then applies to
+//! the reassembled message rather than to each chunk.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::{
+    build_packet, build_packet_v2, field_bytes, flags, PacketType, ProtocolError, Timestamps,
+    HEADER_LEN, HEADER_V2_LEN,
+};
+
+/// Largest UDP payload that fits a 1500-byte Ethernet frame without IP
+/// fragmentation (1500 − 20 IPv4 − 8 UDP).
+pub const DEFAULT_MTU: usize = 1472;
+
+/// Size of the fragment prefix preceding each chunk.
+pub const FRAGMENT_PREFIX_LEN: usize = 9;
+
+/// Default time a partially received message is kept before being dropped.
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default cap on bytes buffered across all partial messages, including the
+/// per-fragment bookkeeping of each message.
+pub const DEFAULT_REASSEMBLY_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default cap on partial messages from one peer.
+pub const DEFAULT_MAX_PENDING_PER_PEER: usize = 8;
+
+/// Default cap on partial messages across all peers.
+pub const DEFAULT_MAX_PENDING: usize = 64;
+
+/// Completed message ids remembered to drop late duplicate fragments.
+const COMPLETED_HISTORY: usize = 64;
+
+// ── Sender side ──────────────────────────────────────────────────────────────
+
+/// Frame `payload` as one datagram of type `ptype` if it fits in `mtu`,
+/// otherwise as a series of Fragment datagrams.
+///
+/// `next_seq` is called once per datagram produced; the first sequence number
+/// doubles as the message id. Returns [`ProtocolError::PayloadTooLarge`] if
+/// `payload` needs more than 65535 fragments at this MTU.
+pub fn fragment_payload(
+    ptype: PacketType,
+    payload: &[u8],
+    mtu: usize,
+    mut next_seq: impl FnMut() -> u32,
+) -> Result<Vec<Vec<u8>>, ProtocolError> {
+    fragment_with(None, ptype, payload, mtu, &mut next_seq)
+}
+
+/// [`fragment_payload`] with v1 headers (`ext` is `None`) or v2 headers
+/// carrying the given flags and timestamps.
+fn fragment_with(
+    ext: Option<(u8, Timestamps)>,
+    ptype: PacketType,
+    payload: &[u8],
+    mtu: usize,
+    next_seq: &mut dyn FnMut() -> u32,
+) -> Result<Vec<Vec<u8>>, ProtocolError> {
+    let hlen = if ext.is_some() { HEADER_V2_LEN } else { HEADER_LEN };
+    let build = |seq, ptype, flags_extra: u8, payload: &[u8]| match ext {
+        Some((flags, ts)) => build_packet_v2(seq, ptype, flags | flags_extra, ts, payload),
+        None => build_packet(seq, ptype, payload),
+    };
+    if hlen + payload.len() <= mtu && payload.len() <= crate::MAX_PAYLOAD_LEN {
+        return Ok(vec![build(next_seq(), ptype, 0, payload)]);
+    }
+
+    let chunk_len = mtu.saturating_sub(hlen + FRAGMENT_PREFIX_LEN).max(1);
+    let count = payload.len().div_ceil(chunk_len);
+    if count > u16::MAX as usize {
+        return Err(ProtocolError::PayloadTooLarge);
+    }
+
+    let mut message_id = None;
+    let fragments = payload
+        .chunks(chunk_len)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let seq = next_seq();
+            let id = *message_id.get_or_insert(seq);
+            let mut v = Vec::with_capacity(FRAGMENT_PREFIX_LEN + chunk.len());
+            v.extend_from_slice(&id.to_le_bytes());
+            v.extend_from_slice(&(index as u16).to_le_bytes());
+            v.extend_from_slice(&(count as u16).to_le_bytes());
+            v.push(ptype as u8);
+            v.extend_from_slice(chunk);
+            build(seq, PacketType::Fragment, flags::FRAGMENTED, &v)
+        })
+        .collect();
+    Ok(fragments)
+}
+
+/// Split an already framed datagram into Fragment datagrams if it exceeds
+/// `mtu`; datagrams that fit are returned unchanged. The original header
+/// sequence is replaced by fresh ones from `next_seq`; a v2 header's flags
+/// and timestamps are kept.
+///
+/// Fails if `pkt` does not decode or needs more than 65535 fragments.
+pub fn fragment_packet(
+    pkt: &[u8],
+    mtu: usize,
+    mut next_seq: impl FnMut() -> u32,
+) -> Result<Vec<Vec<u8>>, ProtocolError> {
+    if pkt.len() <= mtu {
+        return Ok(vec![pkt.to_vec()]);
+    }
+    let (hdr, ptype, payload) = crate::decode_packet(pkt)?;
+    let ext = (hdr.version >= 2)
+        .then_some((hdr.flags, Timestamps { sim_time_us: hdr.sim_time_us, sent_us: hdr.sent_us }));
+    fragment_with(ext, ptype, payload, mtu, &mut next_seq)
+}
+
+// ── Receiver side ────────────────────────────────────────────────────────────
+
+struct Partial {
+    inner_type: u8,
+    chunks:     Vec<Option<Vec<u8>>>,
+    received:   usize,
+    /// Chunk bytes plus the slot table, as charged against the memory cap.
+    bytes:      usize,
+    first_seen: Instant,
+}
+
+/// Memory charged for the slot table of a message with `count` fragments.
+fn slot_bytes(count: usize) -> usize {
+    count * std::mem::size_of::<Option<Vec<u8>>>()
+}
+
+type MessageKey = (SocketAddr, u32);
+
+/// Collects Fragment payloads from any number of peers and yields complete
+/// messages.
+///
+/// Partial messages older than the timeout are dropped. When the memory cap
+/// or a limit on partial messages (per peer and in total) would be exceeded,
+/// the oldest partial messages are evicted first. Duplicate fragments are
+/// ignored.
+pub struct Reassembler {
+    timeout:      Duration,
+    max_bytes:    usize,
+    max_per_peer: usize,
+    max_pending:  usize,
+    pending:      HashMap<MessageKey, Partial>,
+    completed:    VecDeque<MessageKey>,
+    buffered:     usize,
+}
+
+impl Reassembler {
+    pub fn new(timeout: Duration, max_bytes: usize) -> Self {
+        Reassembler {
+            timeout,
+            max_bytes,
+            max_per_peer: DEFAULT_MAX_PENDING_PER_PEER,
+            max_pending: DEFAULT_MAX_PENDING,
+            pending: HashMap::new(),
+            completed: VecDeque::new(),
+            buffered: 0,
+        }
+    }
+
+    /// Limit partial messages to `per_peer` from any one peer and `total`
+    /// overall (both at least 1).
+    pub fn with_max_pending(mut self, per_peer: usize, total: usize) -> Self {
+        self.max_per_peer = per_peer.max(1);
+        self.max_pending = total.max(1);
+        self
+    }
+
+    /// Feed one Fragment payload received from `from`.
+    ///
+    /// Returns `Ok(Some((packet_type, payload)))` once the last missing
+    /// fragment of a message arrives, `Ok(None)` while it is still incomplete
+    /// or the fragment was a duplicate.
+    pub fn push(
+        &mut self,
+        from: SocketAddr,
+        payload: &[u8],
+        now: Instant,
+    ) -> Result<Option<(PacketType, Vec<u8>)>, ProtocolError> {
+        self.expire(now);
+
+        field_bytes(payload, 0, FRAGMENT_PREFIX_LEN, "fragment prefix")?;
+        let id         = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+        let index      = u16::from_le_bytes([payload[4], payload[5]]) as usize;
+        let count      = u16::from_le_bytes([payload[6], payload[7]]) as usize;
+        let inner_type = payload[8];
+        let chunk      = &payload[FRAGMENT_PREFIX_LEN..];
+
+        let ptype = PacketType::from_u8(inner_type)
+            .filter(|t| *t != PacketType::Fragment)
+            .ok_or(ProtocolError::UnknownPacketType(inner_type))?;
+        if count == 0 {
+            return Err(ProtocolError::MalformedFragment { offset: 6 });
+        }
+        if index >= count {
+            return Err(ProtocolError::MalformedFragment { offset: 4 });
+        }
+        // Only the last chunk can be short; an empty one elsewhere would
+        // buffer a message slot without buffering any data.
+        if chunk.is_empty() && index + 1 != count {
+            return Err(ProtocolError::MalformedFragment { offset: FRAGMENT_PREFIX_LEN });
+        }
+
+        let key = (from, id);
+        if self.completed.contains(&key) {
+            return Ok(None);
+        }
+        let is_new = !self.pending.contains_key(&key);
+        let slots = if is_new { slot_bytes(count) } else { 0 };
+        let cost = slots + chunk.len();
+        if cost > self.max_bytes {
+            return Err(ProtocolError::ReassemblyOverflow);
+        }
+        if is_new {
+            let from_peer = |k: &MessageKey| k.0 == from;
+            while self.pending.keys().filter(|k| from_peer(k)).count() >= self.max_per_peer
+                && self.evict_oldest(key, from_peer)
+            {}
+            while self.pending.len() >= self.max_pending && self.evict_oldest(key, |_| true) {}
+        }
+        while self.buffered + cost > self.max_bytes && self.evict_oldest(key, |_| true) {}
+        if self.buffered + cost > self.max_bytes {
+            return Err(ProtocolError::ReassemblyOverflow);
+        }
+
+        let partial = self.pending.entry(key).or_insert_with(|| Partial {
+            inner_type,
+            chunks: vec![None; count],
+            received: 0,
+            bytes: slots,
+            first_seen: now,
+        });
+        self.buffered += slots;
+        if partial.chunks.len() != count {
+            return Err(ProtocolError::MalformedFragment { offset: 6 });
+        }
+        if partial.inner_type != inner_type {
+            return Err(ProtocolError::MalformedFragment { offset: 8 });
+        }
+        if partial.chunks[index].is_some() {
+            return Ok(None); // duplicate
+        }
+        partial.chunks[index] = Some(chunk.to_vec());
+        partial.received += 1;
+        partial.bytes += chunk.len();
+        self.buffered += chunk.len();
+
+        if partial.received < count {
+            return Ok(None);
+        }
+
+        let partial = self.pending.remove(&key).unwrap();
+        self.buffered -= partial.bytes;
+        self.completed.push_back(key);
+        if self.completed.len() > COMPLETED_HISTORY {
+            self.completed.pop_front();
+        }
+        let message = partial.chunks.into_iter().flatten().flatten().collect();
+        Ok(Some((ptype, message)))
+    }
+
+    /// Drop partial messages whose first fragment arrived more than the
+    /// timeout before `now`.
+    pub fn expire(&mut self, now: Instant) {
+        let timeout = self.timeout;
+        let mut freed = 0;
+        self.pending.retain(|_, p| {
+            let keep = now.saturating_duration_since(p.first_seen) <= timeout;
+            if !keep {
+                freed += p.bytes;
+            }
+            keep
+        });
+        self.buffered -= freed;
+    }
+
+    /// Number of messages currently awaiting fragments.
+    pub fn pending_messages(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Bytes currently buffered across all partial messages, including their
+    /// slot tables.
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered
+    }
+
+    /// Evict the oldest partial message other than `keep` whose key matches
+    /// `filter`. Returns `false` if there was nothing to evict.
+    fn evict_oldest(&mut self, keep: MessageKey, filter: impl Fn(&MessageKey) -> bool) -> bool {
+        let oldest = self
+            .pending
+            .iter()
+            .filter(|(k, _)| **k != keep && filter(k))
+            .min_by_key(|(_, p)| p.first_seen)
+            .map(|(k, _)| *k);
+        match oldest.and_then(|k| self.pending.remove(&k)) {
+            Some(p) => {
+                self.buffered -= p.bytes;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_REASSEMBLY_TIMEOUT, DEFAULT_REASSEMBLY_MAX_BYTES)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_packet;
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:5000".parse().unwrap()
+    }
+
+    fn counter() -> impl FnMut() -> u32 {
+        let mut n = 100;
+        move || { n += 1; n }
+    }
+
+    fn big_payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn fragment_payloads(pkts: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        pkts.iter()
+            .map(|p| {
+                let (_, ptype, payload) = decode_packet(p).unwrap();
+                assert_eq!(ptype, PacketType::Fragment);
+                payload.to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn small_payload_is_not_fragmented() {
+        let pkts = fragment_payload(PacketType::CommandJson, b"{}", DEFAULT_MTU, counter()).unwrap();
+        assert_eq!(pkts.len(), 1);
+        assert_eq!(decode_packet(&pkts[0]).unwrap().1, PacketType::CommandJson);
+    }
+
+    #[test]
+    fn fragments_fit_mtu_and_reassemble_out_of_order() {
+        let payload = big_payload(5000);
+        let pkts = fragment_payload(PacketType::CommandJson, &payload, DEFAULT_MTU, counter()).unwrap();
+        assert_eq!(pkts.len(), 4);
+        assert!(pkts.iter().all(|p| p.len() <= DEFAULT_MTU));
+
+        let mut frags = fragment_payloads(&pkts);
+        frags.reverse();
+        let mut r = Reassembler::default();
+        let now = Instant::now();
+        for f in &frags[..3] {
+            assert_eq!(r.push(addr(), f, now).unwrap(), None);
+        }
+        assert_eq!(r.pending_messages(), 1);
+        let (ptype, out) = r.push(addr(), &frags[3], now).unwrap().unwrap();
+        assert_eq!(ptype, PacketType::CommandJson);
+        assert_eq!(out, payload);
+        assert_eq!(r.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn duplicates_are_ignored() {
+        let payload = big_payload(3000);
+        let frags = fragment_payloads(&fragment_payload(PacketType::CommandJson, &payload, 1000, counter()).unwrap());
+        let mut r = Reassembler::default();
+        let now = Instant::now();
+
+        assert_eq!(r.push(addr(), &frags[0], now).unwrap(), None);
+        assert_eq!(r.push(addr(), &frags[0], now).unwrap(), None);
+        for f in &frags[1..frags.len() - 1] {
+            r.push(addr(), f, now).unwrap();
+        }
+        let done = r.push(addr(), frags.last().unwrap(), now).unwrap();
+        assert_eq!(done.unwrap().1, payload);
+
+        // A late duplicate of a completed message does not start a new one.
+        assert_eq!(r.push(addr(), &frags[1], now).unwrap(), None);
+        assert_eq!(r.pending_messages(), 0);
+    }
+
+    #[test]
+    fn incomplete_message_times_out() {
+        let frags = fragment_payloads(&fragment_payload(PacketType::CommandJson, &big_payload(3000), 1000, counter()).unwrap());
+        let mut r = Reassembler::new(Duration::from_millis(500), DEFAULT_REASSEMBLY_MAX_BYTES);
+        let t0 = Instant::now();
+        r.push(addr(), &frags[0], t0).unwrap();
+        assert_eq!(r.pending_messages(), 1);
+
+        r.expire(t0 + Duration::from_secs(1));
+        assert_eq!(r.pending_messages(), 0);
+        assert_eq!(r.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn memory_cap_evicts_oldest_message() {
+        let mut seq = counter();
+        let a = fragment_payloads(&fragment_payload(PacketType::CommandJson, &big_payload(3000), 1000, &mut seq).unwrap());
+        let b = fragment_payloads(&fragment_payload(PacketType::CommandJson, &big_payload(3000), 1000, &mut seq).unwrap());
+        let mut r = Reassembler::new(DEFAULT_REASSEMBLY_TIMEOUT, 1500);
+        let t0 = Instant::now();
+
+        r.push(addr(), &a[0], t0).unwrap();
+        r.push(addr(), &b[0], t0 + Duration::from_millis(1)).unwrap();
+        assert_eq!(r.pending_messages(), 1, "oldest message should be evicted");
+        assert!(r.buffered_bytes() <= 1500);
+    }
+
+    #[test]
+    fn fragment_packet_splits_framed_datagram() {
+        let big = build_packet(7, PacketType::Schema, &big_payload(2000));
+        let pkts = fragment_packet(&big, DEFAULT_MTU, counter()).unwrap();
+        assert_eq!(pkts.len(), 2);
+
+        let mut r = Reassembler::default();
+        let now = Instant::now();
+        let mut out = None;
+        for f in fragment_payloads(&pkts) {
+            out = r.push(addr(), &f, now).unwrap();
+        }
+        let (ptype, payload) = out.unwrap();
+        assert_eq!(ptype, PacketType::Schema);
+        assert_eq!(payload, big_payload(2000));
+    }
+
+    #[test]
+    fn v2_fragments_keep_flags_and_timestamps() {
+        let ts = Timestamps { sim_time_us: 7, sent_us: 8 };
+        let schema = build_packet(7, PacketType::Schema, &big_payload(40_000));
+        let v2 = crate::upgrade_packet(&schema, ts, true).unwrap();
+        let pkts = fragment_packet(&v2, 600, counter()).unwrap();
+        assert!(pkts.len() > 1);
+
+        let mut r = Reassembler::default();
+        let mut out = None;
+        for p in &pkts {
+            assert!(p.len() <= 600);
+            let (hdr, ptype, payload) = decode_packet(p).unwrap();
+            assert_eq!(ptype, PacketType::Fragment);
+            assert!(hdr.has(flags::FRAGMENTED | flags::COMPRESSED));
+            assert_eq!({ hdr.sent_us }, 8);
+            out = r.push(addr(), payload, Instant::now()).unwrap().map(|m| (hdr, m));
+        }
+        let (hdr, (ptype, message)) = out.unwrap();
+        assert_eq!(ptype, PacketType::Schema);
+        assert_eq!(crate::inflate_payload(&hdr, &message).unwrap().as_ref(), big_payload(40_000));
+    }
+
+    #[test]
+    fn malformed_fragments_rejected() {
+        let mut r = Reassembler::default();
+        let now = Instant::now();
+        assert_eq!(
+            r.push(addr(), &[0; 4], now).unwrap_err(),
+            ProtocolError::TruncatedPayload {
+                field:    "fragment prefix".into(),
+                offset:   0,
+                expected: FRAGMENT_PREFIX_LEN,
+                actual:   4,
+            },
+        );
+        // index 3 of count 2
+        let bad = [1, 0, 0, 0, 3, 0, 2, 0, 0x02, 0xAA];
+        assert_eq!(r.push(addr(), &bad, now).unwrap_err(), ProtocolError::MalformedFragment { offset: 4 });
+        // count 0
+        let bad = [1, 0, 0, 0, 0, 0, 0, 0, 0x02, 0xAA];
+        assert_eq!(r.push(addr(), &bad, now).unwrap_err(), ProtocolError::MalformedFragment { offset: 6 });
+        // same message id with a different count, then a different type
+        r.push(addr(), &[2, 0, 0, 0, 0, 0, 2, 0, 0x02, 0xAA], now).unwrap();
+        let bad = [2, 0, 0, 0, 1, 0, 3, 0, 0x02, 0xAA];
+        assert_eq!(r.push(addr(), &bad, now).unwrap_err(), ProtocolError::MalformedFragment { offset: 6 });
+        let bad = [2, 0, 0, 0, 1, 0, 2, 0, 0x03, 0xAA];
+        assert_eq!(r.push(addr(), &bad, now).unwrap_err(), ProtocolError::MalformedFragment { offset: 8 });
+        // nested Fragment
+        let nested = [1, 0, 0, 0, 0, 0, 2, 0, PacketType::Fragment as u8, 0xAA];
+        assert!(matches!(r.push(addr(), &nested, now), Err(ProtocolError::UnknownPacketType(_))));
+    }
+
+    #[test]
+    fn oversized_payload_is_an_error() {
+        // 9-byte chunks at this MTU: 65536 of them would be needed.
+        let mtu = HEADER_LEN + FRAGMENT_PREFIX_LEN + 9;
+        let payload = vec![0; 9 * (u16::MAX as usize + 1)];
+        assert_eq!(
+            fragment_payload(PacketType::Schema, &payload, mtu, counter()),
+            Err(ProtocolError::PayloadTooLarge),
+        );
+    }
+
+    #[test]
+    fn slot_table_counts_against_memory_cap() {
+        let mut r = Reassembler::new(DEFAULT_REASSEMBLY_TIMEOUT, 64 * 1024);
+        let now = Instant::now();
+        // Last fragment of a 65535-fragment message: one byte of data but a
+        // slot table far larger than the cap.
+        let huge = [1, 0, 0, 0, 0xFE, 0xFF, 0xFF, 0xFF, PacketType::Schema as u8, 0xAA];
+        assert_eq!(r.push(addr(), &huge, now).unwrap_err(), ProtocolError::ReassemblyOverflow);
+        assert_eq!(r.pending_messages(), 0);
+
+        let small = [2, 0, 0, 0, 0, 0, 4, 0, PacketType::Schema as u8, 0xAA];
+        r.push(addr(), &small, now).unwrap();
+        assert_eq!(r.buffered_bytes(), 1 + slot_bytes(4));
+    }
+
+    #[test]
+    fn empty_chunk_only_allowed_last() {
+        let mut r = Reassembler::default();
+        let now = Instant::now();
+        let first = [1, 0, 0, 0, 0, 0, 0xFF, 0xFF, PacketType::Schema as u8];
+        assert_eq!(
+            r.push(addr(), &first, now).unwrap_err(),
+            ProtocolError::MalformedFragment { offset: FRAGMENT_PREFIX_LEN },
+        );
+        let last = [1, 0, 0, 0, 1, 0, 2, 0, PacketType::Schema as u8];
+        assert_eq!(r.push(addr(), &last, now).unwrap(), None);
+    }
+
+    #[test]
+    fn pending_messages_limited_per_peer_and_in_total() {
+        let fragment = |id: u8| [id, 0, 0, 0, 0, 0, 2, 0, PacketType::Schema as u8, 0xAA];
+        let peer = |port| SocketAddr::from(([127, 0, 0, 1], port));
+        let mut r = Reassembler::default().with_max_pending(2, 3);
+        let t0 = Instant::now();
+
+        for id in 0..4 {
+            r.push(peer(1), &fragment(id), t0 + Duration::from_millis(id as u64)).unwrap();
+        }
+        assert_eq!(r.pending_messages(), 2, "one peer keeps at most two messages");
+
+        for port in 2..6 {
+            r.push(peer(port), &fragment(0), t0 + Duration::from_millis(10 + port as u64)).unwrap();
+        }
+        assert_eq!(r.pending_messages(), 3);
+
+        // The newest message of the first peer was evicted by the cap, so
+        // completing it starts over.
+        let last = [3, 0, 0, 0, 1, 0, 2, 0, PacketType::Schema as u8, 0xBB];
+        assert_eq!(r.push(peer(1), &last, t0).unwrap(), None);
+    }
+}
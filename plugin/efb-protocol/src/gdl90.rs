@@ -0,0 +1,381 @@
+//! GDL 90 encoder, for third-party EFBs (ForeFlight, Garmin Pilot, …).
+//!
+//! GDL 90 is the de-facto standard for ADS-B receivers talking to tablet
+//! EFBs over UDP port [`GDL90_PORT`]. Each message is framed as
+//!
+//! ```text
+//! 0x7E | message id | message data | CRC-16 (LSB first) | 0x7E
+//! ```
+//!
+//! with 0x7E and 0x7D inside the frame escaped as `0x7D, byte ^ 0x20`. The
+//! CRC is the CRC-16-CCITT of the id and data, before escaping. Multi-byte
+//! fields are big-endian, unlike the rest of this crate.
+//!
+//! Messages built here, each returned as one complete frame:
+//!
+//! | id   | message                     | built from                 |
+//! |------|-----------------------------|----------------------------|
+//! | 0x00 | Heartbeat                   | UTC time                   |
+//! | 0x0A | Ownship Report              | [`SimSnapshot`]            |
+//! | 0x0B | Ownship Geometric Altitude  | [`SimSnapshot`]            |
+//! | 0x14 | Traffic Report              | [`TrafficTarget`]          |
+//! | 0x65 | ForeFlight AHRS             | [`SimSnapshot`]            |
+//!
+//! The snapshot has no pressure altitude, so the Ownship Report carries the
+//! geometric altitude in its pressure-altitude field as well.
+
+use dataref_schema::SimSnapshot;
+
+use crate::traffic::{ThreatLevel, TrafficTarget};
+
+/// UDP port GDL 90 receivers listen on.
+pub const GDL90_PORT: u16 = 4000;
+
+/// Frame delimiter.
+const FLAG: u8 = 0x7E;
+/// Escape byte; the escaped byte follows XORed with 0x20.
+const ESCAPE: u8 = 0x7D;
+
+const MSG_HEARTBEAT: u8 = 0x00;
+const MSG_OWNSHIP: u8 = 0x0A;
+const MSG_GEO_ALTITUDE: u8 = 0x0B;
+const MSG_TRAFFIC: u8 = 0x14;
+const MSG_FOREFLIGHT: u8 = 0x65;
+const FOREFLIGHT_AHRS: u8 = 0x01;
+
+/// Address type of a target with an ICAO address.
+const ADDR_ICAO: u8 = 0;
+/// Address type of a target without one; see [`encode_traffic`].
+const ADDR_SELF_ASSIGNED: u8 = 1;
+/// Integrity and accuracy categories (NIC 11, NACp 11): the sim is exact.
+const NIC_NACP: u8 = 0xBB;
+/// Emitter category "light (< 15 500 lb)".
+const EMITTER_LIGHT: u8 = 1;
+/// Vertical figure of merit in the Geometric Altitude message, metres.
+const VFOM_M: u16 = 3;
+
+const FT_PER_M: f64 = 3.280_84;
+const KT_PER_MS: f32 = 1.943_84;
+
+// ── Framing ──────────────────────────────────────────────────────────────────
+
+static CRC16_TABLE: [u16; 256] = build_crc16_table();
+
+const fn build_crc16_table() -> [u16; 256] {
+    let mut t = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+            bit += 1;
+        }
+        t[i] = crc;
+        i += 1;
+    }
+    t
+}
+
+/// CRC-16-CCITT (polynomial 0x1021, initial value 0) as used by GDL 90.
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter()
+        .fold(0u16, |crc, &b| CRC16_TABLE[(crc >> 8) as usize] ^ (crc << 8) ^ b as u16)
+}
+
+/// Frame a message (id followed by data): append the CRC, escape and add
+/// the flag bytes.
+pub fn frame(message: &[u8]) -> Vec<u8> {
+    let crc = crc16(message).to_le_bytes();
+    let mut out = Vec::with_capacity(message.len() + 8);
+    out.push(FLAG);
+    for &b in message.iter().chain(&crc) {
+        if b == FLAG || b == ESCAPE {
+            out.extend_from_slice(&[ESCAPE, b ^ 0x20]);
+        } else {
+            out.push(b);
+        }
+    }
+    out.push(FLAG);
+    out
+}
+
+// ── Messages ─────────────────────────────────────────────────────────────────
+
+/// Heartbeat, to be sent once a second. `utc_seconds` is the time since
+/// 0000Z, or `None` if unknown.
+pub fn encode_heartbeat(utc_seconds: Option<u32>) -> Vec<u8> {
+    let ts = utc_seconds.map_or(0, |s| s % 86_400);
+    // Status 1: GPS position valid, UAT initialized.
+    let status1 = 0x81;
+    // Status 2: timestamp bit 16, UTC OK.
+    let status2 = (((ts >> 16) & 1) << 7) as u8 | utc_seconds.is_some() as u8;
+    let [ts_lo, ts_hi] = (ts as u16).to_le_bytes();
+    frame(&[MSG_HEARTBEAT, status1, status2, ts_lo, ts_hi, 0, 0])
+}
+
+/// Ownship Report. `callsign` is shown by some EFBs; up to 8 characters.
+pub fn encode_ownship(snap: &SimSnapshot, callsign: &str) -> Vec<u8> {
+    let gs_kt = snap.groundspeed_ms * KT_PER_MS;
+    frame(&report(MSG_OWNSHIP, &Report {
+        alert:     false,
+        addr_type: ADDR_ICAO,
+        address:   0,
+        latitude:  snap.latitude,
+        longitude: snap.longitude,
+        alt_ft:    snap.elevation_m * FT_PER_M,
+        // The snapshot has no on-ground flag; this is well above taxi speed.
+        airborne:  gs_kt > 40.0,
+        gs_kt,
+        vs_fpm:    snap.vvi_fpm,
+        track_deg: snap.ground_track_deg,
+        callsign,
+    }))
+}
+
+/// Ownship Geometric Altitude (height above WGS-84, 5 ft resolution).
+pub fn encode_geo_altitude(snap: &SimSnapshot) -> Vec<u8> {
+    let alt = (snap.elevation_m * FT_PER_M / 5.0).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    let [a0, a1] = alt.to_be_bytes();
+    let [m0, m1] = VFOM_M.to_be_bytes();
+    frame(&[MSG_GEO_ALTITUDE, a0, a1, m0, m1])
+}
+
+/// Traffic Reports for `targets`, one frame each.
+///
+/// Targets without an ICAO address get a self-assigned one from their index,
+/// so EFBs keep them apart. Traffic and resolution advisories set the
+/// report's traffic alert bit.
+pub fn encode_traffic(targets: &[TrafficTarget]) -> Vec<Vec<u8>> {
+    targets
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let (addr_type, address) = match t.icao_address & 0x00FF_FFFF {
+                0 => (ADDR_SELF_ASSIGNED, i as u32 + 1),
+                a => (ADDR_ICAO, a),
+            };
+            frame(&report(MSG_TRAFFIC, &Report {
+                alert:     t.threat >= ThreatLevel::TrafficAdvisory,
+                addr_type,
+                address,
+                latitude:  t.latitude,
+                longitude: t.longitude,
+                alt_ft:    t.altitude_m * FT_PER_M,
+                airborne:  !t.on_ground,
+                gs_kt:     t.groundspeed_kt,
+                vs_fpm:    t.vertical_speed_fpm,
+                track_deg: t.track_deg,
+                callsign:  &t.callsign,
+            }))
+        })
+        .collect()
+}
+
+/// ForeFlight AHRS message: attitude, magnetic heading and airspeeds.
+pub fn encode_ahrs(snap: &SimSnapshot) -> Vec<u8> {
+    let tenths = |deg: f32, max: f32| {
+        if deg.is_finite() { (deg.clamp(-max, max) * 10.0).round() as i16 } else { 0x7FFF }
+    };
+    let roll = tenths(snap.roll_deg, 180.0);
+    let pitch = tenths(snap.pitch_deg, 90.0);
+    // Bit 15 set: magnetic heading.
+    let heading = if snap.mag_heading_deg.is_finite() {
+        0x8000 | ((snap.mag_heading_deg.rem_euclid(360.0) * 10.0).round() as u16 % 3600)
+    } else {
+        0xFFFF
+    };
+    let knots = |kt: f32| if kt.is_finite() { kt.round().clamp(0.0, 65_534.0) as u16 } else { 0xFFFF };
+
+    let mut m = vec![MSG_FOREFLIGHT, FOREFLIGHT_AHRS];
+    m.extend_from_slice(&roll.to_be_bytes());
+    m.extend_from_slice(&pitch.to_be_bytes());
+    m.extend_from_slice(&heading.to_be_bytes());
+    m.extend_from_slice(&knots(snap.ias_kts).to_be_bytes());
+    m.extend_from_slice(&knots(snap.tas_kts).to_be_bytes());
+    frame(&m)
+}
+
+// ── Ownship and Traffic Report body ──────────────────────────────────────────
+
+struct Report<'a> {
+    alert:     bool,
+    addr_type: u8,
+    address:   u32,
+    latitude:  f64,
+    longitude: f64,
+    alt_ft:    f64,
+    airborne:  bool,
+    gs_kt:     f32,
+    vs_fpm:    f32,
+    track_deg: f32,
+    callsign:  &'a str,
+}
+
+/// The 28-byte Ownship/Traffic Report message (id included).
+fn report(id: u8, r: &Report) -> Vec<u8> {
+    // Signed 24-bit fraction of a half circle.
+    let semicircles = |deg: f64| {
+        let v = if deg.is_finite() { (deg * f64::from(1 << 23) / 180.0).round() as i32 } else { 0 };
+        (v as u32 & 0x00FF_FFFF).to_be_bytes()
+    };
+    let alt = if r.alt_ft.is_finite() {
+        ((r.alt_ft + 1000.0) / 25.0).round().clamp(0.0, 0xFFE as f64) as u16
+    } else {
+        0xFFF
+    };
+    // Misc: airborne, true track angle.
+    let misc = (r.airborne as u8) << 3 | 0b01;
+    let h_vel = if r.gs_kt.is_finite() { r.gs_kt.round().clamp(0.0, 0xFFE as f32) as u16 } else { 0xFFF };
+    let v_vel = if r.vs_fpm.is_finite() {
+        ((r.vs_fpm / 64.0).round().clamp(-510.0, 510.0) as i16 as u16) & 0xFFF
+    } else {
+        0x800
+    };
+    let track = if r.track_deg.is_finite() {
+        ((r.track_deg.rem_euclid(360.0) * 256.0 / 360.0).round() as u32 % 256) as u8
+    } else {
+        0
+    };
+
+    let mut m = Vec::with_capacity(28);
+    m.push(id);
+    m.push((r.alert as u8) << 4 | r.addr_type);
+    m.extend_from_slice(&r.address.to_be_bytes()[1..]);
+    m.extend_from_slice(&semicircles(r.latitude)[1..]);
+    m.extend_from_slice(&semicircles(r.longitude)[1..]);
+    m.push((alt >> 4) as u8);
+    m.push(((alt & 0xF) as u8) << 4 | misc);
+    m.push(NIC_NACP);
+    m.push((h_vel >> 4) as u8);
+    m.push(((h_vel & 0xF) as u8) << 4 | (v_vel >> 8) as u8);
+    m.push(v_vel as u8);
+    m.push(track);
+    m.push(EMITTER_LIGHT);
+    m.extend_from_slice(&callsign(r.callsign));
+    m.push(0); // emergency/priority code: none
+    m
+}
+
+/// Callsign field: up to 8 of `0-9`, `A-Z`, space-padded.
+fn callsign(s: &str) -> [u8; 8] {
+    let mut out = [b' '; 8];
+    let chars = s.bytes().filter(u8::is_ascii_alphanumeric).map(|b| b.to_ascii_uppercase());
+    for (slot, b) in out.iter_mut().zip(chars) {
+        *slot = b;
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strip flags and escaping and check the CRC; returns id + data.
+    fn unframe(frame: &[u8]) -> Vec<u8> {
+        assert_eq!((frame[0], frame[frame.len() - 1]), (FLAG, FLAG));
+        let mut out = Vec::new();
+        let mut bytes = frame[1..frame.len() - 1].iter();
+        while let Some(&b) = bytes.next() {
+            assert_ne!(b, FLAG);
+            out.push(if b == ESCAPE { bytes.next().unwrap() ^ 0x20 } else { b });
+        }
+        let crc = out.split_off(out.len() - 2);
+        assert_eq!(crc16(&out).to_le_bytes(), crc[..]);
+        out
+    }
+
+    fn snapshot() -> SimSnapshot {
+        SimSnapshot {
+            latitude:         -26.139_2,
+            longitude:        28.246_2,
+            elevation_m:      1_524.0, // 5000 ft
+            groundspeed_ms:   61.733,  // 120 kt
+            vvi_fpm:          -640.0,
+            ground_track_deg: 270.0,
+            roll_deg:         -15.25,
+            pitch_deg:        2.5,
+            mag_heading_deg:  359.96,
+            ias_kts:          110.4,
+            tas_kts:          120.6,
+            ..SimSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn heartbeat_matches_spec_example() {
+        // GDL 90 ICD §2.2.3: 7E 00 81 41 DB D0 08 02 B3 8B 7E
+        assert_eq!(
+            frame(&[0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02]),
+            [0x7E, 0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02, 0xB3, 0x8B, 0x7E],
+        );
+        let hb = unframe(&encode_heartbeat(Some(86_399)));
+        assert_eq!(hb, [MSG_HEARTBEAT, 0x81, 0x81, 0x7F, 0x51, 0, 0]);
+        assert_eq!(unframe(&encode_heartbeat(None))[2], 0);
+    }
+
+    #[test]
+    fn flag_and_escape_bytes_are_stuffed() {
+        let f = frame(&[0x14, FLAG, ESCAPE, 0x20]);
+        assert_eq!(&f[..6], [FLAG, 0x14, ESCAPE, 0x5E, ESCAPE, 0x5D]);
+        assert_eq!(f.iter().filter(|&&b| b == FLAG).count(), 2);
+        assert_eq!(unframe(&f), [0x14, FLAG, ESCAPE, 0x20]);
+    }
+
+    #[test]
+    fn ownship_report_fields() {
+        let m = unframe(&encode_ownship(&snapshot(), "zs-abc"));
+        assert_eq!(m.len(), 28);
+        assert_eq!(m[0], MSG_OWNSHIP);
+        let i24 = |b: &[u8]| (i32::from_be_bytes([b[0], b[1], b[2], 0]) >> 8) as f64;
+        assert!((i24(&m[5..8]) * 180.0 / f64::from(1 << 23) - -26.139_2).abs() < 180.0 / f64::from(1 << 23));
+        assert!((i24(&m[8..11]) * 180.0 / f64::from(1 << 23) - 28.246_2).abs() < 180.0 / f64::from(1 << 23));
+        // 5000 ft → (5000 + 1000) / 25 = 240; airborne, true track.
+        assert_eq!(u16::from_be_bytes([m[11], m[12]]) >> 4, 240);
+        assert_eq!(m[12] & 0x0F, 0b1001);
+        // 120 kt, -640 fpm → -10 in 64 fpm units.
+        assert_eq!(u16::from_be_bytes([m[14], m[15]]) >> 4, 120);
+        assert_eq!(u16::from_be_bytes([m[15], m[16]]) & 0xFFF, (-10i16 as u16) & 0xFFF);
+        assert_eq!(m[17], 192); // 270°
+        assert_eq!(&m[19..27], b"ZSABC   ");
+    }
+
+    #[test]
+    fn geo_altitude_and_ahrs() {
+        let g = unframe(&encode_geo_altitude(&snapshot()));
+        assert_eq!(g, [MSG_GEO_ALTITUDE, 0x03, 0xE8, 0x00, 0x03]); // 1000 × 5 ft
+
+        let a = unframe(&encode_ahrs(&snapshot()));
+        assert_eq!(a.len(), 12);
+        assert_eq!(a[..2], [MSG_FOREFLIGHT, FOREFLIGHT_AHRS]);
+        let i16_at = |i: usize| i16::from_be_bytes([a[i], a[i + 1]]);
+        assert_eq!((i16_at(2), i16_at(4)), (-153, 25));
+        assert_eq!(u16::from_be_bytes([a[6], a[7]]), 0x8000); // 359.96° rounds to 0, magnetic
+        assert_eq!((i16_at(8), i16_at(10)), (110, 121));
+
+        let unknown = SimSnapshot { roll_deg: f32::NAN, ias_kts: f32::INFINITY, ..snapshot() };
+        let a = unframe(&encode_ahrs(&unknown));
+        assert_eq!((&a[2..4], &a[8..10]), (&[0x7F, 0xFF][..], &[0xFF, 0xFF][..]));
+    }
+
+    #[test]
+    fn traffic_reports_address_and_alert() {
+        let targets = [
+            TrafficTarget {
+                icao_address: 0x8A2C41,
+                callsign:     "SAA335".into(),
+                threat:       ThreatLevel::TrafficAdvisory,
+                on_ground:    true,
+                ..TrafficTarget::default()
+            },
+            TrafficTarget { threat: ThreatLevel::Proximate, ..TrafficTarget::default() },
+        ];
+        let frames: Vec<_> = encode_traffic(&targets).iter().map(|f| unframe(f)).collect();
+        assert_eq!(frames[0][..5], [MSG_TRAFFIC, 0x10 | ADDR_ICAO, 0x8A, 0x2C, 0x41]);
+        assert_eq!(frames[0][12] & 0x08, 0); // on ground
+        assert_eq!(&frames[0][19..27], b"SAA335  ");
+        assert_eq!(frames[1][..5], [MSG_TRAFFIC, ADDR_SELF_ASSIGNED, 0, 0, 2]);
+    }
+}
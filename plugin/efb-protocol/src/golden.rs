@@ -0,0 +1,629 @@
+//! Golden test vectors shared with the Kotlin codec.
+//!
+//! [`corpus`] builds a fixed set of datagrams: every [`PacketType`], v2 and
+//! compressed variants, snapshots with edge values (NaN, infinities, negative
+//! coordinates, full traffic arrays, markers set) and deliberately corrupted
+//! packets. [`write_corpus`] stores each as `<name>.bin` beside a
+//! `manifest.json` recording what a conforming decoder must make of it:
+//!
+//! ```text
+//! { "corpus_version": 1, "protocol_version": 2, "layout_checksum": …,
+//!   "pairing_code": "…",
+//!   "vectors": [
+//!     { "name", "file", "description", "expect": "ok",
+//!       "header": { version, packet_type, flags, sequence, … },
+//!       "payload": decoded payload, null for empty ones },
+//!     { "name", "file", "description", "expect": "error",
+//!       "stage": "packet" | "payload", "error": "BadChecksum" } ] }
+//! ```
+//!
+//! Vectors are decoded in manifest order by one receiver, so a Delta or the
+//! last Fragment of a message depends on earlier vectors. A Group payload
+//! lists only the fields of its group. Non-finite floats appear in payloads
+//! as the strings `NaN`, `Infinity` and `-Infinity`. Authenticated vectors
+//! are signed by the tablet with a fixed, long-past stamp, so only their tag
+//! is checked, not their freshness.
+//!
+//! The corpus is checked in under `efb-protocol/testdata/golden/`; rebuild it
+//! with `cargo run -p efb-protocol --bin efb-golden` and bump
+//! [`CORPUS_VERSION`] whenever an existing vector changes.
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::time::Instant;
+
+use dataref_schema::{SimSnapshot, SNAPSHOT_LAYOUT_CHECKSUM};
+use serde_json::{json, Value};
+
+use crate::auth::{sign_packet, verify_packet};
+use crate::beacon::{decode_beacon, encode_beacon};
+use crate::command::{
+    decode_command_result, decode_request, decode_request_json, encode_command_result, encode_request,
+    encode_request_json,
+};
+use crate::handshake::{decode_hello, decode_hello_ack, encode_hello, encode_hello_ack};
+use crate::schema::{decode_schema, encode_schema};
+use crate::traffic::{decode_traffic, encode_traffic};
+use crate::{
+    build_packet, build_packet_v2, caps, decode_packet, encode_ack, encode_group, encode_sim_data, flags,
+    fragment_packet, inflate_payload, snapshot_truncated, upgrade_packet, Beacon, Command, CommandRequest, CommandResult, CommandStatus, DeltaDecoder,
+    DeltaEncoder, FieldGroup, FieldValue, GroupDecoder, Hello, HelloAck, PacketHeader, PacketType, PairingKey,
+    ProtocolError, Radio, Reassembler, Schema, Signer, ThreatLevel, Timestamps, TrafficTarget, VersionRange, HEADER_LEN,
+    PROTOCOL_VERSION,
+};
+
+/// Bumped whenever an existing vector changes; adding vectors does not.
+pub const CORPUS_VERSION: u32 = 4;
+
+/// Pairing code the authenticated vectors are signed with.
+pub const PAIRING_CODE: &str = "GOLDEN-1";
+
+/// Stamp in the trailer of the authenticated vectors (2023-11-14, in µs).
+const AUTH_STAMP: u64 = 1_700_000_000_000_000;
+
+/// Where the receiver pretends fragments come from.
+const PEER: SocketAddr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 49100);
+
+// ── Vector ───────────────────────────────────────────────────────────────────
+
+/// Decoding step that rejects a corrupted vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// `decode_packet`: header, length or CRC.
+    Packet,
+    /// The packet type's payload decoder (including inflate and auth).
+    Payload,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Packet  => "packet",
+            Self::Payload => "payload",
+        }
+    }
+}
+
+/// One golden datagram.
+#[derive(Debug)]
+pub struct Vector {
+    pub name:        &'static str,
+    pub description: &'static str,
+    pub bytes:       Vec<u8>,
+    /// Error a decoder must report, `None` for a valid packet.
+    pub error:       Option<(Stage, ProtocolError)>,
+}
+
+fn ok(name: &'static str, description: &'static str, bytes: Vec<u8>) -> Vector {
+    Vector { name, description, bytes, error: None }
+}
+
+fn bad(name: &'static str, description: &'static str, bytes: Vec<u8>, stage: Stage, e: ProtocolError) -> Vector {
+    Vector { name, description, bytes, error: Some((stage, e)) }
+}
+
+// ── Corpus ───────────────────────────────────────────────────────────────────
+
+/// Negative coordinates, extreme but finite values, all markers set and full
+/// traffic arrays.
+fn extreme_snapshot() -> SimSnapshot {
+    SimSnapshot {
+        latitude:         -34.8222,
+        longitude:        -58.5358,
+        elevation_m:      -411.0,
+        pitch_deg:        -0.0,
+        roll_deg:         -60.0,
+        vvi_fpm:          -6000.0,
+        oat_degc:         -56.5,
+        fuel_qty_kg:      [f32::MAX, f32::MIN_POSITIVE],
+        egt_degc:         [1500.0, -1.0, 0.5, 1e-6, 750.25, f32::MIN],
+        ap_state_flags:   -1,
+        com1_active_hz:   136_975_000,
+        nav1_active_hz:   108_000_000,
+        transponder_code: 7700,
+        transponder_mode: 3,
+        outer_marker:     true,
+        middle_marker:    true,
+        inner_marker:     true,
+        traffic_lat:      std::array::from_fn(|i| -34.8 - i as f32 * 0.01),
+        traffic_lon:      std::array::from_fn(|i| -58.5 + i as f32 * 0.01),
+        traffic_ele_m:    std::array::from_fn(|i| i as f32 * 150.0),
+        traffic_count:    20,
+        hsi_source:       i32::MIN,
+        ..SimSnapshot::default()
+    }
+}
+
+/// NaN and infinities in both float widths, as X-Plane reports for
+/// unavailable values.
+fn non_finite_snapshot() -> SimSnapshot {
+    SimSnapshot {
+        latitude:      f64::NAN,
+        longitude:     f64::INFINITY,
+        elevation_m:   f64::NEG_INFINITY,
+        nav1_hdef_dot: f32::NAN,
+        nav1_vdef_dot: f32::NAN,
+        gps_dist_nm:   f32::INFINITY,
+        fd_pitch_deg:  f32::NEG_INFINITY,
+        ..SimSnapshot::default()
+    }
+}
+
+/// Build the corpus, in manifest order.
+pub fn corpus() -> Vec<Vector> {
+    let ts = Timestamps { sim_time_us: 3_723_500_000, sent_us: 86_400_000_123 };
+    let key = PairingKey::from_code(PAIRING_CODE);
+    let base = encode_sim_data(1, &SimSnapshot::default());
+    let corrupt = |f: &dyn Fn(&mut Vec<u8>)| {
+        let mut pkt = base.clone();
+        f(&mut pkt);
+        pkt
+    };
+
+    let mut deltas = DeltaEncoder::new(2);
+    let keyframe = deltas.encode(10, &extreme_snapshot());
+    let delta = deltas.encode(11, &SimSnapshot { ias_kts: 142.0, ..extreme_snapshot() });
+    let mut orphan = DeltaEncoder::new(2);
+    orphan.encode(900, &SimSnapshot::default());
+    let orphan_delta = orphan.encode(901, &extreme_snapshot());
+
+    let mut seq = 30..;
+    let fragments = fragment_packet(&encode_sim_data(29, &extreme_snapshot()), 300, || seq.next().unwrap()).unwrap();
+    assert_eq!(fragments.len(), 2);
+
+    let mut signed = upgrade_packet(&encode_ack(44), ts, false).unwrap();
+    sign_packet(&mut signed, &key, Signer::Tablet, AUTH_STAMP);
+    let mut forged = signed.clone();
+    *forged.last_mut().unwrap() ^= 0x01;
+
+    let request = |id, command| CommandRequest { id: Some(id), command };
+
+    vec![
+        // ── Valid packets ──
+        ok("sim_data_default", "v1 SimData of SimSnapshot::default()",
+           base.clone()),
+        ok("sim_data_extremes", "negative lat/lon, extreme floats, markers set, full traffic arrays",
+           encode_sim_data(2, &extreme_snapshot())),
+        ok("sim_data_non_finite", "NaN and infinities in f64 and f32 fields",
+           encode_sim_data(3, &non_finite_snapshot())),
+        ok("sim_data_v2", "v2 header with timestamps, uncompressed",
+           upgrade_packet(&encode_sim_data(4, &extreme_snapshot()), ts, false).unwrap()),
+        ok("sim_data_v2_compressed", "v2 header, deflate-compressed payload",
+           upgrade_packet(&encode_sim_data(5, &extreme_snapshot()), ts, true).unwrap()),
+        ok("keyframe", "Keyframe, base for the next vector", keyframe),
+        ok("delta", "Delta against the keyframe vector (ias_kts changed)", delta),
+        ok("ack", "empty heartbeat ACK", encode_ack(20)),
+        ok("reload", "empty Reload request", build_packet(21, PacketType::Reload, &[])),
+        ok("hello", "Hello advertising this build's versions and capabilities",
+           encode_hello(22, &Hello::local())),
+        ok("hello_ack", "HelloAck choosing v2 with delta and compression",
+           encode_hello_ack(23, &HelloAck { version: 2, capabilities: caps::DELTA | caps::COMPRESSION })),
+        ok("schema", "Schema of the SimData layout", encode_schema(24, &Schema::local())),
+        ok("fragment_1_of_2", "first Fragment of the extreme SimData at MTU 300", fragments[0].clone()),
+        ok("fragment_2_of_2", "last Fragment, completing the message", fragments[1].clone()),
+        ok("command_json", "CommandJson set_dataref with id",
+           encode_request_json(40, &request(7, Command::SetDataref {
+               path: "sim/cockpit/autopilot/heading_mag".into(), value: -12.5,
+           }))),
+        ok("command_binary", "CommandBinary set_standby_freq with id",
+           encode_request(41, &request(8, Command::SetStandbyFreq { radio: Radio::Nav2, hz: 117_950_000 }))
+               .expect("no dataref path")),
+        ok("command_result", "CommandResult reporting an error with message",
+           encode_command_result(42, &CommandResult::error(Some(8), CommandStatus::UnsupportedRadio,
+               "NAV2 standby frequency not available"))),
+        ok("beacon", "discovery Beacon with non-ASCII hostname",
+           encode_beacon(43, &Beacon {
+               versions:       VersionRange::SUPPORTED,
+               capabilities:   caps::DELTA | caps::COMPRESSION,
+               stream_port:    49100,
+               command_port:   49101,
+               tcp_port:       0,
+               plugin_version: "0.1.0".into(),
+               hostname:       "Flugsimulator-Küche".into(),
+               aircraft_icao:  "C172".into(),
+           })),
+        ok("ack_v2_authenticated", "v2 ACK signed by the tablet with PAIRING_CODE", signed),
+        ok("group_fast", "Group of position, attitude and air data",
+           encode_group(45, FieldGroup::Fast, &extreme_snapshot())),
+        ok("group_traffic", "Group of full traffic arrays",
+           encode_group(46, FieldGroup::Traffic, &extreme_snapshot())),
+        ok("traffic", "Traffic with an airborne RA and a callsign-less target on the ground",
+           encode_traffic(47, &traffic_targets())),
+
+        // ── Corrupted packets ──
+        bad("too_short", "10 bytes, less than a header",
+            base[..10].to_vec(), Stage::Packet, ProtocolError::TooShort),
+        bad("bad_magic", "first magic byte flipped",
+            corrupt(&|p| p[0] ^= 0xFF), Stage::Packet, ProtocolError::BadMagic),
+        bad("bad_version", "header version 99",
+            corrupt(&|p| p[4..6].copy_from_slice(&99u16.to_le_bytes())), Stage::Packet, ProtocolError::BadVersion),
+        bad("unknown_type", "packet type 0x7F",
+            corrupt(&|p| p[6] = 0x7F), Stage::Packet, ProtocolError::UnknownPacketType(0x7F)),
+        bad("truncated_payload", "last payload byte missing",
+            base[..base.len() - 1].to_vec(), Stage::Packet, ProtocolError::TruncatedPayload {
+                field: "payload".into(), offset: HEADER_LEN, expected: base.len(), actual: base.len() - 1,
+            }),
+        bad("bad_checksum", "payload byte flipped after the CRC was computed",
+            corrupt(&|p| p[HEADER_LEN + 8] ^= 0x01), Stage::Packet, ProtocolError::BadChecksum),
+        bad("truncated_v2_header", "v2 packet cut inside the extended header",
+            upgrade_packet(&base, ts, false).unwrap()[..HEADER_LEN + 4].to_vec(),
+            Stage::Packet, ProtocolError::TooShort),
+        bad("short_sim_data", "valid header, SimData payload of 100 bytes",
+            build_packet(50, PacketType::SimData, &base[HEADER_LEN..HEADER_LEN + 100]),
+            Stage::Payload, snapshot_truncated(100)),
+        bad("delta_without_keyframe", "Delta against a keyframe that was never sent",
+            orphan_delta, Stage::Payload, ProtocolError::MissingKeyframe),
+        bad("bad_compression", "COMPRESSED flag on bytes that are not deflate",
+            build_packet_v2(51, PacketType::SimData, flags::COMPRESSED, ts, &[0xFF; 16]),
+            Stage::Payload, ProtocolError::BadCompression),
+        bad("malformed_command_json", "CommandJson cut off mid-object",
+            build_packet(52, PacketType::CommandJson, br#"{"cmd":"swap_freq","#),
+            Stage::Payload, ProtocolError::MalformedCommand { offset: 19 }),
+        bad("unknown_command", "CommandBinary opcode 0x7F",
+            build_packet(53, PacketType::CommandBinary, &[0x7F]),
+            Stage::Payload, ProtocolError::UnknownCommand(0x7F)),
+        bad("unknown_radio", "CommandBinary swap_freq for radio 9",
+            build_packet(54, PacketType::CommandBinary, &[0x02, 9]),
+            Stage::Payload, ProtocolError::UnknownRadio(9)),
+        bad("malformed_fragment", "Fragment with index 2 of 2",
+            build_packet(55, PacketType::Fragment, &[1, 0, 0, 0, 2, 0, 2, 0, 0x01, 0xAA]),
+            Stage::Payload, ProtocolError::MalformedFragment { offset: 4 }),
+        bad("bad_auth_tag", "authenticated ACK with the tag's last byte flipped",
+            forged, Stage::Payload, ProtocolError::BadAuth),
+        bad("unknown_group", "Group with group id 9",
+            build_packet(56, PacketType::Group, &[9]),
+            Stage::Payload, ProtocolError::UnknownGroup(9)),
+        bad("malformed_traffic", "Traffic target with threat level 7",
+            build_packet(57, PacketType::Traffic, &{
+                let mut p = encode_traffic(0, &traffic_targets()[..1])[HEADER_LEN..].to_vec();
+                p[1 + 41] = 7;
+                p
+            }),
+            Stage::Payload, ProtocolError::MalformedTraffic { offset: 1 + 41 }),
+    ]
+}
+
+fn traffic_targets() -> Vec<TrafficTarget> {
+    vec![
+        TrafficTarget {
+            icao_address:       0x00_8A_2C_41,
+            callsign:           "SAA335".into(),
+            latitude:           -26.133_692_412_345_67,
+            longitude:          28.242_317_987_654_32,
+            altitude_m:         1_812.375,
+            track_deg:          213.5,
+            groundspeed_kt:     182.25,
+            vertical_speed_fpm: -1_350.0,
+            on_ground:          false,
+            threat:             ThreatLevel::ResolutionAdvisory,
+        },
+        TrafficTarget {
+            icao_address:       0x00_8A_00_01,
+            callsign:           String::new(),
+            latitude:           -26.139_2,
+            longitude:          28.246_2,
+            altitude_m:         1_694.0,
+            track_deg:          30.0,
+            groundspeed_kt:     12.5,
+            vertical_speed_fpm: 0.0,
+            on_ground:          true,
+            threat:             ThreatLevel::Proximate,
+        },
+    ]
+}
+
+// ── Receiver ─────────────────────────────────────────────────────────────────
+
+/// Reference decoder for the corpus: decodes vectors in order into their
+/// manifest form, carrying keyframe and fragment state between them.
+pub struct Receiver {
+    deltas:    DeltaDecoder,
+    groups:    GroupDecoder,
+    fragments: Reassembler,
+    key:       PairingKey,
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Receiver {
+            deltas:    DeltaDecoder::new(),
+            groups:    GroupDecoder::new(),
+            fragments: Reassembler::default(),
+            key:       PairingKey::from_code(PAIRING_CODE),
+        }
+    }
+}
+
+impl Receiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode one datagram to `{"header": …, "payload": …}`.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Value, (Stage, ProtocolError)> {
+        let (hdr, ptype, payload) = decode_packet(bytes).map_err(|e| (Stage::Packet, e))?;
+        let payload = self.payload(bytes, &hdr, ptype, payload).map_err(|e| (Stage::Payload, e))?;
+        // Copy out of the packed header; json! takes references.
+        let (version, packet_type, flags, sequence) = (hdr.version, hdr.packet_type, hdr.flags, hdr.sequence);
+        let (sim_time_us, sent_us, payload_len) = (hdr.sim_time_us, hdr.sent_us, hdr.payload_len);
+        Ok(json!({
+            "header": {
+                "version":     version,
+                "packet_type": packet_type,
+                "flags":       flags,
+                "sequence":    sequence,
+                "sim_time_us": sim_time_us,
+                "sent_us":     sent_us,
+                "payload_len": payload_len,
+            },
+            "payload": payload,
+        }))
+    }
+
+    fn payload(&mut self, bytes: &[u8], hdr: &PacketHeader, ptype: PacketType, payload: &[u8])
+        -> Result<Value, ProtocolError>
+    {
+        if hdr.has(flags::AUTHENTICATED) {
+            verify_packet(bytes, &self.key, Signer::Tablet)?;
+        }
+        if ptype == PacketType::Fragment {
+            // COMPRESSED on a fragment applies to the reassembled message.
+            let done = self.fragments.push(PEER, payload, Instant::now())?;
+            let u16_at = |i: usize| u16::from_le_bytes([payload[i], payload[i + 1]]);
+            let fragment = json!({
+                "message_id":  u32::from_le_bytes(payload[..4].try_into().unwrap()),
+                "index":       u16_at(4),
+                "count":       u16_at(6),
+                "packet_type": payload[8],
+            });
+            return Ok(match done {
+                None => json!({ "fragment": fragment }),
+                Some((inner, message)) => {
+                    let message = inflate_payload(hdr, &message)?;
+                    json!({ "fragment": fragment, "message": self.message(hdr, inner, &message)? })
+                }
+            });
+        }
+        let payload = inflate_payload(hdr, payload)?;
+        self.message(hdr, ptype, &payload)
+    }
+
+    fn message(&mut self, hdr: &PacketHeader, ptype: PacketType, payload: &[u8]) -> Result<Value, ProtocolError> {
+        Ok(match ptype {
+            PacketType::SimData | PacketType::Keyframe | PacketType::Delta => {
+                snapshot_json(&self.deltas.decode(hdr, ptype, payload)?)
+            }
+            PacketType::Group => {
+                let group = crate::groups::peek_group(payload)?;
+                let merged = snapshot_json(&self.groups.decode(hdr, ptype, payload)?);
+                let fields = group.fields().map(|f| (f.name.to_string(), merged[f.name].clone())).collect();
+                json!({ "group": group as u8, "fields": Value::Object(fields) })
+            }
+            PacketType::Traffic => json!({ "targets": decode_traffic(payload)? }),
+            PacketType::Ack | PacketType::Reload => Value::Null,
+            PacketType::Hello => {
+                let h = decode_hello(payload)?;
+                json!({
+                    "min_version":  h.versions.min,
+                    "max_version":  h.versions.max,
+                    "capabilities": h.capabilities,
+                })
+            }
+            PacketType::HelloAck => {
+                let a = decode_hello_ack(payload)?;
+                json!({ "version": a.version, "capabilities": a.capabilities })
+            }
+            PacketType::Schema => {
+                let fields: Vec<Value> = decode_schema(payload)?
+                    .fields
+                    .iter()
+                    .map(|f| json!({
+                        "name":      f.name,
+                        "type":      f.ty as u8,
+                        "array_len": f.array_len,
+                        "offset":    f.offset,
+                        "unit":      f.unit,
+                    }))
+                    .collect();
+                json!({ "fields": fields })
+            }
+            PacketType::CommandJson => request_json(&decode_request_json(payload)?),
+            PacketType::CommandBinary => request_json(&decode_request(payload)?),
+            PacketType::CommandResult => {
+                let r = decode_command_result(payload)?;
+                json!({ "id": r.id, "status": r.status, "message": r.message })
+            }
+            PacketType::Beacon => {
+                let b = decode_beacon(payload)?;
+                json!({
+                    "min_version":    b.versions.min,
+                    "max_version":    b.versions.max,
+                    "capabilities":   b.capabilities,
+                    "stream_port":    b.stream_port,
+                    "command_port":   b.command_port,
+                    "tcp_port":       b.tcp_port,
+                    "plugin_version": b.plugin_version,
+                    "hostname":       b.hostname,
+                    "aircraft_icao":  b.aircraft_icao,
+                })
+            }
+            // Fragments never nest: the reassembler rejects them as inner type.
+            PacketType::Fragment => return Err(ProtocolError::MalformedFragment { offset: 0 }),
+        })
+    }
+}
+
+fn request_json(req: &CommandRequest) -> Value {
+    serde_json::to_value(req).expect("commands serialize")
+}
+
+/// Every snapshot field by wire name.
+fn snapshot_json(snapshot: &SimSnapshot) -> Value {
+    let pkt = encode_sim_data(0, snapshot);
+    let fields = Schema::local().decode(&pkt[HEADER_LEN..]).expect("local schema covers the payload");
+    Value::Object(fields.into_iter().map(|(name, v)| (name, field_json(&v))).collect())
+}
+
+fn field_json(v: &FieldValue) -> Value {
+    match *v {
+        FieldValue::F64(x)       => float_json(x),
+        FieldValue::F32(x)       => float_json(x as f64),
+        FieldValue::I32(x)       => json!(x),
+        FieldValue::U8(x)        => json!(x),
+        FieldValue::Bool(x)      => json!(x),
+        FieldValue::Array(ref a) => Value::Array(a.iter().map(field_json).collect()),
+    }
+}
+
+/// JSON has no NaN or infinity; spell them as Java's `Double.toString` does.
+fn float_json(x: f64) -> Value {
+    match x {
+        x if x.is_nan()      => json!("NaN"),
+        f64::INFINITY        => json!("Infinity"),
+        f64::NEG_INFINITY    => json!("-Infinity"),
+        x                    => json!(x),
+    }
+}
+
+/// Variant name of an error, e.g. `UnknownPacketType`.
+fn error_name(e: &ProtocolError) -> String {
+    let debug = format!("{e:?}");
+    debug.split(['(', ' ']).next().unwrap_or_default().to_string()
+}
+
+// ── Manifest and files ───────────────────────────────────────────────────────
+
+/// Manifest for `vectors`, decoding each with a fresh [`Receiver`].
+///
+/// # Panics
+///
+/// If a vector does not decode to its expected outcome — the corpus or the
+/// codec is wrong.
+pub fn manifest(vectors: &[Vector]) -> Value {
+    let mut rx = Receiver::new();
+    let entries: Vec<Value> = vectors
+        .iter()
+        .map(|v| {
+            let outcome = rx.decode(&v.bytes);
+            let mut entry = json!({
+                "name":        v.name,
+                "file":        format!("{}.bin", v.name),
+                "description": v.description,
+            });
+            match (&v.error, outcome) {
+                (None, Ok(decoded)) => {
+                    entry["expect"] = json!("ok");
+                    entry["header"] = decoded["header"].clone();
+                    entry["payload"] = decoded["payload"].clone();
+                }
+                (Some((stage, expected)), Err((got_stage, got))) if (*stage, expected) == (got_stage, &got) => {
+                    entry["expect"] = json!("error");
+                    entry["stage"] = json!(stage.name());
+                    entry["error"] = json!(error_name(expected));
+                }
+                (expected, got) => panic!("vector {}: expected {expected:?}, decoded {got:?}", v.name),
+            }
+            entry
+        })
+        .collect();
+    json!({
+        "corpus_version":   CORPUS_VERSION,
+        "protocol_version": PROTOCOL_VERSION,
+        "layout_checksum":  SNAPSHOT_LAYOUT_CHECKSUM,
+        "pairing_code":     PAIRING_CODE,
+        "vectors":          entries,
+    })
+}
+
+/// Manifest text as written to disk.
+pub fn manifest_text(vectors: &[Vector]) -> String {
+    let mut text = serde_json::to_string_pretty(&manifest(vectors)).expect("manifest serializes");
+    text.push('\n');
+    text
+}
+
+/// Write every vector and `manifest.json` into `dir`, creating it if needed.
+pub fn write_corpus(dir: &Path) -> io::Result<()> {
+    let vectors = corpus();
+    std::fs::create_dir_all(dir)?;
+    for v in &vectors {
+        std::fs::write(dir.join(format!("{}.bin", v.name)), &v.bytes)?;
+    }
+    std::fs::write(dir.join("manifest.json"), manifest_text(&vectors))
+}
+
+/// Names of the files in `dir` that differ from what [`write_corpus`] would
+/// write.
+pub fn stale_files(dir: &Path) -> Vec<String> {
+    let vectors = corpus();
+    let mut wanted: Vec<(String, Vec<u8>)> = vectors
+        .iter()
+        .map(|v| (format!("{}.bin", v.name), v.bytes.clone()))
+        .collect();
+    wanted.push(("manifest.json".into(), manifest_text(&vectors).into_bytes()));
+    wanted
+        .into_iter()
+        .filter(|(name, bytes)| std::fs::read(dir.join(name)).ok().as_ref() != Some(bytes))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn golden_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/golden")
+    }
+
+    #[test]
+    fn checked_in_corpus_is_current() {
+        let stale = stale_files(&golden_dir());
+        assert!(stale.is_empty(), "golden corpus out of date ({stale:?}); run `cargo run -p efb-protocol --bin efb-golden`");
+    }
+
+    #[test]
+    fn checked_in_vectors_decode_as_recorded() {
+        let dir = golden_dir();
+        let manifest: Value = serde_json::from_slice(&std::fs::read(dir.join("manifest.json")).unwrap()).unwrap();
+        let mut rx = Receiver::new();
+        for entry in manifest["vectors"].as_array().unwrap() {
+            let bytes = std::fs::read(dir.join(entry["file"].as_str().unwrap())).unwrap();
+            let name = &entry["name"];
+            match rx.decode(&bytes) {
+                Ok(decoded) => {
+                    // Through text, like the manifest: serde_json's float
+                    // parsing is not exact to the last bit.
+                    let decoded: Value = serde_json::from_str(&decoded.to_string()).unwrap();
+                    assert_eq!(entry["expect"], "ok", "{name}");
+                    assert_eq!(decoded["header"], entry["header"], "{name}");
+                    assert_eq!(decoded["payload"], entry["payload"], "{name}");
+                }
+                Err((stage, e)) => {
+                    assert_eq!(entry["expect"], "error", "{name}");
+                    assert_eq!(entry["stage"], stage.name(), "{name}");
+                    assert_eq!(entry["error"], error_name(&e), "{name}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn corpus_covers_every_packet_type() {
+        let vectors = corpus();
+        for t in 0x01..=0x0F {
+            assert!(
+                vectors.iter().any(|v| v.error.is_none() && v.bytes[6] == t),
+                "no valid vector of type 0x{t:02X}"
+            );
+        }
+        let mut names: Vec<_> = vectors.iter().map(|v| v.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), vectors.len(), "duplicate vector names");
+    }
+}
@@ -0,0 +1,304 @@
+//! Field-group streaming of the SimData snapshot.
+//!
+//! Attitude changes every frame; radio frequencies and fuel quantities
+//! hardly ever do. Rather than resending the whole [`SimSnapshot`] at one
+//! rate, a sender that negotiated [`caps::FIELD_GROUPS`](crate::caps) sends
+//! each [`FieldGroup`] in its own Group packet on its own schedule, and the
+//! receiver merges them into one snapshot with a [`GroupDecoder`].
+//!
+//! Group payload layout (little-endian):
+//! ```text
+//! [0]    group  : u8  (see FieldGroup)
+//! [1..]  values : the group's fields, in wire order, at their wire width
+//! ```
+//!
+//! Every Group packet carries the full current value of each of its fields,
+//! so a lost one is healed by the next packet of the same group.
+//!
+//! Which group a field belongs to is declared next to the field itself, in
+//! `dataref_schema::SNAPSHOT_FIELDS`.
+
+use dataref_schema::{SimSnapshot, SNAPSHOT_FIELDS};
+
+use crate::{
+    build_packet, deserialize_snapshot, field_bytes, serialize_snapshot, serialize_snapshot_into,
+    PacketHeader, PacketType, ProtocolError, SNAPSHOT_LEN,
+};
+
+pub use dataref_schema::FieldGroup;
+
+/// Sequence numbers behind the last applied packet of a group within which
+/// a Group packet is dropped as reordered. Anything further behind is taken
+/// as the sender having restarted.
+pub(crate) const REORDER_WINDOW: u32 = 256;
+
+/// `(offset, size)` of every field of `group` within a SimData payload.
+fn spans(group: FieldGroup) -> impl Iterator<Item = (usize, usize)> {
+    let mut off = 0;
+    SNAPSHOT_FIELDS
+        .iter()
+        .map(move |f| {
+            let span = (f.group, off, f.wire_size());
+            off += f.wire_size();
+            span
+        })
+        .filter(move |&(g, _, _)| g == group)
+        .map(|(_, off, size)| (off, size))
+}
+
+// ── Encoding ─────────────────────────────────────────────────────────────────
+
+/// Encode one group of `snapshot` as a Group datagram.
+pub fn encode_group(seq: u32, group: FieldGroup, snapshot: &SimSnapshot) -> Vec<u8> {
+    build_packet(seq, PacketType::Group, &group_payload(group, &serialize_snapshot(snapshot)))
+}
+
+/// Whether `group` of `a` and `b` would encode differently. Compares the
+/// encoded bytes, so a NaN that stays NaN is not a change. Serializes on
+/// the stack, as the plugin checks this every tick.
+pub fn group_changed(group: FieldGroup, a: &SimSnapshot, b: &SimSnapshot) -> bool {
+    let (mut x, mut y) = ([0u8; SNAPSHOT_LEN], [0u8; SNAPSHOT_LEN]);
+    serialize_snapshot_into(a, &mut x);
+    serialize_snapshot_into(b, &mut y);
+    spans(group).any(|(off, size)| x[off..off + size] != y[off..off + size])
+}
+
+/// Group payload for `group` taken from a serialized snapshot.
+fn group_payload(group: FieldGroup, full: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(1 + group.values_len());
+    v.push(group as u8);
+    for (off, size) in spans(group) {
+        v.extend_from_slice(&full[off..off + size]);
+    }
+    v
+}
+
+/// The group a Group payload carries, without decoding its values.
+pub fn peek_group(payload: &[u8]) -> Result<FieldGroup, ProtocolError> {
+    let id = field_bytes(payload, 0, 1, "group")?[0];
+    FieldGroup::from_u8(id).ok_or(ProtocolError::UnknownGroup(id))
+}
+
+// ── GroupDecoder ─────────────────────────────────────────────────────────────
+
+/// Receiver side: merges Group packets into a full snapshot.
+///
+/// Fields of groups not yet received keep their [`SimSnapshot::default`]
+/// values; [`is_complete`](Self::is_complete) reports when every group has
+/// arrived at least once.
+pub struct GroupDecoder {
+    /// Serialized merged snapshot.
+    merged: Vec<u8>,
+    /// Sequence number of the last packet applied, per group.
+    applied: [Option<u32>; FieldGroup::COUNT],
+}
+
+impl Default for GroupDecoder {
+    fn default() -> Self {
+        GroupDecoder {
+            merged:  serialize_snapshot(&SimSnapshot::default()),
+            applied: [None; FieldGroup::COUNT],
+        }
+    }
+}
+
+impl GroupDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a packet already validated by [`decode_packet`](crate::decode_packet)
+    /// and return the merged snapshot.
+    ///
+    /// SimData and Keyframe packets are accepted too and replace every
+    /// group at once. A Group packet that arrives after a newer one of the
+    /// same group is ignored.
+    pub fn decode(
+        &mut self,
+        header: &PacketHeader,
+        ptype: PacketType,
+        payload: &[u8],
+    ) -> Result<SimSnapshot, ProtocolError> {
+        let seq = header.sequence;
+        match ptype {
+            PacketType::SimData | PacketType::Keyframe => {
+                let snap = crate::decode_sim_data(payload)?;
+                self.merged.copy_from_slice(&payload[..SNAPSHOT_LEN]);
+                self.applied = [Some(seq); FieldGroup::COUNT];
+                Ok(snap)
+            }
+            PacketType::Group => {
+                let group = peek_group(payload)?;
+                let values = &payload[1..];
+                field_bytes(payload, 1, group.values_len(), "values")?;
+                let last = &mut self.applied[group as usize];
+                let stale = last.is_some_and(|l| (1..=REORDER_WINDOW).contains(&l.wrapping_sub(seq)));
+                if !stale {
+                    let mut src = 0;
+                    for (off, size) in spans(group) {
+                        self.merged[off..off + size].copy_from_slice(&values[src..src + size]);
+                        src += size;
+                    }
+                    *last = Some(seq);
+                }
+                Ok(self.snapshot())
+            }
+            other => Err(ProtocolError::UnknownPacketType(other as u8)),
+        }
+    }
+
+    /// The merged snapshot so far.
+    pub fn snapshot(&self) -> SimSnapshot {
+        deserialize_snapshot(&self.merged).expect("merged payload is SNAPSHOT_LEN bytes")
+    }
+
+    /// Whether `group` has been received.
+    pub fn has(&self, group: FieldGroup) -> bool {
+        self.applied[group as usize].is_some()
+    }
+
+    /// Whether every group has been received at least once.
+    pub fn is_complete(&self) -> bool {
+        self.applied.iter().all(Option::is_some)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_packet, encode_sim_data};
+
+    fn decode_into(dec: &mut GroupDecoder, pkt: &[u8]) -> Result<SimSnapshot, ProtocolError> {
+        let (hdr, ptype, payload) = decode_packet(pkt)?;
+        dec.decode(&hdr, ptype, payload)
+    }
+
+    fn sample() -> SimSnapshot {
+        SimSnapshot {
+            latitude:       47.5,
+            ias_kts:        110.0,
+            rpm:            2400.0,
+            nav1_obs_deg:   270.0,
+            com1_active_hz: 118_300_000,
+            outer_marker:   true,
+            wind_speed_kt:  12.0,
+            traffic_count:  3,
+            traffic_lat:    [47.6; 20],
+            hsi_source:     2,
+            ..SimSnapshot::default()
+        }
+    }
+
+    fn names(group: FieldGroup) -> Vec<&'static str> {
+        group.fields().map(|f| f.name).collect()
+    }
+
+    #[test]
+    fn groups_follow_schema_sections() {
+        let fast = names(FieldGroup::Fast);
+        assert_eq!((fast[0], *fast.last().unwrap()), ("latitude", "barometer_inhg"));
+        assert_eq!(
+            names(FieldGroup::Nav),
+            [
+                "nav1_hdef_dot", "nav1_vdef_dot", "nav1_obs_deg", "gps_dist_nm", "gps_bearing_deg",
+                "ap_state_flags", "fd_pitch_deg", "fd_roll_deg", "ap_heading_bug_deg", "ap_altitude_ft",
+                "ap_vs_fpm", "outer_marker", "middle_marker", "inner_marker", "hsi_source",
+            ]
+        );
+        let slow = names(FieldGroup::Slow);
+        assert_eq!((slow[0], *slow.last().unwrap()), ("rpm", "wind_speed_kt"));
+        assert!(slow.contains(&"com1_active_hz") && slow.contains(&"transponder_mode"));
+        assert_eq!(names(FieldGroup::Traffic), ["traffic_lat", "traffic_lon", "traffic_ele_m", "traffic_count"]);
+
+        let total: usize = FieldGroup::ALL.iter().map(|g| g.values_len()).sum();
+        assert_eq!(total, SNAPSHOT_LEN);
+    }
+
+    #[test]
+    fn group_ids_round_trip() {
+        for g in FieldGroup::ALL {
+            assert_eq!(FieldGroup::from_u8(g as u8), Some(g));
+        }
+        assert_eq!(FieldGroup::from_u8(4), None);
+    }
+
+    #[test]
+    fn merging_every_group_rebuilds_the_snapshot() {
+        let snap = sample();
+        let mut dec = GroupDecoder::new();
+        for (seq, g) in FieldGroup::ALL.into_iter().enumerate() {
+            assert!(!dec.is_complete());
+            let pkt = encode_group(seq as u32, g, &snap);
+            assert_eq!(pkt.len(), crate::HEADER_LEN + 1 + g.values_len());
+            decode_into(&mut dec, &pkt).unwrap();
+            assert!(dec.has(g));
+        }
+        assert!(dec.is_complete());
+        assert_eq!(encode_sim_data(0, &dec.snapshot()), encode_sim_data(0, &snap));
+    }
+
+    #[test]
+    fn group_only_touches_its_fields() {
+        let mut dec = GroupDecoder::new();
+        decode_into(&mut dec, &encode_sim_data(1, &sample())).unwrap();
+        assert!(dec.is_complete());
+
+        let moved = SimSnapshot { latitude: 48.0, rpm: 0.0, ..sample() };
+        let merged = decode_into(&mut dec, &encode_group(2, FieldGroup::Fast, &moved)).unwrap();
+        assert_eq!(merged.latitude, 48.0);
+        assert_eq!(merged.rpm, 2400.0, "slow group must keep its last value");
+    }
+
+    #[test]
+    fn reordered_group_packets_are_ignored() {
+        let mut dec = GroupDecoder::new();
+        let new = SimSnapshot { ias_kts: 120.0, ..sample() };
+        decode_into(&mut dec, &encode_group(11, FieldGroup::Fast, &new)).unwrap();
+        let merged = decode_into(&mut dec, &encode_group(10, FieldGroup::Fast, &sample())).unwrap();
+        assert_eq!(merged.ias_kts, 120.0);
+
+        // Another group is unaffected by that group's sequence number.
+        let merged = decode_into(&mut dec, &encode_group(9, FieldGroup::Slow, &sample())).unwrap();
+        assert_eq!(merged.rpm, 2400.0);
+
+        // A sender restart (far behind) is accepted.
+        let merged = decode_into(&mut dec, &encode_group(11u32.wrapping_sub(1000), FieldGroup::Fast, &sample())).unwrap();
+        assert_eq!(merged.ias_kts, 110.0);
+    }
+
+    #[test]
+    fn change_detection_is_per_group() {
+        let a = sample();
+        let b = SimSnapshot { traffic_lat: [47.7; 20], ..sample() };
+        assert!(group_changed(FieldGroup::Traffic, &a, &b));
+        assert!(!group_changed(FieldGroup::Fast, &a, &b));
+
+        let nan = SimSnapshot { traffic_ele_m: [f32::NAN; 20], ..sample() };
+        assert!(!group_changed(FieldGroup::Traffic, &nan, &nan.clone()));
+    }
+
+    #[test]
+    fn malformed_group_payloads() {
+        let mut dec = GroupDecoder::new();
+        let pkt = build_packet(1, PacketType::Group, &[7, 0, 0]);
+        assert_eq!(decode_into(&mut dec, &pkt).err(), Some(ProtocolError::UnknownGroup(7)));
+        let pkt = build_packet(1, PacketType::Group, &[FieldGroup::Fast as u8, 0, 0]);
+        assert_eq!(
+            decode_into(&mut dec, &pkt).err(),
+            Some(ProtocolError::TruncatedPayload {
+                field:    "values".into(),
+                offset:   1,
+                expected: 1 + FieldGroup::Fast.values_len(),
+                actual:   3,
+            }),
+        );
+        let pkt = build_packet(1, PacketType::Group, &[]);
+        assert_eq!(
+            decode_into(&mut dec, &pkt).err(),
+            Some(ProtocolError::TruncatedPayload { field: "group".into(), offset: 0, expected: 1, actual: 0 }),
+        );
+        assert!(!dec.has(FieldGroup::Fast));
+    }
+}
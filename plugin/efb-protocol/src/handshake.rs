@@ -0,0 +1,220 @@
+//! Hello/HelloAck version and capability negotiation.
+//!
+//! A tablet opens a session by sending Hello with the protocol versions it
+//! can decode and the optional features it understands. The plugin answers
+//! with HelloAck naming the highest version both sides support and the
+//! intersection of the capability sets. Peers that never send Hello are
+//! treated as v1 with no optional capabilities.
+//!
+//! Hello payload layout (little-endian, 8 bytes):
+//! ```text
+//! [0..2]  min_version  : u16
+//! [2..4]  max_version  : u16
+//! [4..8]  capabilities : u32  (see `caps`)
+//! ```
+//!
+//! HelloAck payload layout (little-endian, 6 bytes):
+//! ```text
+//! [0..2]  version      : u16  chosen protocol version
+//! [2..6]  capabilities : u32  capabilities enabled for this session
+//! ```
+//!
+//! Both packets are always sent with the header version set to
+//! [`MIN_PROTOCOL_VERSION`] so that any peer can decode them.
+
+use crate::{build_packet_versioned, field_bytes, PacketType, ProtocolError, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+/// Optional protocol features, advertised as a bitset in Hello/HelloAck.
+pub mod caps {
+    /// Keyframe/Delta SimData encoding.
+    pub const DELTA:            u32 = 1 << 0;
+    /// Deflate-compressed payloads (v2 headers only, see `compress`).
+    pub const COMPRESSION:      u32 = 1 << 1;
+    /// Variable-length Traffic packets (see `traffic`).
+    pub const EXTENDED_TRAFFIC: u32 = 1 << 2;
+    /// SimData split into Group packets sent at per-group rates (see `groups`).
+    pub const FIELD_GROUPS:     u32 = 1 << 3;
+}
+
+/// Capabilities implemented by this build of the codec.
+pub const SUPPORTED_CAPS: u32 =
+    caps::DELTA | caps::COMPRESSION | caps::EXTENDED_TRAFFIC | caps::FIELD_GROUPS;
+
+// ── VersionRange ─────────────────────────────────────────────────────────────
+
+/// Inclusive range of protocol versions a peer accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl VersionRange {
+    /// Every version this build can encode and decode.
+    pub const SUPPORTED: VersionRange = VersionRange {
+        min: MIN_PROTOCOL_VERSION,
+        max: PROTOCOL_VERSION,
+    };
+
+    /// A range that accepts only `version`.
+    pub const fn exact(version: u16) -> Self {
+        VersionRange { min: version, max: version }
+    }
+
+    pub fn contains(&self, version: u16) -> bool {
+        (self.min..=self.max).contains(&version)
+    }
+
+    /// Highest version contained in both ranges.
+    pub fn best_common(&self, other: &VersionRange) -> Option<u16> {
+        let hi = self.max.min(other.max);
+        let lo = self.min.max(other.min);
+        (lo <= hi).then_some(hi)
+    }
+}
+
+// ── Hello / HelloAck ─────────────────────────────────────────────────────────
+
+/// Session request: supported versions and capabilities of the sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hello {
+    pub versions:     VersionRange,
+    pub capabilities: u32,
+}
+
+impl Hello {
+    /// Hello describing this build of the codec.
+    pub fn local() -> Self {
+        Hello { versions: VersionRange::SUPPORTED, capabilities: SUPPORTED_CAPS }
+    }
+
+    /// Choose the session parameters for a peer that sent `remote`.
+    ///
+    /// Returns `None` if the version ranges do not overlap.
+    pub fn negotiate(&self, remote: &Hello) -> Option<HelloAck> {
+        let version = self.versions.best_common(&remote.versions)?;
+        Some(HelloAck { version, capabilities: self.capabilities & remote.capabilities })
+    }
+}
+
+/// Session reply: the version and capabilities both peers will use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelloAck {
+    pub version:      u16,
+    pub capabilities: u32,
+}
+
+impl HelloAck {
+    pub fn has(&self, cap: u32) -> bool {
+        self.capabilities & cap == cap
+    }
+
+    /// Header versions the peers may send in this session: up to the
+    /// negotiated one, and v1 which Hello and HelloAck always use.
+    pub fn versions(&self) -> VersionRange {
+        VersionRange { min: MIN_PROTOCOL_VERSION, max: self.version }
+    }
+}
+
+/// Encode a Hello datagram.
+pub fn encode_hello(seq: u32, hello: &Hello) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(8);
+    payload.extend_from_slice(&hello.versions.min.to_le_bytes());
+    payload.extend_from_slice(&hello.versions.max.to_le_bytes());
+    payload.extend_from_slice(&hello.capabilities.to_le_bytes());
+    build_packet_versioned(MIN_PROTOCOL_VERSION, seq, PacketType::Hello, &payload)
+}
+
+/// Encode a HelloAck datagram.
+pub fn encode_hello_ack(seq: u32, ack: &HelloAck) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(6);
+    payload.extend_from_slice(&ack.version.to_le_bytes());
+    payload.extend_from_slice(&ack.capabilities.to_le_bytes());
+    build_packet_versioned(MIN_PROTOCOL_VERSION, seq, PacketType::HelloAck, &payload)
+}
+
+/// Decode a Hello payload.
+pub fn decode_hello(payload: &[u8]) -> Result<Hello, ProtocolError> {
+    let b = field_bytes(payload, 0, 8, "hello")?;
+    let min = u16::from_le_bytes([b[0], b[1]]);
+    let max = u16::from_le_bytes([b[2], b[3]]);
+    let capabilities = u32::from_le_bytes([b[4], b[5], b[6], b[7]]);
+    Ok(Hello { versions: VersionRange { min, max }, capabilities })
+}
+
+/// Decode a HelloAck payload.
+pub fn decode_hello_ack(payload: &[u8]) -> Result<HelloAck, ProtocolError> {
+    let b = field_bytes(payload, 0, 6, "hello_ack")?;
+    Ok(HelloAck {
+        version:      u16::from_le_bytes([b[0], b[1]]),
+        capabilities: u32::from_le_bytes([b[2], b[3], b[4], b[5]]),
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_packet, decode_packet_in};
+
+    #[test]
+    fn hello_round_trip() {
+        let hello = Hello {
+            versions: VersionRange { min: 1, max: 3 },
+            capabilities: caps::DELTA | caps::EXTENDED_TRAFFIC,
+        };
+        let pkt = encode_hello(7, &hello);
+        let (hdr, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::Hello);
+        assert_eq!({ hdr.version }, MIN_PROTOCOL_VERSION);
+        assert_eq!(decode_hello(payload).unwrap(), hello);
+    }
+
+    #[test]
+    fn hello_ack_round_trip() {
+        let ack = HelloAck { version: 1, capabilities: caps::DELTA };
+        let pkt = encode_hello_ack(0, &ack);
+        let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::HelloAck);
+        assert_eq!(decode_hello_ack(payload).unwrap(), ack);
+        assert!(ack.has(caps::DELTA));
+        assert!(!ack.has(caps::COMPRESSION));
+    }
+
+    #[test]
+    fn negotiation_picks_highest_common_version() {
+        let local  = Hello { versions: VersionRange { min: 1, max: 3 }, capabilities: caps::DELTA | caps::COMPRESSION };
+        let remote = Hello { versions: VersionRange { min: 2, max: 5 }, capabilities: caps::DELTA | caps::EXTENDED_TRAFFIC };
+        let ack = local.negotiate(&remote).unwrap();
+        assert_eq!(ack.version, 3);
+        assert_eq!(ack.capabilities, caps::DELTA);
+    }
+
+    #[test]
+    fn negotiation_fails_without_overlap() {
+        let local  = Hello { versions: VersionRange { min: 1, max: 1 }, capabilities: 0 };
+        let remote = Hello { versions: VersionRange { min: 2, max: 4 }, capabilities: 0 };
+        assert_eq!(local.negotiate(&remote), None);
+    }
+
+    #[test]
+    fn decode_packet_in_respects_range() {
+        let pkt = crate::encode_sim_data(0, &dataref_schema::SimSnapshot::default());
+        assert!(decode_packet_in(&pkt, VersionRange::exact(1)).is_ok());
+        assert_eq!(decode_packet_in(&pkt, VersionRange { min: 2, max: 4 }).unwrap_err(),
+                   ProtocolError::BadVersion);
+    }
+
+    #[test]
+    fn short_hello_rejected() {
+        assert_eq!(
+            decode_hello(&[1, 0, 1]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "hello".into(), offset: 0, expected: 8, actual: 3 },
+        );
+        assert_eq!(
+            decode_hello_ack(&[1, 0]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "hello_ack".into(), offset: 0, expected: 6, actual: 2 },
+        );
+    }
+}
@@ -1,31 +1,92 @@
 //! Binary UDP packet codec for the EFB plugin ↔ Android app protocol.
 //!
-//! Every datagram starts with a fixed 17-byte header followed by the payload.
+//! Every datagram starts with a header followed by the payload: 17 bytes in
+//! version 1, 34 bytes in version 2.
 //!
 //! Header layout (little-endian):
 //! ```text
 //! [0..4]    magic       : u32  = 0xEFB12345
-//! [4..6]    version     : u16  = 1
+//! [4..6]    version     : u16  (negotiated, see `handshake`)
 //! [6]       packet_type : u8   (see PacketType)
-//! [7..9]    payload_len : u16  (bytes)
+//! [7..9]    payload_len : u16  (bytes, as sent — compressed if flagged)
 //! [9..13]   sequence    : u32
 //! [13..17]  checksum    : u32  CRC-32 of payload bytes
+//! version 2 only:
+//! [17]      flags       : u8   (see `flags`)
+//! [18..26]  sim_time_us : u64  simulator time the data was sampled, 0 if none
+//! [26..34]  sent_us     : u64  sender's monotonic clock at send time
 //! ```
+//!
+//! The `encode_*` functions frame packets with the v1 header, which every
+//! peer decodes. Senders convert them for peers that negotiated v2 with
+//! [`upgrade_packet`], optionally compressing the payload.
+
+use std::borrow::Cow;
 
 use dataref_schema::SimSnapshot;
 
+pub mod auth;
+pub mod beacon;
+pub mod capture;
+pub mod command;
+pub mod compress;
+pub mod crc;
+pub mod delta;
+pub mod foreflight;
+pub mod fragment;
+pub mod gdl90;
+pub mod golden;
+pub mod groups;
+pub mod handshake;
+pub mod link;
+pub mod nmea;
+pub mod schema;
+pub mod stream;
+pub mod traffic;
+pub mod view;
+
+pub use auth::{PairingKey, ReplayWindow, Signer, StampClock};
+pub use beacon::{Beacon, BEACON_ADDR};
+pub use capture::{CaptureReader, CaptureRecord, CaptureWriter, Direction};
+pub use command::{Command, CommandRequest, CommandResult, CommandStatus, Radio};
+pub use delta::{DeltaDecoder, DeltaEncoder, DEFAULT_KEYFRAME_INTERVAL};
+pub use fragment::{fragment_packet, fragment_payload, Reassembler, DEFAULT_MTU};
+pub use groups::{encode_group, FieldGroup, GroupDecoder};
+pub use handshake::{caps, Hello, HelloAck, VersionRange, SUPPORTED_CAPS};
+pub use link::{Arrival, LatencyTracker, LinkStats, SequenceTracker};
+pub use schema::{FieldValue, Schema, SchemaField};
+pub use stream::{write_frame, Deframer, FrameReader};
+pub use traffic::{ThreatLevel, TrafficTarget};
+pub use view::SnapshotView;
+
+use crc::crc32;
+
 pub const MAGIC: u32 = 0xEFB1_2345;
-pub const PROTOCOL_VERSION: u16 = 1;
+/// Highest protocol version this build speaks.
+pub const PROTOCOL_VERSION: u16 = 2;
+/// Lowest protocol version this build still accepts; also the header version
+/// of packets built by the `encode_*` functions.
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
 
-/// Size of the fixed packet header in bytes.
+/// Size of the v1 packet header in bytes.
 pub const HEADER_LEN: usize = 17;
 
+/// Size of the v2 packet header in bytes.
+pub const HEADER_V2_LEN: usize = 34;
+
 /// Maximum accepted payload length (64 KiB − 1).
 pub const MAX_PAYLOAD_LEN: usize = 65535;
 
+/// Size of a serialized [`SimSnapshot`] payload in bytes.
+pub const SNAPSHOT_LEN: usize = dataref_schema::SNAPSHOT_WIRE_LEN;
+
+/// Size of a complete SimData datagram (header + snapshot payload).
+pub const SIM_DATA_PACKET_LEN: usize = HEADER_LEN + SNAPSHOT_LEN;
+
 // ── PacketHeader ─────────────────────────────────────────────────────────────
 
-/// Fixed-size packet header present at the start of every datagram.
+/// Packet header present at the start of every datagram. The v2 fields are
+/// zero in v1 packets.
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
 pub struct PacketHeader {
@@ -35,6 +96,43 @@ pub struct PacketHeader {
     pub payload_len: u16,
     pub sequence: u32,
     pub checksum: u32, // CRC-32 of payload bytes
+    pub flags: u8,
+    pub sim_time_us: u64,
+    pub sent_us: u64,
+}
+
+impl PacketHeader {
+    pub fn has(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+}
+
+/// Bits of the v2 header `flags` byte. Unknown bits are ignored.
+pub mod flags {
+    /// Payload is raw deflate (see `compress`); for a Fragment, the
+    /// reassembled message is.
+    pub const COMPRESSED:    u8 = 1 << 0;
+    /// Packet is one piece of a larger message.
+    pub const FRAGMENTED:    u8 = 1 << 1;
+    /// An auth tag follows the payload.
+    pub const AUTHENTICATED: u8 = 1 << 2;
+    /// Payload is a full snapshot a receiver can resynchronise on.
+    pub const KEYFRAME:      u8 = 1 << 3;
+}
+
+/// Header length of a packet with `version`.
+pub fn header_len(version: u16) -> usize {
+    if version >= 2 { HEADER_V2_LEN } else { HEADER_LEN }
+}
+
+/// Time fields of a v2 header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timestamps {
+    /// Simulator time the data was sampled, µs; 0 if not tied to sim time.
+    pub sim_time_us: u64,
+    /// Sender's monotonic clock when the packet was sent, µs. Only
+    /// differences between packets of one sender are meaningful.
+    pub sent_us:     u64,
 }
 
 // ── PacketType ───────────────────────────────────────────────────────────────
@@ -46,6 +144,25 @@ pub enum PacketType {
     CommandJson = 0x02, // tablet → plugin: JSON command payload
     Ack         = 0x03, // tablet → plugin: heartbeat ACK
     Reload      = 0x04, // tablet → plugin: reload dataref list
+    Keyframe    = 0x05, // plugin → tablet: full SimSnapshot, base for deltas
+    Delta       = 0x06, // plugin → tablet: changed fields since last keyframe
+    Hello       = 0x07, // tablet → plugin: supported versions + capabilities
+    HelloAck    = 0x08, // plugin → tablet: negotiated version + capabilities
+    Schema      = 0x09, // plugin → tablet: SimData field layout
+    Fragment    = 0x0A, // either way: one piece of a message larger than the MTU
+    CommandBinary = 0x0B, // tablet → plugin: binary-encoded Command
+    Beacon      = 0x0C, // plugin → multicast: discovery announcement
+    CommandResult = 0x0D, // plugin → tablet: outcome of a command
+    Group       = 0x0E, // plugin → tablet: one FieldGroup of the SimSnapshot
+    Traffic     = 0x0F, // plugin → tablet: list of TCAS targets
+}
+
+impl TryFrom<u8> for PacketType {
+    type Error = ProtocolError;
+
+    fn try_from(v: u8) -> Result<Self, ProtocolError> {
+        Self::from_u8(v).ok_or(ProtocolError::UnknownPacketType(v))
+    }
 }
 
 impl PacketType {
@@ -55,6 +172,17 @@ impl PacketType {
             0x02 => Some(Self::CommandJson),
             0x03 => Some(Self::Ack),
             0x04 => Some(Self::Reload),
+            0x05 => Some(Self::Keyframe),
+            0x06 => Some(Self::Delta),
+            0x07 => Some(Self::Hello),
+            0x08 => Some(Self::HelloAck),
+            0x09 => Some(Self::Schema),
+            0x0A => Some(Self::Fragment),
+            0x0B => Some(Self::CommandBinary),
+            0x0C => Some(Self::Beacon),
+            0x0D => Some(Self::CommandResult),
+            0x0E => Some(Self::Group),
+            0x0F => Some(Self::Traffic),
             _ => None,
         }
     }
@@ -69,8 +197,47 @@ pub enum ProtocolError {
     BadVersion,
     UnknownPacketType(u8),
     PayloadTooLarge,
-    TruncatedPayload,
+    /// Buffer too short: `field` starts at byte `offset`, the buffer had to
+    /// be at least `expected` bytes long (the whole layout where that is
+    /// fixed) but was `actual`. `field` is owned when it comes from a peer's
+    /// Schema rather than this build's layout.
+    TruncatedPayload { field: Cow<'static, str>, offset: usize, expected: usize, actual: usize },
+    /// Fixed-layout payload longer than this build's layout, rejected under
+    /// [`DecodeOptions::STRICT`].
+    TrailingBytes { expected: usize, actual: usize },
     BadChecksum,
+    /// A Delta arrived whose base keyframe was never received.
+    MissingKeyframe,
+    /// Schema packet with an unknown field type or non-UTF-8 name at byte
+    /// `offset`.
+    MalformedSchema { offset: usize },
+    /// Fragment with an out-of-range index or inconsistent count/type; `offset`
+    /// is that of the offending prefix field.
+    MalformedFragment { offset: usize },
+    /// Reassembly buffer cap reached; fragment dropped.
+    ReassemblyOverflow,
+    /// Missing or invalid authentication tag.
+    BadAuth,
+    /// Sequence number already accepted or too old for the replay window.
+    Replay,
+    /// Command opcode not known to this build.
+    UnknownCommand(u8),
+    /// Radio index outside COM1/COM2/NAV1/NAV2.
+    UnknownRadio(u8),
+    /// JSON `cmd` name not known to this build.
+    UnknownCommandName(String),
+    /// JSON `radio` name other than COM1/COM2/NAV1/NAV2.
+    UnknownRadioName(String),
+    /// Command with invalid JSON, non-UTF-8 or empty path, or trailing bytes;
+    /// also a CommandResult with an unknown status. `offset` is where the
+    /// problem starts, or 0 for a JSON object of the wrong shape.
+    MalformedCommand { offset: usize },
+    /// Compressed payload that does not inflate, or inflates past the limit.
+    BadCompression,
+    /// Group packet with a group id not known to this build.
+    UnknownGroup(u8),
+    /// Traffic target with an unknown threat level at byte `offset`.
+    MalformedTraffic { offset: usize },
 }
 
 impl std::fmt::Display for ProtocolError {
@@ -81,26 +248,118 @@ impl std::fmt::Display for ProtocolError {
             Self::BadVersion        => write!(f, "unsupported protocol version"),
             Self::UnknownPacketType(t) => write!(f, "unknown packet type 0x{t:02X}"),
             Self::PayloadTooLarge   => write!(f, "payload exceeds 64 KiB limit"),
-            Self::TruncatedPayload  => write!(f, "payload truncated"),
+            Self::TruncatedPayload { field, offset, expected, actual } => write!(
+                f,
+                "payload truncated: {field} at byte {offset} needs {expected} bytes, got {actual}"
+            ),
+            Self::TrailingBytes { expected, actual } => write!(
+                f,
+                "{} trailing bytes after a {expected}-byte payload",
+                actual.saturating_sub(*expected)
+            ),
             Self::BadChecksum       => write!(f, "CRC-32 mismatch"),
+            Self::MissingKeyframe   => write!(f, "delta references a missing keyframe"),
+            Self::MalformedSchema { offset }   => write!(f, "malformed schema packet at byte {offset}"),
+            Self::MalformedFragment { offset } => write!(f, "malformed fragment at byte {offset}"),
+            Self::ReassemblyOverflow => write!(f, "reassembly buffer full"),
+            Self::BadAuth           => write!(f, "authentication failed"),
+            Self::Replay            => write!(f, "replayed or stale sequence number"),
+            Self::UnknownCommand(c) => write!(f, "unknown command opcode 0x{c:02X}"),
+            Self::UnknownRadio(r)   => write!(f, "unknown radio {r}"),
+            Self::UnknownCommandName(c) => write!(f, "unknown command {c:?}"),
+            Self::UnknownRadioName(r)   => write!(f, "unknown radio {r:?}"),
+            Self::MalformedCommand { offset }  => write!(f, "malformed command at byte {offset}"),
+            Self::BadCompression    => write!(f, "corrupt compressed payload"),
+            Self::UnknownGroup(g)   => write!(f, "unknown field group {g}"),
+            Self::MalformedTraffic { offset }  => write!(f, "malformed traffic packet at byte {offset}"),
         }
     }
 }
 
+impl std::error::Error for ProtocolError {}
+
+// ── DecodeOptions ────────────────────────────────────────────────────────────
+
+/// How fixed-layout payloads such as SimData are length-checked.
+///
+/// A payload shorter than the layout is always an error. Bytes past its end
+/// are fields appended by a newer peer: [`LENIENT`](Self::LENIENT), the
+/// default, ignores them; [`STRICT`](Self::STRICT) reports
+/// [`ProtocolError::TrailingBytes`], for captures and tests that must match
+/// this build exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeOptions {
+    pub reject_trailing: bool,
+}
+
+impl DecodeOptions {
+    pub const STRICT:  DecodeOptions = DecodeOptions { reject_trailing: true };
+    pub const LENIENT: DecodeOptions = DecodeOptions { reject_trailing: false };
+
+    /// Check a SimData payload's length against [`SNAPSHOT_LEN`].
+    pub fn check_snapshot(self, payload: &[u8]) -> Result<(), ProtocolError> {
+        let actual = payload.len();
+        if actual < SNAPSHOT_LEN {
+            return Err(snapshot_truncated(actual));
+        }
+        if self.reject_trailing && actual > SNAPSHOT_LEN {
+            return Err(ProtocolError::TrailingBytes { expected: SNAPSHOT_LEN, actual });
+        }
+        Ok(())
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::LENIENT
+    }
+}
+
 // ── Public API ────────────────────────────────────────────────────────────────
 
 /// Encode a [`SimSnapshot`] into a framed UDP datagram.
 pub fn encode_sim_data(seq: u32, snapshot: &SimSnapshot) -> Vec<u8> {
-    let payload = serialize_snapshot(snapshot);
-    build_packet(seq, PacketType::SimData, &payload)
+    let mut pkt = vec![0u8; SIM_DATA_PACKET_LEN];
+    encode_sim_data_into(seq, snapshot, &mut pkt);
+    pkt
+}
+
+/// Encode a [`SimSnapshot`] datagram into `out` without allocating.
+///
+/// Returns the number of bytes written ([`SIM_DATA_PACKET_LEN`]).
+///
+/// # Panics
+///
+/// If `out` is shorter than [`SIM_DATA_PACKET_LEN`].
+pub fn encode_sim_data_into(seq: u32, snapshot: &SimSnapshot, out: &mut [u8]) -> usize {
+    let (header, payload) = out[..SIM_DATA_PACKET_LEN].split_at_mut(HEADER_LEN);
+    serialize_snapshot_into(snapshot, payload);
+    write_header(header, MIN_PROTOCOL_VERSION, seq, PacketType::SimData, payload);
+    SIM_DATA_PACKET_LEN
 }
 
-/// Decode any incoming datagram.
+/// Encode an empty heartbeat ACK, as sent by tablets to keep the plugin
+/// streaming.
+pub fn encode_ack(seq: u32) -> Vec<u8> {
+    build_packet(seq, PacketType::Ack, &[])
+}
+
+/// Decode any incoming datagram whose version this build supports.
 ///
-/// Returns `(header, packet_type, payload_slice)` on success.
-/// Every validation failure is reported as a [`ProtocolError`]; callers
-/// should log the error and silently drop the packet.
+/// Returns `(header, packet_type, payload_slice)` on success. The payload is
+/// returned as sent; pass it through [`inflate_payload`] if the peer may
+/// compress. Every validation failure is reported as a [`ProtocolError`];
+/// callers should log the error and silently drop the packet.
 pub fn decode_packet(buf: &[u8]) -> Result<(PacketHeader, PacketType, &[u8]), ProtocolError> {
+    decode_packet_in(buf, VersionRange::SUPPORTED)
+}
+
+/// Like [`decode_packet`], but only accepts header versions within `versions`
+/// (typically the range negotiated for one peer).
+pub fn decode_packet_in(
+    buf: &[u8],
+    versions: VersionRange,
+) -> Result<(PacketHeader, PacketType, &[u8]), ProtocolError> {
     if buf.len() < HEADER_LEN {
         return Err(ProtocolError::TooShort);
     }
@@ -115,23 +374,32 @@ pub fn decode_packet(buf: &[u8]) -> Result<(PacketHeader, PacketType, &[u8]), Pr
     if magic != MAGIC {
         return Err(ProtocolError::BadMagic);
     }
-    if version != PROTOCOL_VERSION {
+    if !versions.contains(version) {
         return Err(ProtocolError::BadVersion);
     }
-    let packet_type = PacketType::from_u8(ptype)
-        .ok_or(ProtocolError::UnknownPacketType(ptype))?;
+    let hlen = header_len(version);
+    if buf.len() < hlen {
+        return Err(ProtocolError::TooShort);
+    }
+    let packet_type = PacketType::try_from(ptype)?;
     if plen > MAX_PAYLOAD_LEN {
         return Err(ProtocolError::PayloadTooLarge);
     }
-    if buf.len() < HEADER_LEN + plen {
-        return Err(ProtocolError::TruncatedPayload);
+    if buf.len() < hlen + plen {
+        return Err(ProtocolError::TruncatedPayload {
+            field:    "payload".into(),
+            offset:   hlen,
+            expected: hlen + plen,
+            actual:   buf.len(),
+        });
     }
 
-    let payload = &buf[HEADER_LEN..HEADER_LEN + plen];
+    let payload = &buf[hlen..hlen + plen];
     if crc32(payload) != chk {
         return Err(ProtocolError::BadChecksum);
     }
 
+    let v2 = hlen == HEADER_V2_LEN;
     let header = PacketHeader {
         magic,
         version,
@@ -139,11 +407,57 @@ pub fn decode_packet(buf: &[u8]) -> Result<(PacketHeader, PacketType, &[u8]), Pr
         payload_len: plen as u16,
         sequence: seq,
         checksum: chk,
+        flags:       if v2 { buf[17] } else { 0 },
+        sim_time_us: if v2 { read_u64(buf, 18) } else { 0 },
+        sent_us:     if v2 { read_u64(buf, 26) } else { 0 },
     };
 
     Ok((header, packet_type, payload))
 }
 
+/// The payload of a decoded packet as the sender built it: inflated if the
+/// header is flagged [`flags::COMPRESSED`], borrowed unchanged otherwise.
+///
+/// For a reassembled fragmented message pass the header of any of its
+/// fragments.
+pub fn inflate_payload<'a>(header: &PacketHeader, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, ProtocolError> {
+    if header.has(flags::COMPRESSED) {
+        compress::inflate(payload).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(payload))
+    }
+}
+
+/// Re-frame a v1 packet with the v2 header.
+///
+/// Sets [`flags::KEYFRAME`] on full snapshots and [`flags::FRAGMENTED`] on
+/// fragments. With `compress`, the payload is deflated and flagged
+/// [`flags::COMPRESSED`] if that makes it smaller; fragments are never
+/// compressed on their own. Packets that are already v2 get the new
+/// timestamps and keep their payload.
+pub fn upgrade_packet(pkt: &[u8], ts: Timestamps, compress: bool) -> Result<Vec<u8>, ProtocolError> {
+    let (hdr, ptype, payload) = decode_packet(pkt)?;
+    let mut flags = hdr.flags;
+    if hdr.version < 2 {
+        match ptype {
+            PacketType::SimData | PacketType::Keyframe => flags |= flags::KEYFRAME,
+            PacketType::Fragment => flags |= flags::FRAGMENTED,
+            _ => {}
+        }
+    }
+    let deflated = (compress && !hdr.has(flags::COMPRESSED) && ptype != PacketType::Fragment)
+        .then(|| compress::deflate(payload))
+        .flatten();
+    let payload = match &deflated {
+        Some(smaller) => {
+            flags |= flags::COMPRESSED;
+            smaller.as_slice()
+        }
+        None => payload,
+    };
+    Ok(build_packet_v2(hdr.sequence, ptype, flags, ts, payload))
+}
+
 /// Verify the CRC-32 checksum recorded in `header` against `payload`.
 pub fn verify_checksum(header: &PacketHeader, payload: &[u8]) -> bool {
     // Access packed fields via copy to avoid unaligned reference UB.
@@ -151,31 +465,110 @@ pub fn verify_checksum(header: &PacketHeader, payload: &[u8]) -> bool {
     crc32(payload) == expected
 }
 
-/// Decode a SimData payload back into a [`SimSnapshot`].
+/// Decode a SimData payload back into a [`SimSnapshot`], ignoring trailing
+/// bytes from a newer peer.
 ///
-/// Used by tests and future Kotlin FFI bridge.
+/// Used by tests and the `efb-protocol-ffi` bindings.
 pub fn decode_sim_data(payload: &[u8]) -> Result<SimSnapshot, ProtocolError> {
-    deserialize_snapshot(payload).ok_or(ProtocolError::TruncatedPayload)
+    decode_sim_data_with(payload, DecodeOptions::LENIENT)
+}
+
+/// Like [`decode_sim_data`], with the length check chosen by `options`.
+pub fn decode_sim_data_with(payload: &[u8], options: DecodeOptions) -> Result<SimSnapshot, ProtocolError> {
+    options.check_snapshot(payload)?;
+    Ok(deserialize_snapshot(payload).expect("length checked above"))
 }
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
 
+/// `len` bytes of `buf` from `offset`, or [`ProtocolError::TruncatedPayload`]
+/// naming `field`.
+pub(crate) fn field_bytes<'a>(
+    buf: &'a [u8],
+    offset: usize,
+    len: usize,
+    field: &'static str,
+) -> Result<&'a [u8], ProtocolError> {
+    buf.get(offset..offset + len).ok_or(ProtocolError::TruncatedPayload {
+        field: field.into(),
+        offset,
+        expected: offset + len,
+        actual: buf.len(),
+    })
+}
+
+/// Error for a SimData payload of `actual < SNAPSHOT_LEN` bytes, naming the
+/// first field it cuts off.
+pub(crate) fn snapshot_truncated(actual: usize) -> ProtocolError {
+    let mut offset = 0;
+    for f in &dataref_schema::SNAPSHOT_FIELDS {
+        if offset + f.wire_size() > actual {
+            return ProtocolError::TruncatedPayload { field: f.name.into(), offset, expected: SNAPSHOT_LEN, actual };
+        }
+        offset += f.wire_size();
+    }
+    unreachable!("{actual}-byte payload holds every field")
+}
+
 fn build_packet(seq: u32, ptype: PacketType, payload: &[u8]) -> Vec<u8> {
-    let checksum = crc32(payload);
-    let mut pkt = Vec::with_capacity(HEADER_LEN + payload.len());
-    pkt.extend_from_slice(&MAGIC.to_le_bytes());
-    pkt.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
-    pkt.push(ptype as u8);
-    pkt.extend_from_slice(&(payload.len() as u16).to_le_bytes());
-    pkt.extend_from_slice(&seq.to_le_bytes());
-    pkt.extend_from_slice(&checksum.to_le_bytes());
-    pkt.extend_from_slice(payload);
+    build_packet_versioned(MIN_PROTOCOL_VERSION, seq, ptype, payload)
+}
+
+fn build_packet_v2(seq: u32, ptype: PacketType, flags: u8, ts: Timestamps, payload: &[u8]) -> Vec<u8> {
+    let mut pkt = vec![0u8; HEADER_V2_LEN + payload.len()];
+    write_header(&mut pkt[..HEADER_LEN], 2, seq, ptype, payload);
+    pkt[17] = flags;
+    pkt[18..26].copy_from_slice(&ts.sim_time_us.to_le_bytes());
+    pkt[26..34].copy_from_slice(&ts.sent_us.to_le_bytes());
+    pkt[HEADER_V2_LEN..].copy_from_slice(payload);
     pkt
 }
 
-// Serialize SimSnapshot fields in declaration order (all little-endian).
+fn build_packet_versioned(version: u16, seq: u32, ptype: PacketType, payload: &[u8]) -> Vec<u8> {
+    let mut pkt = vec![0u8; HEADER_LEN + payload.len()];
+    write_header(&mut pkt[..HEADER_LEN], version, seq, ptype, payload);
+    pkt[HEADER_LEN..].copy_from_slice(payload);
+    pkt
+}
+
+/// Fill the 17-byte v1 header (or v1 prefix of a v2 header) `out` for `payload`.
+fn write_header(out: &mut [u8], version: u16, seq: u32, ptype: PacketType, payload: &[u8]) {
+    out[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    out[4..6].copy_from_slice(&version.to_le_bytes());
+    out[6] = ptype as u8;
+    out[7..9].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+    out[9..13].copy_from_slice(&seq.to_le_bytes());
+    out[13..17].copy_from_slice(&crc32(payload).to_le_bytes());
+}
+
 fn serialize_snapshot(s: &SimSnapshot) -> Vec<u8> {
-    let mut v = Vec::with_capacity(512);
+    let mut v = vec![0u8; SNAPSHOT_LEN];
+    serialize_snapshot_into(s, &mut v);
+    v
+}
+
+/// Sequential little-endian writer over a preallocated slice.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl SliceWriter<'_> {
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+    fn push(&mut self, byte: u8) {
+        self.buf[self.pos] = byte;
+        self.pos += 1;
+    }
+}
+
+// Serialize SimSnapshot fields in declaration order (all little-endian) into
+// the first SNAPSHOT_LEN bytes of `out`.
+// The order must match `dataref_schema::SNAPSHOT_FIELDS`.
+fn serialize_snapshot_into(s: &SimSnapshot, out: &mut [u8]) {
+    let v = &mut SliceWriter { buf: &mut out[..SNAPSHOT_LEN], pos: 0 };
     // Position
     v.extend_from_slice(&s.latitude.to_le_bytes());
     v.extend_from_slice(&s.longitude.to_le_bytes());
@@ -240,7 +633,7 @@ fn serialize_snapshot(s: &SimSnapshot) -> Vec<u8> {
     v.push(s.traffic_count);
     // HSI
     v.extend_from_slice(&s.hsi_source.to_le_bytes());
-    v
+    debug_assert_eq!(v.pos, SNAPSHOT_LEN);
 }
 
 #[allow(unused_assignments)] // p is advanced by macros; last increment is intentionally unused
@@ -330,25 +723,11 @@ fn deserialize_snapshot(buf: &[u8]) -> Option<SimSnapshot> {
     Some(snap)
 }
 
-// ── CRC-32 (ISO 3309 / Ethernet polynomial 0xEDB88320) ────────────────────────
-
-fn crc32(data: &[u8]) -> u32 {
-    let mut crc: u32 = 0xFFFF_FFFF;
-    for &byte in data {
-        crc ^= u32::from(byte);
-        for _ in 0..8 {
-            crc = if crc & 1 == 1 {
-                (crc >> 1) ^ 0xEDB8_8320
-            } else {
-                crc >> 1
-            };
-        }
-    }
-    !crc
-}
-
 // ── Raw header field readers (avoid unaligned reference to packed struct) ─────
 
+fn read_u64(buf: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(buf[off..off + 8].try_into().unwrap())
+}
 fn read_u32(buf: &[u8], off: usize) -> u32 {
     u32::from_le_bytes(buf[off..off + 4].try_into().unwrap())
 }
@@ -363,23 +742,25 @@ mod tests {
     use super::*;
 
     fn make_snap() -> SimSnapshot {
-        let mut s = SimSnapshot::default();
-        s.latitude          = -26.1367;
-        s.longitude         = 28.2411;
-        s.elevation_m       = 1694.0;
-        s.ias_kts           = 120.5;
-        s.mag_heading_deg   = 270.0;
-        s.barometer_inhg    = 29.92;
-        s.oat_degc          = 22.0;
-        s.egt_degc          = [680.0, 690.0, 695.0, 685.0, 688.0, 692.0];
-        s.fuel_qty_kg       = [75.0, 75.0];
-        s.traffic_count     = 2;
+        let mut s = SimSnapshot {
+            latitude:         -26.1367,
+            longitude:        28.2411,
+            elevation_m:      1694.0,
+            ias_kts:          120.5,
+            mag_heading_deg:  270.0,
+            barometer_inhg:   29.92,
+            oat_degc:         22.0,
+            egt_degc:         [680.0, 690.0, 695.0, 685.0, 688.0, 692.0],
+            fuel_qty_kg:      [75.0, 75.0],
+            traffic_count:    2,
+            transponder_code: 7000,
+            com1_active_hz:   118_025_000,
+            ..SimSnapshot::default()
+        };
         s.traffic_lat[0]    = -26.14;
         s.traffic_lon[0]    = 28.25;
         s.traffic_lat[1]    = -26.20;
         s.traffic_lon[1]    = 28.30;
-        s.transponder_code  = 7000;
-        s.com1_active_hz    = 118_025_000;
         s
     }
 
@@ -420,7 +801,70 @@ mod tests {
     fn rejects_truncated_payload() {
         let pkt = encode_sim_data(0, &SimSnapshot::default());
         // Truncate to just the header
-        assert_eq!(decode_packet(&pkt[..HEADER_LEN]).unwrap_err(), ProtocolError::TruncatedPayload);
+        assert_eq!(
+            decode_packet(&pkt[..HEADER_LEN]).unwrap_err(),
+            ProtocolError::TruncatedPayload {
+                field:    "payload".into(),
+                offset:   HEADER_LEN,
+                expected: SIM_DATA_PACKET_LEN,
+                actual:   HEADER_LEN,
+            },
+        );
+    }
+
+    #[test]
+    fn short_sim_data_names_the_cut_field() {
+        let pkt = encode_sim_data(0, &SimSnapshot::default());
+        let payload = &pkt[HEADER_LEN..];
+        // latitude, longitude (f64) then elevation_m: 2 bytes into it.
+        let e = decode_sim_data(&payload[..18]).unwrap_err();
+        assert_eq!(
+            e,
+            ProtocolError::TruncatedPayload { field: "elevation_m".into(), offset: 16, expected: SNAPSHOT_LEN, actual: 18 },
+        );
+        assert_eq!(
+            e.to_string(),
+            format!("payload truncated: elevation_m at byte 16 needs {SNAPSHOT_LEN} bytes, got 18"),
+        );
+        assert_eq!(decode_sim_data(&[]).unwrap_err(), snapshot_truncated(0));
+        assert!(matches!(snapshot_truncated(0), ProtocolError::TruncatedPayload { field, offset: 0, .. } if field == "latitude"));
+    }
+
+    #[test]
+    fn trailing_bytes_depend_on_decode_options() {
+        let mut payload = encode_sim_data(0, &make_snap())[HEADER_LEN..].to_vec();
+        assert!(decode_sim_data_with(&payload, DecodeOptions::STRICT).is_ok());
+
+        // A newer plugin appended a field.
+        payload.extend_from_slice(&[1, 2, 3, 4]);
+        let lenient = decode_sim_data_with(&payload, DecodeOptions::default()).unwrap();
+        assert_eq!(serialize_snapshot(&lenient), payload[..SNAPSHOT_LEN]);
+        let e = decode_sim_data_with(&payload, DecodeOptions::STRICT).unwrap_err();
+        assert_eq!(e, ProtocolError::TrailingBytes { expected: SNAPSHOT_LEN, actual: SNAPSHOT_LEN + 4 });
+        assert_eq!(e.to_string(), format!("4 trailing bytes after a {SNAPSHOT_LEN}-byte payload"));
+
+        // Short payloads are rejected either way.
+        for options in [DecodeOptions::STRICT, DecodeOptions::LENIENT] {
+            assert!(matches!(
+                decode_sim_data_with(&payload[..SNAPSHOT_LEN - 1], options),
+                Err(ProtocolError::TruncatedPayload { field, .. }) if field == "hsi_source",
+            ));
+        }
+    }
+
+    #[test]
+    fn packet_type_try_from_u8() {
+        for b in 0x01..=0x0F {
+            assert_eq!(PacketType::try_from(b).map(|t| t as u8), Ok(b));
+        }
+        assert_eq!(PacketType::try_from(0), Err(ProtocolError::UnknownPacketType(0)));
+        assert_eq!(PacketType::try_from(0x10), Err(ProtocolError::UnknownPacketType(0x10)));
+    }
+
+    #[test]
+    fn protocol_error_is_a_std_error() {
+        let e: Box<dyn std::error::Error> = Box::new(ProtocolError::BadChecksum);
+        assert_eq!(e.to_string(), "CRC-32 mismatch");
     }
 
     #[test]
@@ -465,6 +909,42 @@ mod tests {
         assert!(!verify_checksum(&bad_hdr, payload));
     }
 
+    #[test]
+    fn encode_into_matches_encode() {
+        let snap = make_snap();
+        let mut buf = [0xAAu8; SIM_DATA_PACKET_LEN + 8];
+        let n = encode_sim_data_into(9, &snap, &mut buf);
+        assert_eq!(n, SIM_DATA_PACKET_LEN);
+        assert_eq!(&buf[..n], &encode_sim_data(9, &snap)[..]);
+        assert!(buf[n..].iter().all(|&b| b == 0xAA));
+    }
+
+    #[test]
+    fn v2_header_round_trip() {
+        let v1 = encode_sim_data(11, &make_snap());
+        let ts = Timestamps { sim_time_us: 3_600_000_000, sent_us: 42 };
+        let v2 = upgrade_packet(&v1, ts, false).unwrap();
+        assert_eq!(v2.len(), v1.len() - HEADER_LEN + HEADER_V2_LEN);
+
+        let (hdr, ptype, payload) = decode_packet(&v2).unwrap();
+        assert_eq!(ptype, PacketType::SimData);
+        assert_eq!(({ hdr.version }, { hdr.sequence }), (2, 11));
+        assert_eq!(hdr.flags, flags::KEYFRAME);
+        assert_eq!(({ hdr.sim_time_us }, { hdr.sent_us }), (ts.sim_time_us, ts.sent_us));
+        assert_eq!(payload, &v1[HEADER_LEN..]);
+
+        // v1 still decodes, with the v2 fields zeroed.
+        let (hdr, _, _) = decode_packet(&v1).unwrap();
+        assert_eq!(({ hdr.flags }, { hdr.sent_us }), (0, 0));
+    }
+
+    #[test]
+    fn truncated_v2_header_is_too_short() {
+        let v2 = upgrade_packet(&encode_ack(0), Timestamps::default(), false).unwrap();
+        assert_eq!(decode_packet(&v2[..HEADER_V2_LEN - 1]).unwrap_err(), ProtocolError::TooShort);
+        assert!(decode_packet_in(&v2, handshake::VersionRange::exact(1)).is_err());
+    }
+
     #[test]
     fn empty_buffer_returns_too_short() {
         assert_eq!(decode_packet(&[]).unwrap_err(), ProtocolError::TooShort);
@@ -0,0 +1,389 @@
+//! Link-quality statistics derived from packet sequence numbers.
+//!
+//! Feed every accepted packet's header sequence and arrival time into a
+//! [`SequenceTracker`] to get loss, reordering, duplicates, inter-arrival
+//! jitter and a rolling 0–1 quality score — enough to tell a flaky Wi-Fi link
+//! from a stalled sender without a packet capture.
+//!
+//! Packets with a v2 header also carry the sender's clock; a
+//! [`LatencyTracker`] turns those into an end-to-end latency estimate.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Sequence numbers behind the highest seen that are still classified as
+/// reordered (rather than ignored as stale).
+const REORDER_WINDOW: u32 = 64;
+
+/// A jump larger than this (either direction) is treated as the peer
+/// restarting its sequence counter rather than as loss.
+const RESYNC_THRESHOLD: u32 = 1000;
+
+/// Smoothing factor of the rolling loss estimate (≈ last 32 packets).
+const LOSS_ALPHA: f32 = 1.0 / 32.0;
+
+/// Smoothing factor of the mean interval and jitter estimates (RFC 3550 uses 1/16).
+const JITTER_ALPHA: f64 = 1.0 / 16.0;
+
+/// Span of one bucket of the latency baseline history.
+const BASE_BUCKET: Duration = Duration::from_secs(10);
+
+/// Buckets kept; the baseline is the minimum over the last minute.
+const BASE_BUCKETS: usize = 6;
+
+/// Latency above this is taken as the sender having restarted its clock.
+const LATENCY_RESYNC: Duration = Duration::from_secs(10);
+
+/// How a single packet was classified by [`SequenceTracker::record`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrival {
+    /// Next expected sequence number (or the first packet seen).
+    InOrder,
+    /// Arrived after skipping this many sequence numbers.
+    Gap(u32),
+    /// Older than the highest seen but not seen before; fills an earlier gap.
+    Reordered,
+    /// Already seen, or too old to tell.
+    Duplicate,
+    /// Sequence jumped so far that the peer is assumed to have restarted.
+    Resync,
+}
+
+/// Snapshot of link statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkStats {
+    pub received:   u64,
+    pub lost:       u64,
+    pub reordered:  u64,
+    pub duplicates: u64,
+    /// Lifetime loss fraction, `lost / (received + lost)`.
+    pub loss_rate:  f32,
+    /// Smoothed deviation of the inter-arrival interval from its mean.
+    pub jitter:     Duration,
+    /// Smoothed mean inter-arrival interval.
+    pub mean_interval: Duration,
+    /// Rolling score: 1.0 = perfect, 0.0 = unusable.
+    pub quality:    f32,
+}
+
+/// Tracks one peer's sequence numbers.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    highest:      Option<u32>,
+    /// Bit i set ⇒ `highest - i` has been seen.
+    seen:         u64,
+    received:     u64,
+    lost:         u64,
+    reordered:    u64,
+    duplicates:   u64,
+    recent_loss:  f32,
+    last_arrival: Option<Instant>,
+    mean_interval_s: f64,
+    jitter_s:     f64,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a packet with header sequence `seq` that arrived at `now`.
+    pub fn record(&mut self, seq: u32, now: Instant) -> Arrival {
+        let arrival = self.classify(seq);
+        if arrival != Arrival::Duplicate {
+            self.received += 1;
+            self.update_timing(now);
+        }
+        arrival
+    }
+
+    /// Current statistics.
+    pub fn stats(&self) -> LinkStats {
+        let total = self.received + self.lost;
+        let loss_rate = if total == 0 { 0.0 } else { self.lost as f32 / total as f32 };
+
+        let jitter_ratio = if self.mean_interval_s > 0.0 {
+            (self.jitter_s / self.mean_interval_s).min(1.0) as f32
+        } else {
+            0.0
+        };
+        let quality = if self.received == 0 {
+            0.0
+        } else {
+            ((1.0 - self.recent_loss) * (1.0 - 0.5 * jitter_ratio)).clamp(0.0, 1.0)
+        };
+
+        LinkStats {
+            received: self.received,
+            lost: self.lost,
+            reordered: self.reordered,
+            duplicates: self.duplicates,
+            loss_rate,
+            jitter: Duration::from_secs_f64(self.jitter_s),
+            mean_interval: Duration::from_secs_f64(self.mean_interval_s),
+            quality,
+        }
+    }
+
+    /// Forget all history.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn classify(&mut self, seq: u32) -> Arrival {
+        let Some(highest) = self.highest else {
+            self.resync(seq);
+            return Arrival::InOrder;
+        };
+
+        let ahead = seq.wrapping_sub(highest) as i32;
+        if ahead.unsigned_abs() > RESYNC_THRESHOLD {
+            self.resync(seq);
+            return Arrival::Resync;
+        }
+
+        if ahead > 0 {
+            let gap = ahead as u32 - 1;
+            self.seen = if ahead as u32 >= 64 { 0 } else { self.seen << ahead };
+            self.seen |= 1;
+            self.highest = Some(seq);
+            self.lost += u64::from(gap);
+            for _ in 0..gap {
+                self.recent_loss += LOSS_ALPHA * (1.0 - self.recent_loss);
+            }
+            self.recent_loss -= LOSS_ALPHA * self.recent_loss;
+            return if gap == 0 { Arrival::InOrder } else { Arrival::Gap(gap) };
+        }
+
+        let behind = ahead.unsigned_abs();
+        if behind >= REORDER_WINDOW || self.seen & (1u64 << behind) != 0 {
+            self.duplicates += 1;
+            return Arrival::Duplicate;
+        }
+        self.seen |= 1u64 << behind;
+        self.reordered += 1;
+        self.lost = self.lost.saturating_sub(1);
+        self.recent_loss -= LOSS_ALPHA * self.recent_loss;
+        Arrival::Reordered
+    }
+
+    fn resync(&mut self, seq: u32) {
+        self.highest = Some(seq);
+        self.seen = 1;
+    }
+
+    fn update_timing(&mut self, now: Instant) {
+        if let Some(prev) = self.last_arrival {
+            let interval = now.saturating_duration_since(prev).as_secs_f64();
+            if self.mean_interval_s == 0.0 {
+                self.mean_interval_s = interval;
+            } else {
+                let deviation = (interval - self.mean_interval_s).abs();
+                self.jitter_s += JITTER_ALPHA * (deviation - self.jitter_s);
+                self.mean_interval_s += JITTER_ALPHA * (interval - self.mean_interval_s);
+            }
+        }
+        self.last_arrival = Some(now);
+    }
+}
+
+// ── LatencyTracker ───────────────────────────────────────────────────────────
+
+/// Estimates one-way latency from the `sent_us` field of v2 headers.
+///
+/// Sender and receiver clocks are not synchronised, so the transit time
+/// `arrival − sent_us` includes an unknown clock offset. The smallest
+/// transit seen over the last minute is taken as offset plus propagation
+/// delay, which on a LAN is well under a millisecond; latency is the transit
+/// above that baseline — time spent queued in the sender, the access point
+/// and the receiver. Taking the baseline from a rolling window lets it
+/// follow clock drift.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTracker {
+    /// Receiver clock reference for transit times.
+    epoch:      Option<Instant>,
+    /// `(bucket start, minimum transit µs)`, oldest first.
+    base:       VecDeque<(Instant, i64)>,
+    last_us:    f64,
+    smoothed_us: f64,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a packet stamped `sent_us` by its sender that arrived at `now`.
+    /// Returns its latency estimate.
+    pub fn record(&mut self, sent_us: u64, now: Instant) -> Duration {
+        let first = self.epoch.is_none();
+        let epoch = *self.epoch.get_or_insert(now);
+        let transit = now.saturating_duration_since(epoch).as_micros() as i64 - sent_us as i64;
+
+        match self.base.back_mut() {
+            Some((start, min)) if now.saturating_duration_since(*start) < BASE_BUCKET => {
+                *min = (*min).min(transit);
+            }
+            _ => {
+                self.base.push_back((now, transit));
+                if self.base.len() > BASE_BUCKETS {
+                    self.base.pop_front();
+                }
+            }
+        }
+        let mut latency = transit - self.baseline();
+        if latency as u128 > LATENCY_RESYNC.as_micros() {
+            self.base.clear();
+            self.base.push_back((now, transit));
+            self.smoothed_us = 0.0;
+            latency = 0;
+        }
+
+        self.last_us = latency as f64;
+        if first {
+            self.smoothed_us = self.last_us;
+        } else {
+            self.smoothed_us += JITTER_ALPHA * (self.last_us - self.smoothed_us);
+        }
+        Duration::from_micros(latency as u64)
+    }
+
+    /// Smoothed latency, or `None` before the first packet.
+    pub fn latency(&self) -> Option<Duration> {
+        self.epoch.map(|_| Duration::from_secs_f64(self.smoothed_us / 1e6))
+    }
+
+    /// Latency of the most recent packet.
+    pub fn last(&self) -> Option<Duration> {
+        self.epoch.map(|_| Duration::from_secs_f64(self.last_us / 1e6))
+    }
+
+    /// Forget all history.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn baseline(&self) -> i64 {
+        self.base.iter().map(|&(_, min)| min).min().unwrap_or(0)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn clean_link_scores_perfect() {
+        let mut t = SequenceTracker::new();
+        let t0 = Instant::now();
+        for i in 0..100 {
+            assert_eq!(t.record(i, t0 + TICK * i), Arrival::InOrder);
+        }
+        let s = t.stats();
+        assert_eq!(s.received, 100);
+        assert_eq!(s.lost, 0);
+        assert_eq!(s.loss_rate, 0.0);
+        assert!(s.jitter < Duration::from_micros(10));
+        assert!((s.mean_interval.as_secs_f64() - 0.05).abs() < 1e-6);
+        assert!(s.quality > 0.99);
+    }
+
+    #[test]
+    fn gaps_count_as_loss() {
+        let mut t = SequenceTracker::new();
+        let now = Instant::now();
+        t.record(0, now);
+        assert_eq!(t.record(4, now), Arrival::Gap(3));
+        let s = t.stats();
+        assert_eq!(s.lost, 3);
+        assert!((s.loss_rate - 0.6).abs() < 1e-6);
+        assert!(s.quality < 1.0);
+    }
+
+    #[test]
+    fn late_packet_is_reordered_not_lost() {
+        let mut t = SequenceTracker::new();
+        let now = Instant::now();
+        t.record(0, now);
+        t.record(2, now);
+        assert_eq!(t.record(1, now), Arrival::Reordered);
+        let s = t.stats();
+        assert_eq!(s.reordered, 1);
+        assert_eq!(s.lost, 0);
+    }
+
+    #[test]
+    fn duplicates_detected() {
+        let mut t = SequenceTracker::new();
+        let now = Instant::now();
+        t.record(5, now);
+        t.record(6, now);
+        assert_eq!(t.record(5, now), Arrival::Duplicate);
+        assert_eq!(t.record(6, now), Arrival::Duplicate);
+        let s = t.stats();
+        assert_eq!(s.duplicates, 2);
+        assert_eq!(s.received, 2);
+    }
+
+    #[test]
+    fn irregular_arrivals_raise_jitter() {
+        let mut t = SequenceTracker::new();
+        let mut at = Instant::now();
+        for i in 0..200 {
+            at += if i % 2 == 0 { Duration::from_millis(10) } else { Duration::from_millis(90) };
+            t.record(i, at);
+        }
+        let s = t.stats();
+        assert!(s.jitter > Duration::from_millis(20), "jitter {:?}", s.jitter);
+        assert!(s.quality < 0.9);
+    }
+
+    #[test]
+    fn peer_restart_resyncs_without_loss() {
+        let mut t = SequenceTracker::new();
+        let now = Instant::now();
+        t.record(50_000, now);
+        assert_eq!(t.record(0, now), Arrival::Resync);
+        assert_eq!(t.record(1, now), Arrival::InOrder);
+        assert_eq!(t.stats().lost, 0);
+    }
+
+    #[test]
+    fn latency_measured_against_fastest_packet() {
+        let mut l = LatencyTracker::new();
+        assert_eq!(l.latency(), None);
+        let t0 = Instant::now();
+        // Sender clock runs 5 s ahead; the first packet is the fastest.
+        let sent = |i: u32| 5_000_000 + u64::from(i) * 50_000;
+        assert_eq!(l.record(sent(0), t0), Duration::ZERO);
+        for i in 1..100 {
+            l.record(sent(i), t0 + TICK * i + Duration::from_millis(8));
+        }
+        let latency = l.latency().unwrap();
+        assert!((latency.as_secs_f64() - 0.008).abs() < 1e-4, "latency {latency:?}");
+        assert_eq!(l.last(), Some(Duration::from_millis(8)));
+    }
+
+    #[test]
+    fn latency_baseline_follows_restarts() {
+        let mut l = LatencyTracker::new();
+        let t0 = Instant::now();
+        l.record(1_000_000_000, t0);
+        // Sender restarted: its clock is now far behind.
+        assert_eq!(l.record(0, t0 + TICK), Duration::ZERO);
+        assert_eq!(l.record(50_000 + 3_000, t0 + TICK * 2 + Duration::from_millis(3)), Duration::ZERO);
+        assert_eq!(l.record(100_000, t0 + TICK * 3 + Duration::from_millis(3)), Duration::from_millis(3));
+    }
+
+    #[test]
+    fn sequence_wraparound_is_in_order() {
+        let mut t = SequenceTracker::new();
+        let now = Instant::now();
+        t.record(u32::MAX, now);
+        assert_eq!(t.record(0, now), Arrival::InOrder);
+    }
+}
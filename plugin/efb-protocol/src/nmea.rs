@@ -0,0 +1,289 @@
+//! NMEA 0183 encoder, for moving-map software that speaks nothing else.
+//!
+//! Each sentence is ASCII text of the form
+//!
+//! ```text
+//! $<talker+type>,<field>,…*<checksum>\r\n
+//! ```
+//!
+//! where the checksum is the XOR of every byte between `$` and `*`, as two
+//! upper-case hex digits. Sentences built here:
+//!
+//! | sentence | contents                                        |
+//! |----------|-------------------------------------------------|
+//! | `GPRMC`  | time, date, position, groundspeed, track        |
+//! | `GPGGA`  | time, position, fix quality, altitude (MSL)     |
+//! | `GPGSA`  | 3D fix with fixed satellites and DOPs           |
+//! | `PGRMZ`  | Garmin pressure altitude, feet                  |
+//! | `HCHDG`  | magnetic heading                                |
+//!
+//! The simulator has a perfect fix, so the satellite data is made up. The
+//! snapshot has no magnetic variation; those fields are left empty.
+
+use std::fmt::Write as _;
+
+use dataref_schema::SimSnapshot;
+
+/// The conventional TCP/UDP port for NMEA over IP.
+pub const NMEA_PORT: u16 = 10110;
+
+const KT_PER_MS: f32 = 1.943_84;
+const FT_PER_M: f64 = 3.280_84;
+
+// ── Time ─────────────────────────────────────────────────────────────────────
+
+/// UTC time of a fix.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UtcTime {
+    /// Seconds since 0000Z.
+    pub seconds: f32,
+    /// Day, month and year (e.g. 2026), if known.
+    pub date: Option<(u8, u8, u16)>,
+}
+
+impl UtcTime {
+    /// Time and date from seconds since the Unix epoch.
+    pub fn from_unix(secs: f64) -> Self {
+        let days = (secs / 86_400.0).floor();
+        // Civil-from-days (proleptic Gregorian), after H. Hinnant.
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (yoe + era * 400 + (month <= 2) as i64) as u16;
+        UtcTime { seconds: (secs - days * 86_400.0) as f32, date: Some((day, month, year)) }
+    }
+
+    /// UTC time and date from a sim clock that keeps the local date as a day
+    /// of `year` (0 = 1 January) and both the local and UTC time of day.
+    ///
+    /// Local time is within 12 h of UTC, so a larger difference means the
+    /// two straddle midnight and the UTC date is a day either side.
+    pub fn from_sim(year: u16, local_day: i32, local_secs: f32, zulu_secs: f32) -> Self {
+        let shift = match local_secs - zulu_secs {
+            d if d < -43_200.0 => -1,
+            d if d > 43_200.0 => 1,
+            _ => 0,
+        };
+        let days = days_from_civil(year.into(), 1, 1) + i64::from(local_day) + shift;
+        UtcTime { seconds: zulu_secs, ..Self::from_unix(days as f64 * 86_400.0) }
+    }
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date, after H. Hinnant.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// ── Framing ──────────────────────────────────────────────────────────────────
+
+/// XOR of every byte of `body`, the text between `$` and `*`.
+pub fn checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
+
+/// Complete sentence from its body: `$`, body, `*`, checksum and CRLF.
+pub fn sentence(body: &str) -> String {
+    format!("${body}*{:02X}\r\n", checksum(body))
+}
+
+// ── Sentences ────────────────────────────────────────────────────────────────
+
+/// Recommended minimum data: `GPRMC`. The date is empty if unknown.
+pub fn encode_rmc(snap: &SimSnapshot, utc: UtcTime) -> String {
+    let date = utc.date.map_or(String::new(), |(d, m, y)| format!("{d:02}{m:02}{:02}", y % 100));
+    sentence(&format!(
+        "GPRMC,{},A,{},{},{:.1},{},{date},,,A",
+        hhmmss(utc.seconds),
+        latitude(snap.latitude),
+        longitude(snap.longitude),
+        finite_or_zero(snap.groundspeed_ms * KT_PER_MS),
+        angle(snap.ground_track_deg),
+    ))
+}
+
+/// Fix data: `GPGGA`, with the altitude above mean sea level.
+pub fn encode_gga(snap: &SimSnapshot, utc: UtcTime) -> String {
+    sentence(&format!(
+        "GPGGA,{},{},{},1,12,0.8,{:.1},M,0.0,M,,",
+        hhmmss(utc.seconds),
+        latitude(snap.latitude),
+        longitude(snap.longitude),
+        finite_or_zero(snap.elevation_m),
+    ))
+}
+
+/// Satellites and dilution of precision: `GPGSA`, always a 3D fix.
+pub fn encode_gsa() -> String {
+    sentence("GPGSA,A,3,01,02,03,04,05,06,07,08,09,10,11,12,1.5,0.8,1.2")
+}
+
+/// Garmin altitude: `PGRMZ`, pressure altitude in feet.
+///
+/// The snapshot has no pressure altitude, so it is estimated from the
+/// geometric altitude and the altimeter setting, assuming the altimeter is
+/// set to the local QNH.
+pub fn encode_pgrmz(snap: &SimSnapshot) -> String {
+    let mut alt_ft = snap.elevation_m * FT_PER_M;
+    if snap.barometer_inhg.is_finite() && snap.barometer_inhg > 0.0 {
+        alt_ft += (29.92 - f64::from(snap.barometer_inhg)) * 1000.0;
+    }
+    sentence(&format!("PGRMZ,{:.0},f,3", finite_or_zero(alt_ft)))
+}
+
+/// Magnetic heading: `HCHDG`, deviation and variation left empty.
+pub fn encode_hdg(snap: &SimSnapshot) -> String {
+    sentence(&format!("HCHDG,{},,,,", angle(snap.mag_heading_deg)))
+}
+
+/// Every sentence for one fix, in the order receivers expect them.
+pub fn encode_sentences(snap: &SimSnapshot, utc: UtcTime) -> String {
+    let mut out = String::with_capacity(5 * 82);
+    for s in [encode_rmc(snap, utc), encode_gga(snap, utc), encode_gsa(), encode_pgrmz(snap), encode_hdg(snap)] {
+        out.push_str(&s);
+    }
+    out
+}
+
+// ── Fields ───────────────────────────────────────────────────────────────────
+
+fn finite_or_zero<T: Into<f64>>(v: T) -> f64 {
+    let v = v.into();
+    if v.is_finite() { v } else { 0.0 }
+}
+
+/// `hhmmss.ss`.
+fn hhmmss(seconds: f32) -> String {
+    let cs = (finite_or_zero(seconds) * 100.0).round().rem_euclid(8_640_000.0) as u32;
+    let s = cs / 100;
+    format!("{:02}{:02}{:02}.{:02}", s / 3600, s / 60 % 60, s % 60, cs % 100)
+}
+
+/// `ddmm.mmmm,N` or `,S`.
+fn latitude(deg: f64) -> String {
+    coordinate(deg, 2, if deg < 0.0 { 'S' } else { 'N' })
+}
+
+/// `dddmm.mmmm,E` or `,W`.
+fn longitude(deg: f64) -> String {
+    coordinate(deg, 3, if deg < 0.0 { 'W' } else { 'E' })
+}
+
+fn coordinate(deg: f64, width: usize, hemisphere: char) -> String {
+    // Ten-thousandths of a minute, rounded once so 59.99995' carries.
+    let units = (finite_or_zero(deg).abs() * 600_000.0).round() as u64;
+    let (whole, minutes) = (units / 600_000, units % 600_000);
+    let mut s = String::with_capacity(width + 9);
+    let _ = write!(s, "{whole:0width$}{:02}.{:04},{hemisphere}", minutes / 10_000, minutes % 10_000);
+    s
+}
+
+/// Degrees in [0, 360) with one decimal.
+fn angle(deg: f32) -> String {
+    let tenths = (finite_or_zero(deg) * 10.0).round().rem_euclid(3_600.0) as u32;
+    format!("{}.{}", tenths / 10, tenths % 10)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn munich() -> SimSnapshot {
+        SimSnapshot {
+            latitude:         48.0 + 7.038 / 60.0,
+            longitude:        11.0 + 31.0 / 60.0,
+            elevation_m:      545.4,
+            groundspeed_ms:   22.4 / KT_PER_MS,
+            ground_track_deg: 84.4,
+            mag_heading_deg:  359.97,
+            barometer_inhg:   29.92,
+            ..SimSnapshot::default()
+        }
+    }
+
+    fn noon() -> UtcTime {
+        UtcTime { seconds: 12.0 * 3600.0 + 35.0 * 60.0 + 19.0, date: Some((23, 3, 1994)) }
+    }
+
+    #[test]
+    fn checksums_match_reference_sentences() {
+        for s in [
+            "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n",
+            "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n",
+            "$PGRMZ,93,f,3*21\r\n",
+        ] {
+            assert_eq!(sentence(&s[1..s.len() - 5]), s);
+        }
+    }
+
+    #[test]
+    fn rmc_and_gga_from_snapshot() {
+        assert_eq!(
+            encode_rmc(&munich(), noon()),
+            "$GPRMC,123519.00,A,4807.0380,N,01131.0000,E,22.4,84.4,230394,,,A*52\r\n",
+        );
+        assert_eq!(
+            encode_gga(&munich(), noon()),
+            "$GPGGA,123519.00,4807.0380,N,01131.0000,E,1,12,0.8,545.4,M,0.0,M,,*58\r\n",
+        );
+    }
+
+    #[test]
+    fn southern_and_western_hemispheres() {
+        let snap = SimSnapshot { latitude: -33.964_6, longitude: -118.408_5, ..munich() };
+        let rmc = encode_rmc(&snap, UtcTime::default());
+        assert!(rmc.starts_with("$GPRMC,000000.00,A,3357.8760,S,11824.5100,W,22.4,84.4,,,,A*"), "{rmc}");
+        // Minutes that round up to 60 carry into the degrees.
+        assert_eq!(latitude(9.999_999_9), "1000.0000,N");
+    }
+
+    #[test]
+    fn altitude_and_heading_sentences() {
+        assert_eq!(encode_pgrmz(&munich()), "$PGRMZ,1789,f,3*2C\r\n");
+        let low_qnh = SimSnapshot { barometer_inhg: 29.42, ..munich() };
+        assert_eq!(encode_pgrmz(&low_qnh), "$PGRMZ,2289,f,3*2A\r\n");
+        // 359.97° rounds to 360.0, which is shown as 0.0.
+        assert_eq!(encode_hdg(&munich()), "$HCHDG,0.0,,,,*42\r\n");
+    }
+
+    #[test]
+    fn sentences_are_valid_and_short() {
+        let all = encode_sentences(&munich(), noon());
+        let lines: Vec<_> = all.split_terminator("\r\n").collect();
+        assert_eq!(lines.len(), 5);
+        for line in lines {
+            assert!(line.len() + 2 <= 82, "{line}");
+            let (body, sum) = line[1..].split_once('*').unwrap();
+            assert_eq!(u8::from_str_radix(sum, 16).unwrap(), checksum(body));
+        }
+    }
+
+    #[test]
+    fn utc_from_unix_time() {
+        let t = UtcTime::from_unix(1_700_000_000.5);
+        assert_eq!(t.date, Some((14, 11, 2023)));
+        assert_eq!(hhmmss(t.seconds), "221320.50");
+        assert_eq!(UtcTime::from_unix(951_782_400.0).date, Some((29, 2, 2000)));
+    }
+
+    #[test]
+    fn utc_from_sim_clock() {
+        let t = UtcTime::from_sim(2026, 45, 43_200.0, 39_600.0);
+        assert_eq!(t, UtcTime { seconds: 39_600.0, date: Some((15, 2, 2026)) });
+        // 01:00 local on 1 January, east of Greenwich, is still 31 December UTC…
+        assert_eq!(UtcTime::from_sim(2026, 0, 3_600.0, 82_800.0).date, Some((31, 12, 2025)));
+        // …and 20:00 local on 31 December, west of it, is already 1 January.
+        assert_eq!(UtcTime::from_sim(2025, 364, 72_000.0, 3_600.0).date, Some((1, 1, 2026)));
+    }
+}
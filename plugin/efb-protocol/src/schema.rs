@@ -0,0 +1,307 @@
+//! Self-describing SimData layout.
+//!
+//! The plugin sends a Schema packet when a tablet connects, listing every
+//! [`SimSnapshot`](dataref_schema::SimSnapshot) field with its type, array
+//! length, byte offset and unit. A receiver holding the schema can decode any
+//! SimData payload by field name, so fields added by a newer plugin are
+//! skipped instead of shifting every value after them.
+//!
+//! Schema payload layout (little-endian):
+//! ```text
+//! [0..2]  field_count : u16
+//! then field_count entries of:
+//!         name_len    : u8
+//!         name        : [u8; name_len]  UTF-8
+//!         type        : u8              FieldType
+//!         array_len   : u16             1 for scalars
+//!         offset      : u16             byte offset within the SimData payload
+//!         unit_len    : u8
+//!         unit        : [u8; unit_len]  UTF-8, empty if dimensionless
+//! ```
+
+use dataref_schema::{FieldType, SNAPSHOT_FIELDS};
+
+use crate::{build_packet, field_bytes, PacketType, ProtocolError};
+
+// ── Schema ───────────────────────────────────────────────────────────────────
+
+/// One field of a SimData payload as described by a Schema packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaField {
+    pub name:      String,
+    pub ty:        FieldType,
+    pub array_len: u16,
+    pub offset:    u16,
+    pub unit:      String,
+}
+
+impl SchemaField {
+    /// Total encoded size of the field in bytes.
+    pub fn wire_size(&self) -> usize {
+        self.ty.size() * self.array_len as usize
+    }
+}
+
+/// Layout of a SimData payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    pub fields: Vec<SchemaField>,
+}
+
+/// A decoded field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    F64(f64),
+    F32(f32),
+    I32(i32),
+    U8(u8),
+    Bool(bool),
+    Array(Vec<FieldValue>),
+}
+
+impl FieldValue {
+    /// Numeric value of a scalar field; `None` for arrays.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::F64(v)  => Some(v),
+            Self::F32(v)  => Some(v as f64),
+            Self::I32(v)  => Some(v as f64),
+            Self::U8(v)   => Some(v as f64),
+            Self::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+            Self::Array(_) => None,
+        }
+    }
+}
+
+impl Schema {
+    /// Schema of the SimData payload produced by this build.
+    pub fn local() -> Self {
+        let mut offset = 0usize;
+        let fields = SNAPSHOT_FIELDS
+            .iter()
+            .map(|f| {
+                let field = SchemaField {
+                    name:      f.name.to_string(),
+                    ty:        f.ty,
+                    array_len: f.len as u16,
+                    offset:    offset as u16,
+                    unit:      f.unit.to_string(),
+                };
+                offset += f.wire_size();
+                field
+            })
+            .collect();
+        Schema { fields }
+    }
+
+    /// Look up a field description by name.
+    pub fn field(&self, name: &str) -> Option<&SchemaField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Minimum SimData payload length covered by this schema.
+    pub fn payload_len(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|f| f.offset as usize + f.wire_size())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Decode a single field from a SimData payload.
+    ///
+    /// Returns `None` if the schema has no such field or the payload is too
+    /// short to contain it.
+    pub fn get(&self, payload: &[u8], name: &str) -> Option<FieldValue> {
+        read_field(payload, self.field(name)?)
+    }
+
+    /// Decode every field of a SimData payload, in schema order.
+    pub fn decode(&self, payload: &[u8]) -> Result<Vec<(String, FieldValue)>, ProtocolError> {
+        let expected = self.payload_len();
+        self.fields
+            .iter()
+            .map(|f| {
+                let v = read_field(payload, f).ok_or_else(|| ProtocolError::TruncatedPayload {
+                    field:  f.name.clone().into(),
+                    offset: f.offset as usize,
+                    expected,
+                    actual: payload.len(),
+                })?;
+                Ok((f.name.clone(), v))
+            })
+            .collect()
+    }
+}
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// Encode a Schema datagram.
+pub fn encode_schema(seq: u32, schema: &Schema) -> Vec<u8> {
+    let mut v = Vec::with_capacity(2 + schema.fields.len() * 24);
+    v.extend_from_slice(&(schema.fields.len() as u16).to_le_bytes());
+    for f in &schema.fields {
+        push_str(&mut v, &f.name);
+        v.push(f.ty as u8);
+        v.extend_from_slice(&f.array_len.to_le_bytes());
+        v.extend_from_slice(&f.offset.to_le_bytes());
+        push_str(&mut v, &f.unit);
+    }
+    build_packet(seq, PacketType::Schema, &v)
+}
+
+/// Decode a Schema payload.
+pub fn decode_schema(payload: &[u8]) -> Result<Schema, ProtocolError> {
+    let mut p = 0usize;
+    let count = read_u16(payload, &mut p, "field_count")?;
+    let mut fields = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name = read_str(payload, &mut p, "name")?;
+        let ty_byte = field_bytes(payload, p, 1, "type")?[0];
+        let ty = FieldType::from_u8(ty_byte).ok_or(ProtocolError::MalformedSchema { offset: p })?;
+        p += 1;
+        let array_len = read_u16(payload, &mut p, "array_len")?;
+        let offset    = read_u16(payload, &mut p, "offset")?;
+        let unit = read_str(payload, &mut p, "unit")?;
+        fields.push(SchemaField { name, ty, array_len, offset, unit });
+    }
+    Ok(Schema { fields })
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+fn read_field(payload: &[u8], f: &SchemaField) -> Option<FieldValue> {
+    let start = f.offset as usize;
+    let bytes = payload.get(start..start + f.wire_size())?;
+    let elems: Vec<FieldValue> = bytes
+        .chunks_exact(f.ty.size())
+        .map(|b| match f.ty {
+            FieldType::F64  => FieldValue::F64(f64::from_le_bytes(b.try_into().unwrap())),
+            FieldType::F32  => FieldValue::F32(f32::from_le_bytes(b.try_into().unwrap())),
+            FieldType::I32  => FieldValue::I32(i32::from_le_bytes(b.try_into().unwrap())),
+            FieldType::U8   => FieldValue::U8(b[0]),
+            FieldType::Bool => FieldValue::Bool(b[0] != 0),
+        })
+        .collect();
+    if f.array_len == 1 {
+        elems.into_iter().next()
+    } else {
+        Some(FieldValue::Array(elems))
+    }
+}
+
+fn push_str(v: &mut Vec<u8>, s: &str) {
+    let bytes = &s.as_bytes()[..s.len().min(255)];
+    v.push(bytes.len() as u8);
+    v.extend_from_slice(bytes);
+}
+
+fn read_u16(buf: &[u8], p: &mut usize, field: &'static str) -> Result<u16, ProtocolError> {
+    let b = field_bytes(buf, *p, 2, field)?;
+    *p += 2;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_str(buf: &[u8], p: &mut usize, field: &'static str) -> Result<String, ProtocolError> {
+    let len = field_bytes(buf, *p, 1, field)?[0] as usize;
+    let b = field_bytes(buf, *p + 1, len, field)?;
+    let offset = *p + 1;
+    *p += 1 + len;
+    String::from_utf8(b.to_vec()).map_err(|_| ProtocolError::MalformedSchema { offset })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_packet, encode_sim_data, SNAPSHOT_LEN};
+    use dataref_schema::SimSnapshot;
+
+    #[test]
+    fn local_schema_matches_serializer() {
+        let schema = Schema::local();
+        assert_eq!(schema.payload_len(), SNAPSHOT_LEN);
+
+        let snap = SimSnapshot {
+            latitude: -33.9649,
+            ias_kts: 98.0,
+            transponder_code: 7000,
+            middle_marker: true,
+            traffic_count: 3,
+            hsi_source: 2,
+            ..SimSnapshot::default()
+        };
+        let pkt = encode_sim_data(0, &snap);
+        let (_, _, payload) = decode_packet(&pkt).unwrap();
+
+        assert_eq!(schema.get(payload, "latitude"), Some(FieldValue::F64(-33.9649)));
+        assert_eq!(schema.get(payload, "ias_kts"), Some(FieldValue::F32(98.0)));
+        assert_eq!(schema.get(payload, "transponder_code"), Some(FieldValue::I32(7000)));
+        assert_eq!(schema.get(payload, "middle_marker"), Some(FieldValue::Bool(true)));
+        assert_eq!(schema.get(payload, "traffic_count"), Some(FieldValue::U8(3)));
+        assert_eq!(schema.get(payload, "hsi_source"), Some(FieldValue::I32(2)));
+        assert_eq!(schema.get(payload, "no_such_field"), None);
+
+        match schema.get(payload, "fuel_qty_kg") {
+            Some(FieldValue::Array(a)) => assert_eq!(a.len(), 2),
+            other => panic!("expected array, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn schema_packet_round_trip() {
+        let schema = Schema::local();
+        let pkt = encode_schema(3, &schema);
+        let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::Schema);
+        let decoded = decode_schema(payload).unwrap();
+        assert_eq!(decoded, schema);
+        assert_eq!(decoded.field("oat_degc").unwrap().unit, "degC");
+    }
+
+    #[test]
+    fn decode_tolerates_unknown_trailing_fields() {
+        // A newer plugin appends a field; an older tablet's schema still reads
+        // every field it knows about.
+        let mut newer = Schema::local();
+        newer.fields.push(SchemaField {
+            name: "flap_ratio".into(),
+            ty: FieldType::F32,
+            array_len: 1,
+            offset: SNAPSHOT_LEN as u16,
+            unit: String::new(),
+        });
+        let mut payload = crate::serialize_snapshot(&SimSnapshot::default());
+        payload.extend_from_slice(&0.5f32.to_le_bytes());
+
+        let all = newer.decode(&payload).unwrap();
+        assert_eq!(all.len(), Schema::local().fields.len() + 1);
+        assert_eq!(newer.get(&payload, "flap_ratio"), Some(FieldValue::F32(0.5)));
+        assert!(Schema::local().decode(&payload).is_ok());
+        // Without the appended bytes the newer schema names what is missing.
+        assert_eq!(
+            newer.decode(&payload[..SNAPSHOT_LEN]).unwrap_err(),
+            ProtocolError::TruncatedPayload {
+                field:    "flap_ratio".into(),
+                offset:   SNAPSHOT_LEN,
+                expected: SNAPSHOT_LEN + 4,
+                actual:   SNAPSHOT_LEN,
+            },
+        );
+    }
+
+    #[test]
+    fn malformed_schema_rejected() {
+        assert_eq!(
+            decode_schema(&[]).unwrap_err(),
+            ProtocolError::TruncatedPayload { field: "field_count".into(), offset: 0, expected: 2, actual: 0 },
+        );
+        // One field named "x" with type byte 9.
+        let bad = [1, 0, 1, b'x', 9, 1, 0, 0, 0, 0];
+        assert_eq!(decode_schema(&bad).unwrap_err(), ProtocolError::MalformedSchema { offset: 4 });
+        // One field whose name is not UTF-8.
+        let bad = [1, 0, 1, 0xFF, 1, 1, 0, 0, 0, 0];
+        assert_eq!(decode_schema(&bad).unwrap_err(), ProtocolError::MalformedSchema { offset: 3 });
+    }
+}
@@ -0,0 +1,277 @@
+//! Packet framing over byte streams (TCP, `adb reverse` over USB).
+//!
+//! The datagram format already frames itself: the header starts with a fixed
+//! magic and carries the payload length, so a stream is simply packets written
+//! back to back:
+//!
+//! ```text
+//! [header][payload payload_len][trailer]  [header][payload ...] ...
+//! ```
+//!
+//! `trailer` is empty unless the peers are paired, in which case every packet
+//! carries an [`AUTH_TRAILER_LEN`](crate::auth::AUTH_TRAILER_LEN)-byte trailer
+//! after the payload (see [`Deframer::with_trailer_len`]).
+//!
+//! The [`Deframer`] buffers partial reads and resynchronises after corruption:
+//! bytes before the next magic are discarded, and a candidate frame whose
+//! payload fails the CRC-32 is abandoned one byte at a time until the next
+//! valid frame is found. The version is only used to tell the header length;
+//! it and the packet type are not checked here — a well-formed frame the
+//! caller cannot decode is still a frame boundary.
+
+use std::io::{self, Read, Write};
+
+use crate::{crc32, header_len, read_u16, read_u32, HEADER_LEN, MAGIC};
+
+const MAGIC_BYTES: [u8; 4] = MAGIC.to_le_bytes();
+
+/// Bytes requested from the underlying reader per `read` call.
+const READ_CHUNK: usize = 4096;
+
+// ── Deframer ─────────────────────────────────────────────────────────────────
+
+/// Incremental stream → packet splitter, independent of any I/O.
+#[derive(Debug, Default)]
+pub struct Deframer {
+    buf:         Vec<u8>,
+    trailer_len: usize,
+    skipped:     u64,
+}
+
+impl Deframer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect `len` bytes after each payload (the auth trailer of paired peers).
+    pub fn with_trailer_len(mut self, len: usize) -> Self {
+        self.trailer_len = len;
+        self
+    }
+
+    /// Append bytes received from the stream.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Next complete frame (header + payload + trailer), or `None` until more
+    /// bytes are pushed.
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        loop {
+            let Some(start) = find_magic(&self.buf) else {
+                // Keep a possible partial magic at the end.
+                let keep = self.buf.len().min(MAGIC_BYTES.len() - 1);
+                self.discard(self.buf.len() - keep);
+                return None;
+            };
+            self.discard(start);
+
+            if self.buf.len() < HEADER_LEN {
+                return None;
+            }
+            let hlen = header_len(read_u16(&self.buf, 4));
+            let payload_end = hlen + read_u16(&self.buf, 7) as usize;
+            let frame_len = payload_end + self.trailer_len;
+            if self.buf.len() < frame_len {
+                return None;
+            }
+            if crc32(&self.buf[hlen..payload_end]) != read_u32(&self.buf, 13) {
+                self.discard(1); // false or corrupted header — look further on
+                continue;
+            }
+            return Some(self.buf.drain(..frame_len).collect());
+        }
+    }
+
+    /// Bytes currently buffered but not yet returned as a frame.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Total bytes thrown away while resynchronising.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped
+    }
+
+    fn discard(&mut self, n: usize) {
+        self.buf.drain(..n);
+        self.skipped += n as u64;
+    }
+}
+
+fn find_magic(buf: &[u8]) -> Option<usize> {
+    buf.windows(MAGIC_BYTES.len()).position(|w| w == MAGIC_BYTES)
+}
+
+// ── Read / Write adapters ────────────────────────────────────────────────────
+
+/// Reads whole frames from a byte stream.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner:    R,
+    deframer: Deframer,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(inner: R) -> Self {
+        FrameReader { inner, deframer: Deframer::new() }
+    }
+
+    /// See [`Deframer::with_trailer_len`].
+    pub fn with_trailer_len(mut self, len: usize) -> Self {
+        self.deframer = self.deframer.with_trailer_len(len);
+        self
+    }
+
+    /// Block until a frame is available.
+    ///
+    /// Returns `Ok(None)` at end of stream; a trailing partial frame is
+    /// discarded.
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            if let Some(frame) = self.deframer.next_frame() {
+                return Ok(Some(frame));
+            }
+            match self.inner.read(&mut chunk) {
+                Ok(0) => return Ok(None),
+                Ok(n) => self.deframer.push(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Bytes discarded while resynchronising.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.deframer.skipped_bytes()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Write one framed packet (as produced by the `encode_*` functions, plus its
+/// auth tag if signed) to a stream.
+pub fn write_frame<W: Write>(w: &mut W, pkt: &[u8]) -> io::Result<()> {
+    w.write_all(pkt)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{decode_authenticated, sign_packet, AUTH_TRAILER_LEN};
+    use crate::{
+        build_packet, decode_packet, upgrade_packet, PacketType, PairingKey, ReplayWindow, Signer, StampClock,
+        Timestamps,
+    };
+    use std::io::Cursor;
+
+    fn pkt(seq: u32, body: &[u8]) -> Vec<u8> {
+        build_packet(seq, PacketType::CommandJson, body)
+    }
+
+    /// Reader that returns at most one byte per call.
+    struct Trickle<R>(R);
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(1);
+            self.0.read(&mut buf[..n])
+        }
+    }
+
+    #[test]
+    fn frames_round_trip_over_stream() {
+        let mut stream = Vec::new();
+        for seq in 0..3 {
+            write_frame(&mut stream, &pkt(seq, b"{}")).unwrap();
+        }
+        let frames: Vec<_> = FrameReader::new(Cursor::new(stream)).collect::<io::Result<_>>().unwrap();
+        assert_eq!(frames.len(), 3);
+        for (i, f) in frames.iter().enumerate() {
+            assert_eq!({ decode_packet(f).unwrap().0.sequence }, i as u32);
+        }
+    }
+
+    #[test]
+    fn v1_and_v2_frames_mixed() {
+        let v2 = upgrade_packet(&pkt(1, b"{}"), Timestamps { sim_time_us: 5, sent_us: 6 }, false).unwrap();
+        let mut stream = pkt(0, b"{}");
+        stream.extend(&v2);
+        stream.extend(pkt(2, b"{}"));
+        let mut r = FrameReader::new(Trickle(Cursor::new(stream)));
+        for seq in 0..3 {
+            let (hdr, _, _) = decode_packet(&r.read_frame().unwrap().unwrap()).unwrap();
+            assert_eq!(({ hdr.sequence }, { hdr.sent_us }), (seq, if seq == 1 { 6 } else { 0 }));
+        }
+        assert_eq!(r.skipped_bytes(), 0);
+    }
+
+    #[test]
+    fn partial_reads_are_buffered() {
+        let mut stream = pkt(7, b"{\"cmd\":\"swap_freq\"}");
+        stream.extend(pkt(8, b""));
+        let mut r = FrameReader::new(Trickle(Cursor::new(stream)));
+        assert_eq!({ decode_packet(&r.read_frame().unwrap().unwrap()).unwrap().0.sequence }, 7);
+        assert_eq!({ decode_packet(&r.read_frame().unwrap().unwrap()).unwrap().0.sequence }, 8);
+        assert!(r.read_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn resyncs_after_garbage_and_corruption() {
+        let mut stream = b"line noise \xEF\x45\x23".to_vec();
+        let mut corrupted = pkt(1, b"payload");
+        corrupted[HEADER_LEN + 2] ^= 0xFF;
+        stream.extend(&corrupted);
+        stream.extend(pkt(2, b"good"));
+
+        let mut r = FrameReader::new(Cursor::new(stream));
+        let frame = r.read_frame().unwrap().unwrap();
+        assert_eq!({ decode_packet(&frame).unwrap().0.sequence }, 2);
+        assert!(r.read_frame().unwrap().is_none());
+        assert_eq!(r.skipped_bytes() as usize, 14 + corrupted.len());
+    }
+
+    #[test]
+    fn incomplete_tail_waits_for_more_bytes() {
+        let full = pkt(3, b"abcdef");
+        let mut d = Deframer::new();
+        d.push(&full[..HEADER_LEN + 2]);
+        assert!(d.next_frame().is_none());
+        assert_eq!(d.buffered(), HEADER_LEN + 2);
+        d.push(&full[HEADER_LEN + 2..]);
+        assert_eq!(d.next_frame().unwrap(), full);
+        assert_eq!(d.skipped_bytes(), 0);
+    }
+
+    #[test]
+    fn signed_frames_keep_their_tag() {
+        let key = PairingKey::from_code("1234");
+        let mut window = ReplayWindow::since(0);
+        let stamps = StampClock::new();
+        let mut stream = Vec::new();
+        for seq in 0..2 {
+            let mut p = pkt(seq, b"{}");
+            sign_packet(&mut p, &key, Signer::Tablet, stamps.next());
+            write_frame(&mut stream, &p).unwrap();
+        }
+        for frame in FrameReader::new(Cursor::new(stream)).with_trailer_len(AUTH_TRAILER_LEN) {
+            decode_authenticated(&frame.unwrap(), &key, Signer::Tablet, &mut window).unwrap();
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Traffic packet: a variable-length list of TCAS targets.
+//!
+//! The traffic arrays in [`SimSnapshot`] hold at
+//! most 20 positions as f32, which is several metres off at mid latitudes,
+//! and nothing else. Peers that negotiated [`caps::EXTENDED_TRAFFIC`](crate::caps)
+//! also receive Traffic packets carrying every target with full-precision
+//! position, identity and motion.
+//!
+//! Traffic payload layout (little-endian):
+//! ```text
+//! [0]       count              : u8
+//! then `count` targets, each:
+//! [0..4]    icao_address       : u32  24-bit Mode S address, 0 if unknown
+//! [4..12]   latitude           : f64  deg
+//! [12..20]  longitude          : f64  deg
+//! [20..28]  altitude_m         : f64  m MSL
+//! [28..32]  track_deg          : f32  true
+//! [32..36]  groundspeed_kt     : f32
+//! [36..40]  vertical_speed_fpm : f32
+//! [40]      flags              : u8   bit 0: on ground
+//! [41]      threat             : u8   (see ThreatLevel)
+//!           callsign_len       : u8
+//!           callsign           : [u8; callsign_len]  UTF-8, empty if unknown
+//! ```
+//!
+//! Bytes after the last target are ignored, leaving room for new fields.
+
+use dataref_schema::SimSnapshot;
+use serde::{Deserialize, Serialize};
+
+use crate::beacon::{push_str, read_str};
+use crate::{build_packet, field_bytes, PacketType, ProtocolError};
+
+/// Most targets one packet can carry.
+pub const MAX_TARGETS: usize = 255;
+
+/// Size of the fixed part of a target, before the callsign.
+const TARGET_FIXED_LEN: usize = 42;
+
+/// `flags` bit set for a target on the ground.
+const ON_GROUND: u8 = 1 << 0;
+
+// ── ThreatLevel ──────────────────────────────────────────────────────────────
+
+/// TCAS classification of a target relative to the ownship.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreatLevel {
+    /// Other traffic.
+    #[default]
+    None                = 0,
+    /// Within 6 nm and ±1200 ft.
+    Proximate           = 1,
+    /// Traffic advisory.
+    TrafficAdvisory     = 2,
+    /// Resolution advisory.
+    ResolutionAdvisory  = 3,
+}
+
+impl ThreatLevel {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::None),
+            1 => Some(Self::Proximate),
+            2 => Some(Self::TrafficAdvisory),
+            3 => Some(Self::ResolutionAdvisory),
+            _ => None,
+        }
+    }
+}
+
+// ── TrafficTarget ────────────────────────────────────────────────────────────
+
+/// One aircraft seen by the TCAS.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrafficTarget {
+    /// 24-bit ICAO (Mode S) address, 0 if unknown.
+    pub icao_address:       u32,
+    /// Flight id, e.g. `SAA335`; empty if unknown.
+    pub callsign:           String,
+    pub latitude:           f64,
+    pub longitude:          f64,
+    /// Metres above mean sea level.
+    pub altitude_m:         f64,
+    /// True track over the ground, degrees.
+    pub track_deg:          f32,
+    pub groundspeed_kt:     f32,
+    pub vertical_speed_fpm: f32,
+    pub on_ground:          bool,
+    pub threat:             ThreatLevel,
+}
+
+impl TrafficTarget {
+    /// Position-only targets from the fixed traffic arrays of a snapshot.
+    pub fn from_snapshot(snap: &SimSnapshot) -> Vec<Self> {
+        let n = (snap.traffic_count as usize).min(snap.traffic_lat.len());
+        (0..n)
+            .map(|i| Self {
+                latitude:   snap.traffic_lat[i] as f64,
+                longitude:  snap.traffic_lon[i] as f64,
+                altitude_m: snap.traffic_ele_m[i] as f64,
+                ..Self::default()
+            })
+            .collect()
+    }
+}
+
+/// Encode a Traffic datagram.
+///
+/// Targets beyond [`MAX_TARGETS`] are dropped; callsigns longer than 255
+/// bytes are truncated at a character boundary.
+pub fn encode_traffic(seq: u32, targets: &[TrafficTarget]) -> Vec<u8> {
+    let targets = &targets[..targets.len().min(MAX_TARGETS)];
+    let mut v = Vec::with_capacity(1 + targets.len() * (TARGET_FIXED_LEN + 9));
+    v.push(targets.len() as u8);
+    for t in targets {
+        v.extend_from_slice(&t.icao_address.to_le_bytes());
+        v.extend_from_slice(&t.latitude.to_le_bytes());
+        v.extend_from_slice(&t.longitude.to_le_bytes());
+        v.extend_from_slice(&t.altitude_m.to_le_bytes());
+        v.extend_from_slice(&t.track_deg.to_le_bytes());
+        v.extend_from_slice(&t.groundspeed_kt.to_le_bytes());
+        v.extend_from_slice(&t.vertical_speed_fpm.to_le_bytes());
+        v.push(if t.on_ground { ON_GROUND } else { 0 });
+        v.push(t.threat as u8);
+        push_str(&mut v, &t.callsign);
+    }
+    build_packet(seq, PacketType::Traffic, &v)
+}
+
+/// Decode a Traffic payload.
+///
+/// Like the Beacon, invalid UTF-8 in a callsign is replaced rather than
+/// rejected. An unknown threat level is [`ProtocolError::MalformedTraffic`].
+pub fn decode_traffic(payload: &[u8]) -> Result<Vec<TrafficTarget>, ProtocolError> {
+    let count = field_bytes(payload, 0, 1, "count")?[0] as usize;
+    let mut p = 1;
+    let mut targets = Vec::with_capacity(count);
+    for _ in 0..count {
+        let b = field_bytes(payload, p, TARGET_FIXED_LEN, "target")?;
+        let f32_at = |i: usize| f32::from_le_bytes(b[i..i + 4].try_into().unwrap());
+        let f64_at = |i: usize| f64::from_le_bytes(b[i..i + 8].try_into().unwrap());
+        let threat = ThreatLevel::from_u8(b[41]).ok_or(ProtocolError::MalformedTraffic { offset: p + 41 })?;
+        p += TARGET_FIXED_LEN;
+        targets.push(TrafficTarget {
+            icao_address:       u32::from_le_bytes(b[0..4].try_into().unwrap()),
+            latitude:           f64_at(4),
+            longitude:          f64_at(12),
+            altitude_m:         f64_at(20),
+            track_deg:          f32_at(28),
+            groundspeed_kt:     f32_at(32),
+            vertical_speed_fpm: f32_at(36),
+            on_ground:          b[40] & ON_GROUND != 0,
+            threat,
+            callsign:           read_str(payload, &mut p, "callsign")?,
+        });
+    }
+    Ok(targets)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_packet, HEADER_LEN};
+
+    fn sample() -> Vec<TrafficTarget> {
+        vec![
+            TrafficTarget {
+                icao_address:       0x00_A1_B2,
+                callsign:           "SAA335".into(),
+                latitude:           -26.139_123_456_7,
+                longitude:          28.247_654_321_1,
+                altitude_m:         1_712.25,
+                track_deg:          213.5,
+                groundspeed_kt:     245.0,
+                vertical_speed_fpm: -1_200.0,
+                on_ground:          false,
+                threat:             ThreatLevel::TrafficAdvisory,
+            },
+            TrafficTarget {
+                on_ground: true,
+                ..TrafficTarget::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn traffic_round_trip_keeps_f64_positions() {
+        let pkt = encode_traffic(8, &sample());
+        let (_, ptype, payload) = decode_packet(&pkt).unwrap();
+        assert_eq!(ptype, PacketType::Traffic);
+        let targets = decode_traffic(payload).unwrap();
+        assert_eq!(targets, sample());
+        assert_eq!(targets[0].latitude, -26.139_123_456_7);
+    }
+
+    #[test]
+    fn empty_list_is_one_byte() {
+        let pkt = encode_traffic(0, &[]);
+        assert_eq!(&pkt[HEADER_LEN..], [0]);
+        assert!(decode_traffic(&pkt[HEADER_LEN..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncated_extended_and_malformed_payloads() {
+        let pkt = encode_traffic(0, &sample());
+        let payload = &pkt[HEADER_LEN..];
+        for len in [0, 1, TARGET_FIXED_LEN, payload.len() - 1] {
+            assert!(matches!(
+                decode_traffic(&payload[..len]).unwrap_err(),
+                ProtocolError::TruncatedPayload { actual, .. } if actual == len,
+            ));
+        }
+
+        let mut longer = payload.to_vec();
+        longer.extend_from_slice(b"extra");
+        assert_eq!(decode_traffic(&longer).unwrap(), sample());
+
+        let mut bad_threat = payload.to_vec();
+        bad_threat[1 + 41] = 9;
+        assert_eq!(decode_traffic(&bad_threat).unwrap_err(), ProtocolError::MalformedTraffic { offset: 1 + 41 });
+    }
+
+    #[test]
+    fn target_count_is_capped() {
+        let many = vec![TrafficTarget::default(); MAX_TARGETS + 10];
+        let pkt = encode_traffic(0, &many);
+        assert_eq!(decode_traffic(&pkt[HEADER_LEN..]).unwrap().len(), MAX_TARGETS);
+    }
+}
@@ -0,0 +1,276 @@
+//! Zero-copy, lazily decoded view of a SimData payload.
+//!
+//! [`decode_sim_data`](crate::decode_sim_data) materialises all ~460 bytes
+//! into a [`SimSnapshot`] even when the caller only wants a handful of
+//! fields. [`SnapshotView`] borrows the payload instead and reads each field
+//! from its fixed offset on demand:
+//!
+//! ```
+//! # use efb_protocol::{decode_packet, encode_sim_data, SnapshotView};
+//! # let pkt = encode_sim_data(0, &Default::default());
+//! let (_, _, payload) = decode_packet(&pkt)?;
+//! let view = SnapshotView::new(payload)?;
+//! let ias = view.ias_kts();
+//! # Ok::<(), efb_protocol::ProtocolError>(())
+//! ```
+//!
+//! Offsets are derived at compile time from
+//! [`SNAPSHOT_FIELDS`](dataref_schema::SNAPSHOT_FIELDS), so the view always
+//! agrees with the serializer.
+
+use dataref_schema::{SimSnapshot, SNAPSHOT_FIELDS};
+
+use crate::{deserialize_snapshot, DecodeOptions, ProtocolError};
+
+/// Borrowed SimData payload with per-field accessors.
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotView<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> SnapshotView<'a> {
+    /// Wrap a SimData payload (or Keyframe payload).
+    ///
+    /// Trailing bytes beyond [`SNAPSHOT_LEN`](crate::SNAPSHOT_LEN) — fields appended by a newer
+    /// plugin — are ignored.
+    pub fn new(payload: &'a [u8]) -> Result<Self, ProtocolError> {
+        Self::with_options(payload, DecodeOptions::LENIENT)
+    }
+
+    /// Like [`new`](Self::new), with the length check chosen by `options`.
+    pub fn with_options(payload: &'a [u8], options: DecodeOptions) -> Result<Self, ProtocolError> {
+        options.check_snapshot(payload)?;
+        Ok(SnapshotView { buf: payload })
+    }
+
+    /// The underlying payload bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// Decode every field into an owned [`SimSnapshot`].
+    pub fn to_snapshot(&self) -> SimSnapshot {
+        deserialize_snapshot(self.buf).expect("length checked in SnapshotView::new")
+    }
+
+    fn read<T: Wire>(&self, offset: usize) -> T {
+        T::read(&self.buf[offset..offset + T::SIZE])
+    }
+}
+
+// ── Field accessors ──────────────────────────────────────────────────────────
+
+/// Byte offset of `SNAPSHOT_FIELDS[index]` within the payload.
+const fn offset_of(index: usize) -> usize {
+    let mut off = 0;
+    let mut i = 0;
+    while i < index {
+        off += SNAPSHOT_FIELDS[i].wire_size();
+        i += 1;
+    }
+    off
+}
+
+macro_rules! accessors {
+    ($($idx:literal => $name:ident: $ty:ty),* $(,)?) => {
+        impl SnapshotView<'_> {
+            $(
+                #[inline]
+                pub fn $name(&self) -> $ty {
+                    const OFFSET: usize = offset_of($idx);
+                    self.read::<$ty>(OFFSET)
+                }
+            )*
+        }
+
+        /// `(name, field index, encoded size)` of every accessor, checked
+        /// against `SNAPSHOT_FIELDS` by the tests.
+        #[cfg(test)]
+        const ACCESSORS: &[(&str, usize, usize)] = &[
+            $((stringify!($name), $idx, <$ty as Wire>::SIZE)),*
+        ];
+    };
+}
+
+accessors! {
+    // Position
+    0  => latitude:           f64,
+    1  => longitude:          f64,
+    2  => elevation_m:        f64,
+    3  => groundspeed_ms:     f32,
+    // Attitude
+    4  => pitch_deg:          f32,
+    5  => roll_deg:           f32,
+    6  => mag_heading_deg:    f32,
+    7  => ground_track_deg:   f32,
+    // Air data
+    8  => ias_kts:            f32,
+    9  => tas_kts:            f32,
+    10 => vvi_fpm:            f32,
+    11 => turn_rate_deg_sec:  f32,
+    12 => slip_deg:           f32,
+    13 => oat_degc:           f32,
+    14 => barometer_inhg:     f32,
+    // Engine
+    15 => rpm:                f32,
+    16 => map_inhg:           f32,
+    17 => fuel_flow_kg_sec:   f32,
+    18 => oil_press_psi:      f32,
+    19 => oil_temp_degc:      f32,
+    20 => egt_degc:           [f32; 6],
+    21 => fuel_qty_kg:        [f32; 2],
+    22 => bus_volts:          f32,
+    23 => battery_amps:       f32,
+    24 => suction_inhg:       f32,
+    // Navigation
+    25 => nav1_hdef_dot:      f32,
+    26 => nav1_vdef_dot:      f32,
+    27 => nav1_obs_deg:       f32,
+    28 => gps_dist_nm:        f32,
+    29 => gps_bearing_deg:    f32,
+    // Autopilot
+    30 => ap_state_flags:     i32,
+    31 => fd_pitch_deg:       f32,
+    32 => fd_roll_deg:        f32,
+    33 => ap_heading_bug_deg: f32,
+    34 => ap_altitude_ft:     f32,
+    35 => ap_vs_fpm:          f32,
+    // Radios
+    36 => com1_active_hz:     i32,
+    37 => com1_standby_hz:    i32,
+    38 => com2_active_hz:     i32,
+    39 => nav1_active_hz:     i32,
+    40 => nav1_standby_hz:    i32,
+    41 => transponder_code:   i32,
+    42 => transponder_mode:   i32,
+    // Markers
+    43 => outer_marker:       bool,
+    44 => middle_marker:      bool,
+    45 => inner_marker:       bool,
+    // Weather
+    46 => wind_dir_deg:       f32,
+    47 => wind_speed_kt:      f32,
+    // Traffic
+    48 => traffic_lat:        [f32; 20],
+    49 => traffic_lon:        [f32; 20],
+    50 => traffic_ele_m:      [f32; 20],
+    51 => traffic_count:      u8,
+    // HSI
+    52 => hsi_source:         i32,
+}
+
+// ── Wire decoding ────────────────────────────────────────────────────────────
+
+/// A value with a fixed little-endian encoding.
+trait Wire: Sized {
+    const SIZE: usize;
+    /// `b` is exactly `SIZE` bytes long.
+    fn read(b: &[u8]) -> Self;
+}
+
+macro_rules! wire_num {
+    ($($t:ty),*) => {$(
+        impl Wire for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn read(b: &[u8]) -> Self {
+                <$t>::from_le_bytes(b.try_into().unwrap())
+            }
+        }
+    )*};
+}
+wire_num!(f64, f32, i32, u8);
+
+impl Wire for bool {
+    const SIZE: usize = 1;
+    fn read(b: &[u8]) -> Self {
+        b[0] != 0
+    }
+}
+
+impl<T: Wire + Copy + Default, const N: usize> Wire for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+    fn read(b: &[u8]) -> Self {
+        let mut a = [T::default(); N];
+        for (x, chunk) in a.iter_mut().zip(b.chunks_exact(T::SIZE)) {
+            *x = T::read(chunk);
+        }
+        a
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{serialize_snapshot, SNAPSHOT_LEN};
+
+    #[test]
+    fn accessors_cover_field_table() {
+        assert_eq!(ACCESSORS.len(), SNAPSHOT_FIELDS.len());
+        for &(name, idx, size) in ACCESSORS {
+            let f = &SNAPSHOT_FIELDS[idx];
+            assert_eq!(name, f.name);
+            assert_eq!(size, f.wire_size(), "{name}");
+        }
+        assert_eq!(offset_of(SNAPSHOT_FIELDS.len()), SNAPSHOT_LEN);
+    }
+
+    #[test]
+    fn view_reads_same_values_as_deserializer() {
+        let mut snap = SimSnapshot {
+            latitude:         51.4706,
+            longitude:        -0.4619,
+            ias_kts:          142.5,
+            egt_degc:         [700.0, 701.0, 702.0, 703.0, 704.0, 705.0],
+            ap_state_flags:   0x0042,
+            transponder_code: 7700,
+            inner_marker:     true,
+            traffic_count:    1,
+            hsi_source:       1,
+            ..SimSnapshot::default()
+        };
+        snap.traffic_ele_m[19] = 3048.0;
+        let payload = serialize_snapshot(&snap);
+        let view = SnapshotView::new(&payload).unwrap();
+
+        assert_eq!(view.latitude(), 51.4706);
+        assert_eq!(view.longitude(), -0.4619);
+        assert_eq!(view.ias_kts(), 142.5);
+        assert_eq!(view.egt_degc(), snap.egt_degc);
+        assert_eq!(view.ap_state_flags(), 0x0042);
+        assert_eq!(view.transponder_code(), 7700);
+        assert!(view.inner_marker());
+        assert!(!view.outer_marker());
+        assert_eq!(view.traffic_ele_m()[19], 3048.0);
+        assert_eq!(view.traffic_count(), 1);
+        assert_eq!(view.hsi_source(), 1);
+
+        let owned = view.to_snapshot();
+        assert_eq!(serialize_snapshot(&owned), payload);
+    }
+
+    #[test]
+    fn short_payload_rejected() {
+        let payload = vec![0u8; SNAPSHOT_LEN - 1];
+        assert_eq!(
+            SnapshotView::new(&payload).unwrap_err(),
+            ProtocolError::TruncatedPayload {
+                field:    "hsi_source".into(),
+                offset:   SNAPSHOT_LEN - 4,
+                expected: SNAPSHOT_LEN,
+                actual:   SNAPSHOT_LEN - 1,
+            },
+        );
+    }
+
+    #[test]
+    fn strict_view_rejects_trailing_bytes() {
+        let payload = vec![0u8; SNAPSHOT_LEN + 1];
+        assert!(SnapshotView::new(&payload).is_ok());
+        assert_eq!(
+            SnapshotView::with_options(&payload, DecodeOptions::STRICT).unwrap_err(),
+            ProtocolError::TrailingBytes { expected: SNAPSHOT_LEN, actual: SNAPSHOT_LEN + 1 },
+        );
+    }
+}
@@ -0,0 +1,607 @@
+// efb-replay/src/main.rs
+// Records the SimData stream of the X-Plane EFB plugin to an .efbcap file and
+// plays it back to a tablet, so the EFB pages can be exercised without
+// X-Plane running.
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use dataref_schema::SimSnapshot;
+use efb_protocol::auth::sign_packet;
+use efb_protocol::handshake::{decode_hello, encode_hello_ack};
+use efb_protocol::schema::encode_schema;
+use efb_protocol::{
+    decode_packet, encode_ack, encode_sim_data_into, inflate_payload, CaptureReader, CaptureWriter,
+    DeltaDecoder, Direction, Hello, PacketType, PairingKey, Schema, Signer, StampClock, VersionRange,
+    MIN_PROTOCOL_VERSION, SIM_DATA_PACKET_LEN,
+};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// CLI args
+// ---------------------------------------------------------------------------
+
+#[derive(Parser)]
+#[command(name = "efb-replay", about = "Record and replay EFB plugin sessions")]
+struct Args {
+    #[command(subcommand)]
+    cmd: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Pose as a tablet: ACK the plugin and record everything it sends
+    Record {
+        /// Plugin address
+        #[arg(short, long, default_value = "127.0.0.1:49100")]
+        plugin: SocketAddr,
+
+        /// Output capture file (.efbcap)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Stop after this many seconds (default: until interrupted)
+        #[arg(short, long)]
+        duration: Option<f64>,
+
+        /// Sign ACKs for a plugin paired with this code
+        #[arg(long)]
+        pairing_code: Option<String>,
+    },
+
+    /// Play the SimData of a capture to a tablet
+    Play {
+        /// Capture file (.efbcap), recorded by `record` or by the plugin
+        input: PathBuf,
+
+        /// Tablet address (the EFB app listens on port 49100)
+        #[arg(short, long)]
+        target: SocketAddr,
+
+        /// Local address to send from and receive ACKs on
+        #[arg(long, default_value = "0.0.0.0:0")]
+        bind: SocketAddr,
+
+        /// Playback speed multiplier
+        #[arg(short, long, default_value_t = 1.0)]
+        speed: f64,
+
+        /// Start this many seconds into the capture
+        #[arg(long, default_value_t = 0.0)]
+        seek: f64,
+
+        /// Restart from the beginning at the end of the capture
+        #[arg(short, long = "loop")]
+        looping: bool,
+
+        /// Start paused; press Enter to send one frame at a time
+        #[arg(long)]
+        step: bool,
+
+        /// Sign packets for a tablet paired with this code
+        #[arg(long)]
+        pairing_code: Option<String>,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Same as the plugin: stop streaming when the tablet has not ACKed for this long.
+const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
+/// While the watchdog is tripped, resend the current frame this often so the
+/// tablet has something to ACK.
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+/// How often `record` ACKs the plugin.
+const ACK_INTERVAL: Duration = Duration::from_secs(1);
+/// Main loop poll interval.
+const TICK: Duration = Duration::from_millis(1);
+
+const CONTROLS_HELP: &str = "controls: <Enter>/n step  p pause/resume  g <secs> seek  s <x> speed  q quit";
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+fn main() -> Result<()> {
+    match Args::parse().cmd {
+        Cmd::Record { plugin, output, duration, pairing_code } => {
+            let key = pairing_code.as_deref().map(PairingKey::from_code);
+            record(plugin, &output, duration.map(Duration::from_secs_f64), key)
+        }
+        Cmd::Play { input, target, bind, speed, seek, looping, step, pairing_code } => {
+            if speed.is_nan() || speed <= 0.0 {
+                bail!("--speed must be positive");
+            }
+            let file = File::open(&input).with_context(|| format!("Cannot open {}", input.display()))?;
+            let frames = load_frames(BufReader::new(file))
+                .with_context(|| format!("Cannot read {}", input.display()))?;
+            if frames.is_empty() {
+                bail!("{} contains no SimData", input.display());
+            }
+            let mut player = Player::new(frames, speed, looping);
+            player.seek(Duration::from_secs_f64(seek.max(0.0)), Instant::now());
+
+            let socket = UdpSocket::bind(bind).with_context(|| format!("Cannot bind {bind}"))?;
+            let key = pairing_code.as_deref().map(PairingKey::from_code);
+            play(player, Link::new(socket, target, key), step)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+fn record(plugin: SocketAddr, output: &Path, duration: Option<Duration>, key: Option<PairingKey>) -> Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_read_timeout(Some(Duration::from_millis(50)))?;
+    let local = socket.local_addr()?;
+    let file = File::create(output).with_context(|| format!("Cannot create {}", output.display()))?;
+    let mut writer = CaptureWriter::new(BufWriter::new(file))?;
+
+    eprintln!("Recording {plugin} to {}...", output.display());
+    let start = Instant::now();
+    let mut last_ack: Option<Instant> = None;
+    let mut ack_seq = 0u32;
+    let stamps = StampClock::new();
+    let mut received = 0u64;
+    let mut buf = [0u8; 65535];
+
+    while duration.is_none_or(|d| start.elapsed() < d) {
+        if last_ack.is_none_or(|t| t.elapsed() >= ACK_INTERVAL) {
+            let mut ack = encode_ack(ack_seq);
+            ack_seq = ack_seq.wrapping_add(1);
+            if let Some(key) = &key {
+                sign_packet(&mut ack, key, Signer::Tablet, stamps.next());
+            }
+            socket.send_to(&ack, plugin)?;
+            writer.record(Direction::Outbound, local, plugin, &ack)?;
+            writer.flush()?; // at most one ACK interval is lost if interrupted
+            last_ack = Some(Instant::now());
+        }
+        match socket.recv_from(&mut buf) {
+            Ok((n, from)) => {
+                writer.record(Direction::Inbound, from, local, &buf[..n])?;
+                received += 1;
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    writer.flush()?;
+    eprintln!("Recorded {received} packets");
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Capture loading
+// ---------------------------------------------------------------------------
+
+/// A snapshot and the capture time it was sent at.
+#[derive(Debug, Clone)]
+struct Frame {
+    at:       Duration,
+    snapshot: SimSnapshot,
+}
+
+/// Extract the SimData stream (SimData, or Keyframe + Delta) sent to the first
+/// tablet in the capture. Times are shifted so the first frame is at zero.
+fn load_frames(reader: impl Read) -> Result<Vec<Frame>> {
+    let mut decoder = DeltaDecoder::new();
+    let mut tablet: Option<SocketAddr> = None;
+    let mut frames = Vec::new();
+
+    for rec in CaptureReader::new(reader)? {
+        let rec = rec?;
+        let Ok((hdr, ptype, payload)) = decode_packet(&rec.data) else { continue };
+        if !matches!(ptype, PacketType::SimData | PacketType::Keyframe | PacketType::Delta) {
+            continue;
+        }
+        if *tablet.get_or_insert(rec.dst) != rec.dst {
+            continue;
+        }
+        let Ok(payload) = inflate_payload(&hdr, payload) else { continue };
+        // A Delta whose keyframe was lost is skipped, as the tablet would.
+        if let Ok(snapshot) = decoder.decode(&hdr, ptype, &payload) {
+            frames.push(Frame { at: rec.timestamp, snapshot });
+        }
+    }
+
+    if let Some(t0) = frames.first().map(|f| f.at) {
+        for f in &mut frames {
+            f.at -= t0;
+        }
+    }
+    Ok(frames)
+}
+
+// ---------------------------------------------------------------------------
+// Player — maps wall-clock time to capture time
+// ---------------------------------------------------------------------------
+
+struct Player {
+    frames:    Vec<Frame>,
+    /// Index of the next frame to send.
+    next:      usize,
+    speed:     f64,
+    looping:   bool,
+    /// While playing: wall-clock instant `.0` corresponds to capture time `.1`.
+    anchor:    Option<(Instant, Duration)>,
+    /// Capture time while paused.
+    paused_at: Duration,
+}
+
+impl Player {
+    /// A player paused at the start of `frames`.
+    fn new(frames: Vec<Frame>, speed: f64, looping: bool) -> Self {
+        Player { frames, next: 0, speed, looping, anchor: None, paused_at: Duration::ZERO }
+    }
+
+    fn position(&self, now: Instant) -> Duration {
+        match self.anchor {
+            Some((wall, t)) => t + now.saturating_duration_since(wall).mul_f64(self.speed),
+            None => self.paused_at,
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.anchor.is_none()
+    }
+
+    fn play(&mut self, now: Instant) {
+        if self.anchor.is_none() {
+            self.anchor = Some((now, self.paused_at));
+        }
+    }
+
+    fn pause(&mut self, now: Instant) {
+        self.paused_at = self.position(now);
+        self.anchor = None;
+    }
+
+    fn set_speed(&mut self, speed: f64, now: Instant) {
+        let pos = self.position(now);
+        self.speed = speed;
+        if self.anchor.is_some() {
+            self.anchor = Some((now, pos));
+        }
+    }
+
+    fn seek(&mut self, to: Duration, now: Instant) {
+        self.next = self.frames.partition_point(|f| f.at < to);
+        if self.anchor.is_some() {
+            self.anchor = Some((now, to));
+        } else {
+            self.paused_at = to;
+        }
+    }
+
+    /// The latest frame due at `now`. Frames the clock has already passed are
+    /// skipped rather than sent in a burst.
+    fn poll(&mut self, now: Instant) -> Option<&Frame> {
+        self.anchor?;
+        if self.next >= self.frames.len() {
+            if !self.looping {
+                return None;
+            }
+            self.next = 0;
+            self.anchor = Some((now, Duration::ZERO));
+        }
+        let pos = self.position(now);
+        let due = self.frames[self.next..].partition_point(|f| f.at <= pos);
+        if due == 0 {
+            return None;
+        }
+        self.next += due;
+        Some(&self.frames[self.next - 1])
+    }
+
+    /// Pause and advance exactly one frame.
+    fn step(&mut self, now: Instant) -> Option<&Frame> {
+        self.pause(now);
+        if self.next >= self.frames.len() {
+            if !self.looping {
+                return None;
+            }
+            self.next = 0;
+        }
+        self.next += 1;
+        let frame = &self.frames[self.next - 1];
+        self.paused_at = frame.at;
+        Some(frame)
+    }
+
+    /// Most recently sent frame (or the first one before anything was sent).
+    fn current(&self) -> &Frame {
+        &self.frames[self.next.saturating_sub(1)]
+    }
+
+    fn finished(&self) -> bool {
+        !self.looping && self.next >= self.frames.len()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Playback
+// ---------------------------------------------------------------------------
+
+/// Plugin-side UDP endpoint: renumbers and (optionally) signs every packet.
+struct Link {
+    socket: UdpSocket,
+    target: SocketAddr,
+    key:    Option<PairingKey>,
+    stamps: StampClock,
+    seq:    u32,
+    buf:    [u8; SIM_DATA_PACKET_LEN],
+}
+
+impl Link {
+    fn new(socket: UdpSocket, target: SocketAddr, key: Option<PairingKey>) -> Self {
+        Link { socket, target, key, stamps: StampClock::new(), seq: 0, buf: [0; SIM_DATA_PACKET_LEN] }
+    }
+
+    fn next_seq(&mut self) -> u32 {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+        seq
+    }
+
+    fn send_snapshot(&mut self, snap: &SimSnapshot) {
+        let seq = self.next_seq();
+        let n = encode_sim_data_into(seq, snap, &mut self.buf);
+        let pkt = self.buf[..n].to_vec();
+        self.send(pkt)
+    }
+
+    /// Send errors, e.g. while the tablet's Wi-Fi is down, are logged and
+    /// playback goes on; the watchdog holds the capture until it ACKs again.
+    fn send(&mut self, mut pkt: Vec<u8>) {
+        if let Some(key) = &self.key {
+            sign_packet(&mut pkt, key, Signer::Plugin, self.stamps.next());
+        }
+        if let Err(e) = self.socket.send_to(&pkt, self.target) {
+            eprintln!("cannot send to {}: {e}", self.target);
+        }
+    }
+}
+
+enum Control {
+    Step,
+    TogglePause,
+    Seek(Duration),
+    Speed(f64),
+    Quit,
+}
+
+fn parse_control(line: &str) -> Option<Control> {
+    let mut words = line.split_whitespace();
+    let cmd = words.next().unwrap_or("n");
+    let arg = words.next().and_then(|w| w.parse::<f64>().ok());
+    match (cmd, arg) {
+        ("n", _) => Some(Control::Step),
+        ("p", _) => Some(Control::TogglePause),
+        ("g", Some(secs)) if secs >= 0.0 => Some(Control::Seek(Duration::from_secs_f64(secs))),
+        ("s", Some(x)) if x > 0.0 => Some(Control::Speed(x)),
+        ("q", _) => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+fn spawn_stdin_reader() -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn play(mut player: Player, mut link: Link, start_paused: bool) -> Result<()> {
+    link.socket.set_nonblocking(true)?;
+    let controls = spawn_stdin_reader();
+    eprintln!("Playing {} frames to {} — {CONTROLS_HELP}", player.frames.len(), link.target);
+
+    let mut user_paused = start_paused;
+    let mut last_ack = Instant::now(); // grace period until the first ACK
+    let mut last_probe = Instant::now();
+    let mut acked = false;
+    let mut buf = [0u8; 2048];
+
+    loop {
+        let now = Instant::now();
+
+        // Operator controls.
+        while let Ok(line) = controls.try_recv() {
+            match parse_control(&line) {
+                Some(Control::Step) => {
+                    user_paused = true;
+                    match player.step(now) {
+                        Some(f) => {
+                            let (at, snap) = (f.at, f.snapshot.clone());
+                            link.send_snapshot(&snap);
+                            eprintln!("t = {:.2}s", at.as_secs_f64());
+                        }
+                        None => eprintln!("end of capture"),
+                    }
+                }
+                Some(Control::TogglePause) => user_paused = !user_paused,
+                Some(Control::Seek(to)) => player.seek(to, now),
+                Some(Control::Speed(x)) => player.set_speed(x, now),
+                Some(Control::Quit) => return Ok(()),
+                None => eprintln!("{CONTROLS_HELP}"),
+            }
+        }
+
+        // Tablet traffic: ACKs feed the watchdog; Hello gets a plain-v1 answer.
+        loop {
+            match link.socket.recv_from(&mut buf) {
+                Ok((n, from)) => match decode_packet(&buf[..n]) {
+                    Ok((_, PacketType::Ack, _)) => {
+                        last_ack = now;
+                        if !acked {
+                            acked = true;
+                            eprintln!("tablet {from} connected");
+                            let seq = link.next_seq();
+                            link.send(encode_schema(seq, &Schema::local()));
+                        }
+                    }
+                    Ok((_, PacketType::Hello, payload)) => {
+                        // Replayed snapshots are re-encoded as plain v1 SimData.
+                        let replay = Hello { versions: VersionRange::exact(MIN_PROTOCOL_VERSION), capabilities: 0 };
+                        if let Some(ack) = decode_hello(payload).ok().and_then(|h| replay.negotiate(&h)) {
+                            let seq = link.next_seq();
+                            link.send(encode_hello_ack(seq, &ack));
+                        }
+                    }
+                    _ => {}
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                // ICMP port unreachable (WSAECONNRESET on Windows) while the
+                // tablet app is not listening yet.
+                Err(e) if matches!(e.kind(), io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Watchdog: hold the capture clock while the tablet is silent.
+        let stalled = now.duration_since(last_ack) > WATCHDOG_TIMEOUT;
+        if user_paused || stalled {
+            if !player.is_paused() {
+                player.pause(now);
+            }
+        } else {
+            player.play(now);
+        }
+        if stalled && !user_paused && now.duration_since(last_probe) >= PROBE_INTERVAL {
+            let snap = player.current().snapshot.clone();
+            link.send_snapshot(&snap);
+            last_probe = now;
+        }
+
+        if let Some(frame) = player.poll(now) {
+            let snap = frame.snapshot.clone();
+            link.send_snapshot(&snap);
+        }
+        if player.finished() && !user_paused {
+            eprintln!("end of capture");
+            return Ok(());
+        }
+
+        std::thread::sleep(TICK);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use efb_protocol::{encode_sim_data, DeltaEncoder};
+
+    fn frames(times_ms: &[u64]) -> Vec<Frame> {
+        times_ms
+            .iter()
+            .map(|&ms| Frame {
+                at: Duration::from_millis(ms),
+                snapshot: SimSnapshot { ias_kts: ms as f32, ..SimSnapshot::default() },
+            })
+            .collect()
+    }
+
+    fn ias(f: Option<&Frame>) -> Option<f32> {
+        f.map(|f| f.snapshot.ias_kts)
+    }
+
+    #[test]
+    fn plays_in_real_time_and_skips_missed_frames() {
+        let t0 = Instant::now();
+        let mut p = Player::new(frames(&[0, 100, 200, 300]), 1.0, false);
+        assert_eq!(ias(p.poll(t0)), None); // paused
+        p.play(t0);
+        assert_eq!(ias(p.poll(t0)), Some(0.0));
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(50))), None);
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(250))), Some(200.0));
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(300))), Some(300.0));
+        assert!(p.finished());
+    }
+
+    #[test]
+    fn speed_scales_capture_time() {
+        let t0 = Instant::now();
+        let mut p = Player::new(frames(&[0, 1000]), 4.0, false);
+        p.play(t0);
+        p.poll(t0);
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(249))), None);
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(250))), Some(1000.0));
+    }
+
+    #[test]
+    fn seek_and_loop() {
+        let t0 = Instant::now();
+        let mut p = Player::new(frames(&[0, 100, 200]), 1.0, true);
+        p.seek(Duration::from_millis(150), t0);
+        p.play(t0);
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(50))), Some(200.0));
+        // Past the end: wraps to the first frame.
+        assert_eq!(ias(p.poll(t0 + Duration::from_millis(60))), Some(0.0));
+        assert!(!p.finished());
+    }
+
+    #[test]
+    fn step_advances_one_frame_and_pauses() {
+        let t0 = Instant::now();
+        let mut p = Player::new(frames(&[0, 100, 200]), 1.0, false);
+        p.play(t0);
+        assert_eq!(ias(p.step(t0)), Some(0.0));
+        assert_eq!(ias(p.step(t0)), Some(100.0));
+        assert!(p.is_paused());
+        assert_eq!(ias(p.poll(t0 + Duration::from_secs(10))), None);
+        // Resuming continues from the stepped position.
+        let t1 = t0 + Duration::from_secs(10);
+        p.play(t1);
+        assert_eq!(ias(p.poll(t1 + Duration::from_millis(100))), Some(200.0));
+    }
+
+    #[test]
+    fn loads_simdata_and_delta_streams() {
+        let plugin: SocketAddr = "10.0.0.1:49100".parse().unwrap();
+        let tablet: SocketAddr = "10.0.0.2:49100".parse().unwrap();
+        let other: SocketAddr = "10.0.0.3:49100".parse().unwrap();
+        let mut w = CaptureWriter::new(Vec::new()).unwrap();
+        let t0 = Instant::now();
+        let mut enc = DeltaEncoder::new(2);
+        for i in 0..4u32 {
+            let snap = SimSnapshot { ias_kts: i as f32, ..SimSnapshot::default() };
+            let at = t0 + Duration::from_millis(1000 + 50 * u64::from(i));
+            w.record_at(at, Direction::Outbound, plugin, tablet, &enc.encode(i, &snap)).unwrap();
+            // A second tablet's stream and non-SimData traffic are ignored.
+            w.record_at(at, Direction::Outbound, plugin, other, &encode_sim_data(i, &snap)).unwrap();
+            w.record_at(at, Direction::Inbound, tablet, plugin, &encode_ack(i)).unwrap();
+        }
+        let frames = load_frames(io::Cursor::new(w.into_inner())).unwrap();
+        let got: Vec<_> = frames.iter().map(|f| (f.at.as_millis(), f.snapshot.ias_kts)).collect();
+        assert_eq!(got, [(0, 0.0), (50, 1.0), (100, 2.0), (150, 3.0)]);
+    }
+
+    #[test]
+    fn controls_parse() {
+        assert!(matches!(parse_control(""), Some(Control::Step)));
+        assert!(matches!(parse_control("g 12.5"), Some(Control::Seek(d)) if d == Duration::from_millis(12_500)));
+        assert!(matches!(parse_control("s 2"), Some(Control::Speed(x)) if x == 2.0));
+        assert!(parse_control("s -1").is_none());
+        assert!(parse_control("x").is_none());
+    }
+}
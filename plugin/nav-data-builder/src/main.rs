@@ -413,7 +413,7 @@ pub fn parse_xplane_nav_dat(conn: &Connection, path: &Path) -> Result<()> {
             2  => ("NDB",     1_000),      // kHz → Hz
             3  => ("VOR",    10_000),      // 100kHz → Hz
             4  => ("ILS",    10_000),
-            5 | 6 | 7 | 8 => continue,
+            5..=8 => continue,
             9  => ("DME",    10_000),
             12 => ("RNAV",       0),
             13 => ("VOR-DME", 10_000),
@@ -620,7 +620,7 @@ fn parse_openaip_airspace_file(conn: &Connection, path: &Path) -> Result<()> {
                 let text = e.unescape().unwrap_or_default().to_string();
                 match cur_tag.as_str() {
                     "NAME" | "Name"         => name = text,
-                    "CATEGORY" | "Category" => if category.is_empty() { category = text.to_uppercase(); }
+                    "CATEGORY" | "Category" if category.is_empty() => category = text.to_uppercase(),
                     "COUNTRY" | "Country"   => country = text,
                     "POLYGON" | "Polygon"   => polygon = text,
                     "ALT" | "Altitude"      => {
@@ -4,6 +4,7 @@
 //! points below are only present in non-test builds; unit tests use `MockXplm`
 //! and call `EfbPlugin` methods directly.
 
+pub mod nmea_output;
 pub mod plugin;
 pub mod xplm_shim;
 
@@ -26,9 +27,29 @@ pub(crate) mod xplm_sys {
             inOffset:   c_int,
             inMax:      c_int,
         ) -> c_int;
+        pub fn XPLMGetDatavi(
+            inDataRef:  XPLMDataRef,
+            outValues:  *mut c_int,
+            inOffset:   c_int,
+            inMax:      c_int,
+        ) -> c_int;
+        pub fn XPLMGetDatab(
+            inDataRef:  XPLMDataRef,
+            outValue:   *mut c_void,
+            inOffset:   c_int,
+            inMaxBytes: c_int,
+        ) -> c_int;
         pub fn XPLMSetDataf(inDataRef: XPLMDataRef, inValue: c_float);
         pub fn XPLMSetDatai(inDataRef: XPLMDataRef, inValue: c_int);
         pub fn XPLMDebugString(inString: *const c_char);
+        pub fn XPLMLocalToWorld(
+            inX:          f64,
+            inY:          f64,
+            inZ:          f64,
+            outLatitude:  *mut f64,
+            outLongitude: *mut f64,
+            outAltitude:  *mut f64,
+        );
         pub fn XPLMRegisterFlightLoopCallback(
             inFlightLoop: Option<
                 unsafe extern "C" fn(f32, f32, c_int, *mut c_void) -> f32,
@@ -52,7 +73,7 @@ mod entry {
     use super::plugin::{EfbPlugin, DEFAULT_HZ, STREAM_PORT};
     use super::xplm_shim::RealXplm;
     use std::ffi::{c_int, c_void, CString};
-    use std::net::UdpSocket;
+    use std::net::{TcpListener, UdpSocket};
     use std::sync::{Mutex, OnceLock};
 
     static PLUGIN: OnceLock<Mutex<EfbPlugin>> = OnceLock::new();
@@ -100,7 +121,16 @@ mod entry {
         if let Some(plugin) = PLUGIN.get() {
             let mut p = plugin.lock().unwrap();
             p.find_handles();
-            p.start_command_server();
+            // EFB_CAPTURE=/path/to/session.efbcap records all traffic.
+            if let Some(path) = std::env::var_os("EFB_CAPTURE") {
+                if let Err(e) = p.start_capture(&path) {
+                    log(&format!("EFB: cannot start capture: {e}"));
+                }
+            }
+            p.enable(
+                |name| std::env::var(name).ok(),
+                TcpListener::bind(format!("0.0.0.0:{STREAM_PORT}")),
+            );
 
             super::xplm_sys::XPLMRegisterFlightLoopCallback(
                 Some(flight_loop_cb),
@@ -117,6 +147,12 @@ mod entry {
 
     #[no_mangle]
     pub unsafe extern "C" fn XPluginDisable() {
+        if let Some(plugin) = PLUGIN.get() {
+            if let Ok(mut p) = plugin.lock() {
+                let _ = p.stop_capture();
+                p.stop_nmea();
+            }
+        }
         super::xplm_sys::XPLMUnregisterFlightLoopCallback(
             Some(flight_loop_cb),
             std::ptr::null_mut(),
@@ -0,0 +1,298 @@
+//! Where NMEA sentences go: UDP datagrams, TCP clients or a pseudo-terminal.
+//!
+//! Configured with `EFB_NMEA`:
+//!
+//! | setting                     | output                                        |
+//! |-----------------------------|-----------------------------------------------|
+//! | `udp:ip[:port][,ip[:port]]` | one datagram per fix to each address          |
+//! | `tcp:[ip:]port`             | a server; every connected client gets the fix |
+//! | `pty[:/path/to/link]`       | a pseudo-terminal (Linux), optionally linked  |
+//!
+//! Ports default to [`NMEA_PORT`]. Nothing here blocks the flight loop for
+//! long: TCP clients that stall are dropped and a full terminal buffer
+//! drops sentences.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use efb_protocol::nmea::NMEA_PORT;
+
+use crate::plugin::TCP_WRITE_TIMEOUT;
+
+// ── Setting ──────────────────────────────────────────────────────────────────
+
+/// A parsed `EFB_NMEA` setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NmeaOutput {
+    Udp(Vec<SocketAddr>),
+    Tcp(SocketAddr),
+    /// A pseudo-terminal, with a symlink to it at the path if given.
+    Pty(Option<PathBuf>),
+}
+
+impl FromStr for NmeaOutput {
+    type Err = String;
+
+    /// The error is the part of the setting that does not parse.
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (kind, rest) = spec.trim().split_once(':').unwrap_or((spec.trim(), ""));
+        let addr = |s: &str| {
+            s.parse::<SocketAddr>()
+                .or_else(|_| s.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, NMEA_PORT)))
+                .map_err(|_| s.to_string())
+        };
+        match kind {
+            "udp" => {
+                let targets = rest
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(addr)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if targets.is_empty() { Err(spec.to_string()) } else { Ok(Self::Udp(targets)) }
+            }
+            "tcp" if rest.is_empty() => Ok(Self::Tcp(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), NMEA_PORT))),
+            "tcp" => rest
+                .parse::<u16>()
+                .map(|port| SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port))
+                .or_else(|_| addr(rest))
+                .map(Self::Tcp),
+            "pty" => Ok(Self::Pty((!rest.is_empty()).then(|| PathBuf::from(rest)))),
+            _ => Err(spec.to_string()),
+        }
+    }
+}
+
+// ── Sink ─────────────────────────────────────────────────────────────────────
+
+/// An open NMEA output.
+pub enum NmeaSink {
+    Udp { socket: UdpSocket, targets: Vec<SocketAddr> },
+    Tcp { listener: TcpListener, clients: Vec<TcpStream> },
+    Pty(Pty),
+}
+
+impl NmeaSink {
+    pub fn open(output: &NmeaOutput) -> io::Result<Self> {
+        match output {
+            NmeaOutput::Udp(targets) => {
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+                socket.set_broadcast(true)?;
+                Ok(Self::Udp { socket, targets: targets.clone() })
+            }
+            NmeaOutput::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                Ok(Self::Tcp { listener, clients: Vec::new() })
+            }
+            NmeaOutput::Pty(link) => Pty::open(link.as_deref()).map(Self::Pty),
+        }
+    }
+
+    /// Address of the TCP server, if this is one.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp { listener, .. } => listener.local_addr().ok(),
+            _ => None,
+        }
+    }
+
+    /// Path of the terminal readers open, if this is a pseudo-terminal.
+    pub fn pty_path(&self) -> Option<&Path> {
+        match self {
+            Self::Pty(pty) => Some(&pty.path),
+            _ => None,
+        }
+    }
+
+    /// Send one batch of sentences. Errors are per receiver and not reported.
+    pub fn send(&mut self, sentences: &[u8]) {
+        match self {
+            Self::Udp { socket, targets } => {
+                for &to in targets.iter() {
+                    let _ = socket.send_to(sentences, to);
+                }
+            }
+            Self::Tcp { listener, clients } => {
+                while let Ok((stream, _)) = listener.accept() {
+                    // Accepted sockets may inherit the listener's non-blocking mode.
+                    let _ = stream.set_nonblocking(false);
+                    let _ = stream.set_nodelay(true);
+                    let _ = stream.set_write_timeout(Some(TCP_WRITE_TIMEOUT));
+                    clients.push(stream);
+                }
+                clients.retain_mut(|c| c.write_all(sentences).is_ok());
+            }
+            Self::Pty(pty) => {
+                let _ = pty.master.write_all(sentences);
+            }
+        }
+    }
+}
+
+// ── Pseudo-terminal ──────────────────────────────────────────────────────────
+
+/// The master side of a raw, non-blocking pseudo-terminal.
+pub struct Pty {
+    master: File,
+    /// The slave device, e.g. `/dev/pts/3`, or the symlink to it.
+    path:   PathBuf,
+    slave:  PathBuf,
+    link:   Option<PathBuf>,
+}
+
+impl Pty {
+    #[cfg(target_os = "linux")]
+    fn open(link: Option<&Path>) -> io::Result<Self> {
+        use std::ffi::CStr;
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let check = |r: libc::c_int| if r < 0 { Err(io::Error::last_os_error()) } else { Ok(r) };
+        // SAFETY: plain libc calls on a descriptor owned by `master` from the
+        // moment it is created; `name` is NUL-terminated by ptsname_r.
+        let (master, slave_path) = unsafe {
+            let fd = check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_NONBLOCK))?;
+            let master = OwnedFd::from_raw_fd(fd);
+            check(libc::grantpt(fd))?;
+            check(libc::unlockpt(fd))?;
+            let mut name = [0 as libc::c_char; 64];
+            let r = libc::ptsname_r(fd, name.as_mut_ptr(), name.len());
+            if r != 0 {
+                return Err(io::Error::from_raw_os_error(r));
+            }
+            let slave_path = PathBuf::from(CStr::from_ptr(name.as_ptr()).to_str().map_err(io::Error::other)?);
+
+            // Raw mode, so the terminal neither echoes nor rewrites line endings.
+            let slave = File::options()
+                .read(true)
+                .write(true)
+                .custom_flags(libc::O_NOCTTY)
+                .open(&slave_path)?;
+            let mut tio = std::mem::zeroed::<libc::termios>();
+            check(libc::tcgetattr(slave.as_raw_fd(), &mut tio))?;
+            libc::cfmakeraw(&mut tio);
+            check(libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &tio))?;
+            (File::from(master), slave_path)
+        };
+
+        let path = match link {
+            Some(link) => {
+                // Only a symlink, e.g. left by an earlier session, is replaced.
+                match std::fs::symlink_metadata(link) {
+                    Ok(meta) if meta.file_type().is_symlink() => std::fs::remove_file(link)?,
+                    Ok(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("{} exists and is not a symlink", link.display()),
+                        ));
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+                std::os::unix::fs::symlink(&slave_path, link)?;
+                link.to_path_buf()
+            }
+            None => slave_path.clone(),
+        };
+        Ok(Pty { master, path, slave: slave_path, link: link.map(Path::to_path_buf) })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn open(_link: Option<&Path>) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "pseudo-terminals are only supported on Linux"))
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        // Leave the link alone if something else has replaced it since.
+        if let Some(link) = &self.link {
+            if std::fs::read_link(link).is_ok_and(|target| target == self.slave) {
+                let _ = std::fs::remove_file(link);
+            }
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn settings_parse_with_default_ports() {
+        let any = |port| SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port);
+        assert_eq!(
+            "udp:192.168.1.255, 10.0.0.7:2000".parse(),
+            Ok(NmeaOutput::Udp(vec!["192.168.1.255:10110".parse().unwrap(), "10.0.0.7:2000".parse().unwrap()])),
+        );
+        assert_eq!("tcp".parse(), Ok(NmeaOutput::Tcp(any(NMEA_PORT))));
+        assert_eq!("tcp:2947".parse(), Ok(NmeaOutput::Tcp(any(2947))));
+        assert_eq!("tcp:127.0.0.1".parse(), Ok(NmeaOutput::Tcp("127.0.0.1:10110".parse().unwrap())));
+        assert_eq!("pty".parse(), Ok(NmeaOutput::Pty(None)));
+        assert_eq!("pty:/tmp/gps".parse(), Ok(NmeaOutput::Pty(Some("/tmp/gps".into()))));
+        assert_eq!("udp:".parse::<NmeaOutput>(), Err("udp:".to_string()));
+        assert_eq!("udp:ipad.local".parse::<NmeaOutput>(), Err("ipad.local".to_string()));
+        assert_eq!("serial".parse::<NmeaOutput>(), Err("serial".to_string()));
+    }
+
+    #[test]
+    fn tcp_clients_receive_sentences() {
+        let mut sink = NmeaSink::open(&"tcp:127.0.0.1:0".parse().unwrap()).unwrap();
+        let client = TcpStream::connect(sink.local_addr().unwrap()).unwrap();
+        // The connection is accepted on the next send.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        sink.send(b"$PGRMZ,93,f,3*21\r\n");
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        assert_eq!(line, "$PGRMZ,93,f,3*21\r\n");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pty_readers_receive_raw_sentences() {
+        let link = std::env::temp_dir().join(format!("efb-nmea-test-{}", std::process::id()));
+        let mut sink = NmeaSink::open(&NmeaOutput::Pty(Some(link.clone()))).unwrap();
+        assert_eq!(sink.pty_path(), Some(link.as_path()));
+        let reader = File::open(&link).unwrap();
+        sink.send(b"$PGRMZ,93,f,3*21\r\n");
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).unwrap();
+        // Raw mode keeps the CR.
+        assert_eq!(line, "$PGRMZ,93,f,3*21\r\n");
+        drop(sink);
+        assert!(!link.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pty_link_only_replaces_symlinks() {
+        let link = std::env::temp_dir().join(format!("efb-nmea-link-test-{}", std::process::id()));
+        let output = NmeaOutput::Pty(Some(link.clone()));
+
+        // A regular file at the link path is kept and reported.
+        std::fs::write(&link, b"flight plan").unwrap();
+        let err = NmeaSink::open(&output).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&link).unwrap(), b"flight plan");
+        std::fs::remove_file(&link).unwrap();
+
+        // A stale symlink is replaced.
+        std::os::unix::fs::symlink("/nonexistent", &link).unwrap();
+        let sink = NmeaSink::open(&output).unwrap();
+        assert!(File::open(&link).is_ok());
+
+        // A link pointed elsewhere in the meantime survives the sink.
+        std::fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink("/nonexistent", &link).unwrap();
+        drop(sink);
+        assert_eq!(std::fs::read_link(&link).unwrap(), Path::new("/nonexistent"));
+        std::fs::remove_file(&link).unwrap();
+    }
+}
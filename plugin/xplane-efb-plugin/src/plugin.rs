@@ -3,15 +3,43 @@
 //! This module is free of any XPLM types so it can be fully unit-tested via
 //! the `MockXplm` shim.
 
-use std::net::{SocketAddr, UdpSocket};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::Path;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::{mpsc, Arc};
-use std::time::{Duration, Instant};
-
-use dataref_schema::SimSnapshot;
-use efb_protocol::{decode_packet, encode_sim_data, PacketType};
-use serde::Deserialize;
-
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dataref_schema::{paths, SimSnapshot};
+use efb_protocol::auth::{decode_authenticated, sign_packet, AUTH_TRAILER_LEN};
+use efb_protocol::beacon::encode_beacon;
+use efb_protocol::command::{
+    decode_request, decode_request_json, encode_command_result, peek_request_id, CommandRequest,
+    CommandResult, CommandStatus,
+};
+use efb_protocol::foreflight::{encode_xatt, encode_xgps, encode_xtraffic, FOREFLIGHT_PORT};
+use efb_protocol::fragment::FRAGMENT_PREFIX_LEN;
+use efb_protocol::gdl90::{
+    self, encode_ahrs, encode_geo_altitude, encode_heartbeat, encode_ownship, GDL90_PORT,
+};
+use efb_protocol::groups::group_changed;
+use efb_protocol::handshake::{decode_hello, encode_hello_ack};
+use efb_protocol::nmea::{encode_sentences, UtcTime};
+use efb_protocol::schema::encode_schema;
+use efb_protocol::{
+    caps, decode_packet_in, encode_group, encode_sim_data_into, fragment_packet, inflate_payload,
+    upgrade_packet, Beacon, CaptureWriter, Command, DeltaEncoder, Direction, FieldGroup, Hello,
+    HelloAck, LinkStats, PacketHeader, PacketType, PairingKey, ProtocolError, Radio, Reassembler,
+    ReplayWindow, Schema, SequenceTracker, Signer, StampClock, ThreatLevel, Timestamps, TrafficTarget,
+    VersionRange, BEACON_ADDR, DEFAULT_MTU, HEADER_V2_LEN, MIN_PROTOCOL_VERSION, SIM_DATA_PACKET_LEN,
+};
+use efb_protocol::stream::{write_frame, FrameReader};
+use efb_protocol::traffic::encode_traffic;
+
+use crate::nmea_output::{NmeaOutput, NmeaSink};
 use crate::xplm_shim::{DataRefHandle, XplmApi};
 
 // ── Constants ─────────────────────────────────────────────────────────────────
@@ -20,75 +48,39 @@ pub const STREAM_PORT: u16 = 49100;
 pub const DEFAULT_HZ: u8   = 20;
 pub const MAX_HZ: u8        = 60;
 pub const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
-
-// ── X-Plane dataref paths ─────────────────────────────────────────────────────
-
-mod paths {
-    // Position
-    pub const LATITUDE:       &str = "sim/flightmodel/position/latitude";
-    pub const LONGITUDE:      &str = "sim/flightmodel/position/longitude";
-    pub const ELEVATION_M:    &str = "sim/flightmodel/position/elevation";
-    pub const GROUNDSPEED_MS: &str = "sim/flightmodel/position/groundspeed";
-    // Attitude
-    pub const PITCH_DEG:        &str = "sim/flightmodel/position/theta";
-    pub const ROLL_DEG:         &str = "sim/flightmodel/position/phi";
-    pub const MAG_HEADING_DEG:  &str = "sim/flightmodel/position/mag_psi";
-    pub const GROUND_TRACK_DEG: &str = "sim/flightmodel/position/hpath";
-    // Air data
-    pub const IAS_KTS:           &str = "sim/flightmodel/position/indicated_airspeed";
-    pub const TRUE_AIRSPEED_MS:  &str = "sim/flightmodel/position/true_airspeed";
-    pub const VVI_FPM:           &str = "sim/flightmodel/position/vh_ind_fpm";
-    pub const TURN_RATE_DEG_SEC: &str = "sim/cockpit2/gauges/indicators/turn_rate_heading_deg_pilot";
-    pub const SLIP_DEG:          &str = "sim/cockpit/gyros/slip_deg";
-    pub const OAT_DEGC:          &str = "sim/weather/temperature_ambient_c";
-    pub const BAROMETER_INHG:    &str = "sim/cockpit2/gauges/actuators/barometer_setting_in_hg_pilot";
-    // Engine arrays (read index 0 for engine 1)
-    pub const ENGINE_RPM:        &str = "sim/cockpit2/engine/indicators/engine_speed_rpm";
-    pub const MANIFOLD_INHG:     &str = "sim/cockpit2/engine/indicators/manifold_pressure_inhg";
-    pub const FUEL_FLOW_KG_SEC:  &str = "sim/cockpit2/engine/indicators/fuel_flow_kg_sec";
-    pub const OIL_PRESS_PSI:     &str = "sim/cockpit2/engine/indicators/oil_pressure_psi";
-    pub const OIL_TEMP_DEGC:     &str = "sim/cockpit2/engine/indicators/oil_temp_deg_c";
-    pub const EGT_DEGC:          &str = "sim/cockpit2/engine/indicators/EGT_deg_c";
-    pub const FUEL_QTY_KG:       &str = "sim/flightmodel/weight/m_fuel";
-    pub const BUS_VOLTS:         &str = "sim/cockpit2/electrical/bus_volts";
-    pub const BATTERY_AMPS:      &str = "sim/cockpit2/electrical/battery_amps_total";
-    pub const SUCTION_INHG:      &str = "sim/cockpit2/gauges/indicators/airspeed_vacuum_in_hg_pilot";
-    // Navigation
-    pub const NAV1_HDEF_DOT:   &str = "sim/cockpit2/radios/indicators/nav1_hdef_dots_pilot";
-    pub const NAV1_VDEF_DOT:   &str = "sim/cockpit2/radios/indicators/nav1_vdef_dots_pilot";
-    pub const NAV1_OBS_DEG:    &str = "sim/cockpit/radios/nav1_course_degm";
-    pub const GPS_DIST_NM:     &str = "sim/cockpit2/radios/indicators/gps_dme_distance_nm";
-    pub const GPS_BEARING_DEG: &str = "sim/cockpit2/radios/indicators/gps_bearing_deg_mag";
-    // Autopilot
-    pub const AP_STATE_FLAGS:     &str = "sim/cockpit/autopilot/autopilot_state";
-    pub const FD_PITCH_DEG:       &str = "sim/cockpit2/autopilot/flight_director_pitch_deg";
-    pub const FD_ROLL_DEG:        &str = "sim/cockpit2/autopilot/flight_director_roll_deg";
-    pub const AP_HEADING_BUG_DEG: &str = "sim/cockpit/autopilot/heading_mag";
-    pub const AP_ALTITUDE_FT:     &str = "sim/cockpit/autopilot/altitude";
-    pub const AP_VS_FPM:          &str = "sim/cockpit/autopilot/vertical_velocity";
-    // Radios
-    pub const COM1_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/com1_frequency_hz";
-    pub const COM1_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/com1_standby_frequency_hz";
-    pub const COM2_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/com2_frequency_hz";
-    pub const NAV1_ACTIVE_HZ:    &str = "sim/cockpit2/radios/actuators/nav1_frequency_hz";
-    pub const NAV1_STANDBY_HZ:   &str = "sim/cockpit2/radios/actuators/nav1_standby_frequency_hz";
-    pub const TRANSPONDER_CODE:  &str = "sim/cockpit/radios/transponder_code";
-    pub const TRANSPONDER_MODE:  &str = "sim/cockpit/radios/transponder_mode";
-    // Markers
-    pub const OUTER_MARKER:  &str = "sim/cockpit2/annunciators/outer_marker";
-    pub const MIDDLE_MARKER: &str = "sim/cockpit2/annunciators/middle_marker";
-    pub const INNER_MARKER:  &str = "sim/cockpit2/annunciators/inner_marker";
-    // Weather
-    pub const WIND_DIR_DEG:  &str = "sim/weather/wind_direction_degt";
-    pub const WIND_SPEED_KT: &str = "sim/weather/wind_speed_kt";
-    // Traffic (TCAS)
-    pub const TRAFFIC_LAT:   &str = "sim/cockpit2/tcas/targets/position/lat";
-    pub const TRAFFIC_LON:   &str = "sim/cockpit2/tcas/targets/position/lon";
-    pub const TRAFFIC_ELE_M: &str = "sim/cockpit2/tcas/targets/position/ele";
-    pub const TRAFFIC_COUNT: &str = "sim/cockpit2/tcas/targets/N_targets_max";
-    // HSI source
-    pub const HSI_SOURCE: &str = "sim/cockpit2/radios/actuators/HSI_source_select_pilot";
-}
+/// A TCP tablet that cannot take a packet within this time is disconnected,
+/// so a stalled stream never blocks the flight loop for long.
+pub const TCP_WRITE_TIMEOUT: Duration = Duration::from_millis(20);
+/// How often a Beacon is sent while no tablet is connected.
+pub const BEACON_INTERVAL: Duration = Duration::from_secs(1);
+/// Send interval of the nav group to tablets with `caps::FIELD_GROUPS`
+/// (5 Hz). The fast group goes out every tick.
+pub const NAV_GROUP_INTERVAL: Duration = Duration::from_millis(200);
+/// Send interval of the slow group (engine, radios, weather).
+pub const SLOW_GROUP_INTERVAL: Duration = Duration::from_secs(1);
+/// Traffic is sent when it changes, and at least this often so a lost
+/// packet is eventually repaired.
+pub const TRAFFIC_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// Send interval of Traffic packets to tablets with `caps::EXTENDED_TRAFFIC`.
+pub const TRAFFIC_INTERVAL: Duration = Duration::from_millis(500);
+/// Send interval of GDL 90 Heartbeat, Ownship and Traffic reports.
+pub const GDL90_INTERVAL: Duration = Duration::from_secs(1);
+/// Send interval of the GDL 90 ForeFlight AHRS message (5 Hz).
+pub const GDL90_AHRS_INTERVAL: Duration = Duration::from_millis(200);
+/// Send interval of ForeFlight XGPS and XTRAFFIC messages.
+pub const FOREFLIGHT_INTERVAL: Duration = Duration::from_secs(1);
+/// Send interval of ForeFlight XATT messages (5 Hz).
+pub const FOREFLIGHT_ATT_INTERVAL: Duration = Duration::from_millis(200);
+/// Send interval of NMEA sentences, the rate of a typical GPS receiver.
+pub const NMEA_INTERVAL: Duration = Duration::from_secs(1);
+/// Peers with link statistics or a session at once. Beyond this the one
+/// heard from least recently is forgotten, so spoofed sources cannot grow
+/// the plugin's state.
+pub const MAX_PEERS: usize = 16;
+/// Slots in the XP12 TCAS target arrays; slot 0 is the user's aircraft.
+const TCAS_SLOTS: usize = 64;
+/// Bytes per target in `sim/cockpit2/tcas/targets/flight_id`.
+const FLIGHT_ID_LEN: usize = 8;
 
 // ── DataRefHandles ────────────────────────────────────────────────────────────
 
@@ -147,26 +139,97 @@ pub struct DataRefHandles {
     pub traffic_lon:       Option<DataRefHandle>,
     pub traffic_ele_m:     Option<DataRefHandle>,
     pub traffic_count:     Option<DataRefHandle>,
+    pub tcas_num_acf:      Option<DataRefHandle>,
+    pub tcas_mode_s_id:    Option<DataRefHandle>,
+    pub tcas_flight_id:    Option<DataRefHandle>,
+    pub tcas_x:            Option<DataRefHandle>,
+    pub tcas_y:            Option<DataRefHandle>,
+    pub tcas_z:            Option<DataRefHandle>,
+    pub tcas_vx:           Option<DataRefHandle>,
+    pub tcas_vy:           Option<DataRefHandle>,
+    pub tcas_vz:           Option<DataRefHandle>,
+    pub tcas_on_ground:    Option<DataRefHandle>,
+    pub local_x:           Option<DataRefHandle>,
+    pub local_y:           Option<DataRefHandle>,
+    pub local_z:           Option<DataRefHandle>,
+    pub local_vx:          Option<DataRefHandle>,
+    pub local_vy:          Option<DataRefHandle>,
+    pub local_vz:          Option<DataRefHandle>,
     pub hsi_source:        Option<DataRefHandle>,
+    pub acf_icao:          Option<DataRefHandle>,
+    pub acf_tailnum:       Option<DataRefHandle>,
+    pub sim_time_sec:      Option<DataRefHandle>,
+    pub zulu_time_sec:     Option<DataRefHandle>,
+    pub local_time_sec:    Option<DataRefHandle>,
+    pub local_date_days:   Option<DataRefHandle>,
 }
 
 // ── Internal message bus (flight-loop ↔ command-server thread) ────────────────
 
 enum InternalMsg {
-    Ack(SocketAddr),
-    Command(Vec<u8>),   // raw JSON payload
+    Ack(SocketAddr, u32, Instant), // from, header sequence, arrival time
+    Hello(SocketAddr, Hello),
+    Command(SocketAddr, CommandRequest),
+    /// A command that failed to decode, with its id if one was readable.
+    RejectedCommand(SocketAddr, Option<u32>, ProtocolError),
     Reload,
 }
 
-// ── JSON command format ───────────────────────────────────────────────────────
+/// A tablet that negotiated with Hello: its session parameters and the
+/// streaming state that depends on them.
+struct Session {
+    ack:           HelloAck,
+    delta_encoder: DeltaEncoder,
+    /// When each field group was last sent, and the snapshot it was taken
+    /// from, for tablets streaming with `caps::FIELD_GROUPS`.
+    group_sent:    [Option<(Instant, SimSnapshot)>; FieldGroup::COUNT],
+    /// When the last Traffic packet went out, for `caps::EXTENDED_TRAFFIC`.
+    traffic_sent:  Option<Instant>,
+}
+
+impl Session {
+    fn new(ack: HelloAck) -> Self {
+        Session {
+            ack,
+            delta_encoder: DeltaEncoder::default(),
+            group_sent: Default::default(),
+            traffic_sent: None,
+        }
+    }
+}
+
+/// The connected TCP tablet (at most one at a time).
+struct TcpClient {
+    addr:   SocketAddr,
+    local:  SocketAddr,
+    stream: TcpStream,
+}
 
-#[derive(Deserialize)]
-#[serde(tag = "cmd")]
-enum Command {
-    #[serde(rename = "set_dataref")]
-    SetDataref { path: String, value: f64 },
-    #[serde(rename = "swap_freq")]
-    SwapFreq { radio: String },
+/// Pairing key and its replay window. Every receive path holds a clone and so
+/// shares the window: a packet accepted once — over UDP or TCP, from any
+/// address, on any connection — is not accepted again.
+#[derive(Clone)]
+struct Auth {
+    key:    PairingKey,
+    window: Arc<Mutex<ReplayWindow>>,
+}
+
+/// Packet capture shared by the flight loop and the server threads.
+type SharedCapture = Arc<Mutex<Option<CaptureWriter<BufWriter<File>>>>>;
+
+/// Header versions accepted from each tablet that negotiated with Hello,
+/// shared by the flight loop and the server threads.
+type PeerVersions = Arc<Mutex<HashMap<SocketAddr, VersionRange>>>;
+
+/// Append a packet to the capture, if one is running. A failed write stops
+/// the capture rather than the plugin.
+fn capture_packet(capture: &SharedCapture, dir: Direction, src: SocketAddr, dst: SocketAddr, data: &[u8]) {
+    let Ok(mut slot) = capture.lock() else { return };
+    if let Some(w) = slot.as_mut() {
+        if w.record(dir, src, dst, data).is_err() {
+            *slot = None;
+        }
+    }
 }
 
 // ── EfbPlugin ─────────────────────────────────────────────────────────────────
@@ -181,6 +244,51 @@ pub struct EfbPlugin {
     handles:          DataRefHandles,
     streaming_rate_hz: u8,
     cmd_rx:           Option<mpsc::Receiver<InternalMsg>>,
+    cmd_tx:           Option<mpsc::Sender<InternalMsg>>,
+    /// Negotiated sessions per tablet; tablets absent here are v1.
+    sessions:         HashMap<SocketAddr, Session>,
+    /// The version range of each session, for decoding on the server threads.
+    peer_versions:    PeerVersions,
+    /// Outbound datagrams larger than this are sent as fragments.
+    mtu:              usize,
+    reassembler:      Reassembler,
+    /// When set, inbound packets must be signed with this key (and not
+    /// replayed); outbound packets are signed with it too.
+    auth:             Option<Auth>,
+    /// Stamps for signing outbound packets.
+    stamps:           StampClock,
+    /// Link statistics per tablet, fed by inbound ACK sequence numbers.
+    ack_trackers:     HashMap<SocketAddr, SequenceTracker>,
+    /// When each peer in `ack_trackers` or `sessions` last sent an ACK or
+    /// Hello; peers silent for `WATCHDOG_TIMEOUT` are forgotten.
+    last_heard:       HashMap<SocketAddr, Instant>,
+    /// Reused SimData datagram buffer, so streaming does not allocate per tick.
+    tx_buf:           [u8; SIM_DATA_PACKET_LEN],
+    /// Tablet connected over TCP; packets addressed to it use the stream.
+    tcp_client:       Arc<Mutex<Option<TcpClient>>>,
+    /// Every packet sent and received, when recording (see `start_capture`).
+    capture:          SharedCapture,
+    /// Where Beacons are sent while no tablet is connected.
+    beacon_targets:   Vec<SocketAddr>,
+    last_beacon:      Option<Instant>,
+    /// Where GDL 90 is streamed for third-party EFBs; empty when off.
+    gdl90_targets:    Vec<SocketAddr>,
+    /// When the GDL 90 reports and the AHRS message were last sent.
+    gdl90_sent:       Option<Instant>,
+    gdl90_ahrs_sent:  Option<Instant>,
+    /// Where the ForeFlight simulator protocol is sent; empty when off.
+    foreflight_targets:  Vec<SocketAddr>,
+    /// When XGPS/XTRAFFIC and XATT were last sent.
+    foreflight_sent:     Option<Instant>,
+    foreflight_att_sent: Option<Instant>,
+    /// Where NMEA sentences go for moving-map software; `None` when off.
+    nmea:             Option<NmeaSink>,
+    nmea_sent:        Option<Instant>,
+    /// Port of the TCP transport, once `start_tcp_server` is running.
+    tcp_port:         Option<u16>,
+    hostname:         String,
+    /// Zero point of the `sent_us` timestamp in v2 headers.
+    started:          Instant,
 }
 
 impl EfbPlugin {
@@ -195,7 +303,113 @@ impl EfbPlugin {
             handles: DataRefHandles::default(),
             streaming_rate_hz: DEFAULT_HZ,
             cmd_rx: None,
+            cmd_tx: None,
+            sessions: HashMap::new(),
+            peer_versions: Arc::new(Mutex::new(HashMap::new())),
+            mtu: DEFAULT_MTU,
+            reassembler: Reassembler::default(),
+            auth: None,
+            stamps: StampClock::new(),
+            ack_trackers: HashMap::new(),
+            last_heard: HashMap::new(),
+            tx_buf: [0; SIM_DATA_PACKET_LEN],
+            tcp_client: Arc::new(Mutex::new(None)),
+            capture: Arc::new(Mutex::new(None)),
+            beacon_targets: vec![BEACON_ADDR],
+            last_beacon: None,
+            gdl90_targets: Vec::new(),
+            gdl90_sent: None,
+            gdl90_ahrs_sent: None,
+            foreflight_targets: Vec::new(),
+            foreflight_sent: None,
+            foreflight_att_sent: None,
+            nmea: None,
+            nmea_sent: None,
+            tcp_port: None,
+            hostname: local_hostname(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Set the largest datagram sent unfragmented (default [`DEFAULT_MTU`]).
+    pub fn set_mtu(&mut self, mtu: usize) {
+        self.mtu = mtu.max(HEADER_V2_LEN + FRAGMENT_PREFIX_LEN + AUTH_TRAILER_LEN + 1);
+    }
+
+    /// Require all traffic to be authenticated with `key` (`None` disables).
+    /// Packets signed before this call are rejected as replays.
+    ///
+    /// Must be called before [`start_command_server`](Self::start_command_server)
+    /// and [`start_tcp_server`](Self::start_tcp_server) for the receive threads
+    /// to enforce it; [`enable`](Self::enable) does so from `EFB_PAIRING_CODE`.
+    pub fn set_pairing_key(&mut self, key: Option<PairingKey>) {
+        self.auth = key.map(|key| Auth { key, window: Arc::new(Mutex::new(ReplayWindow::new())) });
+    }
+
+    /// Send discovery Beacons to `targets` instead of [`BEACON_ADDR`], e.g. a
+    /// subnet broadcast address on networks that filter multicast. An empty
+    /// list disables the beacon.
+    pub fn set_beacon_targets(&mut self, targets: Vec<SocketAddr>) {
+        self.beacon_targets = targets;
+    }
+
+    /// Also stream GDL 90 to `targets`, typically a subnet broadcast address
+    /// on [`GDL90_PORT`](gdl90::GDL90_PORT), for third-party EFBs. GDL 90
+    /// receivers never answer, so the stream ignores the watchdog. An empty
+    /// list turns it off.
+    pub fn set_gdl90_targets(&mut self, targets: Vec<SocketAddr>) {
+        if !targets.is_empty() {
+            let _ = self.udp_socket.set_broadcast(true);
         }
+        self.gdl90_targets = targets;
+        self.gdl90_sent = None;
+        self.gdl90_ahrs_sent = None;
+    }
+
+    /// Also send the ForeFlight simulator protocol to `targets`, typically a
+    /// broadcast address on port 49002
+    /// ([`FOREFLIGHT_PORT`](efb_protocol::foreflight::FOREFLIGHT_PORT)).
+    /// Like GDL 90, it ignores the watchdog. An empty list turns it off.
+    pub fn set_foreflight_targets(&mut self, targets: Vec<SocketAddr>) {
+        if !targets.is_empty() {
+            let _ = self.udp_socket.set_broadcast(true);
+        }
+        self.foreflight_targets = targets;
+        self.foreflight_sent = None;
+        self.foreflight_att_sent = None;
+    }
+
+    /// Also send NMEA 0183 sentences to `output`, replacing any NMEA output
+    /// already running. Like GDL 90, the sentences ignore the watchdog.
+    pub fn start_nmea(&mut self, output: &NmeaOutput) -> io::Result<&NmeaSink> {
+        self.nmea_sent = None;
+        Ok(self.nmea.insert(NmeaSink::open(output)?))
+    }
+
+    pub fn stop_nmea(&mut self) {
+        self.nmea = None;
+    }
+
+    /// Record every packet sent and received to an `.efbcap` file at `path`,
+    /// replacing any capture already running.
+    pub fn start_capture(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = CaptureWriter::new(BufWriter::new(File::create(path)?))?;
+        if let Some(mut old) = self.capture.lock().unwrap().replace(writer) {
+            old.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Stop recording and flush the capture file.
+    pub fn stop_capture(&mut self) -> io::Result<()> {
+        match self.capture.lock().unwrap().take() {
+            Some(mut w) => w.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        self.udp_socket.local_addr().unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)))
     }
 
     // ── Handle caching ────────────────────────────────────────────────────────
@@ -263,7 +477,29 @@ impl EfbPlugin {
         find!(traffic_lon,       paths::TRAFFIC_LON);
         find!(traffic_ele_m,     paths::TRAFFIC_ELE_M);
         find!(traffic_count,     paths::TRAFFIC_COUNT);
+        find!(tcas_num_acf,      paths::TCAS_NUM_ACF);
+        find!(tcas_mode_s_id,    paths::TCAS_MODE_S_ID);
+        find!(tcas_flight_id,    paths::TCAS_FLIGHT_ID);
+        find!(tcas_x,            paths::TCAS_X);
+        find!(tcas_y,            paths::TCAS_Y);
+        find!(tcas_z,            paths::TCAS_Z);
+        find!(tcas_vx,           paths::TCAS_VX);
+        find!(tcas_vy,           paths::TCAS_VY);
+        find!(tcas_vz,           paths::TCAS_VZ);
+        find!(tcas_on_ground,    paths::TCAS_ON_GROUND);
+        find!(local_x,           paths::LOCAL_X);
+        find!(local_y,           paths::LOCAL_Y);
+        find!(local_z,           paths::LOCAL_Z);
+        find!(local_vx,          paths::LOCAL_VX);
+        find!(local_vy,          paths::LOCAL_VY);
+        find!(local_vz,          paths::LOCAL_VZ);
         find!(hsi_source,        paths::HSI_SOURCE);
+        find!(acf_icao,          paths::ACF_ICAO);
+        find!(acf_tailnum,       paths::ACF_TAILNUM);
+        find!(sim_time_sec,      paths::SIM_TIME_SEC);
+        find!(zulu_time_sec,     paths::ZULU_TIME_SEC);
+        find!(local_time_sec,    paths::LOCAL_TIME_SEC);
+        find!(local_date_days,   paths::LOCAL_DATE_DAYS);
     }
 
     // ── Snapshot assembly ─────────────────────────────────────────────────────
@@ -369,6 +605,69 @@ impl EfbPlugin {
         }
     }
 
+    /// Read the TCAS targets for the Traffic packet. Positions come from the
+    /// local-coordinate arrays, converted to f64 lat/lon by X-Plane; the
+    /// threat level is worked out here since XP12 does not publish one.
+    pub fn read_traffic(&self) -> Vec<TrafficTarget> {
+        let n = self.handles.tcas_num_acf
+            .map_or(0, |h| self.xplm.get_int(h).max(0) as usize)
+            .min(TCAS_SLOTS - 1);
+        if n == 0 {
+            return Vec::new();
+        }
+        // Slot 0 is the user's aircraft; targets start at slot 1.
+        let gfa = |h: Option<DataRefHandle>| {
+            let mut out = vec![0f32; n];
+            if let Some(h) = h { self.xplm.get_float_array(h, 1, &mut out); }
+            out
+        };
+        let gia = |h: Option<DataRefHandle>| {
+            let mut out = vec![0i32; n];
+            if let Some(h) = h { self.xplm.get_int_array(h, 1, &mut out); }
+            out
+        };
+        let (x, y, z) = (gfa(self.handles.tcas_x), gfa(self.handles.tcas_y), gfa(self.handles.tcas_z));
+        let (vx, vy, vz) = (gfa(self.handles.tcas_vx), gfa(self.handles.tcas_vy), gfa(self.handles.tcas_vz));
+        let mode_s = gia(self.handles.tcas_mode_s_id);
+        let on_ground = gia(self.handles.tcas_on_ground);
+        let mut flight_ids = vec![0u8; n * FLIGHT_ID_LEN];
+        if let Some(h) = self.handles.tcas_flight_id {
+            self.xplm.get_bytes(h, FLIGHT_ID_LEN, &mut flight_ids);
+        }
+
+        let gd = |h: Option<DataRefHandle>| h.map_or(0.0_f64, |h| self.xplm.get_double(h));
+        let gf = |h: Option<DataRefHandle>| h.map_or(0.0_f32, |h| self.xplm.get_float(h));
+        let own_pos = [gd(self.handles.local_x), gd(self.handles.local_y), gd(self.handles.local_z)];
+        let own_vel = [gf(self.handles.local_vx), gf(self.handles.local_vy), gf(self.handles.local_vz)];
+
+        (0..n)
+            .map(|i| {
+                let pos = [x[i] as f64, y[i] as f64, z[i] as f64];
+                let (latitude, longitude, altitude_m) = self.xplm.local_to_world(pos[0], pos[1], pos[2]);
+                let id = &flight_ids[i * FLIGHT_ID_LEN..(i + 1) * FLIGHT_ID_LEN];
+                let id = &id[..id.iter().position(|&b| b == 0).unwrap_or(id.len())];
+                let on_ground = on_ground[i] != 0;
+                TrafficTarget {
+                    icao_address:       mode_s[i] as u32 & 0x00FF_FFFF,
+                    callsign:           String::from_utf8_lossy(id).trim().to_string(),
+                    latitude,
+                    longitude,
+                    altitude_m,
+                    // Local +x is east and -z north.
+                    track_deg:          vx[i].atan2(-vz[i]).to_degrees().rem_euclid(360.0),
+                    groundspeed_kt:     vx[i].hypot(vz[i]) * 1.943_84,
+                    vertical_speed_fpm: vy[i] * 196.85,
+                    on_ground,
+                    threat:             tcas_threat(
+                        [pos[0] - own_pos[0], pos[1] - own_pos[1], pos[2] - own_pos[2]],
+                        [vx[i] - own_vel[0], vy[i] - own_vel[1], vz[i] - own_vel[2]],
+                        on_ground,
+                    ),
+                }
+            })
+            .collect()
+    }
+
     // ── Watchdog ──────────────────────────────────────────────────────────────
 
     /// Returns `true` if the tablet has sent an ACK within the watchdog window.
@@ -376,6 +675,11 @@ impl EfbPlugin {
         self.last_ack_time.elapsed() <= WATCHDOG_TIMEOUT
     }
 
+    /// Returns `true` if a tablet has ACKed and the watchdog has not tripped.
+    pub fn has_tablet(&self) -> bool {
+        self.tablet_addr.is_some() && self.is_streaming_active()
+    }
+
     // ── Flight loop tick ──────────────────────────────────────────────────────
 
     /// Called from the X-Plane flight loop callback.
@@ -386,21 +690,171 @@ impl EfbPlugin {
 
         // Drain any messages from the command server thread.
         self.drain_messages();
+        self.expire_peers(Instant::now());
+
+        // One read per tick, shared by the tablet stream and the feeds.
+        let snap = self.read_snapshot();
+
+        if !self.has_tablet() {
+            self.send_beacon_if_due(Instant::now());
+        }
+        if !self.gdl90_targets.is_empty() {
+            self.send_gdl90_if_due(&snap, Instant::now());
+        }
+        if !self.foreflight_targets.is_empty() {
+            self.send_foreflight_if_due(&snap, Instant::now());
+        }
+        if self.nmea.is_some() {
+            self.send_nmea_if_due(&snap, Instant::now());
+        }
 
         if !self.is_streaming_active() {
             return interval; // watchdog tripped — keep ticking but don't stream
         }
 
         if let Some(addr) = self.tablet_addr {
-            let snap = self.read_snapshot();
-            let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
-            let pkt = encode_sim_data(seq, &snap);
-            let _ = self.udp_socket.send_to(&pkt, addr);
+            let session = self.session(addr);
+            if session.is_some_and(|s| s.has(caps::FIELD_GROUPS)) {
+                self.send_due_groups(&snap, addr, Instant::now());
+            } else if session.is_some_and(|s| s.has(caps::DELTA)) {
+                let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+                let Some(s) = self.sessions.get_mut(&addr) else { return interval };
+                let pkt = s.delta_encoder.encode(seq, &snap);
+                self.send_packet(&pkt, addr);
+            } else {
+                let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+                let n = encode_sim_data_into(seq, &snap, &mut self.tx_buf);
+                self.send_packet(&self.tx_buf[..n], addr);
+            }
+            if session.is_some_and(|s| s.has(caps::EXTENDED_TRAFFIC)) {
+                self.send_traffic_if_due(addr, Instant::now());
+            }
+        }
+
+        // One flush per tick keeps a crash from losing more than a tick of capture.
+        if let Some(w) = self.capture.lock().unwrap().as_mut() {
+            let _ = w.flush();
         }
 
         interval
     }
 
+    /// Send each field group whose interval has elapsed; traffic also as
+    /// soon as it changes. Every group is due right after a (re)negotiation.
+    fn send_due_groups(&mut self, snap: &SimSnapshot, to: SocketAddr, now: Instant) {
+        for group in FieldGroup::ALL {
+            let Some(session) = self.sessions.get_mut(&to) else { return };
+            let sent = &mut session.group_sent[group as usize];
+            let due = match sent {
+                None => true,
+                Some((at, sent)) => {
+                    now.duration_since(*at) >= group_interval(group)
+                        || (group == FieldGroup::Traffic && group_changed(group, sent, snap))
+                }
+            };
+            if due {
+                *sent = Some((now, snap.clone()));
+                let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+                self.send_packet(&encode_group(seq, group, snap), to);
+            }
+        }
+    }
+
+    fn send_traffic_if_due(&mut self, to: SocketAddr, now: Instant) {
+        let Some(session) = self.sessions.get_mut(&to) else { return };
+        if session.traffic_sent.is_some_and(|at| now.duration_since(at) < TRAFFIC_INTERVAL) {
+            return;
+        }
+        session.traffic_sent = Some(now);
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        self.send_packet(&encode_traffic(seq, &self.read_traffic()), to);
+    }
+
+    /// Send the GDL 90 reports once a second and AHRS at 5 Hz. The frames
+    /// are not EFB packets, so they are kept out of the capture.
+    fn send_gdl90_if_due(&mut self, snap: &SimSnapshot, now: Instant) {
+        let due = |at: Option<Instant>, interval| at.is_none_or(|t| now.duration_since(t) >= interval);
+        let reports = due(self.gdl90_sent, GDL90_INTERVAL);
+        if !reports && !due(self.gdl90_ahrs_sent, GDL90_AHRS_INTERVAL) {
+            return;
+        }
+        let mut frames = Vec::new();
+        if reports {
+            let utc = self.handles.zulu_time_sec.map(|h| self.xplm.get_float(h) as u32);
+            let traffic = self.feed_traffic(snap);
+            frames.push(encode_heartbeat(utc));
+            frames.push(encode_ownship(snap, &self.byte_string(self.handles.acf_tailnum)));
+            frames.push(encode_geo_altitude(snap));
+            frames.extend(gdl90::encode_traffic(&traffic));
+            self.gdl90_sent = Some(now);
+        }
+        frames.push(encode_ahrs(snap));
+        self.gdl90_ahrs_sent = Some(now);
+        for frame in &frames {
+            for &to in &self.gdl90_targets {
+                let _ = self.udp_socket.send_to(frame, to);
+            }
+        }
+    }
+
+    /// Send XGPS and XTRAFFIC once a second and XATT at 5 Hz, one message
+    /// per datagram.
+    fn send_foreflight_if_due(&mut self, snap: &SimSnapshot, now: Instant) {
+        let due = |at: Option<Instant>, interval| at.is_none_or(|t| now.duration_since(t) >= interval);
+        let gps = due(self.foreflight_sent, FOREFLIGHT_INTERVAL);
+        if !gps && !due(self.foreflight_att_sent, FOREFLIGHT_ATT_INTERVAL) {
+            return;
+        }
+        let mut messages = Vec::new();
+        if gps {
+            messages.push(encode_xgps(snap));
+            messages.extend(encode_xtraffic(&self.feed_traffic(snap)));
+            self.foreflight_sent = Some(now);
+        }
+        messages.push(encode_xatt(snap));
+        self.foreflight_att_sent = Some(now);
+        for msg in &messages {
+            for &to in &self.foreflight_targets {
+                let _ = self.udp_socket.send_to(msg.as_bytes(), to);
+            }
+        }
+    }
+
+    /// Traffic for the third-party feeds: the full TCAS list where the sim
+    /// has one, else the positions in the snapshot.
+    fn feed_traffic(&self, snap: &SimSnapshot) -> Vec<TrafficTarget> {
+        match self.handles.tcas_num_acf {
+            Some(_) => self.read_traffic(),
+            None => TrafficTarget::from_snapshot(snap),
+        }
+    }
+
+    /// Send the NMEA sentences once a second, timed by the sim's clock.
+    /// X-Plane has no year, so that comes from the system clock, as does
+    /// everything if the time datarefs are missing.
+    fn send_nmea_if_due(&mut self, snap: &SimSnapshot, now: Instant) {
+        if self.nmea_sent.is_some_and(|at| now.duration_since(at) < NMEA_INTERVAL) {
+            return;
+        }
+        let unix = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64());
+        let system = UtcTime::from_unix(unix);
+        let h = &self.handles;
+        let utc = match (h.local_date_days, h.local_time_sec, h.zulu_time_sec, system.date) {
+            (Some(day), Some(local), Some(zulu), Some((_, _, year))) => UtcTime::from_sim(
+                year,
+                self.xplm.get_int(day),
+                self.xplm.get_float(local),
+                self.xplm.get_float(zulu),
+            ),
+            _ => system,
+        };
+        let sentences = encode_sentences(snap, utc);
+        if let Some(sink) = self.nmea.as_mut() {
+            sink.send(sentences.as_bytes());
+        }
+        self.nmea_sent = Some(now);
+    }
+
     fn drain_messages(&mut self) {
         let rx = match &self.cmd_rx {
             Some(rx) => {
@@ -417,12 +871,17 @@ impl EfbPlugin {
 
     fn handle_internal_msg(&mut self, msg: InternalMsg) {
         match msg {
-            InternalMsg::Ack(addr) => {
-                self.last_ack_time = Instant::now();
-                self.tablet_addr = Some(addr);
+            InternalMsg::Ack(addr, seq, at) => {
+                self.handle_ack(addr, seq, at);
+            }
+            InternalMsg::Hello(addr, hello) => {
+                self.handle_hello(addr, &hello);
             }
-            InternalMsg::Command(payload) => {
-                self.handle_command(&payload);
+            InternalMsg::Command(addr, req) => {
+                self.handle_command(addr, req);
+            }
+            InternalMsg::RejectedCommand(addr, id, e) => {
+                self.reject_command(addr, id, &e);
             }
             InternalMsg::Reload => {
                 self.find_handles();
@@ -434,60 +893,416 @@ impl EfbPlugin {
 
     /// Process a raw UDP datagram received from the tablet.
     ///
-    /// Malformed packets are silently dropped (never panics).
+    /// Malformed packets are silently dropped (never panics). Fragments are
+    /// buffered until the whole message has arrived.
     pub fn handle_incoming_packet(&mut self, buf: &[u8], from: SocketAddr) {
-        let result = decode_packet(buf);
-        match result {
-            Ok((_, PacketType::Ack, _)) => {
-                self.last_ack_time = Instant::now();
-                self.tablet_addr = Some(from);
+        capture_packet(&self.capture, Direction::Inbound, from, self.local_addr(), buf);
+        let versions = peer_versions(&self.peer_versions, from);
+        match decode_inbound(buf, versions, self.auth.as_ref()) {
+            Ok((hdr, PacketType::Fragment, fragment)) => {
+                match self.reassembler.push(from, fragment, Instant::now()) {
+                    Ok(Some((ptype, payload))) => {
+                        self.dispatch_packet(&hdr, ptype, &payload, from);
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.xplm.log(&format!("EFB: dropped fragment: {e}")),
+                }
             }
-            Ok((_, PacketType::CommandJson, payload)) => {
-                let payload = payload.to_vec();
-                self.handle_command(&payload);
+            Ok((hdr, ptype, payload)) => {
+                self.dispatch_packet(&hdr, ptype, payload, from);
             }
-            Ok((_, PacketType::Reload, _)) => {
+            Err(e) => {
+                self.xplm.log(&format!("EFB: dropped packet: {e}"));
+            }
+        }
+    }
+
+    /// `hdr` is the header of the datagram that delivered the message (the
+    /// last fragment for reassembled messages).
+    fn dispatch_packet(&mut self, hdr: &PacketHeader, ptype: PacketType, payload: &[u8], from: SocketAddr) {
+        let seq = hdr.sequence;
+        let payload = match inflate_payload(hdr, payload) {
+            Ok(payload) => payload,
+            Err(e) => return self.xplm.log(&format!("EFB: dropped packet: {e}")),
+        };
+        let payload = &*payload;
+        match ptype {
+            PacketType::Ack => {
+                self.handle_ack(from, seq, Instant::now());
+            }
+            PacketType::Hello => match decode_hello(payload) {
+                Ok(hello) => self.handle_hello(from, &hello),
+                Err(e) => self.xplm.log(&format!("EFB: dropped Hello: {e}")),
+            },
+            PacketType::CommandJson | PacketType::CommandBinary => {
+                match decode_any_command(ptype, payload) {
+                    Ok(req) => self.handle_command(from, req),
+                    Err(e) => self.reject_command(from, peek_request_id(ptype, payload), &e),
+                }
+            }
+            PacketType::Reload => {
                 self.find_handles();
             }
-            Ok((_, PacketType::SimData, _)) => {
-                // SimData is outbound only — ignore inbound
+            PacketType::SimData
+            | PacketType::Keyframe
+            | PacketType::Delta
+            | PacketType::HelloAck
+            | PacketType::Schema
+            | PacketType::Beacon
+            | PacketType::CommandResult
+            | PacketType::Group
+            | PacketType::Traffic
+            | PacketType::Fragment => {
+                // Outbound-only packet types — ignore inbound
             }
-            Err(e) => {
-                self.xplm.log(&format!("EFB: dropped packet: {e}"));
+        }
+    }
+
+    /// Send a framed datagram, fragmenting it if it exceeds the configured MTU
+    /// and signing each datagram when a pairing key is set.
+    fn send_packet(&self, pkt: &[u8], to: SocketAddr) {
+        let pkt = &*self.frame_for(pkt, to);
+        if self.send_tcp(pkt, to) {
+            return;
+        }
+        let trailer_len = if self.auth.is_some() { AUTH_TRAILER_LEN } else { 0 };
+        if trailer_len == 0 && pkt.len() <= self.mtu {
+            self.send_udp(pkt, to);
+            return;
+        }
+        let next_seq = || self.sequence.fetch_add(1, Ordering::Relaxed);
+        let dgrams = match fragment_packet(pkt, self.mtu - trailer_len, next_seq) {
+            Ok(dgrams) => dgrams,
+            Err(e) => return self.xplm.log(&format!("EFB: cannot send packet to {to}: {e}")),
+        };
+        for mut dgram in dgrams {
+            self.sign(&mut dgram);
+            self.send_udp(&dgram, to);
+        }
+    }
+
+    /// Append the auth trailer when a pairing key is set.
+    fn sign(&self, pkt: &mut Vec<u8>) {
+        if let Some(auth) = &self.auth {
+            sign_packet(pkt, &auth.key, Signer::Plugin, self.stamps.next());
+        }
+    }
+
+    /// Re-frame a v1 packet for the header version negotiated with `to`,
+    /// compressing it if that was negotiated too. HelloAck stays v1 so that
+    /// any tablet can read it.
+    fn frame_for<'a>(&self, pkt: &'a [u8], to: SocketAddr) -> Cow<'a, [u8]> {
+        let Some(session) = self.session(to).filter(|s| s.version >= 2) else {
+            return Cow::Borrowed(pkt);
+        };
+        if pkt.get(6) == Some(&(PacketType::HelloAck as u8)) {
+            return Cow::Borrowed(pkt);
+        }
+        match upgrade_packet(pkt, self.timestamps(), session.has(caps::COMPRESSION)) {
+            Ok(v2) => Cow::Owned(v2),
+            Err(_) => Cow::Borrowed(pkt),
+        }
+    }
+
+    /// v2 header timestamps for a packet sent now.
+    fn timestamps(&self) -> Timestamps {
+        let sim_time = self.handles.sim_time_sec.map_or(0.0, |h| self.xplm.get_float(h));
+        Timestamps {
+            sim_time_us: (f64::from(sim_time) * 1e6) as u64,
+            sent_us:     self.started.elapsed().as_micros() as u64,
+        }
+    }
+
+    fn send_udp(&self, dgram: &[u8], to: SocketAddr) {
+        capture_packet(&self.capture, Direction::Outbound, self.local_addr(), to, dgram);
+        let _ = self.udp_socket.send_to(dgram, to);
+    }
+
+    /// Write a packet to the TCP tablet if `to` is its address. Returns `false`
+    /// if `to` is not connected over TCP. A failed write drops the client.
+    fn send_tcp(&self, pkt: &[u8], to: SocketAddr) -> bool {
+        let Ok(mut slot) = self.tcp_client.lock() else { return false };
+        let Some(client) = slot.as_mut().filter(|c| c.addr == to) else { return false };
+        let mut send = |frame: &[u8]| {
+            capture_packet(&self.capture, Direction::Outbound, client.local, to, frame);
+            write_frame(&mut client.stream, frame)
+        };
+        let res = if self.auth.is_some() {
+            let mut signed = pkt.to_vec();
+            self.sign(&mut signed);
+            send(&signed)
+        } else {
+            send(pkt)
+        };
+        if let Err(e) = res {
+            self.xplm.log(&format!("EFB: TCP client {to} dropped: {e}"));
+            let _ = client.stream.shutdown(Shutdown::Both);
+            *slot = None;
+        }
+        true
+    }
+
+    /// Heartbeat from a tablet: reset the watchdog, update its link statistics
+    /// and stream to its address. A tablet seen for the first time is sent the
+    /// SimData schema.
+    fn handle_ack(&mut self, from: SocketAddr, seq: u32, at: Instant) {
+        self.last_ack_time = Instant::now();
+        self.touch_peer(from, at);
+        self.ack_trackers.entry(from).or_default().record(seq, at);
+        if self.tablet_addr != Some(from) {
+            self.send_schema(from);
+        }
+        self.tablet_addr = Some(from);
+    }
+
+    /// Record that `from` was heard from at `at`. A new peer beyond
+    /// [`MAX_PEERS`] evicts the one heard from least recently, never the
+    /// streaming tablet.
+    fn touch_peer(&mut self, from: SocketAddr, at: Instant) {
+        if !self.last_heard.contains_key(&from) && self.last_heard.len() >= MAX_PEERS {
+            let oldest = self
+                .last_heard
+                .iter()
+                .filter(|(addr, _)| Some(**addr) != self.tablet_addr)
+                .min_by_key(|(_, heard)| **heard)
+                .map(|(addr, _)| *addr);
+            if let Some(addr) = oldest {
+                self.forget_peer(addr);
             }
         }
+        self.last_heard.insert(from, at);
+    }
+
+    /// Forget every peer silent for [`WATCHDOG_TIMEOUT`] except the streaming
+    /// tablet, which keeps its session should it resume.
+    fn expire_peers(&mut self, now: Instant) {
+        let stale: Vec<_> = self
+            .last_heard
+            .iter()
+            .filter(|(addr, heard)| {
+                Some(**addr) != self.tablet_addr && now.saturating_duration_since(**heard) > WATCHDOG_TIMEOUT
+            })
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in stale {
+            self.forget_peer(addr);
+        }
+    }
+
+    fn forget_peer(&mut self, addr: SocketAddr) {
+        self.last_heard.remove(&addr);
+        self.ack_trackers.remove(&addr);
+        self.sessions.remove(&addr);
+        self.peer_versions.lock().unwrap().remove(&addr);
+    }
+
+    // ── Discovery ─────────────────────────────────────────────────────────────
+
+    /// The Beacon describing this plugin and the loaded aircraft.
+    pub fn beacon(&self) -> Beacon {
+        let hello = Hello::local();
+        let port = self.local_addr().port();
+        Beacon {
+            versions:       hello.versions,
+            capabilities:   hello.capabilities,
+            stream_port:    port,
+            command_port:   port,
+            tcp_port:       self.tcp_port.unwrap_or(0),
+            plugin_version: env!("CARGO_PKG_VERSION").to_string(),
+            hostname:       self.hostname.clone(),
+            aircraft_icao:  self.aircraft_icao(),
+        }
+    }
+
+    fn send_beacon_if_due(&mut self, now: Instant) {
+        if self.beacon_targets.is_empty()
+            || self.last_beacon.is_some_and(|t| now.duration_since(t) < BEACON_INTERVAL)
+        {
+            return;
+        }
+        self.last_beacon = Some(now);
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let mut pkt = encode_beacon(seq, &self.beacon());
+        self.sign(&mut pkt);
+        for &to in &self.beacon_targets {
+            self.send_udp(&pkt, to);
+        }
+    }
+
+    /// ICAO type of the loaded aircraft.
+    fn aircraft_icao(&self) -> String {
+        self.byte_string(self.handles.acf_icao)
+    }
+
+    /// Value of a NUL-padded byte dataref such as the aircraft type or tail
+    /// number; empty if missing.
+    fn byte_string(&self, handle: Option<DataRefHandle>) -> String {
+        let Some(h) = handle else { return String::new() };
+        let mut buf = [0u8; 40];
+        let n = self.xplm.get_bytes(h, 0, &mut buf).min(buf.len());
+        let end = buf[..n].iter().position(|&b| b == 0).unwrap_or(n);
+        String::from_utf8_lossy(&buf[..end]).trim().to_string()
+    }
+
+    fn send_schema(&self, to: SocketAddr) {
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        self.send_packet(&encode_schema(seq, &Schema::local()), to);
+    }
+
+    /// Link statistics for a tablet, derived from the sequence numbers and
+    /// arrival times of its ACKs.
+    pub fn link_stats(&self, addr: SocketAddr) -> Option<LinkStats> {
+        self.ack_trackers.get(&addr).map(SequenceTracker::stats)
+    }
+
+    // ── Session negotiation ───────────────────────────────────────────────────
+
+    /// Negotiated session for `addr`, or `None` if it never sent Hello.
+    pub fn session(&self, addr: SocketAddr) -> Option<HelloAck> {
+        self.sessions.get(&addr).map(|s| s.ack)
+    }
+
+    /// Pick the best common version/capabilities for a tablet and reply with
+    /// HelloAck. Tablets with no overlapping version are logged and ignored.
+    ///
+    /// The tablet's streaming state starts over: its next SimData is a
+    /// keyframe and every field group is due. Other tablets are unaffected.
+    fn handle_hello(&mut self, from: SocketAddr, hello: &Hello) {
+        let Some(ack) = Hello::local().negotiate(hello) else {
+            self.xplm.log(&format!(
+                "EFB: no common protocol version with {from} (tablet {}..={})",
+                hello.versions.min, hello.versions.max,
+            ));
+            return;
+        };
+        self.touch_peer(from, Instant::now());
+        self.sessions.insert(from, Session::new(ack));
+        self.peer_versions.lock().unwrap().insert(from, ack.versions());
+
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        self.send_packet(&encode_hello_ack(seq, &ack), from);
     }
 
     // ── Command execution ─────────────────────────────────────────────────────
 
-    fn handle_command(&mut self, payload: &[u8]) {
-        let Ok(text) = std::str::from_utf8(payload) else { return };
-        let Ok(cmd)  = serde_json::from_str::<Command>(text) else { return };
+    /// Execute a command and report the outcome to the tablet that sent it.
+    fn handle_command(&mut self, from: SocketAddr, req: CommandRequest) {
+        let result = match self.execute(req.command) {
+            Ok(()) => CommandResult::ok(req.id),
+            Err((status, message)) => {
+                self.xplm.log(&format!("EFB: command failed: {message}"));
+                CommandResult::error(req.id, status, message)
+            }
+        };
+        self.send_command_result(from, &result);
+    }
+
+    /// Report a command that could not be decoded.
+    fn reject_command(&mut self, from: SocketAddr, id: Option<u32>, e: &ProtocolError) {
+        self.xplm.log(&format!("EFB: rejected command: {e}"));
+        let result = CommandResult::error(id, CommandStatus::from_error(e), e.to_string());
+        self.send_command_result(from, &result);
+    }
+
+    fn send_command_result(&self, to: SocketAddr, result: &CommandResult) {
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        self.send_packet(&encode_command_result(seq, result), to);
+    }
 
+    fn execute(&mut self, cmd: Command) -> Result<(), (CommandStatus, String)> {
         match cmd {
             Command::SetDataref { path, value } => {
-                if let Some(h) = self.xplm.find_dataref(&path) {
-                    self.xplm.set_float(h, value as f32);
-                }
+                let h = self.xplm.find_dataref(&path).ok_or_else(|| {
+                    (CommandStatus::UnknownDataref, format!("dataref not found: {path}"))
+                })?;
+                self.xplm.set_float(h, value as f32);
+                Ok(())
             }
-            Command::SwapFreq { radio } => {
-                self.swap_freq(&radio);
+            Command::SwapFreq { radio } => self.swap_freq(radio),
+            Command::SetStandbyFreq { radio, hz } => {
+                let (_, standby) = self.radio_handles(radio);
+                let sh = standby.ok_or_else(|| unsupported_radio(radio, "standby"))?;
+                self.xplm.set_int(sh, hz);
+                Ok(())
             }
         }
     }
 
-    fn swap_freq(&mut self, radio: &str) {
-        let (active_h, standby_h) = match radio {
-            "COM1" => (self.handles.com1_active_hz,  self.handles.com1_standby_hz),
-            "COM2" => (self.handles.com2_active_hz,  None),   // COM2 standby not cached; skip
-            "NAV1" => (self.handles.nav1_active_hz,  self.handles.nav1_standby_hz),
-            _      => return,
-        };
-        if let (Some(ah), Some(sh)) = (active_h, standby_h) {
-            let a = self.xplm.get_int(ah);
-            let s = self.xplm.get_int(sh);
-            self.xplm.set_int(ah, s);
-            self.xplm.set_int(sh, a);
+    /// Cached (active, standby) frequency handles of a radio.
+    fn radio_handles(&self, radio: Radio) -> (Option<DataRefHandle>, Option<DataRefHandle>) {
+        match radio {
+            Radio::Com1 => (self.handles.com1_active_hz, self.handles.com1_standby_hz),
+            Radio::Com2 => (self.handles.com2_active_hz, None), // COM2 standby not cached; skip
+            Radio::Nav1 => (self.handles.nav1_active_hz, self.handles.nav1_standby_hz),
+            Radio::Nav2 => (None, None),                        // NAV2 not cached; skip
+        }
+    }
+
+    fn swap_freq(&mut self, radio: Radio) -> Result<(), (CommandStatus, String)> {
+        let (active_h, standby_h) = self.radio_handles(radio);
+        let ah = active_h.ok_or_else(|| unsupported_radio(radio, "active"))?;
+        let sh = standby_h.ok_or_else(|| unsupported_radio(radio, "standby"))?;
+        let a = self.xplm.get_int(ah);
+        let s = self.xplm.get_int(sh);
+        self.xplm.set_int(ah, s);
+        self.xplm.set_int(sh, a);
+        Ok(())
+    }
+
+    // ── Enable ────────────────────────────────────────────────────────────────
+
+    /// Apply the `EFB_*` settings looked up through `setting`, then start the
+    /// UDP command server and, if `tcp` bound, the TCP server.
+    ///
+    /// The pairing key is set before either server starts, so neither ever
+    /// accepts an unsigned packet once `EFB_PAIRING_CODE` is configured.
+    pub fn enable(
+        &mut self,
+        setting: impl Fn(&str) -> Option<String>,
+        tcp: io::Result<TcpListener>,
+    ) {
+        // EFB_PAIRING_CODE=<code shown on the tablet> signs and requires
+        // signatures on all traffic.
+        if let Some(code) = setting("EFB_PAIRING_CODE").filter(|c| !c.is_empty()) {
+            self.set_pairing_key(Some(PairingKey::from_code(&code)));
+            self.xplm.log("EFB: pairing code set; unsigned packets are rejected");
+        }
+        // EFB_GDL90=192.168.1.255[,ip[:port]…] also streams GDL 90 for
+        // third-party EFBs (default port 4000).
+        if let Some(spec) = setting("EFB_GDL90") {
+            match parse_udp_targets(&spec, GDL90_PORT) {
+                Ok(targets) => self.set_gdl90_targets(targets),
+                Err(bad) => self.xplm.log(&format!("EFB: invalid EFB_GDL90 target: {bad}")),
+            }
+        }
+        // EFB_FOREFLIGHT=192.168.1.255[,ip[:port]…] also sends the
+        // ForeFlight XGPS/XATT/XTRAFFIC protocol (default port 49002).
+        if let Some(spec) = setting("EFB_FOREFLIGHT") {
+            match parse_udp_targets(&spec, FOREFLIGHT_PORT) {
+                Ok(targets) => self.set_foreflight_targets(targets),
+                Err(bad) => self.xplm.log(&format!("EFB: invalid EFB_FOREFLIGHT target: {bad}")),
+            }
+        }
+        // EFB_NMEA=udp:ip[:port]… | tcp:[ip:]port | pty[:/path/to/link]
+        // also sends NMEA 0183 for moving-map software (default port 10110).
+        if let Some(spec) = setting("EFB_NMEA") {
+            match spec.parse::<NmeaOutput>() {
+                Ok(output) => match self.start_nmea(&output) {
+                    Ok(sink) => {
+                        if let Some(path) = sink.pty_path() {
+                            let msg = format!("EFB: NMEA on {}", path.display());
+                            self.xplm.log(&msg);
+                        }
+                    }
+                    Err(e) => self.xplm.log(&format!("EFB: cannot start NMEA output: {e}")),
+                },
+                Err(bad) => self.xplm.log(&format!("EFB: invalid EFB_NMEA setting: {bad}")),
+            }
+        }
+        self.start_command_server();
+        match tcp {
+            Ok(listener) => self.start_tcp_server(listener),
+            Err(e) => self.xplm.log(&format!("EFB: TCP transport unavailable: {e}")),
         }
     }
 
@@ -495,32 +1310,23 @@ impl EfbPlugin {
 
     /// Spawn the UDP command-server thread.
     ///
-    /// Receives packets on the shared socket and forwards decoded messages via
-    /// the internal mpsc channel so the flight loop thread can process them.
+    /// Receives packets on the shared socket, reassembles fragmented messages,
+    /// and forwards decoded messages via the internal mpsc channel so the
+    /// flight loop thread can process them.
     pub fn start_command_server(&mut self) {
-        let (tx, rx) = mpsc::channel::<InternalMsg>();
-        self.cmd_rx = Some(rx);
-
+        let tx = self.message_sender();
         let socket = Arc::clone(&self.udp_socket);
+        let local = self.local_addr();
+        let mut inbound = Inbound::new(
+            Arc::clone(&self.peer_versions),
+            self.auth.clone(),
+            Arc::clone(&self.capture),
+        );
         std::thread::spawn(move || {
-            let mut buf = [0u8; 65535 + efb_protocol::HEADER_LEN];
+            let mut buf = [0u8; 65535 + efb_protocol::HEADER_LEN + AUTH_TRAILER_LEN];
             loop {
                 match socket.recv_from(&mut buf) {
-                    Ok((n, from)) => {
-                        let data = &buf[..n];
-                        match decode_packet(data) {
-                            Ok((_, PacketType::Ack, _)) => {
-                                let _ = tx.send(InternalMsg::Ack(from));
-                            }
-                            Ok((_, PacketType::CommandJson, payload)) => {
-                                let _ = tx.send(InternalMsg::Command(payload.to_vec()));
-                            }
-                            Ok((_, PacketType::Reload, _)) => {
-                                let _ = tx.send(InternalMsg::Reload);
-                            }
-                            Ok(_) | Err(_) => {} // silently drop
-                        }
-                    }
+                    Ok((n, from)) => inbound.route(&buf[..n], from, local, &tx),
                     Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                         std::thread::sleep(Duration::from_millis(1));
                     }
@@ -529,6 +1335,228 @@ impl EfbPlugin {
             }
         });
     }
+
+    /// Spawn a thread accepting tablets over TCP (e.g. `adb reverse` over USB).
+    ///
+    /// Packets are framed back to back on the stream (see
+    /// [`efb_protocol::stream`]); once the TCP tablet ACKs, everything sent to
+    /// it goes over the stream instead of UDP. Only one TCP tablet is served
+    /// at a time — a new connection replaces the previous one.
+    pub fn start_tcp_server(&mut self, listener: TcpListener) {
+        self.tcp_port = listener.local_addr().ok().map(|a| a.port());
+        let tx = self.message_sender();
+        let slot = Arc::clone(&self.tcp_client);
+        let peer_versions = Arc::clone(&self.peer_versions);
+        let auth = self.auth.clone();
+        let capture = Arc::clone(&self.capture);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let (Ok(addr), Ok(local)) = (stream.peer_addr(), stream.local_addr()) else { continue };
+                let Ok(writer) = stream.try_clone() else { continue };
+                let _ = stream.set_nodelay(true);
+                let _ = writer.set_write_timeout(Some(TCP_WRITE_TIMEOUT));
+                let client = TcpClient { addr, local, stream: writer };
+                if let Some(old) = slot.lock().unwrap().replace(client) {
+                    let _ = old.stream.shutdown(Shutdown::Both);
+                }
+
+                let tx = tx.clone();
+                let slot = Arc::clone(&slot);
+                let mut inbound =
+                    Inbound::new(Arc::clone(&peer_versions), auth.clone(), Arc::clone(&capture));
+                std::thread::spawn(move || {
+                    let trailer = if inbound.auth.is_some() { AUTH_TRAILER_LEN } else { 0 };
+                    let mut frames = FrameReader::new(stream).with_trailer_len(trailer);
+                    while let Ok(Some(frame)) = frames.read_frame() {
+                        inbound.route(&frame, addr, local, &tx);
+                    }
+                    let mut slot = slot.lock().unwrap();
+                    if slot.as_ref().is_some_and(|c| c.addr == addr) {
+                        *slot = None;
+                    }
+                });
+            }
+        });
+    }
+
+    /// Sender half of the channel drained by the flight loop, created on first use.
+    fn message_sender(&mut self) -> mpsc::Sender<InternalMsg> {
+        if let Some(tx) = &self.cmd_tx {
+            return tx.clone();
+        }
+        let (tx, rx) = mpsc::channel::<InternalMsg>();
+        self.cmd_rx = Some(rx);
+        self.cmd_tx = Some(tx.clone());
+        tx
+    }
+}
+
+/// Longest a field group goes unsent; zero means every tick.
+fn group_interval(group: FieldGroup) -> Duration {
+    match group {
+        FieldGroup::Fast    => Duration::ZERO,
+        FieldGroup::Nav     => NAV_GROUP_INTERVAL,
+        FieldGroup::Slow    => SLOW_GROUP_INTERVAL,
+        FieldGroup::Traffic => TRAFFIC_REFRESH_INTERVAL,
+    }
+}
+
+/// Simplified TCAS II classification of a target from its position and
+/// velocity relative to the ownship (local metres and m/s). Uses fixed
+/// sensitivity-level thresholds rather than altitude-dependent ones.
+fn tcas_threat(rel_pos: [f64; 3], rel_vel: [f32; 3], on_ground: bool) -> ThreatLevel {
+    const M_PER_NM: f64 = 1852.0;
+    const FT_PER_M: f64 = 3.280_84;
+    let range_nm = rel_pos[0].hypot(rel_pos[2]) / M_PER_NM;
+    let dalt_ft = (rel_pos[1] * FT_PER_M).abs();
+    // Time to closest approach in seconds, infinite when diverging.
+    let range_rate = (rel_pos[0] * rel_vel[0] as f64 + rel_pos[2] * rel_vel[2] as f64) / (range_nm * M_PER_NM);
+    let tau = if range_rate < 0.0 { range_nm * M_PER_NM / -range_rate } else { f64::INFINITY };
+
+    let threat = if dalt_ft < 600.0 && (tau < 25.0 || range_nm < 0.35) {
+        ThreatLevel::ResolutionAdvisory
+    } else if dalt_ft < 850.0 && (tau < 40.0 || range_nm < 0.55) {
+        ThreatLevel::TrafficAdvisory
+    } else if range_nm < 6.0 && dalt_ft < 1200.0 {
+        ThreatLevel::Proximate
+    } else {
+        ThreatLevel::None
+    };
+    // TCAS inhibits advisories against traffic on the ground.
+    if on_ground { threat.min(ThreatLevel::Proximate) } else { threat }
+}
+
+/// Parse a list of UDP targets such as the `EFB_GDL90` setting:
+/// comma-separated `ip` or `ip:port` entries, the port defaulting to
+/// `default_port`. Returns the first entry that does not parse as the error.
+pub fn parse_udp_targets(spec: &str, default_port: u16) -> Result<Vec<SocketAddr>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<SocketAddr>()
+                .or_else(|_| s.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, default_port)))
+                .map_err(|_| s.to_string())
+        })
+        .collect()
+}
+
+/// Error for a radio whose `which` (active/standby) frequency has no dataref.
+fn unsupported_radio(radio: Radio, which: &str) -> (CommandStatus, String) {
+    let name = format!("{radio:?}").to_uppercase();
+    (CommandStatus::UnsupportedRadio, format!("{name} {which} frequency not available"))
+}
+
+/// Name of this machine for the Beacon, from the environment or
+/// `/etc/hostname`.
+fn local_hostname() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "X-Plane".to_string())
+}
+
+/// Receive-side state of one server thread: negotiated versions,
+/// authentication, fragment reassembly and capture.
+struct Inbound {
+    peer_versions: PeerVersions,
+    auth:          Option<Auth>,
+    reassembler:   Reassembler,
+    capture:       SharedCapture,
+}
+
+impl Inbound {
+    fn new(peer_versions: PeerVersions, auth: Option<Auth>, capture: SharedCapture) -> Self {
+        Inbound { peer_versions, auth, reassembler: Reassembler::default(), capture }
+    }
+
+    /// Decode one datagram or stream frame received on `local` and forward the
+    /// resulting message to the flight loop. Undecodable packets are captured,
+    /// then silently dropped.
+    fn route(&mut self, data: &[u8], from: SocketAddr, local: SocketAddr, tx: &mpsc::Sender<InternalMsg>) {
+        capture_packet(&self.capture, Direction::Inbound, from, local, data);
+        let versions = peer_versions(&self.peer_versions, from);
+        match decode_inbound(data, versions, self.auth.as_ref()) {
+            Ok((hdr, PacketType::Fragment, fragment)) => {
+                if let Ok(Some((ptype, payload))) =
+                    self.reassembler.push(from, fragment, Instant::now())
+                {
+                    forward_to_flight_loop(tx, from, &hdr, ptype, &payload);
+                }
+            }
+            Ok((hdr, ptype, payload)) => {
+                forward_to_flight_loop(tx, from, &hdr, ptype, payload);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Header versions accepted from `from`: those up to the version negotiated
+/// with it, or v1 only if it never sent Hello.
+fn peer_versions(versions: &PeerVersions, from: SocketAddr) -> VersionRange {
+    let versions = versions.lock().unwrap();
+    versions.get(&from).copied().unwrap_or(VersionRange::exact(MIN_PROTOCOL_VERSION))
+}
+
+/// Decode an inbound datagram whose header version is within `versions`,
+/// enforcing authentication and replay protection when a pairing key is set.
+fn decode_inbound<'a>(
+    buf: &'a [u8],
+    versions: VersionRange,
+    auth: Option<&Auth>,
+) -> Result<(PacketHeader, PacketType, &'a [u8]), ProtocolError> {
+    let Some(auth) = auth else {
+        return decode_packet_in(buf, versions);
+    };
+    // Check the version before the tag so the replay window only ever
+    // records packets that are accepted.
+    decode_packet_in(&buf[..buf.len().saturating_sub(AUTH_TRAILER_LEN)], versions)?;
+    decode_authenticated(buf, &auth.key, Signer::Tablet, &mut auth.window.lock().unwrap())
+}
+
+/// Decode either command encoding.
+fn decode_any_command(ptype: PacketType, payload: &[u8]) -> Result<CommandRequest, ProtocolError> {
+    match ptype {
+        PacketType::CommandBinary => decode_request(payload),
+        _ => decode_request_json(payload),
+    }
+}
+
+/// Translate an inbound packet into a message for the flight loop thread.
+/// Outbound-only and undecodable packets are silently dropped.
+///
+/// ACKs are timestamped here rather than on the flight loop so that link
+/// jitter is not quantised to the streaming interval.
+fn forward_to_flight_loop(
+    tx: &mpsc::Sender<InternalMsg>,
+    from: SocketAddr,
+    hdr: &PacketHeader,
+    ptype: PacketType,
+    payload: &[u8],
+) {
+    let Ok(payload) = inflate_payload(hdr, payload) else { return };
+    let payload = &*payload;
+    let msg = match ptype {
+        PacketType::Ack => InternalMsg::Ack(from, hdr.sequence, Instant::now()),
+        PacketType::Hello => match decode_hello(payload) {
+            Ok(hello) => InternalMsg::Hello(from, hello),
+            Err(_) => return,
+        },
+        PacketType::CommandJson | PacketType::CommandBinary => {
+            match decode_any_command(ptype, payload) {
+                Ok(req) => InternalMsg::Command(from, req),
+                Err(e) => InternalMsg::RejectedCommand(from, peek_request_id(ptype, payload), e),
+            }
+        }
+        PacketType::Reload => InternalMsg::Reload,
+        _ => return,
+    };
+    let _ = tx.send(msg);
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -537,7 +1565,7 @@ impl EfbPlugin {
 mod tests {
     use super::*;
     use crate::xplm_shim::{DataRefValue, MockXplm};
-    use efb_protocol::{MAGIC, PROTOCOL_VERSION, HEADER_LEN};
+    use efb_protocol::{decode_packet, MAGIC, PROTOCOL_VERSION, HEADER_LEN};
 
     fn make_mock() -> MockXplm {
         let m = MockXplm::new();
@@ -595,12 +1623,15 @@ mod tests {
         m.set_dataref(paths::TRAFFIC_ELE_M,     DataRefValue::FloatArray(vec![1700.0, 1650.0]));
         m.set_dataref(paths::TRAFFIC_COUNT,     DataRefValue::Int(2));
         m.set_dataref(paths::HSI_SOURCE,        DataRefValue::Int(0));
+        m.set_dataref(paths::ACF_ICAO,          DataRefValue::Bytes(b"C172\0\0\0\0".to_vec()));
         m
     }
 
     fn make_plugin(mock: MockXplm) -> EfbPlugin {
         let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
-        EfbPlugin::new(Box::new(mock), socket)
+        let mut plugin = EfbPlugin::new(Box::new(mock), socket);
+        plugin.set_beacon_targets(Vec::new());
+        plugin
     }
 
     #[test]
@@ -695,13 +1726,808 @@ mod tests {
         assert!(plugin.handles.latitude.is_some());
     }
 
+    #[test]
+    fn hello_negotiates_session_and_replies() {
+        use efb_protocol::handshake::{decode_hello_ack, encode_hello};
+        use efb_protocol::VersionRange;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        let hello = Hello {
+            versions: VersionRange { min: 1, max: 9 },
+            capabilities: caps::DELTA | 1 << 31,
+        };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+
+        let session = plugin.session(addr).expect("session not recorded");
+        assert_eq!(session.version, PROTOCOL_VERSION);
+        assert_eq!(session.capabilities, caps::DELTA);
+
+        let mut buf = [0u8; 2048];
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+        assert_eq!(ptype, PacketType::HelloAck);
+        assert_eq!(decode_hello_ack(payload).unwrap(), session);
+
+        // First ACK delivers the schema; streaming to a DELTA-capable tablet
+        // then starts with a keyframe.
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Schema);
+        plugin.flight_loop_tick();
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Keyframe);
+        plugin.flight_loop_tick();
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Delta);
+    }
+
+    #[test]
+    fn sessions_are_kept_per_tablet() {
+        use efb_protocol::handshake::encode_hello;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+        let other: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+        let hello = Hello { capabilities: caps::DELTA, ..Hello::local() };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        let mut buf = [0u8; 2048];
+        tablet.recv_from(&mut buf).unwrap(); // HelloAck
+        tablet.recv_from(&mut buf).unwrap(); // Schema
+        plugin.flight_loop_tick();
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Keyframe);
+
+        // Another tablet's Hello does not restart this tablet's stream.
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), other);
+        plugin.flight_loop_tick();
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Delta);
+
+        // v2 headers are only accepted from tablets that negotiated v2.
+        let mut ack = build_ack_packet();
+        ack[9..13].copy_from_slice(&1u32.to_le_bytes());
+        let v2_ack = upgrade_packet(&ack, Timestamps::default(), false).unwrap();
+        let stranger: SocketAddr = "127.0.0.1:23456".parse().unwrap();
+        plugin.handle_incoming_packet(&v2_ack, stranger);
+        assert!(plugin.link_stats(stranger).is_none());
+        plugin.handle_incoming_packet(&v2_ack, addr);
+        assert_eq!(plugin.link_stats(addr).unwrap().received, 2);
+    }
+
+    #[test]
+    fn peer_state_is_capped_and_expires() {
+        use efb_protocol::handshake::encode_hello;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        let hello = encode_hello(0, &Hello::local());
+        for port in 0..2 * MAX_PEERS as u16 {
+            let addr = SocketAddr::from(([127, 0, 0, 1], 20_000 + port));
+            plugin.handle_incoming_packet(&hello, addr);
+            plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        }
+        assert_eq!(plugin.ack_trackers.len(), MAX_PEERS);
+        assert_eq!(plugin.sessions.len(), MAX_PEERS);
+        assert_eq!(plugin.peer_versions.lock().unwrap().len(), MAX_PEERS);
+
+        // Once silent for the watchdog timeout, only the streaming tablet is kept.
+        let tablet = plugin.tablet_addr.unwrap();
+        plugin.expire_peers(Instant::now() + WATCHDOG_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(plugin.ack_trackers.keys().collect::<Vec<_>>(), [&tablet]);
+        assert_eq!(plugin.sessions.keys().collect::<Vec<_>>(), [&tablet]);
+        assert_eq!(plugin.peer_versions.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn beacon_sent_until_a_tablet_connects() {
+        use efb_protocol::beacon::decode_beacon;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        plugin.set_beacon_targets(vec![listener.local_addr().unwrap()]);
+
+        let mut buf = [0u8; 2048];
+        plugin.flight_loop_tick();
+        let (n, _) = listener.recv_from(&mut buf).unwrap();
+        let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+        assert_eq!(ptype, PacketType::Beacon);
+        let beacon = decode_beacon(payload).unwrap();
+        assert_eq!(beacon, plugin.beacon());
+        assert_eq!(beacon.aircraft_icao, "C172");
+        assert_eq!(beacon.stream_port, plugin.local_addr().port());
+        assert_eq!(beacon.tcp_port, 0);
+        assert!(!beacon.hostname.is_empty());
+
+        // At most one per interval.
+        plugin.flight_loop_tick();
+        assert!(listener.recv_from(&mut buf).is_err());
+
+        // Silent while a tablet is streaming, back once the watchdog trips.
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        plugin.handle_incoming_packet(&build_ack_packet(), tablet.local_addr().unwrap());
+        plugin.last_beacon = Some(Instant::now() - BEACON_INTERVAL);
+        plugin.flight_loop_tick();
+        assert!(listener.recv_from(&mut buf).is_err());
+
+        plugin.last_ack_time = Instant::now() - Duration::from_secs(10);
+        plugin.flight_loop_tick();
+        let (n, _) = listener.recv_from(&mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..n]).unwrap().1, PacketType::Beacon);
+    }
+
+    #[test]
+    fn v2_session_gets_timestamped_compressed_packets() {
+        use efb_protocol::handshake::encode_hello;
+        use efb_protocol::{decode_sim_data, flags, inflate_payload};
+
+        let mock = make_mock();
+        mock.set_dataref(paths::SIM_TIME_SEC, DataRefValue::Float(12.5));
+        mock.set_dataref(paths::LATITUDE, DataRefValue::Double(51.4706));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        let hello = Hello { capabilities: caps::COMPRESSION, ..Hello::local() };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+        let mut buf = [0u8; 2048];
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        let (hdr, ptype, _) = decode_packet(&buf[..n]).unwrap();
+        assert_eq!((ptype, { hdr.version }), (PacketType::HelloAck, 1));
+
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        tablet.recv_from(&mut buf).unwrap(); // Schema
+        plugin.flight_loop_tick();
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        assert!(n < SIM_DATA_PACKET_LEN);
+        let (hdr, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+        assert_eq!((ptype, { hdr.version }), (PacketType::SimData, 2));
+        assert!(hdr.has(flags::COMPRESSED | flags::KEYFRAME));
+        assert_eq!({ hdr.sim_time_us }, 12_500_000);
+        let snap = decode_sim_data(&inflate_payload(&hdr, payload).unwrap()).unwrap();
+        assert_eq!(snap.latitude, 51.4706);
+    }
+
+    #[test]
+    fn field_groups_are_scheduled_independently() {
+        use efb_protocol::handshake::encode_hello;
+        use efb_protocol::groups::peek_group;
+
+        let mock = make_mock();
+        mock.set_dataref(paths::TRAFFIC_COUNT, DataRefValue::Int(0));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        let hello = Hello { capabilities: caps::DELTA | caps::FIELD_GROUPS, ..Hello::local() };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        let mut buf = [0u8; 2048];
+        tablet.recv_from(&mut buf).unwrap(); // HelloAck
+        tablet.recv_from(&mut buf).unwrap(); // Schema
+
+        let mut tick = |plugin: &mut EfbPlugin| {
+            plugin.flight_loop_tick();
+            let mut groups = Vec::new();
+            while let Ok((n, _)) = tablet.recv_from(&mut buf) {
+                let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+                assert_eq!(ptype, PacketType::Group);
+                groups.push(peek_group(payload).unwrap());
+            }
+            // Waiting out the read timeout must not make slower groups due.
+            for (at, _) in plugin.sessions.get_mut(&addr).unwrap().group_sent.iter_mut().flatten() {
+                *at = Instant::now();
+            }
+            groups
+        };
+
+        // Everything right after negotiation, then only the fast group.
+        assert_eq!(tick(&mut plugin), FieldGroup::ALL);
+        assert_eq!(tick(&mut plugin), [FieldGroup::Fast]);
+
+        // Traffic goes out as soon as it changes.
+        let count = plugin.handles.traffic_count.unwrap();
+        plugin.xplm.set_int(count, 2);
+        assert_eq!(tick(&mut plugin), [FieldGroup::Fast, FieldGroup::Traffic]);
+        assert_eq!(tick(&mut plugin), [FieldGroup::Fast]);
+
+        // Slower groups once their interval has passed.
+        let now = Instant::now();
+        for (at, _) in plugin.sessions.get_mut(&addr).unwrap().group_sent.iter_mut().flatten() {
+            *at = now - SLOW_GROUP_INTERVAL;
+        }
+        assert_eq!(tick(&mut plugin), [FieldGroup::Fast, FieldGroup::Nav, FieldGroup::Slow]);
+    }
+
+    #[test]
+    fn extended_traffic_session_gets_traffic_packets() {
+        use efb_protocol::handshake::encode_hello;
+        use efb_protocol::traffic::decode_traffic;
+
+        let mock = make_mock();
+        mock.set_dataref(paths::TCAS_NUM_ACF,   DataRefValue::Int(2));
+        // Slot 0 is the ownship, 1 km north of it head-on at 100 m/s and
+        // 100 m above, then an aircraft taxiing 3 km east.
+        mock.set_dataref(paths::TCAS_X,         DataRefValue::FloatArray(vec![0.0, 0.0, 3000.0]));
+        mock.set_dataref(paths::TCAS_Y,         DataRefValue::FloatArray(vec![1000.0, 1100.0, 1000.0]));
+        mock.set_dataref(paths::TCAS_Z,         DataRefValue::FloatArray(vec![0.0, -1000.0, 0.0]));
+        mock.set_dataref(paths::TCAS_VX,        DataRefValue::FloatArray(vec![0.0, 0.0, 5.0]));
+        mock.set_dataref(paths::TCAS_VY,        DataRefValue::FloatArray(vec![0.0, -2.54, 0.0]));
+        mock.set_dataref(paths::TCAS_VZ,        DataRefValue::FloatArray(vec![0.0, 50.0, 0.0]));
+        mock.set_dataref(paths::TCAS_ON_GROUND, DataRefValue::IntArray(vec![0, 0, 1]));
+        mock.set_dataref(paths::TCAS_MODE_S_ID, DataRefValue::IntArray(vec![0, 0x8A2C41, 0x8A0001]));
+        mock.set_dataref(paths::TCAS_FLIGHT_ID, DataRefValue::Bytes(b"\0\0\0\0\0\0\0\0SAA335\0\0".to_vec()));
+        mock.set_dataref(paths::LOCAL_X,        DataRefValue::Double(0.0));
+        mock.set_dataref(paths::LOCAL_Y,        DataRefValue::Double(1000.0));
+        mock.set_dataref(paths::LOCAL_Z,        DataRefValue::Double(0.0));
+        mock.set_dataref(paths::LOCAL_VX,       DataRefValue::Float(0.0));
+        mock.set_dataref(paths::LOCAL_VY,       DataRefValue::Float(0.0));
+        mock.set_dataref(paths::LOCAL_VZ,       DataRefValue::Float(-50.0));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+
+        let targets = plugin.read_traffic();
+        assert_eq!(targets.len(), 2);
+        let head_on = &targets[0];
+        assert_eq!(head_on.icao_address, 0x8A2C41);
+        assert_eq!(head_on.callsign, "SAA335");
+        assert_eq!(head_on.latitude, 1000.0 / 111_319.5);
+        assert_eq!(head_on.altitude_m, 1100.0);
+        assert_eq!(head_on.track_deg, 180.0);
+        assert!((head_on.groundspeed_kt - 97.19).abs() < 0.01);
+        assert!((head_on.vertical_speed_fpm + 500.0).abs() < 0.1);
+        assert_eq!(head_on.threat, ThreatLevel::ResolutionAdvisory);
+        let taxiing = &targets[1];
+        assert!(taxiing.on_ground && taxiing.callsign.is_empty());
+        assert_eq!(taxiing.track_deg, 90.0);
+        assert_eq!(taxiing.threat, ThreatLevel::Proximate);
+
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+        let hello = Hello { capabilities: caps::EXTENDED_TRAFFIC, ..Hello::local() };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        let mut buf = [0u8; 4096];
+        tablet.recv_from(&mut buf).unwrap(); // HelloAck
+        tablet.recv_from(&mut buf).unwrap(); // Schema
+
+        let mut tick = |plugin: &mut EfbPlugin| {
+            plugin.flight_loop_tick();
+            let mut types = Vec::new();
+            while let Ok((n, _)) = tablet.recv_from(&mut buf) {
+                let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+                if ptype == PacketType::Traffic {
+                    assert_eq!(decode_traffic(payload).unwrap(), targets);
+                }
+                types.push(ptype);
+            }
+            types
+        };
+        assert_eq!(tick(&mut plugin), [PacketType::SimData, PacketType::Traffic]);
+        plugin.sessions.get_mut(&addr).unwrap().traffic_sent = Some(Instant::now());
+        assert_eq!(tick(&mut plugin), [PacketType::SimData]);
+        plugin.sessions.get_mut(&addr).unwrap().traffic_sent = Some(Instant::now() - TRAFFIC_INTERVAL);
+        assert_eq!(tick(&mut plugin), [PacketType::SimData, PacketType::Traffic]);
+    }
+
+    #[test]
+    fn tcas_threat_levels() {
+        let level = |dx: f64, dy_ft: f64, closing_ms: f32, on_ground| {
+            tcas_threat([dx, dy_ft / 3.280_84, 0.0], [-closing_ms, 0.0, 0.0], on_ground)
+        };
+        assert_eq!(level(5000.0, 300.0, 250.0, false), ThreatLevel::ResolutionAdvisory); // tau 20 s
+        assert_eq!(level(5000.0, 700.0, 250.0, false), ThreatLevel::TrafficAdvisory);
+        assert_eq!(level(5000.0, 300.0, 150.0, false), ThreatLevel::TrafficAdvisory);   // tau 33 s
+        assert_eq!(level(5000.0, 300.0, -50.0, false), ThreatLevel::Proximate);         // diverging
+        assert_eq!(level(500.0, 300.0, 0.0, false), ThreatLevel::ResolutionAdvisory);   // 0.27 nm
+        assert_eq!(level(5000.0, 1500.0, 250.0, false), ThreatLevel::None);
+        assert_eq!(level(12_000.0, 0.0, 0.0, false), ThreatLevel::None);
+        assert_eq!(level(500.0, 0.0, 0.0, true), ThreatLevel::Proximate);
+    }
+
+    #[test]
+    fn gdl90_is_streamed_without_a_tablet() {
+        let mock = make_mock();
+        mock.set_dataref(paths::ZULU_TIME_SEC, DataRefValue::Float(45_296.7));
+        mock.set_dataref(paths::ACF_TAILNUM,   DataRefValue::Bytes(b"ZS-EFB\0\0".to_vec()));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let efb = UdpSocket::bind("127.0.0.1:0").unwrap();
+        efb.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        plugin.set_gdl90_targets(vec![efb.local_addr().unwrap()]);
+
+        let tick = |plugin: &mut EfbPlugin| {
+            plugin.flight_loop_tick();
+            let mut ids = Vec::new();
+            let mut buf = [0u8; 256];
+            while let Ok((n, _)) = efb.recv_from(&mut buf) {
+                assert_eq!((buf[0], buf[n - 1]), (0x7E, 0x7E));
+                if buf[1] == 0x0A {
+                    assert!(buf[..n].windows(8).any(|w| w == b"ZSEFB   "));
+                }
+                ids.push(buf[1]);
+            }
+            // Waiting out the read timeout must not make AHRS due.
+            if plugin.gdl90_ahrs_sent.is_some() {
+                plugin.gdl90_ahrs_sent = Some(Instant::now());
+            }
+            ids
+        };
+        // Heartbeat, ownship, geometric altitude, two traffic reports, AHRS.
+        assert_eq!(tick(&mut plugin), [0x00, 0x0A, 0x0B, 0x14, 0x14, 0x65]);
+        assert!(tick(&mut plugin).is_empty());
+        plugin.gdl90_ahrs_sent = Some(Instant::now() - GDL90_AHRS_INTERVAL);
+        assert_eq!(tick(&mut plugin), [0x65]);
+
+        plugin.set_gdl90_targets(Vec::new());
+        assert!(tick(&mut plugin).is_empty());
+    }
+
+    #[test]
+    fn foreflight_is_sent_without_a_tablet() {
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        let efb = UdpSocket::bind("127.0.0.1:0").unwrap();
+        efb.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        plugin.set_foreflight_targets(vec![efb.local_addr().unwrap()]);
+
+        let tick = |plugin: &mut EfbPlugin| {
+            plugin.flight_loop_tick();
+            let mut kinds = Vec::new();
+            let mut buf = [0u8; 256];
+            while let Ok(n) = efb.recv(&mut buf) {
+                let msg = std::str::from_utf8(&buf[..n]).unwrap();
+                let (kind, _) = msg.split_once("X-Plane,").unwrap();
+                kinds.push(kind.to_string());
+            }
+            // Waiting out the read timeout must not make XATT due.
+            if plugin.foreflight_att_sent.is_some() {
+                plugin.foreflight_att_sent = Some(Instant::now());
+            }
+            kinds
+        };
+        assert_eq!(tick(&mut plugin), ["XGPS", "XTRAFFIC", "XTRAFFIC", "XATT"]);
+        assert!(tick(&mut plugin).is_empty());
+        plugin.foreflight_att_sent = Some(Instant::now() - FOREFLIGHT_ATT_INTERVAL);
+        assert_eq!(tick(&mut plugin), ["XATT"]);
+
+        plugin.set_foreflight_targets(Vec::new());
+        assert!(tick(&mut plugin).is_empty());
+    }
+
+    #[test]
+    fn udp_targets_parse_with_default_port() {
+        assert_eq!(
+            parse_udp_targets("192.168.1.255, 10.0.0.7:4001,", gdl90::GDL90_PORT).unwrap(),
+            ["192.168.1.255:4000".parse().unwrap(), "10.0.0.7:4001".parse::<SocketAddr>().unwrap()],
+        );
+        assert_eq!(parse_udp_targets("ipad.local", gdl90::GDL90_PORT), Err("ipad.local".to_string()));
+    }
+
+    #[test]
+    fn nmea_is_sent_once_a_second_without_a_tablet() {
+        let mock = make_mock();
+        // 14:34 local on 1 January, 12:34:56.5 UTC.
+        mock.set_dataref(paths::ZULU_TIME_SEC,   DataRefValue::Float(45_296.5));
+        mock.set_dataref(paths::LOCAL_TIME_SEC,  DataRefValue::Float(52_496.5));
+        mock.set_dataref(paths::LOCAL_DATE_DAYS, DataRefValue::Int(0));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let gps = UdpSocket::bind("127.0.0.1:0").unwrap();
+        gps.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        plugin.start_nmea(&NmeaOutput::Udp(vec![gps.local_addr().unwrap()])).unwrap();
+
+        plugin.flight_loop_tick();
+        let mut buf = [0u8; 1024];
+        let n = gps.recv(&mut buf).unwrap();
+        let text = std::str::from_utf8(&buf[..n]).unwrap();
+        let types: Vec<_> = text.lines().map(|l| &l[1..6]).collect();
+        assert_eq!(types, ["GPRMC", "GPGGA", "GPGSA", "PGRMZ", "HCHDG"]);
+        assert!(text.starts_with("$GPRMC,123456.50,A,"), "{text}");
+        let date = text.lines().next().unwrap().split(',').nth(9).unwrap();
+        assert!(date.starts_with("0101"), "{text}");
+
+        plugin.flight_loop_tick();
+        assert!(gps.recv(&mut buf).is_err());
+        plugin.stop_nmea();
+    }
+
+    #[test]
+    fn hello_without_common_version_is_ignored() {
+        use efb_protocol::handshake::encode_hello;
+        use efb_protocol::VersionRange;
+
+        let mut plugin = make_plugin(make_mock());
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let hello = Hello { versions: VersionRange { min: 50, max: 60 }, capabilities: 0 };
+        plugin.handle_incoming_packet(&encode_hello(0, &hello), addr);
+        assert!(plugin.session(addr).is_none());
+    }
+
+    #[test]
+    fn first_ack_sends_schema_once() {
+        use efb_protocol::schema::decode_schema;
+
+        let mut plugin = make_plugin(make_mock());
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+
+        let mut buf = [0u8; 4096];
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+        assert_eq!(ptype, PacketType::Schema);
+        assert_eq!(decode_schema(payload).unwrap(), Schema::local());
+        assert!(tablet.recv_from(&mut buf).is_err(), "schema resent on repeat ACK");
+    }
+
+    #[test]
+    fn oversized_packets_are_fragmented_to_mtu() {
+        use efb_protocol::schema::decode_schema;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.set_mtu(300);
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        // First ACK triggers the (>300 byte) schema.
+        plugin.handle_incoming_packet(&build_ack_packet(), addr);
+
+        let mut reassembler = Reassembler::default();
+        let mut buf = [0u8; 2048];
+        let (ptype, payload) = loop {
+            let (n, _) = tablet.recv_from(&mut buf).expect("fragment not received");
+            assert!(n <= 300, "datagram of {n} bytes exceeds MTU");
+            let (_, t, frag) = decode_packet(&buf[..n]).unwrap();
+            assert_eq!(t, PacketType::Fragment);
+            if let Some(done) = reassembler.push(addr, frag, Instant::now()).unwrap() {
+                break done;
+            }
+        };
+        assert_eq!(ptype, PacketType::Schema);
+        assert_eq!(decode_schema(&payload).unwrap(), Schema::local());
+    }
+
+    #[test]
+    fn fragmented_command_is_reassembled_and_executed() {
+        let mock = make_mock();
+        mock.set_dataref("sim/cockpit/autopilot/heading_mag", DataRefValue::Float(0.0));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+
+        let json = br#"{"cmd":"set_dataref","path":"sim/cockpit/autopilot/heading_mag","value":123.0}"#;
+        let mut seq = 0;
+        let frags = efb_protocol::fragment_payload(PacketType::CommandJson, json, 60, || { seq += 1; seq })
+            .unwrap();
+        assert!(frags.len() > 1);
+
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        for f in frags.iter().rev() {
+            plugin.handle_incoming_packet(f, addr);
+        }
+        let h = plugin.xplm.find_dataref("sim/cockpit/autopilot/heading_mag").unwrap();
+        assert!((plugin.xplm.get_float(h) - 123.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn paired_plugin_rejects_unsigned_and_replayed_commands() {
+        use efb_protocol::auth::decode_authenticated;
+
+        let key = PairingKey::from_code("1234-5678");
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        plugin.set_pairing_key(Some(key.clone()));
+        let stamps = StampClock::new();
+
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let com1 = |p: &EfbPlugin| {
+            let h = p.xplm.find_dataref(paths::COM1_ACTIVE_HZ).unwrap();
+            p.xplm.get_int(h)
+        };
+        let swap = br#"{"cmd":"swap_freq","radio":"COM1"}"#;
+
+        // Unsigned command is dropped.
+        plugin.handle_incoming_packet(&build_command_json_packet(swap), addr);
+        assert_eq!(com1(&plugin), 118_025_000);
+
+        // Signed command is executed.
+        let mut signed = build_command_json_packet(swap);
+        sign_packet(&mut signed, &key, Signer::Tablet, stamps.next());
+        plugin.handle_incoming_packet(&signed, addr);
+        assert_eq!(com1(&plugin), 121_500_000);
+
+        // Replaying the same datagram, from any address, does not swap back.
+        plugin.handle_incoming_packet(&signed, addr);
+        plugin.handle_incoming_packet(&signed, "127.0.0.1:12346".parse().unwrap());
+        assert_eq!(com1(&plugin), 121_500_000);
+
+        // A packet the plugin signed is not accepted back as the tablet's.
+        let mut reflected = build_command_json_packet(swap);
+        plugin.sign(&mut reflected);
+        plugin.handle_incoming_packet(&reflected, addr);
+        assert_eq!(com1(&plugin), 121_500_000);
+
+        // Outbound packets are signed with the same key.
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let mut ack = build_ack_packet();
+        ack[9..13].copy_from_slice(&1u32.to_le_bytes()); // fresh sequence number
+        sign_packet(&mut ack, &key, Signer::Tablet, stamps.next());
+        plugin.handle_incoming_packet(&ack, tablet.local_addr().unwrap());
+        let mut buf = [0u8; 4096];
+        let (n, _) = tablet.recv_from(&mut buf).unwrap();
+        let mut window = ReplayWindow::since(0);
+        let (_, ptype, _) = decode_authenticated(&buf[..n], &key, Signer::Plugin, &mut window).unwrap();
+        assert_eq!(ptype, PacketType::Schema);
+    }
+
+    #[test]
+    fn pairing_code_setting_guards_udp_and_tcp() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        plugin.enable(
+            |name| (name == "EFB_PAIRING_CODE").then(|| "1234-5678".to_string()),
+            TcpListener::bind("127.0.0.1:0"),
+        );
+        let rx = plugin.cmd_rx.take().unwrap();
+        let next_id = || match rx.recv_timeout(Duration::from_secs(2)).unwrap() {
+            InternalMsg::Command(_, req) => req.id.unwrap(),
+            _ => panic!("expected a command"),
+        };
+        let key = PairingKey::from_code("1234-5678");
+        let stamps = StampClock::new();
+        let command = |id: u32, signed: bool| {
+            let json = format!(r#"{{"cmd":"swap_freq","radio":"COM1","id":{id}}}"#);
+            let mut pkt = build_command_json_packet(json.as_bytes());
+            if signed {
+                sign_packet(&mut pkt, &key, Signer::Tablet, stamps.next());
+            }
+            pkt
+        };
+
+        // UDP: the unsigned command is dropped, the signed one delivered.
+        let server = plugin.local_addr();
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let first = command(1, true);
+        udp.send_to(&command(0, false), server).unwrap();
+        udp.send_to(&first, server).unwrap();
+        assert_eq!(next_id(), 1);
+
+        // Replayed from another port: dropped.
+        let other = UdpSocket::bind("127.0.0.1:0").unwrap();
+        other.send_to(&first, server).unwrap();
+        other.send_to(&command(2, true), server).unwrap();
+        assert_eq!(next_id(), 2);
+
+        // TCP shares the window: the UDP command replayed there is dropped.
+        let port = plugin.tcp_port.unwrap();
+        let third = command(3, true);
+        let mut tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        tcp.write_all(&[first, third.clone()].concat()).unwrap();
+        assert_eq!(next_id(), 3);
+
+        // So does the next connection.
+        let mut tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        tcp.write_all(&[third, command(4, true)].concat()).unwrap();
+        assert_eq!(next_id(), 4);
+    }
+
+    #[test]
+    fn ack_sequence_feeds_link_stats() {
+        let mut plugin = make_plugin(make_mock());
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        assert!(plugin.link_stats(addr).is_none());
+
+        for seq in [0u32, 1, 2, 5, 4, 4] {
+            let mut ack = build_ack_packet();
+            ack[9..13].copy_from_slice(&seq.to_le_bytes());
+            plugin.handle_incoming_packet(&ack, addr);
+        }
+        let stats = plugin.link_stats(addr).unwrap();
+        assert_eq!(stats.received, 5);
+        assert_eq!(stats.lost, 1);       // seq 3 never arrived
+        assert_eq!(stats.reordered, 1);  // seq 4 after 5
+        assert_eq!(stats.duplicates, 1); // second seq 4
+    }
+
+    #[test]
+    fn binary_commands_are_executed() {
+        use efb_protocol::command::encode_command;
+
+        let mock = make_mock();
+        mock.set_dataref(paths::COM1_ACTIVE_HZ,  DataRefValue::Int(118_025_000));
+        mock.set_dataref(paths::COM1_STANDBY_HZ, DataRefValue::Int(121_500_000));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let int = |p: &EfbPlugin, path: &str| {
+            let h = p.xplm.find_dataref(path).unwrap();
+            p.xplm.get_int(h)
+        };
+
+        let set = Command::SetStandbyFreq { radio: Radio::Com1, hz: 124_800_000 };
+        plugin.handle_incoming_packet(&encode_command(1, &set).unwrap(), addr);
+        assert_eq!(int(&plugin, paths::COM1_STANDBY_HZ), 124_800_000);
+
+        let swap = Command::SwapFreq { radio: Radio::Com1 };
+        plugin.handle_incoming_packet(&encode_command(2, &swap).unwrap(), addr);
+        assert_eq!(int(&plugin, paths::COM1_ACTIVE_HZ), 124_800_000);
+        assert_eq!(int(&plugin, paths::COM1_STANDBY_HZ), 118_025_000);
+    }
+
+    #[test]
+    fn invalid_json_command_is_rejected() {
+        let mock = make_mock();
+        mock.set_dataref(paths::COM1_ACTIVE_HZ,  DataRefValue::Int(118_025_000));
+        mock.set_dataref(paths::COM1_STANDBY_HZ, DataRefValue::Int(121_500_000));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+        let pkt = build_command_json_packet(br#"{"cmd":"swap_freq","radio":"ADF1"}"#);
+        plugin.handle_incoming_packet(&pkt, addr);
+        let h = plugin.xplm.find_dataref(paths::COM1_ACTIVE_HZ).unwrap();
+        assert_eq!(plugin.xplm.get_int(h), 118_025_000);
+    }
+
+    #[test]
+    fn every_command_gets_a_result() {
+        use efb_protocol::command::{decode_command_result, encode_request, request_payload};
+
+        let mock = make_mock();
+        mock.set_dataref("sim/cockpit/autopilot/heading_mag", DataRefValue::Float(0.0));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let addr = tablet.local_addr().unwrap();
+
+        let req = |id, command| CommandRequest { id: Some(id), command };
+        let set = |path: &str| Command::SetDataref { path: path.into(), value: 90.0 };
+        let mut bad_utf8 = request_payload(&req(9, set("x"))).unwrap();
+        bad_utf8[7] = 0xFF; // the one path byte after opcode, id and length
+        let packets = [
+            encode_request(0, &req(1, set("sim/cockpit/autopilot/heading_mag"))).unwrap(),
+            encode_request(0, &req(2, set("sim/no/such/dataref"))).unwrap(),
+            encode_request(0, &req(3, Command::SwapFreq { radio: Radio::Nav2 })).unwrap(),
+            encode_request(0, &req(4, Command::SetStandbyFreq { radio: Radio::Com2, hz: 122_800_000 })).unwrap(),
+            build_command_json_packet(br#"{"id":5,"cmd":"swap_freq","radio":"ADF1"}"#),
+            build_command_binary_packet(&[0x82, 6, 0, 0, 0, 9]),
+            build_command_binary_packet(&[0xFF, 7, 0, 0, 0]),
+            build_command_json_packet(b"not json"),
+            build_command_binary_packet(&bad_utf8),
+        ];
+        let expected = [
+            (Some(1), CommandStatus::Ok),
+            (Some(2), CommandStatus::UnknownDataref),
+            (Some(3), CommandStatus::UnsupportedRadio),
+            (Some(4), CommandStatus::UnsupportedRadio),
+            (Some(5), CommandStatus::UnknownRadio),
+            (Some(6), CommandStatus::UnknownRadio),
+            (Some(7), CommandStatus::UnknownCommand),
+            (None,    CommandStatus::Malformed),
+            (Some(9), CommandStatus::Malformed),
+        ];
+
+        let mut buf = [0u8; 2048];
+        for (pkt, (id, status)) in packets.iter().zip(expected) {
+            plugin.handle_incoming_packet(pkt, addr);
+            let (n, _) = tablet.recv_from(&mut buf).unwrap();
+            let (_, ptype, payload) = decode_packet(&buf[..n]).unwrap();
+            assert_eq!(ptype, PacketType::CommandResult);
+            let result = decode_command_result(payload).unwrap();
+            assert_eq!((result.id, result.status), (id, status), "{result:?}");
+            assert_eq!(result.message.is_empty(), result.is_ok());
+        }
+    }
+
+    #[test]
+    fn tcp_tablet_receives_stream_and_sends_commands() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let mock = make_mock();
+        mock.set_dataref(paths::COM1_ACTIVE_HZ,  DataRefValue::Int(118_025_000));
+        mock.set_dataref(paths::COM1_STANDBY_HZ, DataRefValue::Int(121_500_000));
+        let mut plugin = make_plugin(mock);
+        plugin.find_handles();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        plugin.start_tcp_server(listener);
+
+        let mut tablet = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        tablet.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        // ACK and a command in one write: the deframer must split them.
+        let mut bytes = build_ack_packet();
+        bytes.extend(build_command_json_packet(br#"{"cmd":"swap_freq","radio":"COM1"}"#));
+        tablet.write_all(&bytes).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while plugin.tablet_addr.is_none() && Instant::now() < deadline {
+            plugin.drain_messages();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(plugin.tablet_addr, Some(tablet.local_addr().unwrap()));
+        plugin.flight_loop_tick();
+
+        let h = plugin.xplm.find_dataref(paths::COM1_ACTIVE_HZ).unwrap();
+        assert_eq!(plugin.xplm.get_int(h), 121_500_000);
+
+        let mut frames = FrameReader::new(tablet);
+        let first = frames.read_frame().unwrap().unwrap();
+        assert_eq!(decode_packet(&first).unwrap().1, PacketType::Schema);
+        let second = frames.read_frame().unwrap().unwrap();
+        assert_eq!(decode_packet(&second).unwrap().1, PacketType::CommandResult);
+        let third = frames.read_frame().unwrap().unwrap();
+        assert_eq!(decode_packet(&third).unwrap().1, PacketType::SimData);
+    }
+
+    #[test]
+    fn capture_records_both_directions() {
+        use efb_protocol::CaptureReader;
+
+        let mut plugin = make_plugin(make_mock());
+        plugin.find_handles();
+        let path = std::env::temp_dir().join(format!("efb-capture-test-{}.efbcap", std::process::id()));
+        plugin.start_capture(&path).unwrap();
+
+        let tablet = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tablet_addr = tablet.local_addr().unwrap();
+        let ack = build_ack_packet();
+        plugin.handle_incoming_packet(&ack, tablet_addr);
+        plugin.flight_loop_tick();
+        plugin.stop_capture().unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let records: Vec<_> = CaptureReader::new(file).unwrap().collect::<io::Result<_>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let kinds: Vec<_> = records
+            .iter()
+            .map(|r| (r.direction, decode_packet(&r.data).unwrap().1))
+            .collect();
+        assert_eq!(kinds, [
+            (Direction::Inbound,  PacketType::Ack),
+            (Direction::Outbound, PacketType::Schema),
+            (Direction::Outbound, PacketType::SimData),
+        ]);
+        assert_eq!(records[0].data, ack);
+        assert_eq!(records[0].src, tablet_addr);
+        assert_eq!(records[2].dst, tablet_addr);
+        assert!(records[2].timestamp >= records[0].timestamp);
+    }
+
     // ── Packet builders for tests ─────────────────────────────────────────────
 
     fn build_command_json_packet(json: &[u8]) -> Vec<u8> {
         let crc = efb_crc(json);
         let mut pkt = Vec::with_capacity(HEADER_LEN + json.len());
         pkt.extend_from_slice(&MAGIC.to_le_bytes());
-        pkt.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        pkt.extend_from_slice(&MIN_PROTOCOL_VERSION.to_le_bytes()); // v1 header
         pkt.push(0x02); // CommandJson
         pkt.extend_from_slice(&(json.len() as u16).to_le_bytes());
         pkt.extend_from_slice(&0u32.to_le_bytes()); // seq
@@ -710,11 +2536,24 @@ mod tests {
         pkt
     }
 
+    fn build_command_binary_packet(payload: &[u8]) -> Vec<u8> {
+        let crc = efb_crc(payload);
+        let mut pkt = Vec::with_capacity(HEADER_LEN + payload.len());
+        pkt.extend_from_slice(&MAGIC.to_le_bytes());
+        pkt.extend_from_slice(&MIN_PROTOCOL_VERSION.to_le_bytes()); // v1 header
+        pkt.push(0x0B); // CommandBinary
+        pkt.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        pkt.extend_from_slice(&0u32.to_le_bytes()); // seq
+        pkt.extend_from_slice(&crc.to_le_bytes());
+        pkt.extend_from_slice(payload);
+        pkt
+    }
+
     fn build_ack_packet() -> Vec<u8> {
         let crc = efb_crc(b"");
         let mut pkt = Vec::with_capacity(HEADER_LEN);
         pkt.extend_from_slice(&MAGIC.to_le_bytes());
-        pkt.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        pkt.extend_from_slice(&MIN_PROTOCOL_VERSION.to_le_bytes()); // v1 header
         pkt.push(0x03); // Ack
         pkt.extend_from_slice(&0u16.to_le_bytes()); // payload_len = 0
         pkt.extend_from_slice(&0u32.to_le_bytes()); // seq
@@ -726,7 +2565,7 @@ mod tests {
         let crc = efb_crc(b"");
         let mut pkt = Vec::with_capacity(HEADER_LEN);
         pkt.extend_from_slice(&MAGIC.to_le_bytes());
-        pkt.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        pkt.extend_from_slice(&MIN_PROTOCOL_VERSION.to_le_bytes()); // v1 header
         pkt.push(0x04); // Reload
         pkt.extend_from_slice(&0u16.to_le_bytes());
         pkt.extend_from_slice(&0u32.to_le_bytes());
@@ -18,6 +18,8 @@ pub enum DataRefValue {
     Double(f64),
     Int(i32),
     FloatArray(Vec<f32>),
+    IntArray(Vec<i32>),
+    Bytes(Vec<u8>),
 }
 
 // ── Trait ─────────────────────────────────────────────────────────────────────
@@ -32,8 +34,15 @@ pub trait XplmApi: Send + Sync {
     fn get_int(&self, handle: DataRefHandle) -> i32;
     /// Read up to `out.len()` floats starting at `offset`.
     fn get_float_array(&self, handle: DataRefHandle, offset: usize, out: &mut [f32]);
+    /// Read up to `out.len()` ints starting at `offset`.
+    fn get_int_array(&self, handle: DataRefHandle, offset: usize, out: &mut [i32]);
+    /// Read up to `out.len()` bytes starting at `offset`; returns the count read.
+    fn get_bytes(&self, handle: DataRefHandle, offset: usize, out: &mut [u8]) -> usize;
     fn set_float(&self, handle: DataRefHandle, value: f32);
     fn set_int(&self, handle: DataRefHandle, value: i32);
+    /// Convert OpenGL local coordinates (m) to latitude, longitude (deg)
+    /// and elevation (m MSL).
+    fn local_to_world(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64);
     fn log(&self, message: &str);
 }
 
@@ -152,6 +161,26 @@ impl XplmApi for MockXplm {
         }
     }
 
+    fn get_int_array(&self, handle: DataRefHandle, offset: usize, out: &mut [i32]) {
+        let g = self.inner.lock().unwrap();
+        let path = g.handles.get(handle).cloned().unwrap_or_default();
+        if let Some(DataRefValue::IntArray(arr)) = g.datarefs.get(&path) {
+            for (i, slot) in out.iter_mut().enumerate() {
+                *slot = arr.get(offset + i).copied().unwrap_or(0);
+            }
+        }
+    }
+
+    fn get_bytes(&self, handle: DataRefHandle, offset: usize, out: &mut [u8]) -> usize {
+        let g = self.inner.lock().unwrap();
+        let path = g.handles.get(handle).cloned().unwrap_or_default();
+        let Some(DataRefValue::Bytes(bytes)) = g.datarefs.get(&path) else { return 0 };
+        let src = bytes.get(offset..).unwrap_or_default();
+        let n = src.len().min(out.len());
+        out[..n].copy_from_slice(&src[..n]);
+        n
+    }
+
     fn set_float(&self, handle: DataRefHandle, value: f32) {
         let mut g = self.inner.lock().unwrap();
         let path = g.handles.get(handle).cloned().unwrap_or_default();
@@ -166,6 +195,12 @@ impl XplmApi for MockXplm {
         g.set_int_log.push((path, value));
     }
 
+    /// Flat earth with the local origin at 0°N 0°E: +x east, +y up, -z north.
+    fn local_to_world(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        const M_PER_DEG: f64 = 111_319.5;
+        (-z / M_PER_DEG, x / M_PER_DEG, y)
+    }
+
     fn log(&self, message: &str) {
         self.inner.lock().unwrap().log_messages.push(message.to_string());
     }
@@ -214,6 +249,29 @@ mod real {
             }
         }
 
+        fn get_int_array(&self, handle: DataRefHandle, offset: usize, out: &mut [i32]) {
+            unsafe {
+                crate::xplm_sys::XPLMGetDatavi(
+                    handle as _,
+                    out.as_mut_ptr(),
+                    offset as i32,
+                    out.len() as i32,
+                );
+            }
+        }
+
+        fn get_bytes(&self, handle: DataRefHandle, offset: usize, out: &mut [u8]) -> usize {
+            let n = unsafe {
+                crate::xplm_sys::XPLMGetDatab(
+                    handle as _,
+                    out.as_mut_ptr().cast(),
+                    offset as i32,
+                    out.len() as i32,
+                )
+            };
+            n.max(0) as usize
+        }
+
         fn set_float(&self, handle: DataRefHandle, value: f32) {
             unsafe { crate::xplm_sys::XPLMSetDataf(handle as _, value) }
         }
@@ -222,6 +280,12 @@ mod real {
             unsafe { crate::xplm_sys::XPLMSetDatai(handle as _, value) }
         }
 
+        fn local_to_world(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+            let (mut lat, mut lon, mut alt) = (0.0, 0.0, 0.0);
+            unsafe { crate::xplm_sys::XPLMLocalToWorld(x, y, z, &mut lat, &mut lon, &mut alt) }
+            (lat, lon, alt)
+        }
+
         fn log(&self, message: &str) {
             if let Ok(c) = CString::new(message) {
                 unsafe { crate::xplm_sys::XPLMDebugString(c.as_ptr()) }